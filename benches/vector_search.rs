@@ -0,0 +1,96 @@
+//! `MemoryIndex::search_vector` without sqlite-vec, on a real index: full
+//! scan of the packed vectors (used below the ANN threshold, and for every
+//! model before the HNSW index existed) against the HNSW index.
+//!
+//! Run with `cargo bench --bench vector_search`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+use zier_alpha::memory::MemoryIndex;
+
+const DIMS: usize = 384;
+const K: usize = 10;
+const MODEL: &str = "bench";
+
+fn vectors(n: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..n)
+        .map(|_| {
+            (0..DIMS)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((state >> 33) as f32 / u32::MAX as f32) - 0.25
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Index `n` one-chunk notes and give each chunk a vector
+async fn build_index(workspace: &Path, db_path: &Path, n: usize) -> MemoryIndex {
+    let index = MemoryIndex::new_with_db_path(workspace, db_path).unwrap();
+    for i in 0..n {
+        let path = workspace.join(format!("note-{}.md", i));
+        fs::write(
+            &path,
+            format!("# Note {}\n\nBenchmark note number {}.\n", i, i),
+        )
+        .unwrap();
+        index.index_file(&path, false).await.unwrap();
+    }
+
+    index
+        .register_embedding_model(MODEL, "bench")
+        .await
+        .unwrap();
+    let chunks = index.chunks_without_embeddings(MODEL, n).await.unwrap();
+    for ((chunk_id, _), vector) in chunks.iter().zip(vectors(chunks.len(), 42)) {
+        index
+            .store_embedding(chunk_id, &vector, MODEL)
+            .await
+            .unwrap();
+    }
+    index.flush_vector_index().await.unwrap();
+    index
+}
+
+fn bench_vector_search(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let query = vectors(1, 7).remove(0);
+    let mut group = c.benchmark_group("vector_search");
+    group.sample_size(20);
+
+    for n in [2_000, 10_000] {
+        let temp = TempDir::new().unwrap();
+        let db_path = temp.path().join("bench.sqlite");
+        rt.block_on(build_index(temp.path(), &db_path, n));
+
+        // Same database, searched both ways
+        let scan = MemoryIndex::new_with_db_path(temp.path(), &db_path)
+            .unwrap()
+            .with_ann_min_vectors(usize::MAX);
+        let hnsw = MemoryIndex::new_with_db_path(temp.path(), &db_path)
+            .unwrap()
+            .with_ann_min_vectors(1);
+        // Load the persisted HNSW index outside the measurement
+        rt.block_on(hnsw.search_vector(&query, MODEL, K)).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("scan", n), &scan, |b, index| {
+            b.iter(|| black_box(rt.block_on(index.search_vector(&query, MODEL, K)).unwrap()))
+        });
+
+        group.bench_with_input(BenchmarkId::new("hnsw", n), &hnsw, |b, index| {
+            b.iter(|| black_box(rt.block_on(index.search_vector(&query, MODEL, K)).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_vector_search);
+criterion_main!(benches);
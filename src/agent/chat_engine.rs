@@ -1,13 +1,17 @@
+use crate::agent::tool_executor::rejection_output;
+use crate::agent::tools::is_cognitive_path;
 use crate::agent::{
     is_silent_reply, AgentConfig, ImageAttachment, LLMResponseContent, Message, Role,
-    SessionManager, SmartClient, SmartResponse, StreamEvent, StreamResult, ToolExecutor, Usage,
-    SILENT_REPLY_TOKEN,
+    SessionManager, SmartClient, SmartResponse, StreamEvent, StreamResult, ToolCall, ToolExecutor,
+    Usage, SILENT_REPLY_TOKEN,
 };
 use crate::capabilities::vision::VisionService;
 use crate::config::Config;
+use crate::memory::split_anchor;
 use anyhow::Result;
 use futures::StreamExt;
-use tracing::{debug, info};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
 
 pub struct ChatEngine {
     client: SmartClient,
@@ -15,6 +19,27 @@ pub struct ChatEngine {
     tool_executor: ToolExecutor,
     config: Config,
     agent_config: AgentConfig,
+    /// Tools executed since the last turn ended (for the auto-commit message),
+    /// flagged when the call wrote a cognitive file
+    turn_tools: Mutex<Vec<(String, bool)>>,
+}
+
+/// Whether a tool call targets a cognitive file (MEMORY.md, memory/*.md, ...)
+fn writes_cognitive_file(call: &ToolCall) -> bool {
+    match call.name.as_str() {
+        "memory_write" | "forget" => true,
+        "write_file" | "edit_file" | "edit_section" => {
+            serde_json::from_str::<serde_json::Value>(&call.arguments)
+                .ok()
+                .and_then(|args| args["path"].as_str().map(str::to_string))
+                .map(|path| {
+                    let (file, _) = split_anchor(&path);
+                    is_cognitive_path(file.trim_start_matches("./"))
+                })
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
 }
 
 impl ChatEngine {
@@ -31,6 +56,7 @@ impl ChatEngine {
             tool_executor,
             config,
             agent_config,
+            turn_tools: Mutex::new(Vec::new()),
         }
     }
 
@@ -38,6 +64,65 @@ impl ChatEngine {
         &self.client
     }
 
+    /// Execute a tool call within the current session's context
+    pub async fn execute_tool(&self, call: &crate::agent::ToolCall) -> Result<String> {
+        let session_id = self.session_manager.session().read().await.id().to_string();
+        if let Ok(mut tools) = self.turn_tools.lock() {
+            let writes = writes_cognitive_file(call);
+            match tools.iter_mut().find(|(name, _)| *name == call.name) {
+                Some(entry) => entry.1 |= writes,
+                None => tools.push((call.name.clone(), writes)),
+            }
+        }
+        self.tool_executor
+            .execute_tool_for_session(call, &session_id, self.client.is_local())
+            .await
+    }
+
+    /// End of a turn: commit cognitive files the turn's tools changed
+    /// (`memory.auto_commit`). Failures are logged, never surfaced.
+    async fn commit_turn(&self) {
+        let tools = match self.turn_tools.lock() {
+            Ok(mut tools) => std::mem::take(&mut *tools),
+            Err(_) => return,
+        };
+        if tools.is_empty() || !self.config.memory.auto_commit {
+            return;
+        }
+
+        let session_id = self.session_manager.session().read().await.id().to_string();
+        let writers: Vec<&str> = tools
+            .iter()
+            .filter(|(_, writes)| *writes)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let names: Vec<&str> = tools.iter().map(|(name, _)| name.as_str()).collect();
+        // Files changed by other means (e.g. bash) get no attribution
+        let summary = if writers.is_empty() {
+            "Update memory".to_string()
+        } else {
+            format!("Update memory via {}", writers.join(", "))
+        };
+        let message = format!(
+            "{}\n\nSession: {}\nTools: {}",
+            summary,
+            session_id,
+            names.join(", ")
+        );
+        let workspace = self.config.workspace_path();
+        let result = tokio::task::spawn_blocking(move || {
+            crate::memory::WorkspaceHistory::open_or_init(&workspace)?.commit_cognitive(&message)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(Some(id))) => debug!("Committed workspace changes: {}", id),
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => warn!("Workspace auto-commit failed: {}", e),
+            Err(e) => warn!("Workspace auto-commit task failed: {}", e),
+        }
+    }
+
     pub async fn chat(&self, message: &str) -> Result<(String, Option<Usage>)> {
         self.chat_with_images(message, Vec::new()).await
     }
@@ -78,6 +163,7 @@ impl ChatEngine {
             images.clear();
         }
 
+        self.tool_executor.begin_turn();
         self.session_manager
             .session()
             .write()
@@ -156,6 +242,8 @@ impl ChatEngine {
             .await
             .add_metadata_to_last_message(Some(metadata.0), Some(metadata.1));
 
+        self.commit_turn().await;
+
         Ok((final_response, total_usage))
     }
 
@@ -191,7 +279,7 @@ impl ChatEngine {
                         call.name, call.arguments
                     );
 
-                    let result = self.tool_executor.execute_tool(call).await;
+                    let result = self.execute_tool(call).await;
 
                     if let Err(ref e) = result {
                         if let Some(approval_err) =
@@ -315,6 +403,7 @@ impl ChatEngine {
         message: &str,
         images: Vec<ImageAttachment>,
     ) -> Result<StreamResult> {
+        self.tool_executor.begin_turn();
         self.session_manager
             .session()
             .write()
@@ -414,6 +503,7 @@ impl ChatEngine {
             final_images.clear();
         }
 
+        self.tool_executor.begin_turn();
         self.session_manager
             .session()
             .write()
@@ -458,6 +548,40 @@ impl ChatEngine {
             let mut iteration = 0;
 
             loop {
+                // Run the calls the last assistant message still waits on:
+                // new ones, or ones that were just approved. Rejected calls
+                // already have their result.
+                for call in self.pending_tool_calls().await {
+                    if self.tool_executor.call_requires_approval(&call)
+                        && !self.tool_executor.is_approved(&call.id)
+                    {
+                        yield Ok(StreamEvent::ApprovalRequired {
+                            name: call.name.clone(),
+                            id: call.id.clone(),
+                            arguments: call.arguments.clone(),
+                            reason: self.tool_executor.approval_reason(&call),
+                        });
+                        return;
+                    }
+
+                    yield Ok(StreamEvent::ToolCallStart {
+                        name: call.name.clone(),
+                        id: call.id.clone(),
+                        arguments: call.arguments.clone(),
+                    });
+
+                    let result = self.execute_tool(&call).await;
+                    let output = result.unwrap_or_else(|e| format!("Error: {}", e));
+
+                    yield Ok(StreamEvent::ToolCallEnd {
+                        name: call.name.clone(),
+                        id: call.id.clone(),
+                        output: output.clone(),
+                    });
+
+                    self.provide_tool_result(call.id.clone(), output).await;
+                }
+
                 iteration += 1;
                 if iteration > max_tool_iterations {
                     yield Err(anyhow::anyhow!("Max tool iterations exceeded"));
@@ -509,46 +633,13 @@ impl ChatEngine {
                             });
                         }
 
-                        if let Some(calls) = tool_calls {
-                            for call in &calls {
-                                if self.tool_executor.requires_approval(&call.name) {
-                                    yield Ok(StreamEvent::ApprovalRequired {
-                                        name: call.name.clone(),
-                                        id: call.id.clone(),
-                                        arguments: call.arguments.clone(),
-                                    });
-                                    return;
-                                }
-
-                                yield Ok(StreamEvent::ToolCallStart {
-                                    name: call.name.clone(),
-                                    id: call.id.clone(),
-                                    arguments: call.arguments.clone(),
-                                });
-
-                                let result = self.tool_executor.execute_tool(call).await;
-                                let output = result.unwrap_or_else(|e| format!("Error: {}", e));
-
-                                yield Ok(StreamEvent::ToolCallEnd {
-                                    name: call.name.clone(),
-                                    id: call.id.clone(),
-                                    output: output.clone(),
-                                });
-
-                                self.session_manager.session().write().await.add_message(Message {
-                                    role: Role::Tool,
-                                    content: output,
-                                    tool_calls: None,
-                                    tool_call_id: Some(call.id.clone()),
-                                    images: Vec::new(),
-                                });
-                            }
-                            // Continue loop for next turn
-                        } else {
+                        if tool_calls.is_none() {
                             // No tool calls, done
+                            self.commit_turn().await;
                             yield Ok(StreamEvent::Done);
                             break;
                         }
+                        // Continue loop: the calls run at its top
                     }
                     Err(e) => {
                         yield Err(e);
@@ -559,6 +650,34 @@ impl ChatEngine {
         }
     }
 
+    /// Calls of the last assistant message without a result yet. Empty
+    /// once anything but tool results follows that message.
+    pub async fn pending_tool_calls(&self) -> Vec<ToolCall> {
+        let messages = self
+            .session_manager
+            .session()
+            .read()
+            .await
+            .messages_for_llm();
+        let Some(idx) = messages.iter().rposition(|m| m.role != Role::Tool) else {
+            return Vec::new();
+        };
+        if messages[idx].role != Role::Assistant {
+            return Vec::new();
+        }
+        let done: std::collections::HashSet<_> = messages[idx + 1..]
+            .iter()
+            .filter_map(|m| m.tool_call_id.as_deref())
+            .collect();
+        messages[idx]
+            .tool_calls
+            .iter()
+            .flatten()
+            .filter(|c| !done.contains(c.id.as_str()))
+            .cloned()
+            .collect()
+    }
+
     pub async fn resume_chat_stream_with_tools(
         &self,
     ) -> Result<impl futures::Stream<Item = Result<StreamEvent>> + '_> {
@@ -579,6 +698,22 @@ impl ChatEngine {
             });
     }
 
+    /// Answer a pending call with the user's refusal, so the model learns
+    /// of it when the tool loop continues
+    pub async fn reject_tool_call(&self, call_id: String, reason: Option<&str>) -> Result<()> {
+        if !self
+            .pending_tool_calls()
+            .await
+            .iter()
+            .any(|c| c.id == call_id)
+        {
+            anyhow::bail!("No pending tool call: {}", call_id);
+        }
+        self.provide_tool_result(call_id, rejection_output(reason))
+            .await;
+        Ok(())
+    }
+
     pub async fn continue_chat(&self) -> Result<(String, Option<Usage>)> {
         let messages = self
             .session_manager
@@ -608,7 +743,7 @@ impl ChatEngine {
                         continue;
                     }
 
-                    let result = self.tool_executor.execute_tool(call).await;
+                    let result = self.execute_tool(call).await;
 
                     if let Err(ref e) = result {
                         if let Some(approval_err) =
@@ -648,7 +783,11 @@ impl ChatEngine {
                 let tool_schemas = self.tool_executor.tool_schemas();
                 let response = self.client.chat(&messages, Some(&tool_schemas)).await?;
 
-                return self.handle_response(response).await;
+                let result = self.handle_response(response).await;
+                if result.is_ok() {
+                    self.commit_turn().await;
+                }
+                return result;
             }
         }
         anyhow::bail!("Nothing to continue")
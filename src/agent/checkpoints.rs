@@ -0,0 +1,518 @@
+//! File checkpoints for agent edits
+//!
+//! Before `write_file`, `edit_file`, `edit_section` or `memory_write` changes
+//! a file, its pre-image is saved under
+//! `{state_dir}/checkpoints/<workspace>/<session_id>/`, once per file and turn.
+//! Side effects of `bash`, scripts and external tools are not tracked, and
+//! `forget` is deliberately left out so purged content isn't kept. `forget`
+//! also redacts the pre-images saved by earlier turns (see
+//! [`CheckpointStore::redact`]).
+//!
+//! Checkpoints are numbered from 1 among the session's turns that changed
+//! files, in the order they ran. Turns without file changes get no number,
+//! so checkpoint 3 is the third turn that edited something, not the
+//! conversation's third turn. [`CheckpointStore::rewind`] puts every file
+//! changed since a checkpoint back the way it was before it (deleting files
+//! the agent created) and drops the checkpoints undone.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::agent::tool_executor::{current_session_id, current_turn};
+
+const MANIFEST: &str = "manifest.jsonl";
+
+/// One saved pre-image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    /// Start of the turn (ms since the epoch); groups a turn's checkpoints
+    turn: u64,
+    path: PathBuf,
+    /// Blob with the file's content; `None` when the file didn't exist
+    blob: Option<String>,
+}
+
+/// A turn that changed files
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnCheckpoint {
+    /// Checkpoint number: 1-based among the session's turns that changed
+    /// files, not the conversation turn index
+    pub turn: usize,
+    pub started_at: DateTime<Utc>,
+    pub files: Vec<PathBuf>,
+}
+
+/// What rewinding a file does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RewindAction {
+    /// Write back the content it had
+    Restore,
+    /// Remove it: the agent created it
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRewind {
+    pub path: PathBuf,
+    pub action: RewindAction,
+    /// Unified diff from the current content; `None` for binary files
+    pub diff: Option<String>,
+}
+
+/// Files a rewind changes; files already back to their old state are left out
+#[derive(Debug, Clone, Serialize)]
+pub struct RewindPlan {
+    pub turn: usize,
+    pub changes: Vec<FileRewind>,
+}
+
+/// Saved pre-images of one session that contain some text
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointMatches {
+    pub session_id: String,
+    /// Files with a matching pre-image
+    pub files: Vec<PathBuf>,
+}
+
+/// Checkpoints of all sessions
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    root: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Store in the workspace's own directory under
+    /// `{state_dir}/checkpoints`, so profiles sharing a state dir stay apart
+    pub fn for_workspace(workspace: &Path) -> Self {
+        let state_dir = workspace.parent().unwrap_or(workspace);
+        let name = workspace
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "workspace".to_string());
+        Self::new(state_dir.join("checkpoints").join(name))
+    }
+
+    fn session_dir(&self, session_id: &str) -> PathBuf {
+        self.root.join(session_id)
+    }
+
+    /// Save the pre-image of `path` for the turn executing tools. Does
+    /// nothing outside a turn (see [`current_session_id`]).
+    pub fn record(&self, path: &Path) -> Result<()> {
+        match (current_session_id(), current_turn()) {
+            (Some(session_id), Some(turn)) => self.snapshot(&session_id, turn, path).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Save the pre-image of `path` unless `turn` already has one; returns
+    /// whether it was saved
+    pub fn snapshot(&self, session_id: &str, turn: u64, path: &Path) -> Result<bool> {
+        let path = absolute(path);
+        let entries = self.entries(session_id)?;
+        if entries.iter().any(|e| e.turn == turn && e.path == path) {
+            return Ok(false);
+        }
+
+        let dir = self.session_dir(session_id);
+        let blob = match fs::read(&path) {
+            Ok(content) => {
+                let hash = format!("{:x}", Sha256::digest(&content));
+                let blob_path = dir.join("blobs").join(&hash);
+                if !blob_path.exists() {
+                    fs::create_dir_all(dir.join("blobs"))?;
+                    fs::write(&blob_path, &content)?;
+                }
+                Some(hash)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Cannot checkpoint {}", path.display()))
+            }
+        };
+
+        debug!("Checkpoint of {} for turn {}", path.display(), turn);
+        let line = serde_json::to_string(&Entry { turn, path, blob })?;
+        fs::create_dir_all(&dir)?;
+        let mut manifest = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(MANIFEST))?;
+        writeln!(manifest, "{}", line)?;
+        Ok(true)
+    }
+
+    fn entries(&self, session_id: &str) -> Result<Vec<Entry>> {
+        let path = self.session_dir(session_id).join(MANIFEST);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(data
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| match serde_json::from_str(l) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Skipping bad checkpoint in {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Entries grouped by turn, oldest turn first
+    fn turns(&self, session_id: &str) -> Result<Vec<(u64, Vec<Entry>)>> {
+        let mut turns: BTreeMap<u64, Vec<Entry>> = BTreeMap::new();
+        for entry in self.entries(session_id)? {
+            turns.entry(entry.turn).or_default().push(entry);
+        }
+        Ok(turns.into_iter().collect())
+    }
+
+    /// Turns of the session that changed files
+    pub fn list(&self, session_id: &str) -> Result<Vec<TurnCheckpoint>> {
+        Ok(self
+            .turns(session_id)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, (started, entries))| TurnCheckpoint {
+                turn: i + 1,
+                started_at: Utc
+                    .timestamp_millis_opt(started as i64)
+                    .single()
+                    .unwrap_or_default(),
+                files: entries.into_iter().map(|e| e.path).collect(),
+            })
+            .collect())
+    }
+
+    /// What rewinding to before `turn` would change
+    pub fn preview(&self, session_id: &str, turn: usize) -> Result<RewindPlan> {
+        let (plan, _) = self.plan(session_id, turn)?;
+        Ok(plan)
+    }
+
+    /// Sessions with pre-images matching `re`
+    pub fn find(&self, re: &Regex) -> Result<Vec<CheckpointMatches>> {
+        let mut found = Vec::new();
+        let Ok(sessions) = fs::read_dir(&self.root) else {
+            return Ok(found);
+        };
+        for session in sessions.flatten() {
+            let session_id = session.file_name().to_string_lossy().to_string();
+            let blobs = session.path().join("blobs");
+            let mut matched: HashMap<String, bool> = HashMap::new();
+            let mut files = BTreeSet::new();
+            for entry in self.entries(&session_id)? {
+                let Some(blob) = entry.blob else {
+                    continue;
+                };
+                let is_match = *matched.entry(blob).or_insert_with_key(|blob| {
+                    fs::read(blobs.join(blob))
+                        .map(|content| re.is_match(&String::from_utf8_lossy(&content)))
+                        .unwrap_or(false)
+                });
+                if is_match {
+                    files.insert(entry.path);
+                }
+            }
+            if !files.is_empty() {
+                found.push(CheckpointMatches {
+                    session_id,
+                    files: files.into_iter().collect(),
+                });
+            }
+        }
+        found.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        Ok(found)
+    }
+
+    /// Rewrite pre-images matching `re` with `redact`, so forgotten text
+    /// doesn't outlive the files. Binary pre-images can't be redacted and
+    /// their checkpoints are dropped. Returns what was rewritten.
+    pub fn redact(
+        &self,
+        re: &Regex,
+        redact: impl Fn(&str) -> String,
+    ) -> Result<Vec<CheckpointMatches>> {
+        let found = self.find(re)?;
+        for session in &found {
+            let dir = self.session_dir(&session.session_id);
+            // Old blob -> redacted blob, or `None` to drop the checkpoint
+            let mut redacted: HashMap<String, Option<String>> = HashMap::new();
+            let mut entries = Vec::new();
+            for mut entry in self.entries(&session.session_id)? {
+                if let Some(blob) = entry.blob.take() {
+                    let new = match redacted.get(&blob) {
+                        Some(new) => new.clone(),
+                        None => {
+                            let new = self.redact_blob(&dir, &blob, re, &redact)?;
+                            redacted.insert(blob, new.clone());
+                            new
+                        }
+                    };
+                    match new {
+                        Some(new) => entry.blob = Some(new),
+                        None => continue,
+                    }
+                }
+                entries.push(entry);
+            }
+            self.rewrite(&session.session_id, &entries)?;
+        }
+        Ok(found)
+    }
+
+    /// Blob to use instead of `blob`: itself when it doesn't match, a
+    /// redacted copy, or `None` when it can't be redacted
+    fn redact_blob(
+        &self,
+        dir: &Path,
+        blob: &str,
+        re: &Regex,
+        redact: &impl Fn(&str) -> String,
+    ) -> Result<Option<String>> {
+        let content = fs::read(dir.join("blobs").join(blob))?;
+        if !re.is_match(&String::from_utf8_lossy(&content)) {
+            return Ok(Some(blob.to_string()));
+        }
+        let Ok(text) = std::str::from_utf8(&content) else {
+            return Ok(None);
+        };
+        let content = redact(text);
+        let hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+        fs::write(dir.join("blobs").join(&hash), content)?;
+        Ok(Some(hash))
+    }
+
+    /// Put files back the way they were before `turn` and forget the
+    /// checkpoints of `turn` and later ones
+    pub fn rewind(&self, session_id: &str, turn: usize) -> Result<RewindPlan> {
+        let (plan, contents) = self.plan(session_id, turn)?;
+        for (change, content) in plan.changes.iter().zip(contents) {
+            match content {
+                Some(content) => {
+                    if let Some(parent) = change.path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&change.path, content)?;
+                }
+                None => fs::remove_file(&change.path)?,
+            }
+        }
+
+        let kept: Vec<Entry> = self
+            .turns(session_id)?
+            .into_iter()
+            .take(turn - 1)
+            .flat_map(|(_, entries)| entries)
+            .collect();
+        self.rewrite(session_id, &kept)?;
+        Ok(plan)
+    }
+
+    /// The plan for rewinding to before `turn`, with the content to write
+    /// for each change (`None` deletes)
+    fn plan(&self, session_id: &str, turn: usize) -> Result<(RewindPlan, Vec<Option<Vec<u8>>>)> {
+        let turns = self.turns(session_id)?;
+        if turn == 0 || turn > turns.len() {
+            anyhow::bail!(
+                "No checkpoint for turn {} ({} turn(s) changed files)",
+                turn,
+                turns.len()
+            );
+        }
+
+        // The oldest pre-image from `turn` on is the file's state before it
+        let mut before: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+        for (_, entries) in &turns[turn - 1..] {
+            for entry in entries {
+                before
+                    .entry(entry.path.clone())
+                    .or_insert_with(|| entry.blob.clone());
+            }
+        }
+
+        let blobs = self.session_dir(session_id).join("blobs");
+        let mut changes = Vec::new();
+        let mut contents = Vec::new();
+        for (path, blob) in before {
+            let old = match &blob {
+                Some(hash) => Some(
+                    fs::read(blobs.join(hash))
+                        .with_context(|| format!("Missing checkpoint of {}", path.display()))?,
+                ),
+                None => None,
+            };
+            let current = fs::read(&path).ok();
+            if current == old {
+                continue;
+            }
+            let action = match old {
+                Some(_) => RewindAction::Restore,
+                None => RewindAction::Delete,
+            };
+            changes.push(FileRewind {
+                diff: diff(&path, current.as_deref(), old.as_deref()),
+                path,
+                action,
+            });
+            contents.push(old);
+        }
+        Ok((RewindPlan { turn, changes }, contents))
+    }
+
+    /// Replace the manifest with `entries` and drop blobs nothing uses
+    fn rewrite(&self, session_id: &str, entries: &[Entry]) -> Result<()> {
+        let dir = self.session_dir(session_id);
+        if entries.is_empty() {
+            if dir.exists() {
+                fs::remove_dir_all(&dir)?;
+            }
+            return Ok(());
+        }
+
+        let mut data = String::new();
+        for entry in entries {
+            data.push_str(&serde_json::to_string(entry)?);
+            data.push('\n');
+        }
+        fs::write(dir.join(MANIFEST), data)?;
+
+        if let Ok(blobs) = fs::read_dir(dir.join("blobs")) {
+            for blob in blobs.flatten() {
+                let name = blob.file_name().to_string_lossy().to_string();
+                if !entries.iter().any(|e| e.blob.as_deref() == Some(&name)) {
+                    let _ = fs::remove_file(blob.path());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Diff from `current` to `old`, missing files counting as empty
+fn diff(path: &Path, current: Option<&[u8]>, old: Option<&[u8]>) -> Option<String> {
+    let current = std::str::from_utf8(current.unwrap_or_default()).ok()?;
+    let old = std::str::from_utf8(old.unwrap_or_default()).ok()?;
+    let name = path.display().to_string();
+    Some(
+        similar::TextDiff::from_lines(current, old)
+            .unified_diff()
+            .context_radius(2)
+            .header(&name, &name)
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rewind_restores_and_deletes() {
+        let temp = TempDir::new().unwrap();
+        let store = CheckpointStore::new(temp.path().join("checkpoints"));
+        let edited = temp.path().join("notes.txt");
+        let created = temp.path().join("new.txt");
+        fs::write(&edited, "v1\n").unwrap();
+
+        // Turn 1 edits notes.txt twice; only the first pre-image counts
+        assert!(store.snapshot("s", 100, &edited).unwrap());
+        fs::write(&edited, "v2\n").unwrap();
+        assert!(!store.snapshot("s", 100, &edited).unwrap());
+        fs::write(&edited, "v3\n").unwrap();
+
+        // Turn 2 edits it again and creates new.txt
+        store.snapshot("s", 200, &edited).unwrap();
+        fs::write(&edited, "v4\n").unwrap();
+        store.snapshot("s", 200, &created).unwrap();
+        fs::write(&created, "fresh\n").unwrap();
+
+        let turns = store.list("s").unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1].files, vec![edited.clone(), created.clone()]);
+
+        let preview = store.preview("s", 2).unwrap();
+        assert_eq!(preview.changes.len(), 2);
+        // Ordered by path
+        assert_eq!(preview.changes[0].path, created);
+        assert_eq!(preview.changes[0].action, RewindAction::Delete);
+        assert!(preview.changes[1].diff.as_ref().unwrap().contains("+v3"));
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "v4\n");
+
+        store.rewind("s", 2).unwrap();
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "v3\n");
+        assert!(!created.exists());
+        assert_eq!(store.list("s").unwrap().len(), 1);
+
+        store.rewind("s", 1).unwrap();
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "v1\n");
+        assert!(store.list("s").unwrap().is_empty());
+        assert!(store.rewind("s", 1).is_err());
+    }
+
+    #[test]
+    fn test_find_matching_pre_images() {
+        let temp = TempDir::new().unwrap();
+        let store = CheckpointStore::new(temp.path().join("checkpoints"));
+        let notes = temp.path().join("notes.txt");
+        let other = temp.path().join("other.txt");
+        fs::write(&notes, "PIN 1234-SECRET\n").unwrap();
+        fs::write(&other, "nothing here\n").unwrap();
+
+        store.snapshot("a", 100, &notes).unwrap();
+        store.snapshot("a", 100, &other).unwrap();
+        store.snapshot("b", 100, &other).unwrap();
+        fs::write(&notes, "PIN [forgotten]\n").unwrap();
+
+        let found = store.find(&Regex::new("(?i)1234-secret").unwrap()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].session_id, "a");
+        assert_eq!(found[0].files, vec![notes.clone()]);
+
+        let redacted = store
+            .redact(&Regex::new("(?i)1234-secret").unwrap(), |text| {
+                text.replace("1234-SECRET", "[forgotten]")
+            })
+            .unwrap();
+        assert_eq!(redacted.len(), 1);
+        assert!(store.find(&Regex::new("1234").unwrap()).unwrap().is_empty());
+        let blobs = temp.path().join("checkpoints").join("a").join("blobs");
+        for blob in fs::read_dir(&blobs).unwrap() {
+            let content = fs::read_to_string(blob.unwrap().path()).unwrap();
+            assert!(!content.contains("1234"));
+        }
+
+        // Rewinding still works, with the redacted content
+        fs::write(&notes, "PIN changed\n").unwrap();
+        store.rewind("a", 1).unwrap();
+        assert_eq!(fs::read_to_string(&notes).unwrap(), "PIN [forgotten]\n");
+    }
+}
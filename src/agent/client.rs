@@ -7,9 +7,11 @@ use crate::config::{
     models::{resolve_model_config, ModelConfig},
     Config,
 };
+use crate::security::secrets::SecretScanner;
 use anyhow::Result;
 use async_trait::async_trait;
 use std::env;
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::warn;
 
@@ -24,16 +26,41 @@ pub struct SmartResponse {
 pub struct SmartClient {
     config: Config,
     model_alias: String,
+    /// Redacts secrets from every request (`secrets.redact_outbound`)
+    secrets: Option<Arc<SecretScanner>>,
 }
 
 impl SmartClient {
     pub fn new(config: Config, model_alias: String) -> Self {
+        let secrets = SecretScanner::from_config(&config).filter(|s| s.config().redact_outbound);
         Self {
             config,
             model_alias,
+            secrets,
         }
     }
 
+    /// `messages` with secrets in their text and tool call arguments
+    /// replaced by vault placeholders
+    fn redact(&self, messages: &[Message]) -> Option<Vec<Message>> {
+        let secrets = self.secrets.as_ref()?;
+        Some(
+            messages
+                .iter()
+                .map(|m| {
+                    let mut m = m.clone();
+                    m.content = secrets.redact(&m.content, "outbound");
+                    if let Some(calls) = &mut m.tool_calls {
+                        for call in calls {
+                            call.arguments = secrets.redact(&call.arguments, "outbound");
+                        }
+                    }
+                    m
+                })
+                .collect(),
+        )
+    }
+
     pub fn resolve_config(&self, model_alias: &str) -> Result<ModelConfig> {
         let mut config = if self.config.models.contains_key(model_alias) {
             resolve_model_config(model_alias, &self.config.models)?
@@ -201,12 +228,59 @@ impl SmartClient {
         }
     }
 
+    /// Whether the model and all its fallbacks are served from this
+    /// machine (Ollama, the mock provider, or a loopback endpoint)
+    pub fn is_local(&self) -> bool {
+        let Ok(primary) = self.resolve_config(&self.model_alias) else {
+            return false;
+        };
+        let fallbacks = primary.fallback_models.clone().unwrap_or_default();
+        self.model_is_local(&primary)
+            && fallbacks.iter().all(|alias| {
+                self.resolve_config(alias)
+                    .map(|config| self.model_is_local(&config))
+                    .unwrap_or(false)
+            })
+    }
+
+    fn model_is_local(&self, config: &ModelConfig) -> bool {
+        let (provider_name, _) = if let Some(ref p) = config.provider {
+            (p.to_lowercase(), config.model.clone())
+        } else {
+            parse_provider_model(&config.model)
+        };
+        let providers = &self.config.providers;
+        let base_url = match provider_name.as_str() {
+            "mock" => return true,
+            "claude-cli" => return false,
+            "ollama" => providers
+                .ollama
+                .as_ref()
+                .map(|c| c.endpoint.clone())
+                .or_else(|| Some("http://localhost:11434".to_string())),
+            "openai" => providers.openai.as_ref().map(|c| c.base_url.clone()),
+            "anthropic" => providers.anthropic.as_ref().map(|c| c.base_url.clone()),
+            other => providers
+                .extra
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(other))
+                .map(|(_, v)| v.base_url.clone()),
+        };
+        config
+            .api_base
+            .clone()
+            .or(base_url)
+            .is_some_and(|url| is_loopback_url(&url))
+    }
+
     pub async fn chat(
         &self,
         messages: &[Message],
         tools: Option<&[ToolSchema]>,
     ) -> Result<SmartResponse> {
         let start = Instant::now();
+        let redacted = self.redact(messages);
+        let messages = redacted.as_deref().unwrap_or(messages);
         let mut candidates = vec![self.model_alias.clone()];
 
         // Add fallbacks from primary
@@ -270,7 +344,10 @@ impl SmartClient {
         // Just use primary model for summarization for now
         let config = self.resolve_config(&self.model_alias)?;
         let provider = self.create_provider_from_config(&config)?;
-        provider.summarize(text).await
+        match &self.secrets {
+            Some(secrets) => provider.summarize(&secrets.redact(text, "outbound")).await,
+            None => provider.summarize(text).await,
+        }
     }
 
     pub async fn chat_stream(
@@ -278,6 +355,8 @@ impl SmartClient {
         messages: &[Message],
         tools: Option<&[ToolSchema]>,
     ) -> Result<StreamResult> {
+        let redacted = self.redact(messages);
+        let messages = redacted.as_deref().unwrap_or(messages);
         let mut candidates = vec![self.model_alias.clone()];
 
         // Add fallbacks from primary
@@ -355,6 +434,22 @@ fn glob_match(pattern: &str, text: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Whether a URL points at this machine
+fn is_loopback_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
 fn parse_provider_model(s: &str) -> (String, String) {
     if let Some((p, m)) = s.split_once('/') {
         (p.to_lowercase(), m.to_string())
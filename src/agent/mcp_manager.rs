@@ -9,6 +9,9 @@ use tokio::process::{Child, Command};
 use tokio::sync::{mpsc, oneshot, RwLock};
 use tracing::{debug, error, info};
 
+use crate::config::ResourceLimits;
+use crate::security::limits::{self, LimitGuard};
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct McpConfig {
     #[serde(default = "default_cache_dir")]
@@ -58,6 +61,9 @@ pub struct ServerConfig {
     pub strategy: Option<String>,
     #[serde(default)]
     pub native_tools: Vec<String>,
+    /// Filled in from `[sandbox]` limits when the agent starts
+    #[serde(skip)]
+    pub limits: ResourceLimits,
 }
 
 #[derive(Serialize)]
@@ -93,6 +99,8 @@ struct ServerHandle {
     last_used: Arc<RwLock<Instant>>,
     shutdown_tx: Arc<RwLock<Option<oneshot::Sender<()>>>>,
     pending: Arc<RwLock<HashMap<u64, oneshot::Sender<Result<serde_json::Value>>>>>,
+    /// Cgroup of the server process, removed with the handle
+    _limits: Arc<LimitGuard>,
 }
 
 pub struct McpManager {
@@ -215,6 +223,14 @@ impl McpManager {
             cmd.envs(env);
         }
 
+        // No output cap or wall clock for a long-running server
+        let limits = ResourceLimits {
+            wall_clock_secs: None,
+            max_output_bytes: None,
+            ..config.limits.clone()
+        };
+        let guard = limits::apply(&mut cmd, &limits);
+
         let mut child = cmd.spawn().context("Failed to spawn MCP server process")?;
         let stdin = child
             .stdin
@@ -389,6 +405,7 @@ impl McpManager {
             last_used: Arc::new(RwLock::new(Instant::now())),
             shutdown_tx: Arc::new(RwLock::new(Some(shutdown_tx))),
             pending,
+            _limits: Arc::new(guard),
         };
 
         // Insert into map, handling concurrent insertion
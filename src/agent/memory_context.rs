@@ -15,7 +15,10 @@ impl MemoryContextBuilder {
         Self { memory, config }
     }
 
-    pub async fn build_memory_context(&self) -> Result<String> {
+    /// Build the workspace context for a new session. With a non-local
+    /// provider, private files and blocks are withheld (see
+    /// [`MemoryManager::shareable_content`]).
+    pub async fn build_memory_context(&self, local_provider: bool) -> Result<String> {
         let mut context = String::new();
         let use_delimiters = self.config.tools.use_content_delimiters;
 
@@ -28,6 +31,7 @@ impl MemoryContextBuilder {
 
         // Load IDENTITY.md first (OpenClaw-compatible: agent identity context)
         if let Ok(identity_content) = self.memory.read_identity_file().await {
+            let identity_content = self.shareable("IDENTITY.md", &identity_content, local_provider);
             if !identity_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -45,6 +49,7 @@ impl MemoryContextBuilder {
 
         // Load USER.md (OpenClaw-compatible: user info)
         if let Ok(user_content) = self.memory.read_user_file().await {
+            let user_content = self.shareable("USER.md", &user_content, local_provider);
             if !user_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -62,6 +67,7 @@ impl MemoryContextBuilder {
 
         // Load SOUL.md (persona/tone) - this defines who the agent is
         if let Ok(soul_content) = self.memory.read_soul_file().await {
+            let soul_content = self.shareable("SOUL.md", &soul_content, local_provider);
             if !soul_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -78,6 +84,7 @@ impl MemoryContextBuilder {
 
         // Load AGENTS.md (OpenClaw-compatible: list of connected agents)
         if let Ok(agents_content) = self.memory.read_agents_file().await {
+            let agents_content = self.shareable("AGENTS.md", &agents_content, local_provider);
             if !agents_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -95,6 +102,7 @@ impl MemoryContextBuilder {
 
         // Load TOOLS.md (OpenClaw-compatible: local tool notes)
         if let Ok(tools_content) = self.memory.read_tools_file().await {
+            let tools_content = self.shareable("TOOLS.md", &tools_content, local_provider);
             if !tools_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -112,6 +120,7 @@ impl MemoryContextBuilder {
 
         // Load MEMORY.md if it exists
         if let Ok(memory_content) = self.memory.read_memory_file().await {
+            let memory_content = self.shareable("MEMORY.md", &memory_content, local_provider);
             if !memory_content.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -129,6 +138,7 @@ impl MemoryContextBuilder {
 
         // Load today's and yesterday's daily logs
         if let Ok(recent_logs) = self.memory.read_recent_daily_logs(2).await {
+            let recent_logs = self.shareable("memory/*.md", &recent_logs, local_provider);
             if !recent_logs.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -146,6 +156,7 @@ impl MemoryContextBuilder {
 
         // Load HEARTBEAT.md if it exists
         if let Ok(heartbeat) = self.memory.read_heartbeat_file().await {
+            let heartbeat = self.shareable("HEARTBEAT.md", &heartbeat, local_provider);
             if !heartbeat.is_empty() {
                 if use_delimiters {
                     context.push_str(&sanitize::wrap_memory_content(
@@ -163,6 +174,13 @@ impl MemoryContextBuilder {
 
         Ok(context)
     }
+
+    /// File content that may go into the prompt (empty if none may)
+    fn shareable(&self, file: &str, content: &str, local_provider: bool) -> String {
+        self.memory
+            .shareable_content(file, content, local_provider, "memory_context")
+            .unwrap_or_default()
+    }
 }
 
 /// Welcome message shown on first run (brand new workspace)
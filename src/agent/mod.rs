@@ -1,4 +1,5 @@
 pub mod chat_engine;
+pub mod checkpoints;
 pub mod client;
 pub mod compaction;
 pub mod llm_error;
@@ -16,6 +17,9 @@ pub mod tool_executor;
 pub mod tools;
 
 pub use chat_engine::ChatEngine;
+pub use checkpoints::{
+    CheckpointMatches, CheckpointStore, FileRewind, RewindAction, RewindPlan, TurnCheckpoint,
+};
 pub use llm_error::LlmError;
 pub use mcp_manager::McpManager;
 pub use memory_context::MemoryContextBuilder;
@@ -41,7 +45,9 @@ pub use system_prompt::{
     HEARTBEAT_OK_TOKEN, SILENT_REPLY_TOKEN,
 };
 pub use tool_executor::ToolExecutor;
-pub use tools::{create_default_tools, extract_tool_detail, ScriptTool, Tool, ToolResult};
+pub use tools::{
+    create_default_tools, extract_tool_detail, ScriptTool, Tool, ToolResult, OWNER_ONLY_TOOLS,
+};
 pub mod disk_monitor;
 pub use disk_monitor::DiskMonitor;
 
@@ -54,9 +60,11 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info};
 
-use crate::config::Config;
+use crate::config::{ApprovalRule, Config};
+use crate::ingress::ApprovalDecision;
 use crate::memory::{MemoryChunk, MemoryManager};
 use crate::scripting::ScriptService;
+use crate::security::approval::GrantScope;
 pub use client::{SmartClient, SmartResponse};
 pub use compaction::{CompactionStrategy, NativeCompactor, ScriptCompactor};
 
@@ -148,7 +156,14 @@ impl Agent {
 
         if let Some(mcp_config) = &app_config.extensions.mcp {
             if !mcp_config.servers.is_empty() {
-                let server_configs: Vec<_> = mcp_config.servers.values().cloned().collect();
+                let server_configs: Vec<_> = mcp_config
+                    .servers
+                    .iter()
+                    .map(|(name, server)| mcp_manager::ServerConfig {
+                        limits: app_config.sandbox.limits_for(name),
+                        ..server.clone()
+                    })
+                    .collect();
                 mcp_manager.initialize(server_configs).await;
 
                 // Connect to servers and load tools
@@ -194,11 +209,12 @@ impl Agent {
                 conf.args.clone(),
                 Some(project_dir.clone()),
                 conf.sandbox,
-                Some(app_config.sandbox.clone()),
+                Some(app_config.sandbox.for_tool(name)),
                 conf.path_args.clone(),
                 Some(app_config.workspace_path()),
                 Some(app_config.workdir.strategy.clone()),
-            );
+            )
+            .with_sensitive(conf.sensitive);
             tools.push(Arc::new(tool));
         }
 
@@ -222,7 +238,7 @@ impl Agent {
         // But Agent::new creates default tools.
 
         let session_manager = SessionManager::new(app_config.clone());
-        let tool_executor = ToolExecutor::new(tools, app_config.clone());
+        let tool_executor = ToolExecutor::new(tools, app_config.clone()).with_grants(agent_id);
         let memory_context = Arc::new(MemoryContextBuilder::new(
             memory.clone(),
             app_config.clone(),
@@ -496,7 +512,11 @@ impl Agent {
             fallback_prompt
         };
 
-        let memory_context = self.memory_context.build_memory_context().await?;
+        let local_provider = self.chat_engine.client().is_local();
+        let memory_context = self
+            .memory_context
+            .build_memory_context(local_provider)
+            .await?;
 
         let full_context = if memory_context.is_empty() {
             system_prompt
@@ -545,7 +565,7 @@ impl Agent {
     }
 
     pub async fn execute_tool(&self, call: &ToolCall) -> Result<String> {
-        self.tool_executor.execute_tool(call).await
+        self.chat_engine.execute_tool(call).await
     }
 
     pub async fn compact_session(&mut self) -> Result<(usize, usize)> {
@@ -591,6 +611,43 @@ impl Agent {
             .await
     }
 
+    /// Turns of the current session that changed files (see [`checkpoints`])
+    pub async fn checkpoints(&self) -> Result<Vec<TurnCheckpoint>> {
+        let session_id = self.session_manager.session().read().await.id().to_string();
+        self.checkpoint_store().list(&session_id)
+    }
+
+    /// Put files the agent changed back the way they were before checkpoint
+    /// `turn` (the last one by default). Checkpoints count only turns that
+    /// changed files; see [`checkpoints`]. With `dry_run`, only say what
+    /// would change. Fails when `tools.checkpoints` is off.
+    pub async fn rewind(&self, turn: Option<usize>, dry_run: bool) -> Result<RewindPlan> {
+        if !self.app_config.tools.checkpoints {
+            anyhow::bail!("File checkpoints are disabled (tools.checkpoints = false)");
+        }
+        let session_id = self.session_manager.session().read().await.id().to_string();
+        let store = self.checkpoint_store();
+        tokio::task::spawn_blocking(move || {
+            let turn = match turn {
+                Some(turn) => turn,
+                None => match store.list(&session_id)?.last() {
+                    Some(last) => last.turn,
+                    None => anyhow::bail!("No file changes to rewind in this session"),
+                },
+            };
+            if dry_run {
+                store.preview(&session_id, turn)
+            } else {
+                store.rewind(&session_id, turn)
+            }
+        })
+        .await?
+    }
+
+    fn checkpoint_store(&self) -> CheckpointStore {
+        CheckpointStore::for_workspace(&self.app_config.workspace_path())
+    }
+
     /// Get the current system prompt (context) of the active session.
     pub async fn system_prompt(&self) -> Option<String> {
         self.session_manager
@@ -698,6 +755,59 @@ impl Agent {
         self.tool_executor.approve_tool_call(call_id);
     }
 
+    /// Calls of the last assistant message still waiting for a result
+    pub async fn pending_tool_calls(&self) -> Vec<ToolCall> {
+        self.chat_engine.pending_tool_calls().await
+    }
+
+    /// Answer a pending call of this session, as any front-end does.
+    /// Approvals reaching further than `Approve` also approve calls like it
+    /// from now on (see [`crate::security::approval`]) and return the allow
+    /// rule added; a denial gives the model `reason` as the call's result.
+    pub async fn resolve_tool_call(
+        &mut self,
+        call_id: &str,
+        decision: ApprovalDecision,
+        reason: Option<&str>,
+    ) -> Result<Option<ApprovalRule>> {
+        let Some(scope) = decision.scope() else {
+            self.reject_tool_call(call_id, reason).await?;
+            return Ok(None);
+        };
+        let call = self
+            .pending_tool_calls()
+            .await
+            .into_iter()
+            .find(|c| c.id == call_id)
+            .ok_or_else(|| anyhow::anyhow!("No pending tool call: {}", call_id))?;
+        let rule = self.grant_tool_call(&call, scope)?;
+        self.approve_tool_call(call_id);
+        Ok(rule)
+    }
+
+    /// Approve calls like `call` for `scope`; returns the allow rule added
+    pub fn grant_tool_call(
+        &self,
+        call: &ToolCall,
+        scope: GrantScope,
+    ) -> Result<Option<ApprovalRule>> {
+        self.tool_executor.grant(call, scope)
+    }
+
+    /// Reject a pending call: the model gets "User denied this tool call"
+    /// (with `reason`) as its result and can change plans when the tool
+    /// loop continues
+    pub async fn reject_tool_call(&mut self, call_id: &str, reason: Option<&str>) -> Result<()> {
+        self.chat_engine
+            .reject_tool_call(call_id.to_string(), reason)
+            .await
+    }
+
+    /// Why a call needs approval (e.g. the turn saw untrusted content)
+    pub fn approval_reason(&self, call: &ToolCall) -> Option<String> {
+        self.tool_executor.approval_reason(call)
+    }
+
     pub async fn resume_chat_stream_with_tools(
         &mut self,
     ) -> Result<impl futures::Stream<Item = Result<StreamEvent>> + '_> {
@@ -136,6 +136,8 @@ pub enum StreamEvent {
         name: String,
         id: String,
         arguments: String,
+        /// Why, when it depends on the call or the turn (e.g. taint)
+        reason: Option<String>,
     },
     /// Tool call completed
     ToolCallEnd {
@@ -50,6 +50,12 @@ pub fn build_system_prompt(params: SystemPromptParams) -> String {
             .to_string(),
     );
     lines.push(String::new());
+    lines.push(
+        "Secrets are shown as placeholders like `[secret:github_token:1a2b3c4d]`. \
+         Pass a placeholder unchanged in tool arguments and the tool receives the real value."
+            .to_string(),
+    );
+    lines.push(String::new());
 
     // Tooling section
     if !params.tool_names.is_empty() {
@@ -168,13 +174,24 @@ pub fn build_system_prompt(params: SystemPromptParams) -> String {
     lines.push("- SOUL.md: Your persona and tone guidance (if present)".to_string());
     lines.push("- memory/YYYY-MM-DD.md: Daily logs for session notes".to_string());
     lines.push(String::new());
-    lines.push(
-        "CRITICAL: To save or remember information, you MUST EXPLICITLY call write_file or edit_file. \
-         Simply stating that you have saved or remembered something is NOT enough and will NOT persist. \
-         Use MEMORY.md for important persistent facts (names, preferences, project details). \
-         Always use read_file first to check current content before editing."
-            .to_string(),
-    );
+    if params.tool_names.contains(&"memory_write") {
+        lines.push(
+            "CRITICAL: To save or remember information, you MUST EXPLICITLY call memory_write \
+             (or write_file/edit_file for other files). \
+             Simply stating that you have saved or remembered something is NOT enough and will NOT persist. \
+             Use memory_write for important persistent facts (names, preferences, project details); \
+             if it reports a contradiction, resolve it with the user instead of overwriting blindly."
+                .to_string(),
+        );
+    } else {
+        lines.push(
+            "CRITICAL: To save or remember information, you MUST EXPLICITLY call write_file or edit_file. \
+             Simply stating that you have saved or remembered something is NOT enough and will NOT persist. \
+             Use MEMORY.md for important persistent facts (names, preferences, project details). \
+             Always use read_file first to check current content before editing."
+                .to_string(),
+        );
+    }
     lines.push(String::new());
 
     // Memory recall guidance
@@ -191,6 +208,13 @@ pub fn build_system_prompt(params: SystemPromptParams) -> String {
                     .to_string(),
             );
         }
+        if params.tool_names.contains(&"memory_graph") {
+            lines.push(
+                "Notes link to each other with [[wikilinks]]; use memory_graph to find related people, \
+                 projects and decisions (neighbors, backlinks, path)."
+                    .to_string(),
+            );
+        }
         lines.push(
             "If low confidence after search, say you checked but found no relevant notes."
                 .to_string(),
@@ -346,8 +370,12 @@ fn get_tool_summary(tool_name: &str) -> &'static str {
         "read_file" => "Read file contents",
         "write_file" => "Create or overwrite files",
         "edit_file" => "Make precise edits to files",
+        "edit_section" => "Replace one heading's section of a markdown file",
         "memory_search" => "Semantically search MEMORY.md + memory/*.md",
-        "memory_get" => "Fetch specific lines from memory files (use after memory_search)",
+        "memory_get" => "Fetch a section or lines of a memory file (use after memory_search)",
+        "memory_write" => "Save a fact to MEMORY.md with duplicate/conflict checks",
+        "memory_graph" => "Follow [[wikilinks]] and entity relations between memory notes",
+        "forget" => "Purge something the owner asked to forget from memory and transcripts",
         "web_fetch" => "Fetch and extract content from a URL",
         _ => "Tool",
     }
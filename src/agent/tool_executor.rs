@@ -1,10 +1,76 @@
 use crate::agent::sanitize;
 use crate::agent::{Tool, ToolCall, ToolSchema};
-use crate::config::Config;
+use crate::config::{ApprovalRule, Config, RuleAction, TaintAction};
+use crate::ingress::TrustLevel;
+use crate::security::approval::{self, ApprovalPolicy, GrantScope};
+use crate::security::secrets::SecretScanner;
+use crate::security::taint::TurnTaint;
+use crate::security::AuditLog;
 use anyhow::Result;
+use serde_json::json;
 use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use tracing::warn;
+use tracing::{debug, warn};
+
+tokio::task_local! {
+    /// Turn executing tools; tools read it for provenance and trust decisions.
+    static TOOL_TURN: ToolTurn;
+
+    /// Trust level of the message being handled
+    static TURN_TRUST: TrustLevel;
+}
+
+/// Run `f` (usually an agent turn) on behalf of a message of `trust`
+pub async fn with_trust<F: Future>(trust: TrustLevel, f: F) -> F::Output {
+    TURN_TRUST.scope(trust, f).await
+}
+
+/// Trust level tools run under. Owner outside [`with_trust`]: the CLI,
+/// desktop app and HTTP API act for the owner.
+pub fn current_trust() -> TrustLevel {
+    TURN_TRUST
+        .try_with(|trust| *trust)
+        .unwrap_or(TrustLevel::OwnerCommand)
+}
+
+/// Context of the turn a tool call belongs to
+#[derive(Clone)]
+struct ToolTurn {
+    session_id: String,
+    /// Start of the turn in ms since the epoch; keys file checkpoints
+    turn: u64,
+    /// Tool output is only sent to local LLM providers
+    local_provider: bool,
+}
+
+/// Session id of the turn currently executing tools, if any
+pub fn current_session_id() -> Option<String> {
+    TOOL_TURN.try_with(|turn| turn.session_id.clone()).ok()
+}
+
+/// Start of the turn currently executing tools (ms since the epoch), if
+/// any. Stays the same while a turn waits for approvals.
+pub fn current_turn() -> Option<u64> {
+    TOOL_TURN.try_with(|turn| turn.turn).ok()
+}
+
+/// Whether the current turn's tool output stays with local LLM providers.
+/// False outside a turn, where the destination is unknown.
+pub fn turn_uses_local_provider() -> bool {
+    TOOL_TURN
+        .try_with(|turn| turn.local_provider)
+        .unwrap_or(false)
+}
+
+/// Tool result given to the model for a call the user rejected
+pub fn rejection_output(reason: Option<&str>) -> String {
+    match reason.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(reason) => format!("User denied this tool call: {}", reason),
+        None => "User denied this tool call".to_string(),
+    }
+}
 
 #[derive(Debug, thiserror::Error)]
 #[error("Tool '{0}' requires approval")]
@@ -15,6 +81,23 @@ pub struct ToolExecutor {
     tools: Vec<Arc<dyn Tool>>,
     config: Config,
     approval_manager: Arc<ApprovalManager>,
+    secrets: Option<Arc<SecretScanner>>,
+    taint: Arc<TurnTaint>,
+    policy: Arc<ApprovalPolicy>,
+    audit: AuditLog,
+    /// See [`current_turn`]
+    turn_started: Arc<AtomicU64>,
+}
+
+/// What happens to a tool call before it runs
+enum Gate {
+    Allow,
+    /// Needs the user's approval, with the reason when there is one
+    Ask(Option<String>),
+    /// Refused by an approval rule
+    Deny(String),
+    /// Refused by taint tracking
+    Block(String),
 }
 
 pub struct ApprovalManager {
@@ -34,6 +117,10 @@ impl ApprovalManager {
         set.insert(call_id.to_string());
     }
 
+    pub fn is_approved(&self, call_id: &str) -> bool {
+        self.approved_calls.lock().unwrap().contains(call_id)
+    }
+
     pub fn consume(&self, call_id: &str) -> bool {
         let mut set = self.approved_calls.lock().unwrap();
         set.remove(call_id)
@@ -48,13 +135,30 @@ impl Default for ApprovalManager {
 
 impl ToolExecutor {
     pub fn new(tools: Vec<Arc<dyn Tool>>, config: Config) -> Self {
+        let secrets = SecretScanner::from_config(&config);
+        let audit = AuditLog::for_workspace(&config.workspace_path());
+        let policy = Arc::new(ApprovalPolicy::new(&config.tools.approval_rules));
         Self {
             tools,
             config,
             approval_manager: Arc::new(ApprovalManager::new()),
+            secrets,
+            taint: Arc::new(TurnTaint::new()),
+            policy,
+            audit,
+            turn_started: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Load and save "always" grants for `agent_id` (see
+    /// [`crate::security::approval`])
+    pub fn with_grants(mut self, agent_id: &str) -> Self {
+        let policy = ApprovalPolicy::new(&self.config.tools.approval_rules)
+            .with_store(&self.config.workspace_path(), agent_id);
+        self.policy = Arc::new(policy);
+        self
+    }
+
     pub fn tools(&self) -> &[Arc<dyn Tool>] {
         &self.tools
     }
@@ -75,6 +179,114 @@ impl ToolExecutor {
             .any(|t| t == tool_name)
     }
 
+    fn tool(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.iter().find(|t| t.name() == name)
+    }
+
+    /// Whether this call needs approval: an `ask` rule or its tool's entry
+    /// in `tools.require_approval` (unless an `allow` rule or grant covers
+    /// it), the tool flags these arguments, or the turn is tainted. Calls
+    /// that will be refused outright don't need it.
+    pub fn call_requires_approval(&self, call: &ToolCall) -> bool {
+        matches!(self.gate(call), Gate::Ask(_))
+    }
+
+    /// Why this call needs approval, to show the user. `None` for tools
+    /// that always need it (`tools.require_approval`).
+    pub fn approval_reason(&self, call: &ToolCall) -> Option<String> {
+        match self.gate(call) {
+            Gate::Ask(reason) => reason,
+            _ => None,
+        }
+    }
+
+    /// Decide a call before it runs. Deny rules win, then taint tracking,
+    /// then what the tool flags itself; `allow` rules and grants only lift
+    /// `ask` rules and `tools.require_approval`.
+    fn gate(&self, call: &ToolCall) -> Gate {
+        let tool = self.tool(&call.name);
+        let rule = self
+            .policy
+            .evaluate(call, &|path| tool.and_then(|t| t.target_path(path)));
+        if let Some((RuleAction::Deny, reason)) = &rule {
+            return Gate::Deny(
+                reason
+                    .clone()
+                    .unwrap_or_else(|| format!("'{}' matches a deny rule", call.name)),
+            );
+        }
+        match self.taint_verdict(call) {
+            Some((TaintAction::Block, reason)) => return Gate::Block(reason),
+            Some((TaintAction::Approve, reason)) => return Gate::Ask(Some(reason)),
+            None => {}
+        }
+        if let Some(reason) = self
+            .tool(&call.name)
+            .and_then(|t| t.approval_reason(&call.arguments))
+        {
+            return Gate::Ask(Some(reason));
+        }
+        match rule {
+            Some((RuleAction::Allow, _)) => Gate::Allow,
+            Some((RuleAction::Ask, reason)) => Gate::Ask(reason),
+            _ if self.requires_approval(&call.name) => Gate::Ask(None),
+            _ => Gate::Allow,
+        }
+    }
+
+    /// Remember the user's approval of `call` for `scope`: later calls like
+    /// it skip the prompt. Returns the allow rule that was added.
+    pub fn grant(&self, call: &ToolCall, scope: GrantScope) -> Result<Option<ApprovalRule>> {
+        let tool = self.tool(&call.name);
+        let rule = self
+            .policy
+            .grant(call, scope, &|path| tool.and_then(|t| t.target_path(path)))?;
+        if let Some(rule) = &rule {
+            debug!("Granted {} ({:?})", approval::describe(rule), scope);
+            self.audit.record(
+                "approval_grant",
+                json!({ "tool": call.name, "scope": scope, "rule": rule }),
+            );
+        }
+        Ok(rule)
+    }
+
+    /// What taint tracking does with this call: nothing unless the turn saw
+    /// untrusted content and the call is privileged (see
+    /// [`crate::security::taint`])
+    pub fn taint_verdict(&self, call: &ToolCall) -> Option<(TaintAction, String)> {
+        let policy = &self.config.tools.taint;
+        if !policy.enabled {
+            return None;
+        }
+        let privileged = policy.privileged_tools.contains(&call.name)
+            || self
+                .tool(&call.name)
+                .is_some_and(|t| t.privileged(&call.arguments));
+        if !privileged {
+            return None;
+        }
+        let reason = self.taint.reason(&call.name)?;
+        Some((policy.action, reason))
+    }
+
+    /// A new user message starts a turn without untrusted content, with
+    /// its own file checkpoints
+    pub fn begin_turn(&self) {
+        self.taint.clear();
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let _ = self
+            .turn_started
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            });
+    }
+
+    /// Untrusted sources seen so far this turn
+    pub fn taint_sources(&self) -> Vec<String> {
+        self.taint.sources()
+    }
+
     pub fn approval_required_tools(&self) -> &[String] {
         &self.config.tools.require_approval
     }
@@ -83,9 +295,54 @@ impl ToolExecutor {
         self.approval_manager.approve(call_id);
     }
 
+    /// Whether the call was approved and hasn't run yet
+    pub fn is_approved(&self, call_id: &str) -> bool {
+        self.approval_manager.is_approved(call_id)
+    }
+
+    /// Execute a tool call on behalf of a session (see [`current_session_id`]).
+    /// `local_provider` tells tools whether their output stays on this machine.
+    pub async fn execute_tool_for_session(
+        &self,
+        call: &ToolCall,
+        session_id: &str,
+        local_provider: bool,
+    ) -> Result<String> {
+        // Calls before the first turn (resumed sessions) start one
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let _ = self
+            .turn_started
+            .compare_exchange(0, now, Ordering::SeqCst, Ordering::SeqCst);
+        let turn = ToolTurn {
+            session_id: session_id.to_string(),
+            turn: self.turn_started.load(Ordering::SeqCst),
+            local_provider,
+        };
+        TOOL_TURN.scope(turn, self.execute_tool(call)).await
+    }
+
     pub async fn execute_tool(&self, call: &ToolCall) -> Result<String> {
+        let verdict = self.taint_verdict(call);
+        let gate = self.gate(call);
+        if let Gate::Deny(reason) = &gate {
+            warn!("Refused tool call: {}", reason);
+            anyhow::bail!("Denied by approval rule: {}", reason);
+        }
+        if let Gate::Block(reason) = &gate {
+            warn!("Refused tool call: {}", reason);
+            self.audit.record(
+                "tainted_call",
+                json!({
+                    "tool": call.name,
+                    "decision": "blocked",
+                    "sources": self.taint.sources(),
+                }),
+            );
+            anyhow::bail!("Blocked by taint policy: {}", reason);
+        }
+
         // Check approval
-        if self.requires_approval(&call.name) {
+        if let Gate::Ask(_) = gate {
             if !self.approval_manager.consume(&call.id) {
                 // Return special error that ChatEngine can catch?
                 // Or just bail.
@@ -99,11 +356,37 @@ impl ToolExecutor {
                     call.clone(),
                 )));
             }
+            if verdict.is_some() {
+                self.audit.record(
+                    "tainted_call",
+                    json!({
+                        "tool": call.name,
+                        "decision": "approved",
+                        "sources": self.taint.sources(),
+                    }),
+                );
+            }
         }
 
         for tool in &self.tools {
             if tool.name() == call.name {
-                let raw_output = tool.execute(&call.arguments).await?;
+                // Placeholders of redacted secrets become the real values
+                // again, but only for local programs
+                let arguments = match &self.secrets {
+                    Some(secrets)
+                        if tool.runs_locally()
+                            || secrets.config().reveal_tools.contains(&call.name) =>
+                    {
+                        secrets.reveal(&call.name, &call.arguments)
+                    }
+                    _ => call.arguments.clone(),
+                };
+                let raw_output = tool.execute(&arguments).await?;
+                self.record_untrusted(tool.as_ref(), &arguments);
+                let secrets = self
+                    .secrets
+                    .as_deref()
+                    .filter(|s| s.config().redact_tool_output);
 
                 // Apply sanitization if configured
                 if self.config.tools.use_content_delimiters {
@@ -112,7 +395,8 @@ impl ToolExecutor {
                     } else {
                         None
                     };
-                    let result = sanitize::wrap_tool_output(&call.name, &raw_output, max_chars);
+                    let result =
+                        sanitize::wrap_tool_output(&call.name, &raw_output, max_chars, secrets);
 
                     // Log warnings for suspicious patterns
                     if self.config.tools.log_injection_warnings && !result.warnings.is_empty() {
@@ -125,9 +409,31 @@ impl ToolExecutor {
                     return Ok(result.content);
                 }
 
+                if let Some(secrets) = secrets {
+                    return Ok(secrets.redact(&raw_output, &format!("tool:{}", call.name)));
+                }
                 return Ok(raw_output);
             }
         }
         anyhow::bail!("Unknown tool: {}", call.name)
     }
+
+    /// Taint the turn if this call brought in untrusted content
+    fn record_untrusted(&self, tool: &dyn Tool, arguments: &str) {
+        let policy = &self.config.tools.taint;
+        if !policy.enabled {
+            return;
+        }
+        let source = tool.untrusted_source(arguments).or_else(|| {
+            policy
+                .untrusted_tools
+                .iter()
+                .any(|t| t == tool.name())
+                .then(|| tool.name().to_string())
+        });
+        if let Some(source) = source {
+            debug!("Turn tainted by {}", source);
+            self.taint.record(source);
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub mod external;
 pub mod mcp;
@@ -14,10 +15,23 @@ pub mod runner;
 pub mod script;
 pub mod system;
 
+use super::checkpoints::CheckpointStore;
 use super::providers::ToolSchema;
+use crate::agent::tool_executor::current_trust;
 use crate::agent::DiskMonitor;
-use crate::config::{Config, SandboxPolicy, WorkdirStrategy};
-use crate::memory::MemoryManager;
+use crate::config::{
+    BashProfile, Config, FederatedWorkspaceConfig, SandboxPolicy, WorkdirStrategy,
+};
+use crate::ingress::TrustLevel;
+use crate::memory::{
+    find_section, parse_date_bound, private_line_ranges, replace_section, split_anchor,
+    ConflictMode, FactWrite, ForgetOptions, MemoryManager, PrivacyPolicy, SearchFilter,
+    WriteOutcome, WITHHELD,
+};
+use crate::scripting::safety::CommandSafety;
+use crate::scripting::SafetyPolicy;
+use crate::security::egress::{Egress, EgressAllowlist};
+use crate::security::{limits, taint, AuditLog};
 pub use script::ScriptTool;
 
 use path::{check_path_permitted, PermissionMode};
@@ -33,8 +47,41 @@ pub trait Tool: Send + Sync {
     fn name(&self) -> &str;
     fn schema(&self) -> ToolSchema;
     async fn execute(&self, arguments: &str) -> Result<String>;
+
+    /// Why this particular call needs the user's approval, for tools that
+    /// are only risky with some arguments
+    fn approval_reason(&self, _arguments: &str) -> Option<String> {
+        None
+    }
+
+    /// Where this call's output comes from when it can't be trusted (web
+    /// pages, MCP servers, downloaded files). It taints the rest of the turn.
+    fn untrusted_source(&self, _arguments: &str) -> Option<String> {
+        None
+    }
+
+    /// Whether this call is gated once the turn is tainted (see
+    /// [`crate::security::taint`])
+    fn privileged(&self, _arguments: &str) -> bool {
+        false
+    }
+
+    /// Whether the tool runs a local program that may be given the real
+    /// values of redacted secrets (see [`crate::security::secrets`])
+    fn runs_locally(&self) -> bool {
+        false
+    }
+
+    /// The file a `path` argument names, resolved the way `execute` would.
+    /// Approval rules and grants match this rather than the raw argument.
+    fn target_path(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
 }
 
+/// Tools only the owner may trigger; scheduled jobs never get them
+pub const OWNER_ONLY_TOOLS: &[&str] = &["forget"];
+
 pub fn create_default_tools(
     config: &Config,
     memory: Option<Arc<MemoryManager>>,
@@ -59,38 +106,91 @@ pub fn create_default_tools_with_project(
     };
 
     let strategy = &config.workdir.strategy;
-
-    Ok(vec![
-        Arc::new(BashTool::new(
-            workspace.clone(),
-            project_dir.clone(),
-            strategy.clone(),
-            config.tools.bash_timeout_ms,
-        )),
-        Arc::new(ReadFileTool::new(
-            workspace.clone(),
-            project_dir.clone(),
-            strategy.clone(),
-            config.sandbox.clone(),
-        )),
-        Arc::new(WriteFileTool::new(
-            workspace.clone(),
-            project_dir.clone(),
-            strategy.clone(),
-            disk_monitor.clone(),
-            config.sandbox.clone(),
-        )),
-        Arc::new(EditFileTool::new(
-            workspace.clone(),
-            project_dir.clone(),
-            strategy.clone(),
-            disk_monitor.clone(),
-            config.sandbox.clone(),
-        )),
+    let audit = AuditLog::for_workspace(&workspace);
+    let privacy = PrivacyGuard::new(
+        PrivacyPolicy::new(&config.memory.private_paths),
+        audit.clone(),
+    );
+    let checkpoints = config
+        .tools
+        .checkpoints
+        .then(|| CheckpointStore::for_workspace(&workspace));
+
+    let mut tools: Vec<Arc<dyn Tool>> = vec![
+        Arc::new(
+            BashTool::new(
+                workspace.clone(),
+                project_dir.clone(),
+                strategy.clone(),
+                config.tools.bash_timeout_ms,
+            )
+            .with_sandbox(config.sandbox.for_tool("bash")),
+        ),
+        Arc::new(
+            ReadFileTool::new(
+                workspace.clone(),
+                project_dir.clone(),
+                strategy.clone(),
+                config.sandbox.clone(),
+            )
+            .with_privacy(privacy.clone())
+            .with_untrusted_paths(taint::untrusted_roots(&config.tools.taint)),
+        ),
+        Arc::new(
+            WriteFileTool::new(
+                workspace.clone(),
+                project_dir.clone(),
+                strategy.clone(),
+                disk_monitor.clone(),
+                config.sandbox.clone(),
+            )
+            .with_checkpoints(checkpoints.clone()),
+        ),
+        Arc::new(
+            EditFileTool::new(
+                workspace.clone(),
+                project_dir.clone(),
+                strategy.clone(),
+                disk_monitor.clone(),
+                config.sandbox.clone(),
+            )
+            .with_checkpoints(checkpoints.clone()),
+        ),
+        Arc::new(
+            EditSectionTool::new(
+                workspace.clone(),
+                project_dir.clone(),
+                strategy.clone(),
+                disk_monitor.clone(),
+                config.sandbox.clone(),
+            )
+            .with_privacy(privacy.clone())
+            .with_checkpoints(checkpoints.clone()),
+        ),
         memory_search_tool,
-        Arc::new(MemoryGetTool::new(workspace)),
-        Arc::new(WebFetchTool::new(config.tools.web_fetch_max_bytes)),
-    ])
+        Arc::new(
+            MemoryGetTool::new(workspace)
+                .with_federation(&config.memory.federation)
+                .with_privacy(privacy.clone()),
+        ),
+        Arc::new(
+            WebFetchTool::new(config.tools.web_fetch_max_bytes)
+                .with_sandbox(&config.sandbox.for_tool("web_fetch"), Some(audit)),
+        ),
+    ];
+
+    // Graph queries and structured writes need the index
+    if let Some(mem) = memory {
+        tools.push(Arc::new(
+            MemoryWriteTool::new(Arc::clone(&mem)).with_checkpoints(checkpoints),
+        ));
+        tools.push(Arc::new(
+            MemoryGraphTool::new(Arc::clone(&mem)).with_privacy(privacy),
+        ));
+        tools.push(Arc::new(ForgetTool::new(mem)));
+    }
+
+    Ok(tools)
 }
 
 /// Helper to resolve a path based on strategy and cognitive routing
@@ -134,6 +234,28 @@ pub fn resolve_path(
     }
 }
 
+/// Whether a file tool's `path` argument (`file.md#Heading` when `anchored`)
+/// lands outside the workspace, which makes writing it privileged in a
+/// tainted turn. Unreadable arguments count as outside.
+fn writes_outside_workspace(
+    arguments: &str,
+    anchored: bool,
+    workspace: &PathBuf,
+    project_dir: &PathBuf,
+    strategy: &WorkdirStrategy,
+) -> bool {
+    let args: Value = match serde_json::from_str(arguments) {
+        Ok(args) => args,
+        Err(_) => return true,
+    };
+    let Some(path) = args["path"].as_str() else {
+        return true;
+    };
+    let path = if anchored { split_anchor(path).0 } else { path };
+    let resolved_path = resolve_path(path, workspace, project_dir, strategy);
+    !path::is_within(&resolved_path, workspace)
+}
+
 /// Check if a path refers to a cognitive memory file
 pub fn is_cognitive_path(path: &str) -> bool {
     let p = path.to_lowercase();
@@ -153,6 +275,8 @@ pub struct BashTool {
     project_dir: PathBuf,
     strategy: WorkdirStrategy,
     default_timeout_ms: u64,
+    /// OS sandbox settings and the per-trust profiles (`[sandbox.bash]`)
+    sandbox: SandboxPolicy,
 }
 
 impl BashTool {
@@ -167,8 +291,41 @@ impl BashTool {
             project_dir,
             strategy,
             default_timeout_ms,
+            sandbox: SandboxPolicy::default(),
         }
     }
+
+    pub fn with_sandbox(mut self, sandbox: SandboxPolicy) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    fn run_dir(&self) -> &PathBuf {
+        match self.strategy {
+            WorkdirStrategy::Overlay => &self.project_dir,
+            WorkdirStrategy::Mount => &self.workspace,
+        }
+    }
+
+    /// Profile for the trust level of the current turn
+    fn profile(&self) -> BashProfile {
+        match current_trust() {
+            TrustLevel::OwnerCommand => self.sandbox.bash.owner,
+            _ => self.sandbox.bash.trusted,
+        }
+    }
+
+    /// Classify a command with the `SafetyPolicy` script `exec` calls use.
+    /// Shell chaining is only refused by the strict profile: `bash -c` is a shell.
+    fn classify(&self, command: &str, profile: BashProfile) -> Result<CommandSafety> {
+        if profile == BashProfile::Unrestricted {
+            return Ok(CommandSafety::Allowed);
+        }
+        let argv = vec!["bash".to_string(), "-c".to_string(), command.to_string()];
+        SafetyPolicy::new(self.project_dir.clone(), self.workspace.clone())
+            .with_shell_chaining(profile != BashProfile::Strict)
+            .check_command(&argv, Some(self.run_dir()))
+    }
 }
 
 #[async_trait]
@@ -198,6 +355,23 @@ impl Tool for BashTool {
         }
     }
 
+    fn approval_reason(&self, arguments: &str) -> Option<String> {
+        let args: Value = serde_json::from_str(arguments).ok()?;
+        let command = args["command"].as_str()?;
+        match self.classify(command, self.profile()) {
+            Ok(CommandSafety::RequireApproval(reason)) => Some(reason),
+            _ => None,
+        }
+    }
+
+    fn privileged(&self, _arguments: &str) -> bool {
+        true
+    }
+
+    fn runs_locally(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args: Value = serde_json::from_str(arguments)?;
         let command = args["command"]
@@ -208,27 +382,47 @@ impl Tool for BashTool {
             .as_u64()
             .unwrap_or(self.default_timeout_ms);
 
-        let run_dir = match self.strategy {
-            WorkdirStrategy::Overlay => &self.project_dir,
-            WorkdirStrategy::Mount => &self.workspace,
-        };
+        let run_dir = self.run_dir();
+        let profile = self.profile();
+
+        // RequireApproval was settled by the tool executor before this call
+        match self.classify(command, profile)? {
+            CommandSafety::HardBlock(reason) => {
+                warn!("Blocking bash command: {}", reason);
+                anyhow::bail!("Command blocked by safety policy: {}", reason);
+            }
+            CommandSafety::SoftBlock(reason) => warn!("Soft block triggered: {}", reason),
+            CommandSafety::Allowed | CommandSafety::RequireApproval(_) => {}
+        }
 
         debug!(
-            "Executing bash command in {:?} (timeout: {}ms): {}",
-            run_dir, timeout_ms, command
+            "Executing bash command in {:?} (timeout: {}ms, profile: {:?}): {}",
+            run_dir, timeout_ms, profile, command
         );
 
         // Run command with timeout
         let timeout_duration = std::time::Duration::from_millis(timeout_ms);
-        let mut cmd = tokio::process::Command::new("bash");
-        cmd.arg("-c")
-            .arg(command)
-            .current_dir(run_dir)
-            .kill_on_drop(true);
-
-        let output = tokio::time::timeout(timeout_duration, cmd.output())
-        .await
-        .map_err(|_| anyhow::anyhow!("Command timed out after {}ms", timeout_ms))??;
+        let output = if profile == BashProfile::Unrestricted {
+            let mut cmd = tokio::process::Command::new("bash");
+            cmd.arg("-c")
+                .arg(command)
+                .current_dir(run_dir)
+                .kill_on_drop(true);
+            tokio::time::timeout(timeout_duration, limits::output(cmd, &self.sandbox.limits))
+                .await
+                .map_err(|_| anyhow::anyhow!("Command timed out after {}ms", timeout_ms))??
+        } else {
+            let mut policy = self.sandbox.clone();
+            policy.enable_os_sandbox |= profile == BashProfile::Strict;
+            let args = vec!["-c".to_string(), command.to_string()];
+            let audit = AuditLog::for_workspace(&self.workspace);
+            tokio::time::timeout(
+                timeout_duration,
+                runner::run_sandboxed_command("bash", &args, run_dir, None, &policy, Some(&audit)),
+            )
+            .await
+            .map_err(|_| anyhow::anyhow!("Command timed out after {}ms", timeout_ms))??
+        };
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -257,12 +451,147 @@ impl Tool for BashTool {
     }
 }
 
+/// Withholds private memory (`memory.private_paths`, `<!-- private -->`
+/// blocks) from file reads and graph queries whose output goes to a
+/// non-local provider
+#[derive(Clone, Default)]
+pub struct PrivacyGuard {
+    policy: PrivacyPolicy,
+    audit: Option<AuditLog>,
+}
+
+impl PrivacyGuard {
+    pub fn new(policy: PrivacyPolicy, audit: AuditLog) -> Self {
+        Self {
+            policy,
+            audit: Some(audit),
+        }
+    }
+
+    /// Private line ranges of a workspace file (relative path `file`), or
+    /// None if the whole file is private. Nothing is private for local providers.
+    fn private_ranges(&self, file: &str, content: &str) -> Option<Vec<(usize, usize)>> {
+        if super::tool_executor::turn_uses_local_provider() {
+            return Some(Vec::new());
+        }
+        if self.policy.is_private_path(file) {
+            return None;
+        }
+        Some(private_line_ranges(content))
+    }
+
+    /// Number `lines[start..end]`, withholding private ones, and audit-log
+    /// what was withheld
+    fn number_lines(
+        &self,
+        tool: &str,
+        file: &str,
+        lines: &[&str],
+        start: usize,
+        end: usize,
+        private: &[(usize, usize)],
+    ) -> String {
+        let mut out = Vec::new();
+        let mut withheld = 0;
+        let mut in_run = false;
+        for (i, line) in lines[start..end].iter().enumerate() {
+            let n = start + i + 1;
+            if private.iter().any(|&(s, e)| n >= s && n <= e) {
+                if !in_run {
+                    out.push(format!("{:4}\t{}", n, WITHHELD));
+                    withheld += 1;
+                    in_run = true;
+                }
+            } else {
+                out.push(format!("{:4}\t{}", n, line));
+                in_run = false;
+            }
+        }
+        if withheld > 0 {
+            self.record(tool, file, withheld, false);
+        }
+        out.join("\n")
+    }
+
+    /// Refusal for a wholly private file
+    fn refuse(&self, tool: &str, file: &str) -> String {
+        self.record(tool, file, 1, true);
+        format!(
+            "{} is marked private and is not shared with cloud providers",
+            file
+        )
+    }
+
+    /// Drop graph edges extracted from private files or blocks under
+    /// `workspace`, and audit-log what was dropped
+    fn filter_edges(
+        &self,
+        tool: &str,
+        workspace: &Path,
+        edges: Vec<crate::memory::GraphEdge>,
+    ) -> Vec<crate::memory::GraphEdge> {
+        if super::tool_executor::turn_uses_local_provider() {
+            return edges;
+        }
+        // Private ranges by file; read once per file
+        let mut files: HashMap<String, Option<Vec<(usize, usize)>>> = HashMap::new();
+        let mut dropped: BTreeMap<String, usize> = BTreeMap::new();
+        let mut kept = Vec::new();
+        for edge in edges {
+            let Some(path) = &edge.path else {
+                kept.push(edge);
+                continue;
+            };
+            let private = files.entry(path.clone()).or_insert_with(|| {
+                let content = std::fs::read_to_string(workspace.join(path)).unwrap_or_default();
+                self.private_ranges(path, &content)
+            });
+            let withheld = match (private, edge.line) {
+                (None, _) => true,
+                (Some(ranges), Some(line)) => {
+                    let line = line as usize;
+                    ranges.iter().any(|&(s, e)| line >= s && line <= e)
+                }
+                (Some(_), None) => false,
+            };
+            if withheld {
+                *dropped.entry(path.clone()).or_default() += 1;
+            } else {
+                kept.push(edge);
+            }
+        }
+        for (file, count) in dropped {
+            let whole = matches!(files.get(&file), Some(None));
+            self.record(tool, &file, count, whole);
+        }
+        kept
+    }
+
+    fn record(&self, tool: &str, file: &str, blocks: usize, dropped: bool) {
+        if let Some(audit) = &self.audit {
+            audit.record(
+                "private_withheld",
+                json!({
+                    "context": tool,
+                    "file": file,
+                    "blocks": blocks,
+                    "dropped": dropped,
+                    "session": super::tool_executor::current_session_id(),
+                }),
+            );
+        }
+    }
+}
+
 // Read File Tool
 pub struct ReadFileTool {
     workspace: PathBuf,
     project_dir: PathBuf,
     strategy: WorkdirStrategy,
     policy: SandboxPolicy,
+    privacy: PrivacyGuard,
+    /// Directories of untrusted files, e.g. downloads
+    untrusted_paths: Vec<PathBuf>,
 }
 
 impl ReadFileTool {
@@ -277,8 +606,22 @@ impl ReadFileTool {
             project_dir,
             strategy,
             policy,
+            privacy: PrivacyGuard::default(),
+            untrusted_paths: Vec::new(),
         }
     }
+
+    /// Withhold private memory when reading workspace files (builder pattern)
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Files under these directories taint the turn (builder pattern)
+    pub fn with_untrusted_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.untrusted_paths = paths;
+        self
+    }
 }
 
 #[async_trait]
@@ -312,6 +655,23 @@ impl Tool for ReadFileTool {
         }
     }
 
+    fn untrusted_source(&self, arguments: &str) -> Option<String> {
+        let args: Value = serde_json::from_str(arguments).ok()?;
+        let path = args["path"].as_str()?;
+        let resolved_path = resolve_path(path, &self.workspace, &self.project_dir, &self.strategy);
+        taint::is_untrusted_path(&resolved_path, &self.untrusted_paths)
+            .then(|| format!("read_file {}", resolved_path.display()))
+    }
+
+    fn target_path(&self, path: &str) -> Option<PathBuf> {
+        Some(resolve_path(
+            path,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        ))
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args: Value = serde_json::from_str(arguments)?;
         let path = args["path"]
@@ -344,13 +704,21 @@ impl Tool for ReadFileTool {
             .map(|l| (start + l).min(total_lines))
             .unwrap_or(total_lines);
 
-        let selected: Vec<String> = lines[start..end]
-            .iter()
-            .enumerate()
-            .map(|(i, line)| format!("{:4}\t{}", start + i + 1, line))
-            .collect();
+        // Private memory only applies inside the workspace
+        let private = match resolved_path.strip_prefix(&self.workspace) {
+            Ok(relative) => {
+                let file = relative.to_string_lossy();
+                match self.privacy.private_ranges(&file, &content) {
+                    Some(ranges) => ranges,
+                    None => return Ok(self.privacy.refuse("read_file", &file)),
+                }
+            }
+            Err(_) => Vec::new(),
+        };
 
-        Ok(selected.join("\n"))
+        Ok(self
+            .privacy
+            .number_lines("read_file", path, &lines, start, end, &private))
     }
 }
 
@@ -361,6 +729,7 @@ pub struct WriteFileTool {
     strategy: WorkdirStrategy,
     disk_monitor: Arc<DiskMonitor>,
     policy: SandboxPolicy,
+    checkpoints: Option<CheckpointStore>,
 }
 
 impl WriteFileTool {
@@ -377,8 +746,15 @@ impl WriteFileTool {
             strategy,
             disk_monitor,
             policy,
+            checkpoints: None,
         }
     }
+
+    /// Save the pre-image of files before changing them (builder pattern)
+    pub fn with_checkpoints(mut self, checkpoints: Option<CheckpointStore>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
 }
 
 #[async_trait]
@@ -408,6 +784,25 @@ impl Tool for WriteFileTool {
         }
     }
 
+    fn privileged(&self, arguments: &str) -> bool {
+        writes_outside_workspace(
+            arguments,
+            false,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        )
+    }
+
+    fn target_path(&self, path: &str) -> Option<PathBuf> {
+        Some(resolve_path(
+            path,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        ))
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         if self.disk_monitor.is_degraded() {
             return Err(anyhow::anyhow!(
@@ -435,6 +830,8 @@ impl Tool for WriteFileTool {
 
         debug!("Writing file: {}", resolved_path.display());
 
+        checkpoint(self.checkpoints.as_ref(), &resolved_path);
+
         // Create parent directories if needed
         if let Some(parent) = resolved_path.parent() {
             fs::create_dir_all(parent)?;
@@ -450,6 +847,16 @@ impl Tool for WriteFileTool {
     }
 }
 
+/// Save `path`'s pre-image for the current turn. A failure is logged and
+/// doesn't stop the edit.
+fn checkpoint(checkpoints: Option<&CheckpointStore>, path: &Path) {
+    if let Some(checkpoints) = checkpoints {
+        if let Err(e) = checkpoints.record(path) {
+            warn!("No checkpoint for {}: {}", path.display(), e);
+        }
+    }
+}
+
 // Edit File Tool
 pub struct EditFileTool {
     workspace: PathBuf,
@@ -457,6 +864,7 @@ pub struct EditFileTool {
     strategy: WorkdirStrategy,
     disk_monitor: Arc<DiskMonitor>,
     policy: SandboxPolicy,
+    checkpoints: Option<CheckpointStore>,
 }
 
 impl EditFileTool {
@@ -473,8 +881,15 @@ impl EditFileTool {
             strategy,
             disk_monitor,
             policy,
+            checkpoints: None,
         }
     }
+
+    /// Save the pre-image of files before changing them (builder pattern)
+    pub fn with_checkpoints(mut self, checkpoints: Option<CheckpointStore>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
 }
 
 #[async_trait]
@@ -512,6 +927,25 @@ impl Tool for EditFileTool {
         }
     }
 
+    fn privileged(&self, arguments: &str) -> bool {
+        writes_outside_workspace(
+            arguments,
+            false,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        )
+    }
+
+    fn target_path(&self, path: &str) -> Option<PathBuf> {
+        Some(resolve_path(
+            path,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        ))
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         if self.disk_monitor.is_degraded() {
             return Err(anyhow::anyhow!(
@@ -562,6 +996,7 @@ impl Tool for EditFileTool {
             return Err(anyhow::anyhow!("old_string not found in file"));
         };
 
+        checkpoint(self.checkpoints.as_ref(), &resolved_path);
         fs::write(&resolved_path, &new_content)?;
 
         Ok(format!(
@@ -572,6 +1007,183 @@ impl Tool for EditFileTool {
     }
 }
 
+// Edit Section Tool - replace one heading-addressed section of a markdown file
+pub struct EditSectionTool {
+    workspace: PathBuf,
+    project_dir: PathBuf,
+    strategy: WorkdirStrategy,
+    disk_monitor: Arc<DiskMonitor>,
+    policy: SandboxPolicy,
+    privacy: PrivacyGuard,
+    checkpoints: Option<CheckpointStore>,
+}
+
+impl EditSectionTool {
+    pub fn new(
+        workspace: PathBuf,
+        project_dir: PathBuf,
+        strategy: WorkdirStrategy,
+        disk_monitor: Arc<DiskMonitor>,
+        policy: SandboxPolicy,
+    ) -> Self {
+        Self {
+            workspace,
+            project_dir,
+            strategy,
+            disk_monitor,
+            policy,
+            privacy: PrivacyGuard::default(),
+            checkpoints: None,
+        }
+    }
+
+    /// Refuse to overwrite private memory the model can't see (builder pattern)
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Save the pre-image of files before changing them (builder pattern)
+    pub fn with_checkpoints(mut self, checkpoints: Option<CheckpointStore>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for EditSectionTool {
+    fn name(&self) -> &str {
+        "edit_section"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "edit_section".to_string(),
+            description: "Replace the content under one heading of a markdown file (e.g., 'MEMORY.md#Preferences/Editor'), including its subsections, leaving the rest of the file untouched. Read it first with memory_get.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File and heading path, e.g. 'MEMORY.md#Preferences/Editor'"
+                    },
+                    "section": {
+                        "type": "string",
+                        "description": "Heading path, if not given as a '#' anchor in path"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "New content below the heading (the heading itself is kept)"
+                    }
+                },
+                "required": ["path", "content"]
+            }),
+        }
+    }
+
+    fn privileged(&self, arguments: &str) -> bool {
+        writes_outside_workspace(
+            arguments,
+            true,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        )
+    }
+
+    fn target_path(&self, path: &str) -> Option<PathBuf> {
+        Some(resolve_path(
+            split_anchor(path).0,
+            &self.workspace,
+            &self.project_dir,
+            &self.strategy,
+        ))
+    }
+
+    async fn execute(&self, arguments: &str) -> Result<String> {
+        if self.disk_monitor.is_degraded() {
+            return Err(anyhow::anyhow!(
+                "Disk full - operation not permitted until space is freed."
+            ));
+        }
+
+        let args: Value = serde_json::from_str(arguments)?;
+        let (path, anchor) = split_anchor(
+            args["path"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Missing path"))?,
+        );
+        let anchor = args["section"]
+            .as_str()
+            .or(anchor)
+            .ok_or_else(|| anyhow::anyhow!("Missing section (use 'file.md#Heading/Sub')"))?;
+        let body = args["content"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing content"))?;
+
+        let resolved_path = resolve_path(path, &self.workspace, &self.project_dir, &self.strategy);
+        check_path_permitted(
+            &resolved_path,
+            &self.policy,
+            &self.workspace,
+            &self.project_dir,
+            PermissionMode::Read,
+        )?;
+        check_path_permitted(
+            &resolved_path,
+            &self.policy,
+            &self.workspace,
+            &self.project_dir,
+            PermissionMode::Write,
+        )?;
+
+        debug!("Editing section {} of {}", anchor, resolved_path.display());
+
+        // Held until the rename, so memory_write can't interleave
+        let _guard = MemoryManager::lock_file(&resolved_path).await;
+        let content = fs::read_to_string(&resolved_path)?;
+        let section = find_section(&content, anchor)?;
+
+        // The model never saw withheld lines, so it may not overwrite them
+        if let Ok(relative) = resolved_path.strip_prefix(&self.workspace) {
+            let file = relative.to_string_lossy();
+            let hidden = match self.privacy.private_ranges(&file, &content) {
+                Some(ranges) => ranges
+                    .iter()
+                    .any(|&(s, e)| s <= section.end_line && e >= section.heading_line),
+                None => true,
+            };
+            if hidden {
+                anyhow::bail!(
+                    "Section '{}' of {} holds private content and can't be replaced in this turn",
+                    section.anchor(),
+                    path
+                );
+            }
+        }
+
+        let (new_content, updated) = replace_section(&content, anchor, body)?;
+        checkpoint(self.checkpoints.as_ref(), &resolved_path);
+
+        // Write to a temp file then rename, so readers never see a partial file
+        let tmp_path = resolved_path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            uuid::Uuid::new_v4().as_simple()
+        ));
+        fs::write(&tmp_path, &new_content)?;
+        fs::rename(&tmp_path, &resolved_path)?;
+
+        Ok(format!(
+            "Replaced section {} in {} (now lines {}-{})",
+            updated.anchor(),
+            resolved_path.display(),
+            updated.heading_line,
+            updated.end_line
+        ))
+    }
+}
+
 // Memory Search Tool
 pub struct MemorySearchTool {
     workspace: PathBuf,
@@ -714,6 +1326,37 @@ impl Tool for MemorySearchToolWithIndex {
                     "limit": {
                         "type": "integer",
                         "description": "Maximum number of results (default: 5)"
+                    },
+                    "expand_graph": {
+                        "type": "boolean",
+                        "description": "Also return notes linked to the results via [[wikilinks]] (default: false)"
+                    },
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Only search files matching one of these globs (e.g. \"memory/*\", \"projects/**\")"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Skip files matching these globs"
+                    },
+                    "sources": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["memory", "text", "html", "pdf", "code"] },
+                        "description": "Only search these document kinds"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "Earliest file date: YYYY-MM-DD, today, yesterday, or relative like 7d / 2w (daily logs are dated by file name, other files by modification time)"
+                    },
+                    "until": {
+                        "type": "string",
+                        "description": "Latest file date, same formats as since"
+                    },
+                    "min_score": {
+                        "type": "number",
+                        "description": "Drop results scoring below this value"
                     }
                 },
                 "required": ["query"]
@@ -727,6 +1370,9 @@ impl Tool for MemorySearchToolWithIndex {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
         let limit = args["limit"].as_u64().unwrap_or(5) as usize;
+        let expand_graph = args["expand_graph"].as_bool().unwrap_or(false);
+        let mut filter = search_filter_from_args(&args)?;
+        filter.cloud_bound = !super::tool_executor::turn_uses_local_provider();
 
         let search_type = if self.memory.has_embeddings() {
             "hybrid"
@@ -738,7 +1384,13 @@ impl Tool for MemorySearchToolWithIndex {
             search_type, query, limit
         );
 
-        let results = self.memory.search(query, limit).await?;
+        let results = if expand_graph {
+            self.memory
+                .search_with_graph(query, limit, 1, &filter)
+                .await?
+        } else {
+            self.memory.search_filtered(query, limit, &filter).await?
+        };
 
         if results.is_empty() {
             return Ok("No results found".to_string());
@@ -751,9 +1403,16 @@ impl Tool for MemorySearchToolWithIndex {
             .map(|(i, chunk)| {
                 let preview: String = chunk.content.chars().take(200).collect();
                 let preview = preview.replace('\n', " ");
+                // memory_get takes the origin to read from a federated workspace
+                let origin = chunk
+                    .origin
+                    .as_ref()
+                    .map(|o| format!("[origin: {}] ", o))
+                    .unwrap_or_default();
                 format!(
-                    "{}. {} (lines {}-{}, score: {:.3})\n   {}{}",
+                    "{}. {}{} (lines {}-{}, score: {:.3})\n   {}{}",
                     i + 1,
+                    origin,
                     chunk.file,
                     chunk.line_start,
                     chunk.line_end,
@@ -768,19 +1427,92 @@ impl Tool for MemorySearchToolWithIndex {
     }
 }
 
+/// Build a search filter from memory_search tool arguments
+fn search_filter_from_args(args: &Value) -> Result<SearchFilter> {
+    let strings = |key: &str| -> Vec<String> {
+        match &args[key] {
+            Value::String(s) => vec![s.clone()],
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    let date = |key: &str| args[key].as_str().map(parse_date_bound).transpose();
+
+    Ok(SearchFilter {
+        paths: strings("paths"),
+        exclude: strings("exclude"),
+        sources: strings("sources"),
+        since: date("since")?,
+        until: date("until")?,
+        min_score: args["min_score"].as_f64(),
+        ..Default::default()
+    })
+}
+
 // Memory Get Tool - efficient snippet fetching after memory_search
 pub struct MemoryGetTool {
     workspace: PathBuf,
+    /// Federated workspaces readable by origin name: (workspace, allow_cloud)
+    federation: HashMap<String, (PathBuf, bool)>,
+    privacy: PrivacyGuard,
 }
 
 impl MemoryGetTool {
     pub fn new(workspace: PathBuf) -> Self {
-        Self { workspace }
+        Self {
+            workspace,
+            federation: HashMap::new(),
+            privacy: PrivacyGuard::default(),
+        }
+    }
+
+    /// Withhold private memory from non-local providers (builder pattern)
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Allow reads from the configured federated workspaces (builder pattern)
+    pub fn with_federation(mut self, federation: &[FederatedWorkspaceConfig]) -> Self {
+        for entry in federation {
+            self.federation.insert(
+                entry.name.clone(),
+                (entry.workspace_path(&self.workspace), entry.allow_cloud),
+            );
+        }
+        self
+    }
+
+    /// Resolve a path inside a federated workspace. Only relative paths that
+    /// stay within it are accepted.
+    fn resolve_federated(&self, origin: &str, path: &str) -> Result<PathBuf> {
+        let (workspace, allow_cloud) = self
+            .federation
+            .get(origin)
+            .ok_or_else(|| anyhow::anyhow!("Unknown memory origin: {}", origin))?;
+        if !allow_cloud && !super::tool_executor::turn_uses_local_provider() {
+            anyhow::bail!(
+                "Memory from '{}' may not be sent to a cloud provider",
+                origin
+            );
+        }
+        let relative = Path::new(path);
+        if relative.is_absolute()
+            || relative
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            anyhow::bail!("Paths in '{}' must be relative to its workspace", origin);
+        }
+        Ok(workspace.join(relative))
     }
 
     fn resolve_path(&self, path: &str) -> PathBuf {
-        // Handle paths relative to workspace
-        if path.starts_with("memory/") || path == "MEMORY.md" || path == "HEARTBEAT.md" {
+        // Cognitive files are relative to the workspace
+        if is_cognitive_path(path) {
             self.workspace.join(path)
         } else {
             PathBuf::from(shellexpand::tilde(path).to_string())
@@ -797,13 +1529,21 @@ impl Tool for MemoryGetTool {
     fn schema(&self) -> ToolSchema {
         ToolSchema {
             name: "memory_get".to_string(),
-            description: "Safe snippet read from MEMORY.md or memory/*.md with optional line range; use after memory_search to pull only the needed lines and keep context small.".to_string(),
+            description: "Safe snippet read from MEMORY.md or memory/*.md by heading or line range; use after memory_search to pull only the needed lines and keep context small.".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "path": {
                         "type": "string",
-                        "description": "Path to the file (e.g., 'MEMORY.md' or 'memory/2024-01-15.md')"
+                        "description": "Path to the file (e.g., 'MEMORY.md' or 'memory/2024-01-15.md'), optionally with a heading path ('MEMORY.md#Preferences/Editor') to read one section"
+                    },
+                    "section": {
+                        "type": "string",
+                        "description": "Heading path of the section to read (e.g., 'Preferences/Editor'); alternative to a '#' anchor in path"
+                    },
+                    "origin": {
+                        "type": "string",
+                        "description": "Federated workspace to read from, as labeled in memory_search results (read-only)"
                     },
                     "from": {
                         "type": "integer",
@@ -811,7 +1551,7 @@ impl Tool for MemoryGetTool {
                     },
                     "lines": {
                         "type": "integer",
-                        "description": "Number of lines to read (default: 50)"
+                        "description": "Number of lines to read (default: 50, or the whole section)"
                     }
                 },
                 "required": ["path"]
@@ -821,14 +1561,20 @@ impl Tool for MemoryGetTool {
 
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args: Value = serde_json::from_str(arguments)?;
-        let path = args["path"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Missing path"))?;
+        let (path, anchor) = split_anchor(
+            args["path"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Missing path"))?,
+        );
+        let anchor = args["section"].as_str().or(anchor);
 
         let from = args["from"].as_u64().unwrap_or(1).max(1) as usize;
         let lines_count = args["lines"].as_u64().unwrap_or(50) as usize;
 
-        let resolved_path = self.resolve_path(path);
+        let resolved_path = match args["origin"].as_str() {
+            Some(origin) => self.resolve_federated(origin, path)?,
+            None => self.resolve_path(path),
+        };
 
         debug!(
             "Memory get: {} (from: {}, lines: {})",
@@ -845,9 +1591,34 @@ impl Tool for MemoryGetTool {
         let lines: Vec<&str> = content.lines().collect();
         let total_lines = lines.len();
 
+        let file = match args["origin"].as_str() {
+            Some(_) => path.to_string(),
+            None => resolved_path
+                .strip_prefix(&self.workspace)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.to_string()),
+        };
+        let Some(private) = self.privacy.private_ranges(&file, &content) else {
+            return Ok(self.privacy.refuse("memory_get", &file));
+        };
+
         // Convert from 1-indexed to 0-indexed
-        let start = (from - 1).min(total_lines);
-        let end = (start + lines_count).min(total_lines);
+        let (start, end, label) = match anchor {
+            Some(anchor) => {
+                let section = find_section(&content, anchor)?;
+                let start = section.heading_line - 1;
+                let end = match args["lines"].as_u64() {
+                    Some(n) => (start + n as usize).min(section.end_line),
+                    None => section.end_line,
+                };
+                (start, end, format!("{}#{}", path, section.anchor()))
+            }
+            None => {
+                let start = (from - 1).min(total_lines);
+                let end = (start + lines_count).min(total_lines);
+                (start, end, path.to_string())
+            }
+        };
 
         if start >= total_lines {
             return Ok(format!(
@@ -856,20 +1627,349 @@ impl Tool for MemoryGetTool {
             ));
         }
 
-        let selected: Vec<String> = lines[start..end]
-            .iter()
-            .enumerate()
-            .map(|(i, line)| format!("{:4}\t{}", start + i + 1, line))
-            .collect();
+        let selected = self
+            .privacy
+            .number_lines("memory_get", &file, &lines, start, end, &private);
 
         let header = format!(
             "# {} (lines {}-{} of {})\n",
-            path,
+            label,
             start + 1,
             end,
             total_lines
         );
-        Ok(header + &selected.join("\n"))
+        Ok(header + &selected)
+    }
+}
+
+// Memory Graph Tool - wikilink/entity graph queries over the memory index
+pub struct MemoryGraphTool {
+    memory: Arc<MemoryManager>,
+    privacy: PrivacyGuard,
+}
+
+impl MemoryGraphTool {
+    pub fn new(memory: Arc<MemoryManager>) -> Self {
+        Self {
+            memory,
+            privacy: PrivacyGuard::default(),
+        }
+    }
+
+    /// Withhold links from private memory from non-local providers (builder
+    /// pattern)
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    fn shareable(&self, edges: Vec<crate::memory::GraphEdge>) -> Vec<crate::memory::GraphEdge> {
+        self.privacy
+            .filter_edges(self.name(), self.memory.workspace(), edges)
+    }
+}
+
+fn format_edges(edges: &[crate::memory::GraphEdge]) -> String {
+    edges
+        .iter()
+        .map(|e| match (&e.path, e.line) {
+            (Some(path), Some(line)) => {
+                format!(
+                    "{} -[{}]-> {} ({}:{})",
+                    e.from, e.relation, e.to, path, line
+                )
+            }
+            _ => format!("{} -[{}]-> {} ({})", e.from, e.relation, e.to, e.origin),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[async_trait]
+impl Tool for MemoryGraphTool {
+    fn name(&self) -> &str {
+        "memory_graph"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "memory_graph".to_string(),
+            description: "Query the memory link graph built from [[wikilinks]] and `predicate:: [[Target]]` fields: neighbors, backlinks, or the path between two notes/entities. Use 'relate' to record a relation between entities (e.g. Alice works_on Alpha).".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["neighbors", "backlinks", "path", "relate"],
+                        "description": "Query to run"
+                    },
+                    "node": {
+                        "type": "string",
+                        "description": "Note or entity name (e.g. 'Alice' or 'projects/Alpha.md'); subject for 'relate'"
+                    },
+                    "target": {
+                        "type": "string",
+                        "description": "Destination node for 'path', object for 'relate'"
+                    },
+                    "relation": {
+                        "type": "string",
+                        "description": "Relation name for 'relate' (e.g. 'works_on')"
+                    },
+                    "node_kind": {
+                        "type": "string",
+                        "description": "Optional entity kind for node when relating (e.g. 'person')"
+                    },
+                    "target_kind": {
+                        "type": "string",
+                        "description": "Optional entity kind for target when relating (e.g. 'project')"
+                    },
+                    "depth": {
+                        "type": "integer",
+                        "description": "Hops for 'neighbors' (default: 1, max: 6)"
+                    }
+                },
+                "required": ["action", "node"]
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: &str) -> Result<String> {
+        let args: Value = serde_json::from_str(arguments)?;
+        let action = args["action"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing action"))?;
+        let node = args["node"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing node"))?;
+
+        debug!("Memory graph: {} {}", action, node);
+
+        match action {
+            "neighbors" => {
+                let depth = args["depth"].as_u64().unwrap_or(1) as usize;
+                let edges = self.shareable(self.memory.graph_neighbors(node, depth).await?);
+                if edges.is_empty() {
+                    return Ok(format!("No links found for '{}'", node));
+                }
+                Ok(format_edges(&edges))
+            }
+            "backlinks" => {
+                let edges = self.shareable(self.memory.graph_backlinks(node).await?);
+                if edges.is_empty() {
+                    return Ok(format!("No backlinks found for '{}'", node));
+                }
+                Ok(format_edges(&edges))
+            }
+            "path" => {
+                let target = args["target"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing target"))?;
+                // A path through a private link is not shown at all
+                let path = self.memory.graph_path(node, target).await?;
+                let path = path.filter(|p| self.shareable(p.edges.clone()).len() == p.edges.len());
+                match path {
+                    Some(path) => Ok(format!(
+                        "{}\n\n{}",
+                        path.nodes.join(" -> "),
+                        format_edges(&path.edges)
+                    )),
+                    None => Ok(format!("No path between '{}' and '{}'", node, target)),
+                }
+            }
+            "relate" => {
+                let target = args["target"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing target"))?;
+                let relation = args["relation"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing relation"))?;
+                self.memory
+                    .graph_relate(
+                        node,
+                        relation,
+                        target,
+                        args["node_kind"].as_str(),
+                        args["target_kind"].as_str(),
+                    )
+                    .await?;
+                Ok(format!("Recorded: {} -[{}]-> {}", node, relation, target))
+            }
+            other => anyhow::bail!("Unknown action: {}", other),
+        }
+    }
+}
+
+// Memory Write Tool - structured, deduplicated writes to MEMORY.md
+pub struct MemoryWriteTool {
+    memory: Arc<MemoryManager>,
+    checkpoints: Option<CheckpointStore>,
+}
+
+impl MemoryWriteTool {
+    pub fn new(memory: Arc<MemoryManager>) -> Self {
+        Self {
+            memory,
+            checkpoints: None,
+        }
+    }
+
+    /// Save MEMORY.md before changing it (builder pattern)
+    pub fn with_checkpoints(mut self, checkpoints: Option<CheckpointStore>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryWriteTool {
+    fn name(&self) -> &str {
+        "memory_write"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "memory_write".to_string(),
+            description: "Save a fact to MEMORY.md under a section (e.g. 'Preferences', 'People'). Checks existing entries first: duplicates are not written, facts that add detail to an entry replace it, and contradictions are reported back so you can decide. Prefer this over editing MEMORY.md directly.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "section": {
+                        "type": "string",
+                        "description": "Section heading to file the fact under (created if missing)"
+                    },
+                    "fact": {
+                        "type": "string",
+                        "description": "One self-contained fact, e.g. 'Editor: helix' or 'Alice owns the billing service'"
+                    },
+                    "on_conflict": {
+                        "type": "string",
+                        "enum": ["report", "replace", "keep"],
+                        "description": "If the fact contradicts an existing entry: report it (default), replace the entry, or keep both"
+                    }
+                },
+                "required": ["section", "fact"]
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: &str) -> Result<String> {
+        let args: Value = serde_json::from_str(arguments)?;
+        let section = args["section"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing section"))?;
+        let fact = args["fact"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing fact"))?;
+        let on_conflict: ConflictMode = args["on_conflict"].as_str().unwrap_or("report").parse()?;
+
+        debug!("Memory write: [{}] {}", section, fact);
+        checkpoint(
+            self.checkpoints.as_ref(),
+            &self.memory.workspace().join("MEMORY.md"),
+        );
+
+        let outcome = self
+            .memory
+            .write_fact(FactWrite {
+                section: section.to_string(),
+                fact: fact.to_string(),
+                on_conflict,
+                session_id: crate::agent::tool_executor::current_session_id(),
+            })
+            .await?;
+
+        Ok(match outcome {
+            WriteOutcome::Added { section, line } => {
+                format!("Saved to MEMORY.md under '{}' (line {})", section, line)
+            }
+            WriteOutcome::Merged { line, previous } => format!(
+                "Merged into existing entry at MEMORY.md:{} (was: {})",
+                line, previous
+            ),
+            WriteOutcome::Replaced { line, previous } => format!(
+                "Replaced entry at MEMORY.md:{} (was: {})",
+                line, previous
+            ),
+            WriteOutcome::Duplicate { line, existing } => format!(
+                "Not saved: already recorded at MEMORY.md:{}: {}",
+                line, existing
+            ),
+            WriteOutcome::Conflict {
+                line,
+                existing,
+                reason,
+            } => format!(
+                "Not saved: contradicts MEMORY.md:{}: {} ({}). Confirm with the user, then call again with on_conflict \"replace\" to overwrite it or \"keep\" to record both.",
+                line, existing, reason
+            ),
+        })
+    }
+}
+
+// Forget Tool - owner-requested purge of a topic from all persisted memory
+pub struct ForgetTool {
+    memory: Arc<MemoryManager>,
+}
+
+impl ForgetTool {
+    pub fn new(memory: Arc<MemoryManager>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl Tool for ForgetTool {
+    fn name(&self) -> &str {
+        "forget"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema {
+            name: "forget".to_string(),
+            description: "Forget something the owner asked you to forget (a person, a secret pasted by mistake) in MEMORY.md, daily logs, artifacts, session transcripts and the search index. First call without confirm to see where it occurs, show the owner, and only call again with confirm true once they agree.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Exact text to forget (case-insensitive, at least 3 characters)"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["redact", "delete"],
+                        "description": "redact replaces the text (default), delete removes whole lines from memory files"
+                    },
+                    "confirm": {
+                        "type": "boolean",
+                        "description": "Apply the purge. Without it only a report is returned."
+                    }
+                },
+                "required": ["query"]
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: &str) -> Result<String> {
+        let args: Value = serde_json::from_str(arguments)?;
+        let query = args["query"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+        let options = ForgetOptions {
+            mode: args["mode"].as_str().unwrap_or("redact").parse()?,
+            apply: args["confirm"].as_bool().unwrap_or(false),
+            sessions_dir: None,
+        };
+
+        debug!("Forget (apply: {})", options.apply);
+
+        let report = self.memory.forget(query, &options).await?;
+        if report.is_empty() || report.applied {
+            return Ok(report.to_string());
+        }
+        Ok(format!(
+            "{}\nNothing has been changed yet. Confirm with the owner, then call again with confirm true.",
+            report
+        ))
     }
 }
 
@@ -877,6 +1977,8 @@ impl Tool for MemoryGetTool {
 pub struct WebFetchTool {
     client: reqwest::Client,
     max_bytes: usize,
+    /// Hosts fetches (and redirects) may reach; `None` is unrestricted
+    allowlist: Option<EgressAllowlist>,
 }
 
 impl WebFetchTool {
@@ -884,8 +1986,25 @@ impl WebFetchTool {
         Self {
             client: reqwest::Client::new(),
             max_bytes,
+            allowlist: None,
         }
     }
+
+    /// Restrict fetches to `sandbox.allow_domains` when it's set and the
+    /// network isn't open, recording blocked fetches in `audit`
+    pub fn with_sandbox(mut self, sandbox: &SandboxPolicy, audit: Option<AuditLog>) -> Self {
+        if let Egress::Allowlist(mut allowlist) = Egress::of(sandbox) {
+            if let Some(audit) = audit {
+                allowlist = allowlist.with_audit(audit);
+            }
+            self.client = reqwest::Client::builder()
+                .redirect(allowlist.redirect_policy())
+                .build()
+                .unwrap_or_default();
+            self.allowlist = Some(allowlist);
+        }
+        self
+    }
 }
 
 #[async_trait]
@@ -911,6 +2030,14 @@ impl Tool for WebFetchTool {
         }
     }
 
+    fn untrusted_source(&self, arguments: &str) -> Option<String> {
+        let args: Value = serde_json::from_str(arguments).unwrap_or_default();
+        match args["url"].as_str() {
+            Some(url) => Some(format!("web_fetch {}", url)),
+            None => Some("web_fetch".to_string()),
+        }
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args: Value = serde_json::from_str(arguments)?;
         let url = args["url"]
@@ -918,6 +2045,9 @@ impl Tool for WebFetchTool {
             .ok_or_else(|| anyhow::anyhow!("Missing url"))?;
 
         debug!("Fetching URL: {}", url);
+        if let Some(allowlist) = &self.allowlist {
+            allowlist.check_url(url)?;
+        }
 
         let response = self
             .client
@@ -950,7 +2080,7 @@ pub fn extract_tool_detail(tool_name: &str, arguments: &str) -> Option<String> {
     let args: Value = serde_json::from_str(arguments).ok()?;
 
     match tool_name {
-        "edit_file" | "write_file" | "read_file" => args
+        "edit_file" | "write_file" | "read_file" | "edit_section" => args
             .get("path")
             .or_else(|| args.get("file_path"))
             .and_then(|v| v.as_str())
@@ -966,6 +2096,14 @@ pub fn extract_tool_detail(tool_name: &str, arguments: &str) -> Option<String> {
             .get("query")
             .and_then(|v| v.as_str())
             .map(|s| format!("\"{}\"", s)),
+        "memory_write" => args
+            .get("section")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        "memory_graph" => args
+            .get("node")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
         "web_fetch" => args
             .get("url")
             .and_then(|v| v.as_str())
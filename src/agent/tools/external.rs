@@ -1,6 +1,7 @@
 use crate::agent::providers::ToolSchema;
 use crate::agent::tools::{resolve_path, Tool};
 use crate::config::{SandboxPolicy, WorkdirStrategy};
+use crate::security::AuditLog;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -21,6 +22,8 @@ pub struct ExternalTool {
     path_args: Vec<String>,
     workspace: Option<PathBuf>,
     strategy: Option<WorkdirStrategy>,
+    /// Gated once the turn is tainted
+    sensitive: bool,
 }
 
 impl ExternalTool {
@@ -48,9 +51,16 @@ impl ExternalTool {
             path_args,
             workspace,
             strategy,
+            sensitive: false,
         }
     }
 
+    /// Mark as privileged for taint tracking (builder pattern)
+    pub fn with_sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
     async fn run_sandboxed(&self, extra_args: &[String]) -> Result<String> {
         let policy = self
             .policy
@@ -70,12 +80,14 @@ impl ExternalTool {
             self.command, full_args
         );
 
+        let audit = self.workspace.as_deref().map(AuditLog::for_workspace);
         let output = crate::agent::tools::runner::run_sandboxed_command(
             &self.command,
             &full_args,
             &cwd,
             None,
             policy,
+            audit.as_ref(),
         )
         .await?;
 
@@ -111,8 +123,12 @@ impl ExternalTool {
 
         debug!("Executing external tool {}: {:?}", self.name, cmd);
 
-        let output = cmd
-            .output()
+        let limits = self
+            .policy
+            .as_ref()
+            .map(|p| p.limits.clone())
+            .unwrap_or_default();
+        let output = crate::security::limits::output(cmd, &limits)
             .await
             .context("Failed to execute external command")?;
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -169,6 +185,14 @@ impl Tool for ExternalTool {
         }
     }
 
+    fn privileged(&self, _arguments: &str) -> bool {
+        self.sensitive
+    }
+
+    fn runs_locally(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args_val: Value = serde_json::from_str(arguments)?;
 
@@ -47,6 +47,10 @@ impl Tool for McpTool {
         }
     }
 
+    fn untrusted_source(&self, _arguments: &str) -> Option<String> {
+        Some(format!("MCP server {}", self.server_name))
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         let args: Value = serde_json::from_str(arguments)?;
 
@@ -8,6 +8,31 @@ pub enum PermissionMode {
     Write,
 }
 
+/// Canonicalize to resolve symlinks. If the file doesn't exist, we use the
+/// absolute path as-is, but we try to canonicalize the parent.
+fn canonicalize_lenient(abs_path: &Path) -> PathBuf {
+    if let Ok(canon) = abs_path.canonicalize() {
+        canon
+    } else {
+        // If path doesn't exist, try to canonicalize parent to resolve at least directory symlinks
+        if let Some(parent) = abs_path.parent() {
+            if let Ok(canon_parent) = parent.canonicalize() {
+                canon_parent.join(abs_path.file_name().unwrap())
+            } else {
+                abs_path.to_path_buf()
+            }
+        } else {
+            abs_path.to_path_buf()
+        }
+    }
+}
+
+/// Whether absolute `path` lies under `root`, after resolving symlinks
+pub fn is_within(path: &Path, root: &Path) -> bool {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    canonicalize_lenient(path).starts_with(root)
+}
+
 /// Verify that a path is permitted by the sandbox policy.
 ///
 /// The path is checked against:
@@ -29,22 +54,7 @@ pub fn check_path_permitted(
         project_dir.join(path)
     };
 
-    // Attempt to canonicalize to resolve symlinks
-    // If file doesn't exist, we use the absolute path as-is, but we try to canonicalize parent
-    let check_path = if let Ok(canon) = abs_path.canonicalize() {
-        canon
-    } else {
-        // If path doesn't exist, try to canonicalize parent to resolve at least directory symlinks
-        if let Some(parent) = abs_path.parent() {
-            if let Ok(canon_parent) = parent.canonicalize() {
-                canon_parent.join(abs_path.file_name().unwrap())
-            } else {
-                abs_path.clone()
-            }
-        } else {
-            abs_path.clone()
-        }
-    };
+    let check_path = canonicalize_lenient(&abs_path);
 
     // Collect allowed roots
     let mut allowed_roots = Vec::new();
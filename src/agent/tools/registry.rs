@@ -45,11 +45,12 @@ impl ToolRegistry {
                 conf.args.clone(),
                 Some(project_dir.clone()),
                 conf.sandbox,
-                Some(config.sandbox.clone()),
+                Some(config.sandbox.for_tool(name)),
                 conf.path_args.clone(),
                 Some(config.workspace_path()),
                 Some(config.workdir.strategy.clone()),
-            );
+            )
+            .with_sensitive(conf.sensitive);
             tools_map.insert(name.clone(), Arc::new(tool));
         }
 
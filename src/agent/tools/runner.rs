@@ -4,6 +4,37 @@ use std::process::Stdio;
 use tokio::process::Command;
 use tracing::{debug, warn};
 use crate::config::SandboxPolicy;
+use crate::security::egress::{self, Egress, EgressProxy};
+use crate::security::limits;
+use crate::security::AuditLog;
+
+/// Egress proxy for a policy that allowlists domains instead of allowing
+/// the whole network, recording its connections in `audit`
+#[cfg(unix)]
+fn egress_proxy(
+    policy: &SandboxPolicy,
+    audit: Option<&AuditLog>,
+) -> Result<Option<std::sync::Arc<EgressProxy>>> {
+    match Egress::of(policy) {
+        Egress::Allowlist(mut allowlist) => {
+            if let Some(audit) = audit {
+                allowlist = allowlist.with_audit(audit.clone());
+            }
+            Ok(Some(egress::proxy_for(&allowlist)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// `env` plus the proxy variables for a proxy on `127.0.0.1:port`
+fn with_proxy_env(
+    env: Option<std::collections::HashMap<String, String>>,
+    port: u16,
+) -> Option<std::collections::HashMap<String, String>> {
+    let mut env = env.unwrap_or_default();
+    env.extend(egress::proxy_env(port));
+    Some(env)
+}
 
 #[cfg(target_os = "macos")]
 pub async fn run_sandboxed_command(
@@ -12,16 +43,24 @@ pub async fn run_sandboxed_command(
     cwd: &PathBuf,
     env: Option<std::collections::HashMap<String, String>>,
     policy: &SandboxPolicy,
+    audit: Option<&AuditLog>,
 ) -> Result<std::process::Output> {
     if !policy.enable_os_sandbox {
-        return run_direct(command, args, cwd, env).await;
+        return run_direct(command, args, cwd, env, policy, audit).await;
     }
 
     // Determine script path (heuristic: first arg ending in .py, .sh, .js, etc. or just first arg)
     let script_path = args.first().map(|s| s.as_str()).unwrap_or("unknown");
 
     // Generate profile
-    let profile_content = crate::security::compile_profile(policy, command, script_path);
+    let proxy = egress_proxy(policy, audit)?;
+    let proxy_port = proxy.as_ref().map(|p| p.addr.port());
+    let profile_content =
+        crate::security::compile_profile_with_proxy(policy, command, script_path, proxy_port);
+    let env = match proxy_port {
+        Some(port) => with_proxy_env(env, port),
+        None => env,
+    };
 
     // Write profile to temp file
     let mut temp_file = tempfile::Builder::new()
@@ -53,7 +92,7 @@ pub async fn run_sandboxed_command(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    let output = cmd.output().await.context("Failed to run sandboxed command on macOS")?;
+    let output = limits::output(cmd, &policy.limits).await.context("Failed to run sandboxed command on macOS")?;
 
     // Explicitly keep temp_file alive until here
     drop(temp_file);
@@ -68,19 +107,33 @@ pub async fn run_sandboxed_command(
     cwd: &PathBuf,
     env: Option<std::collections::HashMap<String, String>>,
     policy: &SandboxPolicy,
+    audit: Option<&AuditLog>,
 ) -> Result<std::process::Output> {
     if !policy.enable_os_sandbox {
-        return run_direct(command, args, cwd, env).await;
+        return run_direct(command, args, cwd, env, policy, audit).await;
     }
 
-    // Check for bubblewrap
-    let has_bwrap = Command::new("bwrap")
-        .arg("--version")
-        .output()
-        .await
-        .is_ok();
+    use crate::config::LinuxSandboxBackend;
+    use crate::security::linux_sandbox::SandboxProbe;
+
+    let backend = SandboxProbe::get().backend(policy)?;
+    let proxy = egress_proxy(policy, audit)?;
 
-    if has_bwrap {
+    if backend == LinuxSandboxBackend::Landlock {
+        return run_landlock(command, args, cwd, env, policy, proxy.as_deref()).await;
+    }
+
+    // Namespaced commands reach the proxy through the bridge on BRIDGE_PORT
+    let (command, args, env) = match &proxy {
+        Some(proxy) => (
+            egress::bridge_executable()?.to_string_lossy().to_string(),
+            egress::bridge_args(&proxy.socket, command, args),
+            with_proxy_env(env, egress::BRIDGE_PORT),
+        ),
+        None => (command.to_string(), args.to_vec(), env),
+    };
+
+    if backend == LinuxSandboxBackend::Bubblewrap {
         debug!("Using bubblewrap for sandboxing");
         let mut cmd = Command::new("bwrap");
         cmd.kill_on_drop(true);
@@ -135,20 +188,20 @@ pub async fn run_sandboxed_command(
         // But to be safe for "project" dir:
         if cwd.exists() {
              cmd.args(&["--bind", cwd.to_str().unwrap(), cwd.to_str().unwrap()]);
+             cmd.args(["--chdir", cwd.to_str().unwrap()]);
         }
 
         // Network
         if policy.allow_network {
             cmd.args(&["--share-net"]);
+        } else if let Some(proxy) = &proxy {
+            let socket = proxy.socket.to_string_lossy();
+            cmd.args(["--bind", &socket, &socket]);
+            cmd.args(["--ro-bind", &command, &command]);
         }
 
-        // Command
-        cmd.arg(command);
-        cmd.args(args);
-
         // Env
-        // bwrap clears env by default. We need to pass them.
-        // --setenv VAR VALUE
+        // --setenv VAR VALUE, given before the command like all options
         if let Some(env_vars) = env {
             for (k, v) in env_vars {
                 cmd.arg("--setenv");
@@ -157,13 +210,19 @@ pub async fn run_sandboxed_command(
             }
         }
 
+        // Command
+        cmd.arg(&command);
+        cmd.args(&args);
+
         cmd.stdin(Stdio::null());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
-        cmd.output().await.context("Failed to run bwrap command")
+        limits::output(cmd, &policy.limits).await.context("Failed to run bwrap command")
     } else {
-        warn!("Bubblewrap not found, falling back to unshare (weaker sandbox)");
+        if policy.linux_backend == LinuxSandboxBackend::Auto {
+            warn!("Neither bubblewrap nor Landlock available, falling back to unshare (weaker sandbox)");
+        }
         // Fallback to unshare
         let mut cmd = Command::new("unshare");
         cmd.kill_on_drop(true);
@@ -190,8 +249,8 @@ pub async fn run_sandboxed_command(
 
         // We can't easily do bind mounts with just unshare command wrapper without a script.
         // We will just run the command in the namespace.
-        cmd.arg(command);
-        cmd.args(args);
+        cmd.arg(&command);
+        cmd.args(&args);
         cmd.current_dir(cwd);
 
         if let Some(env_vars) = env {
@@ -202,8 +261,53 @@ pub async fn run_sandboxed_command(
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
-        cmd.output().await.context("Failed to run unshare command")
+        limits::output(cmd, &policy.limits).await.context("Failed to run unshare command")
+    }
+}
+
+/// Run with Landlock + seccomp applied by the child before `exec`
+#[cfg(target_os = "linux")]
+async fn run_landlock(
+    command: &str,
+    args: &[String],
+    cwd: &PathBuf,
+    env: Option<std::collections::HashMap<String, String>>,
+    policy: &SandboxPolicy,
+    proxy: Option<&EgressProxy>,
+) -> Result<std::process::Output> {
+    let proxy_port = proxy.map(|p| p.addr.port());
+    let mut sandbox = crate::security::linux_sandbox::NativeSandbox::new(policy, cwd, proxy_port)
+        .context("Failed to prepare Landlock sandbox")?;
+    let env = match proxy_port {
+        Some(port) => with_proxy_env(env, port),
+        None => env,
+    };
+
+    debug!("Using Landlock/seccomp for sandboxing");
+    let mut cmd = Command::new(command);
+    cmd.args(args);
+    cmd.current_dir(cwd);
+    cmd.kill_on_drop(true);
+
+    unsafe {
+        cmd.pre_exec(move || {
+            // PR_SET_PDEATHSIG = 1
+            if libc::prctl(1, libc::SIGKILL) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            sandbox.apply()
+        });
+    }
+
+    if let Some(env_vars) = env {
+        cmd.envs(env_vars);
     }
+
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    limits::output(cmd, &policy.limits).await.context("Failed to run Landlock-sandboxed command")
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
@@ -212,10 +316,11 @@ pub async fn run_sandboxed_command(
     args: &[String],
     cwd: &PathBuf,
     env: Option<std::collections::HashMap<String, String>>,
-    _policy: &SandboxPolicy,
+    policy: &SandboxPolicy,
+    audit: Option<&AuditLog>,
 ) -> Result<std::process::Output> {
     warn!("Sandboxing not supported on this platform, running command directly");
-    run_direct(command, args, cwd, env).await
+    run_direct(command, args, cwd, env, policy, audit).await
 }
 
 async fn run_direct(
@@ -223,7 +328,18 @@ async fn run_direct(
     args: &[String],
     cwd: &PathBuf,
     env: Option<std::collections::HashMap<String, String>>,
+    policy: &SandboxPolicy,
+    audit: Option<&AuditLog>,
 ) -> Result<std::process::Output> {
+    // Not enforced without the OS sandbox, but well-behaved clients comply
+    #[cfg(not(unix))]
+    let _ = audit;
+    #[cfg(unix)]
+    let env = match egress_proxy(policy, audit)? {
+        Some(proxy) => with_proxy_env(env, proxy.addr.port()),
+        None => env,
+    };
+
     let mut cmd = Command::new(command);
     cmd.args(args);
     cmd.current_dir(cwd);
@@ -249,5 +365,5 @@ async fn run_direct(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    cmd.output().await.context("Failed to run command")
+    limits::output(cmd, &policy.limits).await.context("Failed to run command")
 }
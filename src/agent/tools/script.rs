@@ -34,6 +34,10 @@ impl Tool for ScriptTool {
         }
     }
 
+    fn runs_locally(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, arguments: &str) -> Result<String> {
         self.service
             .execute_tool(&self.definition.name, arguments)
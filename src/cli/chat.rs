@@ -13,12 +13,14 @@ use zier_alpha::agent::{
     attachments::{process_attach_command, Attachment},
     extract_tool_detail, get_last_session_id_for_agent, get_skills_summary,
     list_sessions_for_agent, load_skills, parse_skill_command, search_sessions_for_agent, Agent,
-    AgentConfig, ContextStrategy, ImageAttachment, ScriptTool, Skill,
+    AgentConfig, ContextStrategy, ImageAttachment, RewindAction, ScriptTool, Skill,
 };
 use zier_alpha::concurrency::WorkspaceLock;
 use zier_alpha::config::Config;
+use zier_alpha::ingress::ApprovalDecision;
 use zier_alpha::memory::MemoryManager;
 use zier_alpha::scripting::ScriptService;
+use zier_alpha::security::approval::describe;
 
 /// Adjust a byte index to the nearest valid UTF-8 char boundary (searching forward).
 fn floor_char_boundary(s: &str, index: usize) -> usize {
@@ -531,6 +533,7 @@ pub async fn run(args: ChatArgs, agent_id: &str) -> Result<()> {
                             name,
                             id,
                             arguments,
+                            reason,
                         }) => {
                             let detail = extract_tool_detail(&name, &arguments);
                             if verbose_chat {
@@ -541,16 +544,28 @@ pub async fn run(args: ChatArgs, agent_id: &str) -> Result<()> {
                             } else {
                                 println!("\n[{}]", name);
                             }
+                            if let Some(reason) = reason {
+                                println!("Reason: {}", reason);
+                            }
 
-                            print!("Execute {}? [y/N]: ", name);
+                            print!(
+                                "Execute {}? [y/N/s=this session/a=always, or a reason to deny]: ",
+                                name
+                            );
                             stdout.flush()?;
 
                             let mut input = String::new();
                             io::stdin().read_line(&mut input)?;
-                            let input = input.trim().to_lowercase();
-
-                            approval_info =
-                                Some((id, name, arguments, input == "y" || input == "yes"));
+                            let input = input.trim();
+                            let (decision, reason) = match input.to_lowercase().as_str() {
+                                "y" | "yes" => (ApprovalDecision::Approve, None),
+                                "s" | "session" => (ApprovalDecision::ApproveSession, None),
+                                "a" | "always" => (ApprovalDecision::ApproveAlways, None),
+                                "" | "n" | "no" => (ApprovalDecision::Deny, None),
+                                _ => (ApprovalDecision::Deny, Some(input.to_string())),
+                            };
+
+                            approval_info = Some((id, name, decision, reason));
                             break; // Exit inner stream loop
                         }
                         Ok(zier_alpha::agent::StreamEvent::ToolCallEnd { output, .. }) => {
@@ -570,23 +585,24 @@ pub async fn run(args: ChatArgs, agent_id: &str) -> Result<()> {
                 }
             } // pinned_stream dropped here
 
-            if let Some((id, name, arguments, approved)) = approval_info {
-                let output = if approved {
-                    let call = zier_alpha::agent::ToolCall {
-                        id: id.clone(),
-                        name: name.clone(),
-                        arguments: arguments.clone(),
-                    };
-                    agent
-                        .execute_tool(&call)
-                        .await
-                        .unwrap_or_else(|e| format!("Error: {}", e))
-                } else {
-                    println!("Skipped: {}", name);
-                    "User denied execution".to_string()
-                };
+            if let Some((id, name, decision, reason)) = approval_info {
+                // Approved calls run when the stream resumes; denied ones
+                // give the model the refusal as their result
+                match agent
+                    .resolve_tool_call(&id, decision, reason.as_deref())
+                    .await
+                {
+                    Ok(Some(rule)) if decision == ApprovalDecision::ApproveAlways => {
+                        println!("Always allowing {}", describe(&rule))
+                    }
+                    Ok(Some(rule)) => println!("Allowing {} this session", describe(&rule)),
+                    Ok(None) if decision == ApprovalDecision::Deny => {
+                        println!("Skipped: {}", name)
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Warning: Failed to save approval: {}", e),
+                }
 
-                agent.provide_tool_result(id, output).await;
                 current_stream = Box::pin(agent.resume_chat_stream_with_tools().await?);
             } else {
                 break;
@@ -639,6 +655,7 @@ async fn handle_command(
             println!("  /attachments      - List pending attachments");
             println!("  /compact          - Compact session history");
             println!("  /clear            - Clear session history (keeps context)");
+            println!("  /rewind [n]       - Restore files to before checkpoint n (default: last)");
             println!("  /memory <query>   - Search memory");
             println!("  /reindex          - Rebuild memory index");
             println!("  /save             - Save current session");
@@ -796,6 +813,74 @@ async fn handle_command(
             Err(e) => CommandResult::Error(format!("Failed to compact: {}", e)),
         },
 
+        "/rewind" => {
+            let turn = match parts.get(1).map(|t| t.parse::<usize>()) {
+                Some(Ok(turn)) => Some(turn),
+                Some(Err(_)) => {
+                    return CommandResult::Error(format!("Invalid checkpoint: {}", parts[1]));
+                }
+                None => None,
+            };
+            if turn.is_none() {
+                if let Ok(turns) = agent.checkpoints().await {
+                    if !turns.is_empty() {
+                        println!("\nCheckpoints (one per turn that changed files):");
+                        for checkpoint in &turns {
+                            println!(
+                                "  {}. {} ({} file(s))",
+                                checkpoint.turn,
+                                checkpoint
+                                    .started_at
+                                    .with_timezone(&chrono::Local)
+                                    .format("%Y-%m-%d %H:%M"),
+                                checkpoint.files.len()
+                            );
+                        }
+                    }
+                }
+            }
+
+            let plan = match agent.rewind(turn, true).await {
+                Ok(plan) => plan,
+                Err(e) => return CommandResult::Error(format!("Failed to rewind: {}", e)),
+            };
+            if plan.changes.is_empty() {
+                println!(
+                    "\nFiles are already as they were before checkpoint {}.\n",
+                    plan.turn
+                );
+                return CommandResult::Continue;
+            }
+            println!("\nRewinding to before checkpoint {}:", plan.turn);
+            for change in &plan.changes {
+                let action = match change.action {
+                    RewindAction::Restore => "restore",
+                    RewindAction::Delete => "delete",
+                };
+                println!("  {} {}", action, change.path.display());
+            }
+            for diff in plan.changes.iter().filter_map(|c| c.diff.as_ref()) {
+                print!("\n{}", diff);
+            }
+
+            print!("\nRewind {} file(s)? [y/N]: ", plan.changes.len());
+            let _ = io::stdout().flush();
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input).is_err()
+                || !matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+            {
+                println!("Rewind cancelled.\n");
+                return CommandResult::Continue;
+            }
+            match agent.rewind(Some(plan.turn), false).await {
+                Ok(done) => {
+                    println!("Rewound {} file(s).\n", done.changes.len());
+                    CommandResult::Continue
+                }
+                Err(e) => CommandResult::Error(format!("Failed to rewind: {}", e)),
+            }
+        }
+
         "/clear" => {
             agent.clear_session().await;
             println!("\nSession cleared.\n");
@@ -143,6 +143,7 @@ async fn run_daemon_server(config: Config, agent_id: &str) -> Result<()> {
 
     let memory = MemoryManager::new_with_full_config(&config.memory, Some(&config), agent_id)?;
     let _watcher = memory.start_watcher()?;
+    memory.spawn_embedding_migration().await?;
 
     println!("Daemon started successfully");
 
@@ -176,6 +177,13 @@ async fn run_daemon_services(config: &Config, agent_id: &str) -> Result<()> {
         }
     }
 
+    if config.memory.consolidation.enabled {
+        if let Some(ref schedule) = config.memory.consolidation.schedule {
+            scheduler.register_consolidation_job(schedule).await?;
+            println!("  Memory consolidation: scheduled ({})", schedule);
+        }
+    }
+
     // Capture jobs for ingress loop before moving scheduler into Arc<Mutex>
     let scheduler_jobs = scheduler.jobs.clone();
 
@@ -240,8 +248,10 @@ async fn run_daemon_services(config: &Config, agent_id: &str) -> Result<()> {
     let prompt_registry = std::sync::Arc::new(prompt_registry);
 
     // Create channel for approval UI requests from coordinator to Telegram service.
+    // The coordinator also serves pending approvals over HTTP.
     let (approval_ui_tx, approval_ui_rx) = mpsc::channel(100);
-    let approval_coord = std::sync::Arc::new(ApprovalCoordinator::new(approval_ui_tx));
+    let approval_coord =
+        std::sync::Arc::new(ApprovalCoordinator::new(approval_ui_tx).with_config(config));
 
     // VIZIER: Start Ingress Consumer Loop
     let bus_receiver = bus.receiver();
@@ -320,7 +330,8 @@ async fn run_daemon_services(config: &Config, agent_id: &str) -> Result<()> {
             "  Server: http://{}:{}",
             config.server.bind, config.server.port
         );
-        let server = Server::new_with_gate(config, turn_gate, Some(bus.clone()))?;
+        let server = Server::new_with_gate(config, turn_gate, Some(bus.clone()))?
+            .with_approvals(approval_coord.clone());
         server.run().await?;
     } else if heartbeat_handle.is_some() {
         // Server not enabled but heartbeat is - wait for Ctrl+C
@@ -420,6 +431,7 @@ async fn start_daemon(foreground: bool, agent_id: &str) -> Result<()> {
     // Initialize components
     let memory = MemoryManager::new_with_full_config(&config.memory, Some(&config), agent_id)?;
     let _watcher = memory.start_watcher()?;
+    memory.spawn_embedding_migration().await?;
 
     println!("Daemon started successfully");
 
@@ -49,6 +49,8 @@ pub async fn log_bootup(agent_id: &str, config: &Config, model: &str) {
     // PID
     let pid = std::process::id();
 
+    let sandbox = sandbox_summary(config);
+
     // Print in a concise, readable block
     info!(
         "🚀 Zier Alpha Bootup Diagnostic\n\
@@ -56,6 +58,7 @@ pub async fn log_bootup(agent_id: &str, config: &Config, model: &str) {
          Model: {}\n\
          Workspace: {}\n\
          Extensions: {}\n\
+         Sandbox: {}\n\
          MEMORY.md: {} bytes (~{} tokens)\n\
          Agent ID: {}\n\
          Hostname: {}\n\
@@ -65,6 +68,7 @@ pub async fn log_bootup(agent_id: &str, config: &Config, model: &str) {
         model,
         workspace.display(),
         extensions_str,
+        sandbox,
         mem_size,
         mem_tokens,
         agent_id,
@@ -73,6 +77,37 @@ pub async fn log_bootup(agent_id: &str, config: &Config, model: &str) {
     );
 }
 
+/// OS sandbox backend commands would run under, with the Linux capability probe
+fn sandbox_summary(config: &Config) -> String {
+    let policy = &config.sandbox;
+    let state = if policy.enable_os_sandbox {
+        "on"
+    } else {
+        "off"
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        use zier_alpha::security::linux_sandbox::SandboxProbe;
+        let probe = SandboxProbe::get();
+        let backend = match probe.backend(policy) {
+            Ok(backend) => backend.as_str().to_string(),
+            Err(e) => format!("unavailable ({})", e),
+        };
+        format!("{}, backend {} [{}]", state, backend, probe)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        format!("{}, backend sandbox-exec", state)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        format!("{}, unsupported on this platform", state)
+    }
+}
+
 fn get_git_commit() -> Option<String> {
     // Read .git/HEAD to get current commit or ref
     let head_path = Path::new(".git/HEAD");
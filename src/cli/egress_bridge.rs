@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct EgressBridgeArgs {
+    /// Unix socket of the egress proxy
+    #[arg(long)]
+    pub socket: PathBuf,
+
+    /// Loopback port to forward to the proxy
+    #[arg(long)]
+    pub port: u16,
+
+    /// Command to run, after `--`
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// Runs inside a sandbox's network namespace, so no logging or config: the
+/// command's output must come through untouched. Exits with its status.
+#[cfg(unix)]
+pub fn run(args: &EgressBridgeArgs) -> Result<()> {
+    let (command, command_args) = args.command.split_first().expect("clap requires a command");
+    let code = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(zier_alpha::security::egress::run_bridge(
+            &args.socket,
+            args.port,
+            command,
+            command_args,
+        ))?;
+    std::process::exit(code)
+}
+
+#[cfg(not(unix))]
+pub fn run(_args: &EgressBridgeArgs) -> Result<()> {
+    anyhow::bail!("egress-bridge is only supported on Unix")
+}
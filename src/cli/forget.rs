@@ -0,0 +1,73 @@
+use anyhow::Result;
+use clap::Args;
+use std::io::{self, Write};
+
+use zier_alpha::config::Config;
+use zier_alpha::memory::{ForgetMode, ForgetOptions, MemoryManager};
+
+#[derive(Args)]
+pub struct ForgetArgs {
+    /// Text to forget (case-insensitive, matched literally)
+    pub query: String,
+
+    /// redact (replace matches) or delete (drop matching lines of memory files)
+    #[arg(short, long, default_value = "redact")]
+    pub mode: ForgetMode,
+
+    /// Only report where the text occurs
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+pub async fn run(args: ForgetArgs, agent_id: &str) -> Result<()> {
+    let config = Config::load()?;
+    let memory = MemoryManager::new_with_full_config(&config.memory, Some(&config), agent_id)?;
+
+    let mut options = ForgetOptions {
+        mode: args.mode,
+        ..Default::default()
+    };
+    let report = memory.forget(&args.query, &options).await?;
+    print!("{}", report);
+    if report.is_empty() || args.dry_run {
+        return Ok(());
+    }
+
+    if !args.yes {
+        print!("Forget all of the above? [y/N]: ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let input = input.trim().to_lowercase();
+        if input != "y" && input != "yes" {
+            println!("Nothing changed");
+            return Ok(());
+        }
+    }
+
+    options.apply = true;
+    let report = memory.forget(&args.query, &options).await?;
+    println!(
+        "\nForgot {} file(s) and {} transcript(s); a tombstone was written to {}",
+        report.memory.len() + report.artifacts.len(),
+        report.sessions.len(),
+        memory.audit_log().path().display()
+    );
+    if !report.history.is_empty() {
+        println!(
+            "{} earlier commit(s) in the workspace git history still contain it; history is not rewritten.",
+            report.history.len()
+        );
+    }
+    if !report.checkpoints.is_empty() {
+        println!(
+            "Redacted the file checkpoints of {} session(s).",
+            report.checkpoints.len()
+        );
+    }
+    Ok(())
+}
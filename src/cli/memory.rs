@@ -1,8 +1,14 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use zier_alpha::agent::SmartClient;
 use zier_alpha::config::Config;
-use zier_alpha::memory::MemoryManager;
+use zier_alpha::memory::{
+    parse_date_bound, ConflictPolicy, ConsolidationOptions, GraphEdge, ImportOptions, ImportReport,
+    MemoryManager, OpenClawImport, RunStatus, SearchFilter, DUPLICATE_THRESHOLD,
+};
 
 #[derive(Args)]
 pub struct MemoryArgs {
@@ -20,6 +26,13 @@ pub enum MemoryCommands {
         /// Maximum number of results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Also include notes linked to the results via [[wikilinks]]
+        #[arg(short, long)]
+        expand: bool,
+
+        #[command(flatten)]
+        filter: SearchFilterArgs,
     },
 
     /// Reindex all memory files
@@ -32,12 +45,187 @@ pub enum MemoryCommands {
     /// Show memory statistics
     Stats,
 
+    /// Check the index against the workspace files
+    Doctor {
+        /// Repair what is found (reindex, drop orphaned rows)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Report clusters of near-identical chunks across memory files
+    Duplicates {
+        /// Cosine similarity from which chunks count as duplicates
+        #[arg(short, long, default_value_t = DUPLICATE_THRESHOLD)]
+        threshold: f32,
+
+        /// Maximum number of clusters to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Write the workspace and index metadata to a portable bundle
+    Export {
+        /// Bundle file to write (gzip-compressed JSON Lines)
+        output: PathBuf,
+
+        /// Include cached embeddings so the importer doesn't re-embed
+        #[arg(long)]
+        embeddings: bool,
+    },
+
+    /// Restore a bundle, or import an OpenClaw state directory or workspace
+    Import {
+        /// Bundle file, or OpenClaw directory (e.g. ~/.openclaw)
+        source: PathBuf,
+
+        /// Existing files with other content: skip, overwrite or rename
+        #[arg(long, default_value = "skip")]
+        on_conflict: ConflictPolicy,
+
+        /// Show what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Map an OpenClaw agent to a zier-alpha agent (OLD=NEW, repeatable)
+        #[arg(long = "agent", value_name = "OLD=NEW")]
+        agents: Vec<String>,
+    },
+
     /// List recent memory entries
     Recent {
         /// Number of entries to show
         #[arg(short, long, default_value = "10")]
         count: usize,
     },
+
+    /// Query the wikilink/entity graph
+    Graph {
+        #[command(subcommand)]
+        command: GraphCommands,
+    },
+
+    /// Show commits that changed a workspace file
+    History {
+        /// File relative to the workspace (e.g. MEMORY.md)
+        file: String,
+
+        /// Maximum number of revisions to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show uncommitted changes to cognitive files
+    Diff {
+        /// Limit the diff to one file
+        file: Option<String>,
+
+        /// Compare against this revision instead of the last commit
+        #[arg(long, value_name = "REV")]
+        at: Option<String>,
+    },
+
+    /// Restore a workspace file to an earlier revision
+    Restore {
+        /// File relative to the workspace (e.g. MEMORY.md)
+        file: String,
+
+        /// Revision to restore from (commit id, HEAD~2, ...)
+        #[arg(long, value_name = "REV")]
+        at: String,
+    },
+
+    /// Distill recent daily logs into MEMORY.md
+    Consolidate {
+        /// Show the proposed diff without writing or recording anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Apply the result directly instead of leaving it for review
+        #[arg(long, conflicts_with = "dry_run")]
+        auto_apply: bool,
+
+        /// List recent consolidation runs
+        #[arg(long, conflicts_with_all = ["dry_run", "auto_apply"])]
+        list: bool,
+
+        /// Apply a pending run
+        #[arg(long, value_name = "ID", conflicts_with_all = ["dry_run", "auto_apply", "list"])]
+        apply: Option<i64>,
+
+        /// Discard a pending run
+        #[arg(long, value_name = "ID", conflicts_with_all = ["dry_run", "auto_apply", "list", "apply"])]
+        discard: Option<i64>,
+    },
+}
+
+/// Result filters for `memory search`
+#[derive(Args)]
+pub struct SearchFilterArgs {
+    /// Only search files matching this glob (repeatable)
+    #[arg(long = "path", value_name = "GLOB")]
+    pub paths: Vec<String>,
+
+    /// Skip files matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Only search this document kind: memory, text, html, pdf, code (repeatable)
+    #[arg(long = "source", value_name = "SOURCE")]
+    pub sources: Vec<String>,
+
+    /// Earliest file date: YYYY-MM-DD, today, yesterday, 7d, 2w
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+
+    /// Latest file date (same formats as --since)
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<String>,
+
+    /// Drop results scoring below this value
+    #[arg(long, value_name = "SCORE")]
+    pub min_score: Option<f64>,
+}
+
+impl SearchFilterArgs {
+    fn to_filter(&self) -> Result<SearchFilter> {
+        Ok(SearchFilter {
+            paths: self.paths.clone(),
+            exclude: self.exclude.clone(),
+            sources: self.sources.clone(),
+            since: self.since.as_deref().map(parse_date_bound).transpose()?,
+            until: self.until.as_deref().map(parse_date_bound).transpose()?,
+            min_score: self.min_score,
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Subcommand)]
+pub enum GraphCommands {
+    /// Show links and relations around a note or entity
+    Neighbors {
+        /// Note or entity name (e.g. "Alice" or "projects/Alpha.md")
+        node: String,
+
+        /// Number of hops to follow
+        #[arg(short, long, default_value = "1")]
+        depth: usize,
+    },
+
+    /// Show notes linking to a note or entity
+    Backlinks {
+        /// Note or entity name
+        node: String,
+    },
+
+    /// Find the shortest link path between two notes or entities
+    Path {
+        /// Start node
+        from: String,
+
+        /// End node
+        to: String,
+    },
 }
 
 pub async fn run(args: MemoryArgs, agent_id: &str) -> Result<()> {
@@ -45,15 +233,93 @@ pub async fn run(args: MemoryArgs, agent_id: &str) -> Result<()> {
     let memory = MemoryManager::new_with_full_config(&config.memory, Some(&config), agent_id)?;
 
     match args.command {
-        MemoryCommands::Search { query, limit } => search_memory(&memory, &query, limit).await,
+        MemoryCommands::Search {
+            query,
+            limit,
+            expand,
+            filter,
+        } => search_memory(&memory, &query, limit, expand, &filter.to_filter()?).await,
         MemoryCommands::Reindex { force } => reindex_memory(&memory, force).await,
         MemoryCommands::Stats => show_stats(&memory).await,
+        MemoryCommands::Doctor { fix } => run_doctor(&memory, fix).await,
+        MemoryCommands::Duplicates { threshold, limit } => {
+            show_duplicates(&memory, threshold, limit).await
+        }
+        MemoryCommands::Export { output, embeddings } => {
+            export_memory(&memory, &output, embeddings).await
+        }
+        MemoryCommands::Import {
+            source,
+            on_conflict,
+            dry_run,
+            agents,
+        } => {
+            let options = ImportOptions {
+                on_conflict,
+                dry_run,
+            };
+            import_memory(&memory, &source, options, &agents).await
+        }
         MemoryCommands::Recent { count } => show_recent(&memory, count).await,
+        MemoryCommands::Graph { command } => run_graph(&memory, command).await,
+        MemoryCommands::History { file, limit } => show_history(&memory, &file, limit).await,
+        MemoryCommands::Diff { file, at } => {
+            let diff = memory
+                .workspace_diff(file.as_deref(), at.as_deref())
+                .await?;
+            if diff.is_empty() {
+                println!("No changes");
+            } else {
+                print!("{}", diff);
+            }
+            Ok(())
+        }
+        MemoryCommands::Restore { file, at } => {
+            match memory.restore_file(&file, &at).await? {
+                Some(id) => println!("Restored {} from {} (commit {})", file, at, id),
+                None => println!("Restored {} from {}", file, at),
+            }
+            Ok(())
+        }
+        MemoryCommands::Consolidate {
+            dry_run,
+            auto_apply,
+            list,
+            apply,
+            discard,
+        } => {
+            if list {
+                list_consolidations(&memory).await
+            } else if let Some(id) = apply {
+                let run = memory.apply_consolidation(id).await?;
+                println!("Applied consolidation run {}", run.id);
+                Ok(())
+            } else if let Some(id) = discard {
+                memory.discard_consolidation(id).await?;
+                println!("Discarded consolidation run {}", id);
+                Ok(())
+            } else {
+                let mut options = ConsolidationOptions::from_config(&config);
+                options.dry_run = dry_run;
+                options.auto_apply |= auto_apply;
+                consolidate_memory(&memory, &config, &options).await
+            }
+        }
     }
 }
 
-async fn search_memory(memory: &MemoryManager, query: &str, limit: usize) -> Result<()> {
-    let results = memory.search(query, limit).await?;
+async fn search_memory(
+    memory: &MemoryManager,
+    query: &str,
+    limit: usize,
+    expand: bool,
+    filter: &SearchFilter,
+) -> Result<()> {
+    let results = if expand {
+        memory.search_with_graph(query, limit, 1, filter).await?
+    } else {
+        memory.search_filtered(query, limit, filter).await?
+    };
 
     if results.is_empty() {
         println!("No results found for '{}'", query);
@@ -63,9 +329,15 @@ async fn search_memory(memory: &MemoryManager, query: &str, limit: usize) -> Res
     println!("Found {} results for '{}':\n", results.len(), query);
 
     for (i, result) in results.iter().enumerate() {
+        let origin = result
+            .origin
+            .as_ref()
+            .map(|o| format!("[{}] ", o))
+            .unwrap_or_default();
         println!(
-            "{}. {} (lines {}-{})",
+            "{}. {}{} (lines {}-{})",
             i + 1,
+            origin,
             result.file,
             result.line_start,
             result.line_end
@@ -127,6 +399,23 @@ async fn show_stats(memory: &MemoryManager) -> Result<()> {
     println!("Total files: {}", stats.total_files);
     println!("Total chunks: {}", stats.total_chunks);
     println!("Index size: {} KB", stats.index_size_kb);
+
+    let embeddings = &stats.embeddings;
+    match (&embeddings.active, &embeddings.target) {
+        (_, None) => println!("Embeddings: disabled"),
+        (Some(active), Some(target)) if embeddings.migrating => {
+            println!("Embeddings: {} (serving)", active);
+            println!(
+                "Migrating to: {} ({}/{} chunks)",
+                target, embeddings.embedded, embeddings.total
+            );
+        }
+        (_, Some(target)) => println!(
+            "Embeddings: {} ({}/{} chunks)",
+            target, embeddings.embedded, embeddings.total
+        ),
+    }
+
     println!("\nFiles:");
     for file in &stats.files {
         println!(
@@ -138,6 +427,160 @@ async fn show_stats(memory: &MemoryManager) -> Result<()> {
     Ok(())
 }
 
+async fn run_doctor(memory: &MemoryManager, fix: bool) -> Result<()> {
+    let report = memory.doctor(fix).await?;
+    println!(
+        "Checked {} files and {} chunks",
+        report.files, report.chunks
+    );
+
+    if report.is_healthy() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    for (kind, count) in report.counts() {
+        println!("\n{} ({}):", kind.describe(), count);
+        let subjects = report.issues.iter().filter(|i| i.kind == kind);
+        for issue in subjects.clone().take(10) {
+            println!("  {}", issue.subject.replace('\t', " "));
+        }
+        if count > 10 {
+            println!("  ... and {} more", count - 10);
+        }
+    }
+
+    if report.fixed {
+        println!("\nFixed {} problem(s)", report.issues.len());
+    } else {
+        println!(
+            "\n{} problem(s). Repair with: zier-alpha memory doctor --fix",
+            report.issues.len()
+        );
+    }
+    Ok(())
+}
+
+async fn show_duplicates(memory: &MemoryManager, threshold: f32, limit: usize) -> Result<()> {
+    if memory.embedding_status().await?.active.is_none() {
+        println!("No embeddings in the index; only identical chunks are compared\n");
+    }
+
+    let clusters = memory.duplicates(threshold, limit).await?;
+    if clusters.is_empty() {
+        println!("No duplicates found");
+        return Ok(());
+    }
+
+    for (i, cluster) in clusters.iter().enumerate() {
+        println!(
+            "{}. {} chunks (similarity {:.3})",
+            i + 1,
+            cluster.chunks.len(),
+            cluster.similarity
+        );
+        for chunk in &cluster.chunks {
+            let preview: String = chunk.content.chars().take(80).collect();
+            println!(
+                "   {}:{}-{}  {}",
+                chunk.file,
+                chunk.line_start,
+                chunk.line_end,
+                preview.replace('\n', " ")
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+async fn export_memory(memory: &MemoryManager, output: &Path, embeddings: bool) -> Result<()> {
+    let stats = memory.export_bundle(output, embeddings).await?;
+    println!("Exported memory to {}", output.display());
+    println!("  Files: {}", stats.files);
+    println!("  Relations: {}", stats.relations);
+    println!("  Consolidated logs: {}", stats.consolidated_logs);
+    if embeddings {
+        println!("  Embeddings: {}", stats.embeddings);
+    }
+    Ok(())
+}
+
+async fn import_memory(
+    memory: &MemoryManager,
+    source: &Path,
+    options: ImportOptions,
+    agents: &[String],
+) -> Result<()> {
+    let dry_run = options.dry_run;
+    let report = if source.is_dir() {
+        let mut mapping = HashMap::new();
+        for pair in agents {
+            let (from, to) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected --agent OLD=NEW, got '{}'", pair))?;
+            mapping.insert(from.to_string(), to.to_string());
+        }
+        let import = OpenClawImport {
+            options,
+            agents: mapping,
+            state_dir: zier_alpha::agent::get_state_dir()?,
+        };
+        memory.import_openclaw(source, &import).await?
+    } else {
+        if !agents.is_empty() {
+            anyhow::bail!("--agent only applies to OpenClaw imports");
+        }
+        memory.import_bundle(source, &options).await?
+    };
+
+    print_import_report(&report, dry_run);
+    if !dry_run && memory.has_embeddings() {
+        // Imported embeddings are picked up from the cache
+        let (processed, embedded) = memory.generate_embeddings(50).await?;
+        if processed > 0 {
+            println!("  Embeddings generated: {}", embedded);
+        }
+    }
+    Ok(())
+}
+
+fn print_import_report(report: &ImportReport, dry_run: bool) {
+    let verb = if dry_run { "Would import" } else { "Imported" };
+    println!("{} {} file(s)", verb, report.written.len());
+    for path in &report.written {
+        println!("  + {}", path);
+    }
+    for (from, to) in &report.renamed {
+        println!("  + {} (as {})", from, to);
+    }
+    if report.unchanged > 0 {
+        println!("  {} file(s) already up to date", report.unchanged);
+    }
+    if !report.conflicts.is_empty() {
+        println!(
+            "Kept {} existing file(s) that differ (use --on-conflict overwrite or rename):",
+            report.conflicts.len()
+        );
+        for path in &report.conflicts {
+            println!("  ! {}", path);
+        }
+    }
+    if report.sessions > 0 {
+        println!("Sessions: {}", report.sessions);
+    }
+    if report.relations > 0 {
+        println!("Relations: {}", report.relations);
+    }
+    if report.embeddings > 0 {
+        println!("Cached embeddings: {}", report.embeddings);
+    }
+    if !report.models.is_empty() {
+        println!("Source embedding models: {}", report.models.join(", "));
+    }
+}
+
 async fn show_recent(memory: &MemoryManager, count: usize) -> Result<()> {
     let entries = memory.recent_entries(count).await?;
 
@@ -155,3 +598,129 @@ async fn show_recent(memory: &MemoryManager, count: usize) -> Result<()> {
 
     Ok(())
 }
+
+async fn run_graph(memory: &MemoryManager, command: GraphCommands) -> Result<()> {
+    match command {
+        GraphCommands::Neighbors { node, depth } => {
+            let edges = memory.graph_neighbors(&node, depth).await?;
+            if edges.is_empty() {
+                println!("No links found for '{}'", node);
+                return Ok(());
+            }
+            println!("Links around '{}' (depth {}):\n", node, depth);
+            print_edges(&edges);
+        }
+        GraphCommands::Backlinks { node } => {
+            let edges = memory.graph_backlinks(&node).await?;
+            if edges.is_empty() {
+                println!("No backlinks found for '{}'", node);
+                return Ok(());
+            }
+            println!("Backlinks to '{}':\n", node);
+            print_edges(&edges);
+        }
+        GraphCommands::Path { from, to } => match memory.graph_path(&from, &to).await? {
+            Some(path) => {
+                println!("{}\n", path.nodes.join(" -> "));
+                print_edges(&path.edges);
+            }
+            None => println!("No path between '{}' and '{}'", from, to),
+        },
+    }
+
+    Ok(())
+}
+
+async fn show_history(memory: &MemoryManager, file: &str, limit: usize) -> Result<()> {
+    let revisions = memory.file_history(file, limit).await?;
+    if revisions.is_empty() {
+        println!("No history for {}", file);
+        return Ok(());
+    }
+
+    for rev in revisions {
+        let when = chrono::DateTime::from_timestamp(rev.time, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!("{}  {}  {}", rev.id, when, rev.summary);
+    }
+
+    Ok(())
+}
+
+async fn consolidate_memory(
+    memory: &MemoryManager,
+    config: &Config,
+    options: &ConsolidationOptions,
+) -> Result<()> {
+    let client = SmartClient::new(config.clone(), options.model.clone());
+    let Some(run) = memory.consolidate(&client, options).await? else {
+        println!("No new daily logs to consolidate");
+        return Ok(());
+    };
+
+    println!("Daily logs: {}", run.logs.join(", "));
+    for note in &run.notes {
+        println!("  - {}", note);
+    }
+    if !run.diff.is_empty() {
+        println!("\n{}", run.diff);
+    }
+
+    match run.status {
+        RunStatus::Empty => println!("Nothing to change in MEMORY.md"),
+        _ if options.dry_run => println!("Dry run: nothing written"),
+        RunStatus::Applied => println!("Applied consolidation run {}", run.id),
+        _ => println!(
+            "Consolidation run {} is pending review. Apply with: zier-alpha memory consolidate --apply {}",
+            run.id, run.id
+        ),
+    }
+
+    Ok(())
+}
+
+async fn list_consolidations(memory: &MemoryManager) -> Result<()> {
+    let runs = memory.consolidation_runs(20).await?;
+    if runs.is_empty() {
+        println!("No consolidation runs yet");
+        return Ok(());
+    }
+
+    for run in runs {
+        let when = chrono::DateTime::from_timestamp(run.created_at, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!(
+            "#{:<4} {}  {:<9}  {} log(s), {} note(s)",
+            run.id,
+            when,
+            run.status.as_str(),
+            run.logs.len(),
+            run.notes.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_edges(edges: &[GraphEdge]) {
+    for edge in edges {
+        let location = match (&edge.path, edge.line) {
+            (Some(path), Some(line)) => format!("{}:{}", path, line),
+            _ => edge.origin.clone(),
+        };
+        println!(
+            "  {} -[{}]-> {}  ({})",
+            edge.from, edge.relation, edge.to, location
+        );
+    }
+}
@@ -6,6 +6,8 @@ pub mod daemon;
 #[cfg(feature = "desktop")]
 pub mod desktop;
 pub mod diagnostics;
+pub mod egress_bridge;
+pub mod forget;
 pub mod memory;
 
 use clap::{Parser, Subcommand};
@@ -61,4 +63,11 @@ pub enum Commands {
 
     /// Configuration management
     Config(config::ConfigArgs),
+
+    /// Purge a topic from memory, transcripts and the index
+    Forget(forget::ForgetArgs),
+
+    /// Forward a sandboxed command's proxy traffic (internal)
+    #[command(hide = true)]
+    EgressBridge(egress_bridge::EgressBridgeArgs),
 }
@@ -177,6 +177,7 @@ mod tests {
 
         let lock = WorkspaceLock {
             path: lock_path.clone(),
+            pid_path: lock_path.with_extension("pid"),
         };
         let result = lock.try_acquire().unwrap();
         assert!(result.is_none(), "try_acquire should return None when held");
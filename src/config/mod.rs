@@ -15,7 +15,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -88,6 +88,12 @@ pub struct Config {
 
     #[serde(default)]
     pub sandbox: SandboxPolicy,
+
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+
+    #[serde(default)]
+    pub approvals: ApprovalsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -221,6 +227,10 @@ pub struct ToolsConfig {
     #[serde(default)]
     pub require_approval: Vec<String>,
 
+    /// Finer-grained approval by tool and arguments; see [`ApprovalRule`]
+    #[serde(default)]
+    pub approval_rules: Vec<ApprovalRule>,
+
     /// Maximum characters for tool output (0 = unlimited)
     #[serde(default = "default_tool_output_max_chars")]
     pub tool_output_max_chars: usize,
@@ -239,6 +249,138 @@ pub struct ToolsConfig {
 
     #[serde(default)]
     pub external: HashMap<String, ExternalToolConfig>,
+
+    /// Gate privileged tools once untrusted content enters a turn
+    #[serde(default)]
+    pub taint: TaintConfig,
+
+    /// Save files before write_file, edit_file, edit_section and memory_write
+    /// change them, for `/rewind`. Changes made through bash aren't covered.
+    #[serde(default = "default_true")]
+    pub checkpoints: bool,
+}
+
+/// Outcome of a matching [`ApprovalRule`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Run without asking
+    Allow,
+    /// Ask the user first
+    Ask,
+    /// Refuse the call
+    Deny,
+}
+
+/// Approval rule matching a tool name and, optionally, its arguments. All
+/// given conditions must match. Deny rules win; otherwise saved grants are
+/// checked, then the first matching rule applies.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRule {
+    /// Tool name; glob patterns like "mcp_*" or "*" work too
+    pub tool: String,
+
+    /// Regex matched against the `command` argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Glob, or directory prefix, matched against the `path` argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Host of the `url` argument, in the `sandbox.allow_domains` format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+
+    pub action: RuleAction,
+
+    /// Shown to the user when the rule asks or denies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// What privileged tools do in a turn that saw untrusted content
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaintAction {
+    /// Ask the user first
+    #[default]
+    Approve,
+    /// Refuse the call
+    Block,
+}
+
+/// Per-turn taint tracking (see `security::taint`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaintConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub action: TaintAction,
+
+    /// Tools whose output is untrusted, besides web_fetch and MCP tools
+    #[serde(default)]
+    pub untrusted_tools: Vec<String>,
+
+    /// Files read from these directories are untrusted (e.g. downloads)
+    #[serde(default = "default_untrusted_paths")]
+    pub untrusted_paths: Vec<String>,
+
+    /// Tools gated in a tainted turn, besides bash, file writes outside the
+    /// workspace and external tools marked `sensitive`
+    #[serde(default)]
+    pub privileged_tools: Vec<String>,
+}
+
+/// Pending approval requests shared by all front-ends (see `ingress::approval`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalsConfig {
+    /// Seconds a request waits for an answer before it counts as denied
+    /// (default: `server.telegram_approval.timeout_seconds`, 300)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Deny at once when there is nobody to ask: the request names no
+    /// channel and the owner hasn't been active on any since startup
+    #[serde(default)]
+    pub auto_deny: bool,
+}
+
+/// Secret detection and redaction (see `security::secrets`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Redact secrets in tool outputs before they enter the session
+    #[serde(default = "default_true")]
+    pub redact_tool_output: bool,
+
+    /// Redact secrets in every request sent to an LLM provider
+    #[serde(default = "default_true")]
+    pub redact_outbound: bool,
+
+    /// Also flag long random-looking tokens, not just known key formats
+    #[serde(default = "default_true")]
+    pub entropy: bool,
+
+    /// Minimum length of a token checked for entropy
+    #[serde(default = "default_entropy_min_length")]
+    pub entropy_min_length: usize,
+
+    /// Shannon entropy (bits per character) at which a token counts as a secret
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f64,
+
+    /// Extra detectors: name -> regex. A capture group, if any, is what gets redacted.
+    #[serde(default)]
+    pub patterns: HashMap<String, String>,
+
+    /// Tools besides bash, external tools and scripts that get the real
+    /// values of secret placeholders in their arguments
+    #[serde(default)]
+    pub reveal_tools: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -251,6 +393,9 @@ pub struct ExternalToolConfig {
     pub sandbox: bool,
     #[serde(default)]
     pub path_args: Vec<String>,
+    /// Needs approval (or is blocked) once a turn saw untrusted content
+    #[serde(default)]
+    pub sensitive: bool,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -364,7 +509,8 @@ pub struct MemoryConfig {
     #[serde(default = "default_workspace")]
     pub workspace: String,
 
-    /// Embedding provider: "local" (fastembed, default), "openai", or "none"
+    /// Embedding provider: "none" (default), "local" (fastembed), "openai", "ollama",
+    /// "gguf", or the name of an OpenAI-compatible `[providers.<name>]` entry
     #[serde(default = "default_embedding_provider")]
     pub embedding_provider: String,
 
@@ -397,6 +543,111 @@ pub struct MemoryConfig {
     /// Set to 0 to preserve full message content
     #[serde(default)]
     pub session_max_chars: usize,
+
+    /// Background promotion of daily-log facts into MEMORY.md
+    #[serde(default)]
+    pub consolidation: ConsolidationConfig,
+
+    /// Commit cognitive files (MEMORY.md, memory/*.md, ...) to a git repository
+    /// in the workspace after each turn that changed them (default: false).
+    /// Opting in creates the repository on first commit if missing.
+    #[serde(default)]
+    pub auto_commit: bool,
+
+    /// Other agents' or profiles' workspaces searched read-only alongside
+    /// this one (`[[memory.federation]]`)
+    #[serde(default)]
+    pub federation: Vec<FederatedWorkspaceConfig>,
+
+    /// Files or folders (globs relative to the workspace) that are never sent
+    /// to a non-local LLM provider. Inline `<!-- private -->` ... `<!-- /private -->`
+    /// blocks are withheld the same way.
+    #[serde(default)]
+    pub private_paths: Vec<String>,
+
+    /// Also keep the active model's vectors as JSON in `chunks.embedding`, for
+    /// OpenClaw reading this index (default: false). Vectors are then stored
+    /// twice; otherwise only the packed copies in `chunk_embeddings` exist.
+    #[serde(default)]
+    pub openclaw_embeddings: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedWorkspaceConfig {
+    /// Origin label shown on results (e.g. "main", "work")
+    pub name: String,
+
+    /// Workspace directory. Defaults to the profile's workspace when
+    /// `profile` is set, otherwise to this workspace (another agent's index)
+    #[serde(default)]
+    pub workspace: Option<String>,
+
+    /// Profile whose workspace to search (~/.zier-alpha/workspace-{profile})
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// Agent whose index to search (default: "main")
+    #[serde(default = "default_federation_agent")]
+    pub agent: String,
+
+    /// Allow snippets from this workspace to be sent to non-local LLM
+    /// providers (default: false)
+    #[serde(default)]
+    pub allow_cloud: bool,
+}
+
+impl FederatedWorkspaceConfig {
+    /// Resolve the workspace directory, relative to this agent's workspace
+    pub fn workspace_path(&self, own_workspace: &Path) -> PathBuf {
+        if let Some(workspace) = &self.workspace {
+            PathBuf::from(shellexpand::tilde(workspace).to_string())
+        } else if let Some(profile) = &self.profile {
+            profile_workspace(profile)
+        } else {
+            own_workspace.to_path_buf()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationConfig {
+    /// Run consolidation from the heartbeat or scheduler (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Minimum time between heartbeat-driven runs (default: "24h")
+    #[serde(default = "default_consolidation_interval")]
+    pub interval: String,
+
+    /// Cron expression for the daemon scheduler. When set, the scheduler
+    /// runs consolidation instead of the heartbeat.
+    #[serde(default)]
+    pub schedule: Option<String>,
+
+    /// Only read daily logs from the last N days (default: 7)
+    #[serde(default = "default_consolidation_lookback_days")]
+    pub lookback_days: usize,
+
+    /// Write the diff to MEMORY.md directly instead of leaving it for review
+    #[serde(default)]
+    pub auto_apply: bool,
+
+    /// Model used to extract facts (default: agent.default_model)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl Default for ConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: default_consolidation_interval(),
+            schedule: None,
+            lookback_days: default_consolidation_lookback_days(),
+            auto_apply: false,
+            model: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -511,11 +762,13 @@ pub struct TelegramApprovalConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
 
-    /// Timeout in seconds for approval requests (default: 300 = 5 minutes)
+    /// Timeout in seconds for approval requests (default: 300 = 5 minutes).
+    /// `approvals.timeout_seconds` takes precedence.
     #[serde(default = "default_approval_timeout_seconds")]
     pub timeout_seconds: u64,
 
-    /// Auto-deny if approval times out (default: false)
+    /// Unused: requests are denied when they time out. See
+    /// `approvals.auto_deny` for denying requests nobody can answer.
     #[serde(default = "default_false")]
     pub auto_deny: bool,
 }
@@ -657,6 +910,9 @@ fn default_web_fetch_max_bytes() -> usize {
 fn default_tool_output_max_chars() -> usize {
     50000 // 50k characters max for tool output by default
 }
+fn default_untrusted_paths() -> Vec<String> {
+    vec!["~/Downloads".to_string()]
+}
 fn default_vision_fallback_model() -> String {
     "gpt-4o".to_string()
 }
@@ -687,6 +943,15 @@ fn default_claude_cli_model() -> String {
 fn default_gemini_base_url() -> String {
     "https://generativelanguage.googleapis.com/v1beta".to_string()
 }
+fn default_federation_agent() -> String {
+    "main".to_string()
+}
+fn default_entropy_min_length() -> usize {
+    32
+}
+fn default_entropy_threshold() -> f64 {
+    4.3
+}
 fn default_true() -> bool {
     true
 }
@@ -723,6 +988,12 @@ fn default_index_paths() -> Vec<MemoryIndexPath> {
 fn default_pattern() -> String {
     "**/*.md".to_string()
 }
+fn default_consolidation_interval() -> String {
+    "24h".to_string()
+}
+fn default_consolidation_lookback_days() -> usize {
+    7
+}
 fn default_session_max_messages() -> usize {
     15 // Match OpenClaw's default
 }
@@ -823,11 +1094,41 @@ impl Default for ToolsConfig {
             bash_timeout_ms: default_bash_timeout(),
             web_fetch_max_bytes: default_web_fetch_max_bytes(),
             require_approval: Vec::new(),
+            approval_rules: Vec::new(),
             tool_output_max_chars: default_tool_output_max_chars(),
             log_injection_warnings: default_true(),
             use_content_delimiters: default_true(),
             allowed_builtin: default_allowed_tools(),
             external: HashMap::new(),
+            taint: TaintConfig::default(),
+            checkpoints: default_true(),
+        }
+    }
+}
+
+impl Default for TaintConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            action: TaintAction::default(),
+            untrusted_tools: Vec::new(),
+            untrusted_paths: default_untrusted_paths(),
+            privileged_tools: Vec::new(),
+        }
+    }
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            redact_tool_output: default_true(),
+            redact_outbound: default_true(),
+            entropy: default_true(),
+            entropy_min_length: default_entropy_min_length(),
+            entropy_threshold: default_entropy_threshold(),
+            patterns: HashMap::new(),
+            reveal_tools: Vec::new(),
         }
     }
 }
@@ -855,6 +1156,11 @@ impl Default for MemoryConfig {
             paths: default_index_paths(),
             session_max_messages: default_session_max_messages(),
             session_max_chars: 0, // 0 = unlimited (preserve full content like OpenClaw)
+            consolidation: ConsolidationConfig::default(),
+            auto_commit: false,
+            federation: Vec::new(),
+            private_paths: Vec::new(),
+            openclaw_embeddings: false,
         }
     }
 }
@@ -923,6 +1229,12 @@ impl Config {
             }
         }
 
+        // Validate Memory Consolidation
+        if self.memory.consolidation.enabled {
+            parse_duration(&self.memory.consolidation.interval)
+                .map_err(|e| anyhow::anyhow!("Invalid memory.consolidation.interval: {}", e))?;
+        }
+
         // Validate Active Hours
         if let Some(ref hours) = self.heartbeat.active_hours {
             let re = Regex::new(r"^\d{2}:\d{2}$").unwrap();
@@ -1170,12 +1482,7 @@ impl Config {
         if let Ok(profile) = std::env::var("ZIER_ALPHA_PROFILE") {
             let trimmed = profile.trim().to_lowercase();
             if !trimmed.is_empty() && trimmed != "default" {
-                let base = directories::BaseDirs::new()
-                    .map(|b| b.home_dir().to_path_buf())
-                    .unwrap_or_else(|| PathBuf::from("~"));
-                return base
-                    .join(".zier-alpha")
-                    .join(format!("workspace-{}", trimmed));
+                return profile_workspace(&trimmed);
             }
         }
 
@@ -1185,6 +1492,15 @@ impl Config {
     }
 }
 
+/// Workspace of a named profile: ~/.zier-alpha/workspace-{profile}
+pub fn profile_workspace(profile: &str) -> PathBuf {
+    let base = directories::BaseDirs::new()
+        .map(|b| b.home_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("~"));
+    base.join(".zier-alpha")
+        .join(format!("workspace-{}", profile.trim().to_lowercase()))
+}
+
 fn expand_env(s: &str) -> String {
     if let Some(var_name) = s.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
         std::env::var(var_name).unwrap_or_else(|_| s.to_string())
@@ -1239,13 +1555,37 @@ interval = "30m"
 #   ZIER_ALPHA_PROFILE=work                  - uses ~/.zier-alpha/workspace-work
 workspace = "~/.zier-alpha/workspace"
 
-# Embedding provider: "none" (default), "local" (requires 'fastembed' feature), "openai"
+# Embedding provider: "none" (default), "local" (requires 'fastembed' feature), "openai",
+# "ollama" (uses [providers.ollama] endpoint), or the name of a [providers.<name>] entry
+# for any OpenAI-compatible /embeddings endpoint (llama.cpp server, LM Studio, ...)
 embedding_provider = "none"
+# embedding_model = "nomic-embed-text"
 
 # Session memory settings (for /new command)
 # session_max_messages = 15    # Max messages to save (0 = unlimited)
 # session_max_chars = 0        # Max chars per message (0 = unlimited, preserves full content)
 
+# auto_commit = false          # Commit cognitive files to a git repo in the workspace after each turn (runs git init if needed)
+
+# Promote facts from daily logs (memory/*.md) into MEMORY.md
+# [memory.consolidation]
+# enabled = false
+# interval = "24h"             # Heartbeat-driven: minimum time between runs
+# schedule = "0 0 3 * * *"     # Daemon scheduler cron (takes over from heartbeat)
+# lookback_days = 7
+# auto_apply = false           # false: leave the diff in artifacts/ for review
+
+# Local-only memory: never sent to cloud LLM providers (withheld and audit-logged).
+# Inline blocks can be marked with <!-- private --> ... <!-- /private -->
+# private_paths = ["journal", "health/*.md"]
+
+# Search other agents' or profiles' memory read-only; results are labeled by name
+# [[memory.federation]]
+# name = "work"
+# profile = "work"             # or: workspace = "~/path/to/workspace"
+# agent = "main"               # whose index to read (default: "main")
+# allow_cloud = false          # may snippets reach non-local LLM providers?
+
 [server]
 enabled = true
 port = 31327
@@ -1256,6 +1596,13 @@ enabled = true
 port = 37777
 bind = "127.0.0.1"
 
+# Tool approvals outside an interactive chat (Telegram, scheduled jobs) are
+# asked on the channel the owner was last active on, and listed at
+# GET /api/approvals. Unanswered requests are denied after the timeout.
+# [approvals]
+# timeout_seconds = 300
+# auto_deny = false          # deny at once when there is nobody to ask
+
 [logging]
 level = "info"
 
@@ -1264,4 +1611,76 @@ level = "info"
 # - "overlay": Cognitive files go to workspace, others to project dir.
 # - "mount": Everything is in workspace; project dir is mounted at ./project.
 strategy = "overlay"
+
+# [sandbox]
+# enable_os_sandbox = false  # bubblewrap (Linux) / sandbox-exec (macOS) for commands
+# Linux: "auto" (bubblewrap, else Landlock + seccomp, else unshare),
+# "bubblewrap", "landlock" or "unshare"
+# linux_backend = "auto"
+# allow_network = false
+# Hosts reachable without allow_network, through a local egress proxy that
+# logs every connection. "*.example.com" = subdomains; no port = 80 and 443
+# allow_domains = ["api.github.com", "*.githubusercontent.com", "example.com:8443"]
+# allow_write = ["~/projects"]
+
+# [sandbox.limits]
+# Limits of every tool process (bash, external tools, MCP servers, script exec).
+# rlimits, plus cgroup v2 memory.max/pids.max where delegated. Unset = unlimited,
+# except output, capped at 1 MiB per stream by default.
+# memory_mb = 2048
+# cpu_seconds = 300
+# max_processes = 256
+# wall_clock_secs = 600
+# max_output_bytes = 1048576
+
+# [sandbox.tool_limits.bash]
+# Per-tool (or MCP server) overrides of [sandbox.limits]
+# memory_mb = 4096
+
+# [sandbox.bash]
+# Built-in bash tool profile per trust level: "unrestricted", "standard" or "strict"
+# - "standard": destructive commands refused, risky ones need approval,
+#   OS sandbox when enable_os_sandbox is set
+# - "strict": standard, plus no shell chaining and always the OS sandbox
+# owner = "standard"    # CLI, desktop, HTTP, owner Telegram
+# trusted = "strict"    # scheduled jobs
+
+# [secrets]
+# Redact API keys, tokens and passwords (known formats, high-entropy strings and
+# configured provider keys) from tool outputs and from requests to LLM providers.
+# Placeholders like [secret:github_token:1a2b3c4d] are restored in tool arguments.
+# enabled = true
+# redact_tool_output = true
+# redact_outbound = true
+# entropy = true
+# entropy_min_length = 32
+# entropy_threshold = 4.3
+# [secrets.patterns]
+# internal_token = "itk_[a-z0-9]{32}"
+
+# [[tools.approval_rules]]
+# Approval by tool and arguments: action = "allow", "ask" or "deny". Conditions
+# (all optional): command (regex), path (glob or directory), host (as in
+# allow_domains). Deny rules win, then grants saved from approval prompts
+# ("allow for this session" / "always allow"), then the first matching rule.
+# tool = "bash"
+# command = "^(ls|cat|git (status|diff|log))\\b"
+# action = "allow"
+#
+# [[tools.approval_rules]]
+# tool = "bash"
+# command = "\\brm\\s+-[a-z]*r"
+# action = "deny"
+# reason = "No recursive deletes"
+
+# [tools.taint]
+# Once a turn pulls in untrusted content (web_fetch, MCP tools, files under
+# untrusted_paths), privileged tools need approval ("approve") or are refused
+# ("block"): bash, write_file/edit_file outside the workspace, and external
+# tools with `sensitive = true`.
+# enabled = true
+# action = "approve"
+# untrusted_paths = ["~/Downloads"]
+# untrusted_tools = ["rss_reader"]
+# privileged_tools = ["deploy"]
 "#;
@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Captured stdout/stderr per stream when `max_output_bytes` isn't set
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxPolicy {
@@ -6,6 +10,11 @@ pub struct SandboxPolicy {
     #[serde(default)]
     pub allow_network: bool,
 
+    /// Hosts reachable without `allow_network`: `api.github.com`,
+    /// `*.example.com`, `host:port` (80 and 443 without a port)
+    #[serde(default)]
+    pub allow_domains: Vec<String>,
+
     /// List of paths/globs to allow read access to.
     /// Note: The executable itself and basic system libraries are always allowed.
     #[serde(default)]
@@ -19,15 +28,161 @@ pub struct SandboxPolicy {
     #[serde(default)]
     pub allow_env: bool,
 
-    /// Enforce OS-level sandboxing for external commands (unshare/sandbox-exec)
+    /// Enforce OS-level sandboxing for external commands (see `linux_backend`, sandbox-exec on macOS)
     #[serde(default)]
     pub enable_os_sandbox: bool,
+
+    /// Linux sandbox implementation used when `enable_os_sandbox` is set
+    #[serde(default)]
+    pub linux_backend: LinuxSandboxBackend,
+
+    /// Policy profiles of the built-in bash tool, per trust level
+    #[serde(default)]
+    pub bash: BashProfiles,
+
+    /// Resource limits of every command run under this policy
+    #[serde(default)]
+    pub limits: ResourceLimits,
+
+    /// Per-tool overrides of `limits`, by tool or MCP server name
+    #[serde(default)]
+    pub tool_limits: HashMap<String, ResourceLimits>,
+}
+
+impl SandboxPolicy {
+    /// This policy with `limits` overridden by the `tool_limits` entry of `name`
+    pub fn for_tool(&self, name: &str) -> SandboxPolicy {
+        let mut policy = self.clone();
+        policy.limits = self.limits_for(name);
+        policy
+    }
+
+    pub fn limits_for(&self, name: &str) -> ResourceLimits {
+        match self.tool_limits.get(name) {
+            Some(limits) => limits.or(&self.limits),
+            None => self.limits.clone(),
+        }
+    }
+}
+
+/// Resource limits of a tool process. Unset limits don't apply, except the
+/// output cap, which defaults to [`DEFAULT_MAX_OUTPUT_BYTES`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Address space (RLIMIT_AS) and, with cgroup v2, `memory.max`
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+
+    /// CPU time (RLIMIT_CPU)
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+
+    /// Processes and threads: cgroup v2 `pids.max`, else RLIMIT_NPROC, which
+    /// counts every process of the user
+    #[serde(default)]
+    pub max_processes: Option<u64>,
+
+    /// Wall clock time before the process is killed
+    #[serde(default)]
+    pub wall_clock_secs: Option<u64>,
+
+    /// Captured bytes per output stream; the rest is dropped with a marker
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+}
+
+impl ResourceLimits {
+    /// Field-wise: these limits where set, `base` otherwise
+    pub fn or(&self, base: &ResourceLimits) -> ResourceLimits {
+        ResourceLimits {
+            memory_mb: self.memory_mb.or(base.memory_mb),
+            cpu_seconds: self.cpu_seconds.or(base.cpu_seconds),
+            max_processes: self.max_processes.or(base.max_processes),
+            wall_clock_secs: self.wall_clock_secs.or(base.wall_clock_secs),
+            max_output_bytes: self.max_output_bytes.or(base.max_output_bytes),
+        }
+    }
+
+    pub fn output_cap(&self) -> usize {
+        self.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES)
+    }
+}
+
+/// How commands are confined on Linux
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinuxSandboxBackend {
+    /// Bubblewrap if installed, otherwise Landlock + seccomp if the kernel
+    /// supports it, otherwise `unshare`
+    #[default]
+    Auto,
+    /// `bwrap` with bind mounts of the allowed paths
+    Bubblewrap,
+    /// Landlock filesystem rules and a seccomp network filter applied in-process
+    Landlock,
+    /// `unshare` namespaces only (no filesystem rules)
+    Unshare,
+}
+
+impl LinuxSandboxBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Bubblewrap => "bubblewrap",
+            Self::Landlock => "landlock",
+            Self::Unshare => "unshare",
+        }
+    }
+}
+
+/// How the built-in bash tool runs commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BashProfile {
+    /// Run `bash -c` directly, without checks
+    Unrestricted,
+    /// Refuse destructive commands, ask approval for risky ones, and confine
+    /// to the OS sandbox when `enable_os_sandbox` is set
+    Standard,
+    /// Like `standard`, but shell chaining (`&&`, `|`, `;`, ...) is refused
+    /// and the OS sandbox is always used
+    Strict,
+}
+
+/// Bash profile for each trust level that gets tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BashProfiles {
+    /// Owner turns: CLI, desktop, HTTP API, owner Telegram messages
+    #[serde(default = "default_owner_bash_profile")]
+    pub owner: BashProfile,
+
+    /// Scheduled jobs and other trusted events
+    #[serde(default = "default_trusted_bash_profile")]
+    pub trusted: BashProfile,
+}
+
+fn default_owner_bash_profile() -> BashProfile {
+    BashProfile::Standard
+}
+
+fn default_trusted_bash_profile() -> BashProfile {
+    BashProfile::Strict
+}
+
+impl Default for BashProfiles {
+    fn default() -> Self {
+        Self {
+            owner: default_owner_bash_profile(),
+            trusted: default_trusted_bash_profile(),
+        }
+    }
 }
 
 impl Default for SandboxPolicy {
     fn default() -> Self {
         Self {
             allow_network: false,
+            allow_domains: vec![],
             // By default, only allow reading from common locations if needed,
             // but the compiler will add the executable path.
             // A strict default might be empty.
@@ -37,6 +192,10 @@ impl Default for SandboxPolicy {
             allow_write: vec![],
             allow_env: false,
             enable_os_sandbox: false,
+            linux_backend: LinuxSandboxBackend::default(),
+            bash: BashProfiles::default(),
+            limits: ResourceLimits::default(),
+            tool_limits: HashMap::new(),
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Application state shared between UI and worker
 
 use crate::agent::{SessionInfo, SessionStatus, ToolCall};
+use crate::ingress::ApprovalDecision;
 
 /// Message from UI to worker
 #[derive(Debug, Clone)]
@@ -11,10 +12,8 @@ pub enum UiMessage {
     NewSession,
     /// Resume a session by ID
     ResumeSession(String),
-    /// Approve pending tool calls
-    ApproveTools(Vec<ToolCall>),
-    /// Deny pending tool calls
-    DenyTools,
+    /// Approve or deny pending tool calls
+    ResolveTools(Vec<ToolCall>, ApprovalDecision),
     /// Request session list refresh
     RefreshSessions,
     /// Request status update
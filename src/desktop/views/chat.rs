@@ -3,6 +3,7 @@
 use eframe::egui::{self, Color32, RichText, ScrollArea, TextEdit, Ui};
 
 use crate::desktop::state::{ChatMessage, MessageRole, Panel, ToolStatus, UiMessage, UiState};
+use crate::ingress::ApprovalDecision;
 
 pub struct ChatView;
 
@@ -73,13 +74,17 @@ impl ChatView {
                             ui.label(format!("  - {}", tool.name));
                         }
                         ui.horizontal(|ui| {
-                            if ui.button("Approve").clicked() {
-                                message_to_send = Some(UiMessage::ApproveTools(tools.clone()));
-                                state.pending_approval = None;
-                            }
-                            if ui.button("Deny").clicked() {
-                                message_to_send = Some(UiMessage::DenyTools);
-                                state.pending_approval = None;
+                            for (label, decision) in [
+                                ("Approve", ApprovalDecision::Approve),
+                                ("Approve for session", ApprovalDecision::ApproveSession),
+                                ("Always approve", ApprovalDecision::ApproveAlways),
+                                ("Deny", ApprovalDecision::Deny),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    message_to_send =
+                                        Some(UiMessage::ResolveTools(tools.clone(), decision));
+                                    state.pending_approval = None;
+                                }
                             }
                         });
                     });
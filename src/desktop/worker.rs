@@ -121,74 +121,7 @@ async fn worker_loop(
             UiMessage::Chat(message) => {
                 // Stream response with tool support
                 match agent.chat_stream_with_tools(&message, Vec::new()).await {
-                    Ok(stream) => {
-                        let mut stream = pin!(stream);
-                        let mut pending_tools: Vec<ToolCall> = Vec::new();
-
-                        while let Some(result) = stream.next().await {
-                            match result {
-                                Ok(event) => match event {
-                                    StreamEvent::Content(text) => {
-                                        let _ = tx.send(WorkerMessage::ContentChunk(text));
-                                    }
-                                    StreamEvent::ToolCallStart {
-                                        name,
-                                        id,
-                                        arguments,
-                                    } => {
-                                        let detail = extract_tool_detail(&name, &arguments);
-                                        let _ = tx.send(WorkerMessage::ToolCallStart {
-                                            name,
-                                            id,
-                                            detail,
-                                        });
-                                    }
-                                    StreamEvent::ApprovalRequired {
-                                        name,
-                                        id,
-                                        arguments,
-                                    } => {
-                                        // Send tool call start event so it shows in UI
-                                        let detail = extract_tool_detail(&name, &arguments);
-                                        let _ = tx.send(WorkerMessage::ToolCallStart {
-                                            name: name.clone(),
-                                            id: id.clone(),
-                                            detail,
-                                        });
-
-                                        // Collect for approval
-                                        pending_tools.push(ToolCall {
-                                            id,
-                                            name,
-                                            arguments,
-                                        });
-                                    }
-                                    StreamEvent::ToolCallEnd { name, id, output } => {
-                                        let _ = tx.send(WorkerMessage::ToolCallEnd {
-                                            name,
-                                            id,
-                                            output,
-                                        });
-                                    }
-                                    StreamEvent::Done => {
-                                        if !pending_tools.is_empty() {
-                                            let _ = tx.send(WorkerMessage::ToolsPendingApproval(
-                                                pending_tools.clone(),
-                                            ));
-                                            pending_tools.clear();
-                                        } else {
-                                            let _ = tx.send(WorkerMessage::Done);
-                                        }
-                                        should_auto_save = true;
-                                    }
-                                },
-                                Err(e) => {
-                                    let _ = tx.send(WorkerMessage::Error(e.to_string()));
-                                    break;
-                                }
-                            }
-                        }
-                    }
+                    Ok(stream) => should_auto_save = forward_stream(stream, &tx).await,
                     Err(e) => {
                         let _ = tx.send(WorkerMessage::Error(e.to_string()));
                     }
@@ -220,13 +153,20 @@ async fn worker_loop(
                     let _ = tx.send(WorkerMessage::Error(e.to_string()));
                 }
             },
-            UiMessage::ApproveTools(_tools) => {
-                // Tool approval is handled in chat loop
-                // For now, just send done
-                let _ = tx.send(WorkerMessage::Done);
-            }
-            UiMessage::DenyTools => {
-                let _ = tx.send(WorkerMessage::Done);
+            UiMessage::ResolveTools(tools, decision) => {
+                // Approved calls run when the stream resumes; denied ones
+                // give the model the refusal as their result
+                for call in &tools {
+                    if let Err(e) = agent.resolve_tool_call(&call.id, decision, None).await {
+                        let _ = tx.send(WorkerMessage::Error(e.to_string()));
+                    }
+                }
+                match agent.resume_chat_stream_with_tools().await {
+                    Ok(stream) => should_auto_save = forward_stream(stream, &tx).await,
+                    Err(e) => {
+                        let _ = tx.send(WorkerMessage::Error(e.to_string()));
+                    }
+                }
             }
             UiMessage::RefreshSessions => {
                 if let Ok(sessions) = list_sessions_for_agent(&agent_id).await {
@@ -354,3 +294,77 @@ Available commands:
 
     Ok(())
 }
+
+/// Forward a response stream to the UI. Calls waiting for approval are
+/// sent as [`WorkerMessage::ToolsPendingApproval`] when the stream pauses
+/// for them. Returns whether the session should be auto-saved.
+async fn forward_stream(
+    stream: impl futures::Stream<Item = Result<StreamEvent>>,
+    tx: &Sender<WorkerMessage>,
+) -> bool {
+    let mut stream = pin!(stream);
+    let mut pending_tools: Vec<ToolCall> = Vec::new();
+    let mut should_auto_save = false;
+
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(event) => match event {
+                StreamEvent::Content(text) => {
+                    let _ = tx.send(WorkerMessage::ContentChunk(text));
+                }
+                StreamEvent::ToolCallStart {
+                    name,
+                    id,
+                    arguments,
+                } => {
+                    let detail = extract_tool_detail(&name, &arguments);
+                    let _ = tx.send(WorkerMessage::ToolCallStart { name, id, detail });
+                }
+                StreamEvent::ApprovalRequired {
+                    name,
+                    id,
+                    arguments,
+                    reason,
+                } => {
+                    // Send tool call start event so it shows in UI,
+                    // with why it needs approval
+                    let detail = extract_tool_detail(&name, &arguments);
+                    let detail = match (detail, reason) {
+                        (Some(d), Some(r)) => Some(format!("{} ({})", d, r)),
+                        (d, r) => r.or(d),
+                    };
+                    let _ = tx.send(WorkerMessage::ToolCallStart {
+                        name: name.clone(),
+                        id: id.clone(),
+                        detail,
+                    });
+
+                    // Collect for approval
+                    pending_tools.push(ToolCall {
+                        id,
+                        name,
+                        arguments,
+                    });
+                }
+                StreamEvent::ToolCallEnd { name, id, output } => {
+                    let _ = tx.send(WorkerMessage::ToolCallEnd { name, id, output });
+                }
+                StreamEvent::Done => {
+                    let _ = tx.send(WorkerMessage::Done);
+                    should_auto_save = true;
+                }
+            },
+            Err(e) => {
+                let _ = tx.send(WorkerMessage::Error(e.to_string()));
+                return should_auto_save;
+            }
+        }
+    }
+
+    // The stream ends without `Done` when it waits for approval
+    if !pending_tools.is_empty() {
+        let _ = tx.send(WorkerMessage::ToolsPendingApproval(pending_tools));
+        should_auto_save = true;
+    }
+    should_auto_save
+}
@@ -10,11 +10,12 @@ use tracing::{debug, info, warn};
 
 use super::events::{emit_heartbeat_event, now_ms, HeartbeatEvent, HeartbeatStatus};
 use crate::agent::{
-    build_heartbeat_prompt, is_heartbeat_ok, Agent, AgentConfig, SessionStore, HEARTBEAT_OK_TOKEN,
+    build_heartbeat_prompt, is_heartbeat_ok, Agent, AgentConfig, SessionStore, SmartClient,
+    HEARTBEAT_OK_TOKEN,
 };
 use crate::concurrency::{TurnGate, WorkspaceLock};
 use crate::config::{parse_duration, parse_time, Config};
-use crate::memory::MemoryManager;
+use crate::memory::{ConsolidationOptions, MemoryManager};
 
 pub struct HeartbeatRunner {
     config: Config,
@@ -146,6 +147,56 @@ impl HeartbeatRunner {
                     warn!("Heartbeat error: {}", e);
                 }
             }
+
+            self.consolidate_if_due().await;
+        }
+    }
+
+    /// Run memory consolidation when it is enabled, heartbeat-driven and due
+    async fn consolidate_if_due(&self) {
+        let settings = &self.config.memory.consolidation;
+        if !settings.enabled || settings.schedule.is_some() {
+            return;
+        }
+        let Ok(interval) = parse_duration(&settings.interval) else {
+            return;
+        };
+        match self.memory.consolidation_due(interval).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                warn!("Failed to check consolidation schedule: {}", e);
+                return;
+            }
+        }
+
+        if self.turn_gate.as_ref().is_some_and(|gate| gate.is_busy()) {
+            debug!("Skipping consolidation: agent turn in flight");
+            return;
+        }
+        let _ws_guard = match self.workspace_lock.try_acquire() {
+            Ok(Some(guard)) => guard,
+            Ok(None) => {
+                debug!("Skipping consolidation: workspace locked by another process");
+                return;
+            }
+            Err(e) => {
+                warn!("Failed to acquire workspace lock for consolidation: {}", e);
+                return;
+            }
+        };
+
+        let options = ConsolidationOptions::from_config(&self.config);
+        let client = SmartClient::new(self.config.clone(), options.model.clone());
+        match self.memory.consolidate(&client, &options).await {
+            Ok(Some(run)) => info!(
+                "Memory consolidation run {}: {} ({} logs)",
+                run.id,
+                run.status.as_str(),
+                run.logs.len()
+            ),
+            Ok(None) => debug!("Memory consolidation: no new daily logs"),
+            Err(e) => warn!("Memory consolidation failed: {}", e),
         }
     }
 
@@ -1,15 +1,130 @@
+//! Tool approvals shared by every front-end
+//!
+//! Calls that need the owner's approval outside an interactive chat (a
+//! Telegram conversation, a scheduled job) are registered with the
+//! [`ApprovalCoordinator`]. It asks on the channel the request names, or on
+//! the one the owner was last active on, and waits for an answer until the
+//! request times out. Any front-end can list pending requests, answer them
+//! or [`subscribe`](ApprovalCoordinator::subscribe) to follow them: Telegram
+//! sends inline buttons for requests on its chats, the HTTP server serves
+//! them at `/api/approvals`. Interactive front-ends (CLI, desktop, HTTP
+//! chat) answer their own session's calls with the same [`ApprovalDecision`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
 use tokio::time::{sleep_until, Instant};
+use tracing::{debug, warn};
+
+use crate::config::Config;
+use crate::security::approval::GrantScope;
 
 /// Decision returned by an approval request.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ApprovalDecision {
     Approve,
+    /// Approve, and similar calls until the agent exits
+    #[serde(rename = "session")]
+    ApproveSession,
+    /// Approve, and similar calls from now on
+    #[serde(rename = "always")]
+    ApproveAlways,
     Deny,
 }
 
+impl ApprovalDecision {
+    /// Approval reaching as far as `scope`
+    pub fn approve(scope: GrantScope) -> Self {
+        match scope {
+            GrantScope::Once => Self::Approve,
+            GrantScope::Session => Self::ApproveSession,
+            GrantScope::Always => Self::ApproveAlways,
+        }
+    }
+
+    /// How far an approval reaches; `None` for a denial
+    pub fn scope(self) -> Option<GrantScope> {
+        match self {
+            Self::Approve => Some(GrantScope::Once),
+            Self::ApproveSession => Some(GrantScope::Session),
+            Self::ApproveAlways => Some(GrantScope::Always),
+            Self::Deny => None,
+        }
+    }
+}
+
+/// The owner's answer to a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalAnswer {
+    pub decision: ApprovalDecision,
+    /// Why the call was denied, told to the model
+    pub reason: Option<String>,
+}
+
+impl ApprovalAnswer {
+    pub fn new(decision: ApprovalDecision) -> Self {
+        Self {
+            decision,
+            reason: None,
+        }
+    }
+
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Self {
+            decision: ApprovalDecision::Deny,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// A call to put to the owner
+#[derive(Debug, Clone)]
+pub struct ApprovalRequest {
+    pub call_id: String,
+    pub tool_name: String,
+    pub arguments: String,
+    /// Why the call needs approval, when known
+    pub reason: Option<String>,
+    /// What the call runs for: `telegram:<chat_id>`, `scheduler:<job>`, ...
+    pub source: String,
+    /// Where to ask (`telegram:<chat_id>`, `web`, ...); the channel the
+    /// owner was last active on when `None`
+    pub channel: Option<String>,
+    /// Overrides the configured timeout
+    pub timeout: Option<Duration>,
+}
+
+/// A request waiting for an answer, as front-ends see it
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingApproval {
+    pub call_id: String,
+    pub tool_name: String,
+    pub arguments: String,
+    pub reason: Option<String>,
+    pub source: String,
+    /// Where the owner was asked, if anywhere in particular
+    pub channel: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What subscribers hear about
+#[derive(Debug, Clone)]
+pub enum ApprovalEvent {
+    Requested(PendingApproval),
+    Resolved {
+        approval: PendingApproval,
+        /// `None` when the request timed out or was abandoned
+        answer: Option<ApprovalAnswer>,
+        /// Chat and message id of the request's Telegram message, if any
+        telegram_message: Option<(i64, i64)>,
+    },
+}
+
 /// Request to send an approval UI message to the Telegram service.
 #[derive(Debug)]
 pub struct ApprovalUIRequest {
@@ -17,36 +132,104 @@ pub struct ApprovalUIRequest {
     pub chat_id: i64,
     pub tool_name: String,
     pub arguments: String,
+    /// Why the call needs approval, when known
+    pub reason: Option<String>,
     /// Respond with the Telegram message_id of the UI message.
     pub respond_msg_id: oneshot::Sender<i64>,
 }
 
 /// A pending approval request waiting for user interaction.
 #[derive(Debug)]
-struct PendingApproval {
-    chat_id: i64,
-    message_id: i64,
-    tx: oneshot::Sender<ApprovalDecision>,
+struct PendingEntry {
+    approval: PendingApproval,
+    tx: oneshot::Sender<ApprovalAnswer>,
+    /// Chat and message id of the Telegram message; the message id is -1
+    /// until Telegram returns it
+    telegram_message: Option<(i64, i64)>,
     timeout_at: Instant,
 }
 
-/// Coordinator for managing approval requests from the agent to the UI.
-/// Holds a channel to send UI requests to the Telegram service.
+/// Coordinator for approval requests from agents to whichever front-end
+/// the owner uses. Holds a channel to send UI requests to the Telegram
+/// service, when there is one.
 #[derive(Debug, Clone)]
 pub struct ApprovalCoordinator {
-    pending: Arc<Mutex<HashMap<String, PendingApproval>>>,
-    ui_tx: mpsc::Sender<ApprovalUIRequest>,
+    pending: Arc<Mutex<HashMap<String, PendingEntry>>>,
+    events: broadcast::Sender<ApprovalEvent>,
+    ui_tx: Option<mpsc::Sender<ApprovalUIRequest>>,
+    /// Channel the owner last sent a message on
+    active_channel: Arc<std::sync::Mutex<Option<String>>>,
+    timeout: Duration,
+    auto_deny: bool,
+}
+
+impl Default for ApprovalCoordinator {
+    /// Coordinator without a Telegram front-end
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            ui_tx: None,
+            active_channel: Arc::new(std::sync::Mutex::new(None)),
+            timeout: Duration::from_secs(300),
+            auto_deny: false,
+        }
+    }
 }
 
 impl ApprovalCoordinator {
     /// Create a new ApprovalCoordinator with a sender for UI requests.
     pub fn new(ui_tx: mpsc::Sender<ApprovalUIRequest>) -> Self {
         Self {
-            pending: Arc::new(Mutex::new(HashMap::new())),
-            ui_tx,
+            ui_tx: Some(ui_tx),
+            ..Self::default()
         }
     }
 
+    /// Apply `[approvals]`; Telegram buttons are dropped when
+    /// `server.telegram_approval.enabled` is off
+    pub fn with_config(mut self, config: &Config) -> Self {
+        let seconds = config
+            .approvals
+            .timeout_seconds
+            .unwrap_or(config.server.telegram_approval.timeout_seconds);
+        self.timeout = Duration::from_secs(seconds);
+        self.auto_deny = config.approvals.auto_deny;
+        if !config.server.telegram_approval.enabled {
+            self.ui_tx = None;
+        }
+        self
+    }
+
+    /// Follow requests as they come and go
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalEvent> {
+        self.events.subscribe()
+    }
+
+    /// Note that the owner just used `channel` (`telegram:<chat_id>`,
+    /// `web`, ...); requests without a channel are asked there
+    pub fn note_activity(&self, channel: &str) {
+        *self.active_channel.lock().unwrap() = Some(channel.to_string());
+    }
+
+    pub fn active_channel(&self) -> Option<String> {
+        self.active_channel.lock().unwrap().clone()
+    }
+
+    /// Requests waiting for an answer, oldest first
+    pub async fn list(&self) -> Vec<PendingApproval> {
+        let mut pending: Vec<PendingApproval> = self
+            .pending
+            .lock()
+            .await
+            .values()
+            .map(|e| e.approval.clone())
+            .collect();
+        pending.sort_by_key(|a| a.requested_at);
+        pending
+    }
+
     /// Register a new approval request and wait for a decision.
     /// This sends a UI request to the Telegram service, then waits
     /// for the user to approve/deny or for timeout.
@@ -59,47 +242,117 @@ impl ApprovalCoordinator {
         args: String,
         timeout: std::time::Duration,
     ) -> Option<ApprovalDecision> {
+        self.request_with_reason(call_id, chat_id, tool, args, None, timeout)
+            .await
+    }
+
+    /// Like [`Self::request`], telling the user why the call needs approval
+    pub async fn request_with_reason(
+        &self,
+        call_id: String,
+        chat_id: i64,
+        tool: String,
+        args: String,
+        reason: Option<String>,
+        timeout: std::time::Duration,
+    ) -> Option<ApprovalDecision> {
+        let chat = format!("telegram:{}", chat_id);
+        let request = ApprovalRequest {
+            call_id,
+            tool_name: tool,
+            arguments: args,
+            reason,
+            source: chat.clone(),
+            channel: Some(chat),
+            timeout: Some(timeout),
+        };
+        self.wait(request).await.ok().map(|a| a.decision)
+    }
+
+    /// Put `request` to the owner and wait for the answer. Requests that
+    /// time out, or that nobody can be asked about under `auto_deny`, are
+    /// denied with a reason saying so.
+    pub async fn ask(&self, request: ApprovalRequest) -> ApprovalAnswer {
+        let call_id = request.call_id.clone();
+        self.wait(request).await.unwrap_or_else(|reason| {
+            warn!("Denying {}: {}", call_id, reason);
+            ApprovalAnswer::deny(reason)
+        })
+    }
+
+    /// Wait for an answer; `Err` says why there is none
+    async fn wait(&self, request: ApprovalRequest) -> Result<ApprovalAnswer, &'static str> {
+        let timeout = request.timeout.unwrap_or(self.timeout);
+        let channel = request.channel.or_else(|| self.active_channel());
+        let telegram_chat = match &self.ui_tx {
+            Some(_) => channel
+                .as_deref()
+                .and_then(|c| c.strip_prefix("telegram:"))
+                .and_then(|id| id.parse::<i64>().ok()),
+            None => None,
+        };
+        if self.auto_deny && channel.is_none() {
+            return Err("nobody was available to approve it");
+        }
+
         let (tx_decision, rx_decision) = oneshot::channel();
         let (tx_msg_id, rx_msg_id) = oneshot::channel();
         let deadline = Instant::now() + timeout;
+        let now = Utc::now();
+        let approval = PendingApproval {
+            call_id: request.call_id.clone(),
+            tool_name: request.tool_name,
+            arguments: request.arguments,
+            reason: request.reason,
+            source: request.source,
+            channel,
+            requested_at: now,
+            expires_at: now + chrono::Duration::from_std(timeout).unwrap_or_default(),
+        };
+        let call_id = request.call_id;
 
         // Insert pending entry with placeholder immediately to avoid race
         {
             let mut map = self.pending.lock().await;
-            map.insert(call_id.clone(), PendingApproval {
-                chat_id,
-                message_id: -1, // Placeholder
-                tx: tx_decision,
-                timeout_at: deadline,
-            });
+            map.insert(
+                call_id.clone(),
+                PendingEntry {
+                    approval: approval.clone(),
+                    tx: tx_decision,
+                    telegram_message: telegram_chat.map(|chat_id| (chat_id, -1)),
+                    timeout_at: deadline,
+                },
+            );
         }
-
-        // Build UI request
-        let ui_req = ApprovalUIRequest {
-            call_id: call_id.clone(),
-            chat_id,
-            tool_name: tool,
-            arguments: args,
-            respond_msg_id: tx_msg_id,
-        };
+        let _ = self.events.send(ApprovalEvent::Requested(approval.clone()));
 
         // Send UI request to Telegram service
-        if self.ui_tx.send(ui_req).await.is_err() {
-            // UI service gone, cleanup
-            let mut map = self.pending.lock().await;
-            map.remove(&call_id);
-            return None;
+        let mut got_msg_id = true;
+        if let (Some(ui_tx), Some(chat_id)) = (&self.ui_tx, telegram_chat) {
+            let ui_req = ApprovalUIRequest {
+                call_id: call_id.clone(),
+                chat_id,
+                tool_name: approval.tool_name.clone(),
+                arguments: approval.arguments.clone(),
+                reason: approval.reason.clone(),
+                respond_msg_id: tx_msg_id,
+            };
+            if ui_tx.send(ui_req).await.is_err() {
+                // UI service gone, cleanup
+                self.abandon(&call_id).await;
+                return Err("the approval channel is closed");
+            }
+            got_msg_id = false;
         }
 
         let mut msg_id_fut = Box::pin(rx_msg_id);
         let mut decision_fut = Box::pin(rx_decision);
-        let mut got_msg_id = false;
 
         loop {
             tokio::select! {
                 res = &mut decision_fut => {
                     // Decision received
-                    return res.ok();
+                    return res.map_err(|_| "the request was abandoned");
                 }
                 res = &mut msg_id_fut, if !got_msg_id => {
                     got_msg_id = true;
@@ -107,56 +360,153 @@ impl ApprovalCoordinator {
                         // Update entry with real message_id
                         let mut map = self.pending.lock().await;
                         if let Some(entry) = map.get_mut(&call_id) {
-                            entry.message_id = id;
+                            if let Some((_, message_id)) = &mut entry.telegram_message {
+                                *message_id = id;
+                            }
                         }
                     }
                 }
                 _ = sleep_until(deadline) => {
-                    // Timeout
-                    let mut map = self.pending.lock().await;
-                    map.remove(&call_id);
-                    return None;
+                    self.abandon(&call_id).await;
+                    return Err("no answer before the approval timed out");
                 }
             }
         }
     }
 
-    /// Resolve a pending approval by call_id with the given decision.
-    /// Called by the Telegram service when user clicks a button.
-    /// Returns the chat_id and message_id for UI update, or None if not found.
-    pub async fn resolve(&self, call_id: &str, decision: ApprovalDecision) -> Option<(i64, i64)> {
-        let mut map = self.pending.lock().await;
-        if let Some(entry) = map.remove(call_id) {
-            // Send the decision; if the receiver is gone, ignore.
-            let _ = entry.tx.send(decision);
-            Some((entry.chat_id, entry.message_id))
-        } else {
-            None
+    /// Drop a request without an answer
+    async fn abandon(&self, call_id: &str) {
+        let entry = self.pending.lock().await.remove(call_id);
+        if let Some(entry) = entry {
+            debug!("Approval request {} expired", call_id);
+            let _ = self.events.send(ApprovalEvent::Resolved {
+                approval: entry.approval,
+                answer: None,
+                telegram_message: entry.telegram_message,
+            });
         }
     }
 
+    /// Resolve a pending approval by call_id with the given decision.
+    /// Called by any front-end, e.g. the Telegram service when the user
+    /// clicks a button. Returns the request, or None if not found.
+    pub async fn resolve(
+        &self,
+        call_id: &str,
+        decision: ApprovalDecision,
+    ) -> Option<PendingApproval> {
+        self.answer(call_id, ApprovalAnswer::new(decision)).await
+    }
+
+    /// Like [`Self::resolve`], with a denial reason for the model
+    pub async fn answer(&self, call_id: &str, answer: ApprovalAnswer) -> Option<PendingApproval> {
+        let entry = self.pending.lock().await.remove(call_id)?;
+        // Send the decision; if the receiver is gone, ignore.
+        let _ = entry.tx.send(answer.clone());
+        let _ = self.events.send(ApprovalEvent::Resolved {
+            approval: entry.approval.clone(),
+            answer: Some(answer),
+            telegram_message: entry.telegram_message,
+        });
+        Some(entry.approval)
+    }
+
     /// Clean up any pending approvals that have timed out (i.e., timeout_at <= now).
     /// Returns a vector of (call_id, chat_id, message_id) for each expired entry
-    /// that hasn't been resolved yet. The caller can use this to edit the UI messages.
+    /// that has a Telegram message. Subscribers are told about every one.
     pub async fn cleanup(&self, now: Instant) -> Vec<(String, i64, i64)> {
-        let mut map = self.pending.lock().await;
-        let mut expired = Vec::new();
-
-        // Collect call_ids to remove
-        let mut to_remove = Vec::new();
-        for (call_id, entry) in map.iter() {
-            if entry.timeout_at <= now {
-                to_remove.push(call_id.clone());
+        let expired: Vec<String> = {
+            let map = self.pending.lock().await;
+            map.iter()
+                .filter(|(_, entry)| entry.timeout_at <= now)
+                .map(|(call_id, _)| call_id.clone())
+                .collect()
+        };
+
+        let mut messages = Vec::new();
+        for call_id in expired {
+            let message = self
+                .pending
+                .lock()
+                .await
+                .get(&call_id)
+                .and_then(|e| e.telegram_message);
+            self.abandon(&call_id).await;
+            if let Some((chat_id, message_id)) = message {
+                messages.push((call_id, chat_id, message_id));
             }
         }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(call_id: &str, channel: Option<&str>) -> ApprovalRequest {
+        ApprovalRequest {
+            call_id: call_id.to_string(),
+            tool_name: "bash".to_string(),
+            arguments: r#"{"command":"make deploy"}"#.to_string(),
+            reason: None,
+            source: "scheduler:deploy".to_string(),
+            channel: channel.map(str::to_string),
+            timeout: None,
+        }
+    }
 
-        // Remove and gather info
-        for call_id in to_remove {
-            if let Some(entry) = map.remove(&call_id) {
-                expired.push((call_id, entry.chat_id, entry.message_id));
+    #[tokio::test]
+    async fn test_ask_on_active_channel() {
+        let (ui_tx, mut ui_rx) = mpsc::channel(4);
+        let coord = ApprovalCoordinator::new(ui_tx);
+        coord.note_activity("telegram:42");
+        let mut events = coord.subscribe();
+
+        let asker = coord.clone();
+        let waiting = tokio::spawn(async move { asker.ask(request("call_1", None)).await });
+
+        let ui_req = ui_rx.recv().await.unwrap();
+        assert_eq!(ui_req.chat_id, 42);
+        let _ = ui_req.respond_msg_id.send(7);
+        match events.recv().await.unwrap() {
+            ApprovalEvent::Requested(approval) => {
+                assert_eq!(approval.channel.as_deref(), Some("telegram:42"))
             }
+            other => panic!("unexpected event: {:?}", other),
         }
+        assert_eq!(coord.list().await.len(), 1);
+
+        coord
+            .resolve("call_1", ApprovalDecision::ApproveSession)
+            .await;
+        let answer = waiting.await.unwrap();
+        assert_eq!(answer.decision, ApprovalDecision::ApproveSession);
+        assert!(coord.list().await.is_empty());
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ApprovalEvent::Resolved {
+                answer: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_auto_deny() {
+        let coord = ApprovalCoordinator::default();
+        let mut short = request("call_1", Some("web"));
+        short.timeout = Some(Duration::from_millis(10));
+        let answer = coord.ask(short).await;
+        assert_eq!(answer.decision, ApprovalDecision::Deny);
+        assert!(answer.reason.unwrap().contains("timed out"));
 
-        expired
+        // Nobody to ask: denied right away under auto_deny
+        let mut config = Config::default();
+        config.approvals.auto_deny = true;
+        let coord = ApprovalCoordinator::default().with_config(&config);
+        let answer = coord.ask(request("call_2", None)).await;
+        assert_eq!(answer.decision, ApprovalDecision::Deny);
+        assert!(coord.list().await.is_empty());
     }
 }
@@ -1,10 +1,13 @@
+use crate::agent::tool_executor::with_trust;
 use crate::agent::tools::registry::ToolRegistry;
 use crate::agent::DiskMonitor;
 use crate::agent::Session;
-use crate::agent::Tool;
+use crate::agent::{Tool, OWNER_ONLY_TOOLS};
+use crate::concurrency::WorkspaceLock;
 use crate::config::Config;
+use crate::ingress::approval::ApprovalRequest;
 use crate::ingress::{ApprovalCoordinator, TrustLevel};
-use crate::memory::ArtifactWriter;
+use crate::memory::{ArtifactWriter, ConsolidationOptions};
 use crate::prompts::PromptRegistry;
 use crate::scheduler::JobConfig;
 use crate::scripting::ScriptService;
@@ -18,9 +21,7 @@ use super::types::IngressMessage;
 use crate::agent::Agent;
 use crate::agent::LlmError;
 use crate::agent::ScriptTool;
-use crate::ingress::ApprovalDecision;
-use serde_json;
-use std::time::Duration;
+use crate::agent::SmartClient;
 
 /// Process a single ingress message. This contains the original logic
 /// that was in the ingress_loop's spawned task.
@@ -123,17 +124,26 @@ pub async fn process_ingress_message(
                 return;
             }
 
-            // Execute chat with approval handling
-            let response_result = if !msg.images.is_empty() {
+            // Execute chat, asking for approvals on the chat the message came from
+            approval_coord.note_activity(&msg.source);
+            let turn = if !msg.images.is_empty() {
                 base_agent.chat_with_images(&msg.payload, msg.images).await
             } else {
                 base_agent.chat(&msg.payload).await
             };
+            let response_result = settle_approvals(
+                &mut base_agent,
+                turn,
+                &approval_coord,
+                &msg.source,
+                Some(&msg.source),
+                TrustLevel::OwnerCommand,
+            )
+            .await;
 
             match response_result {
                 Ok(response) => {
                     info!("OwnerCommand response: {}", response);
-                    // Output handling (same as before)
                     if msg.source.starts_with("telegram:") {
                         if let Some(client) = &telegram_client {
                             if let Some(chat_id_str) = msg.source.strip_prefix("telegram:") {
@@ -153,108 +163,7 @@ pub async fn process_ingress_message(
                         info!("Artifact written for {}", msg.source);
                     }
                 }
-                Err(e) => {
-                    // Check if the error is ApprovalRequired
-                    if let Some(LlmError::ApprovalRequired(tool_name, tool_call)) =
-                        e.downcast_ref::<LlmError>()
-                    {
-                        // Extract chat_id from source
-                        let chat_id = if let Some(rest) = msg.source.strip_prefix("telegram:") {
-                            match rest.parse::<i64>() {
-                                Ok(id) => id,
-                                Err(_) => {
-                                    error!(
-                                        "Invalid chat_id in source for approval: {}",
-                                        msg.source
-                                    );
-                                    return;
-                                }
-                            }
-                        } else {
-                            error!("Approval required but message source is not from Telegram");
-                            return;
-                        };
-                        let call_id = tool_call.id.clone();
-                        let args_str = match serde_json::to_string(&tool_call.arguments) {
-                            Ok(s) => s,
-                            Err(_) => tool_call.arguments.to_string(),
-                        };
-                        let timeout =
-                            Duration::from_secs(config.server.telegram_approval.timeout_seconds);
-                        let decision = match approval_coord
-                            .request(
-                                call_id.clone(),
-                                chat_id,
-                                tool_name.clone(),
-                                args_str,
-                                timeout,
-                            )
-                            .await
-                        {
-                            Some(dec) => dec,
-                            None => {
-                                error!("Approval request failed or timed out for {}", call_id);
-                                return;
-                            }
-                        };
-                        match decision {
-                            ApprovalDecision::Approve => {
-                                base_agent.approve_tool_call(&call_id);
-                            }
-                            ApprovalDecision::Deny => {
-                                base_agent
-                                    .provide_tool_result(
-                                        call_id.clone(),
-                                        "User denied.".to_string(),
-                                    )
-                                    .await;
-                            }
-                        };
-                        // Continue chat to get final response after approval/denial
-                        match base_agent.continue_chat().await {
-                            Ok(final_response) => {
-                                // Output handling (same as above)
-                                if msg.source.starts_with("telegram:") {
-                                    if let Some(client) = &telegram_client {
-                                        if let Some(chat_id_str) =
-                                            msg.source.strip_prefix("telegram:")
-                                        {
-                                            if let Ok(chat_id) = chat_id_str.parse::<i64>() {
-                                                if let Err(e) = client
-                                                    .send_message(chat_id, &final_response)
-                                                    .await
-                                                {
-                                                    error!(
-                                                        "Failed to send telegram response: {}",
-                                                        e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else if let Err(e) = artifact_writer
-                                    .write(
-                                        &final_response,
-                                        &msg.source,
-                                        "OwnerCommand",
-                                        base_agent.model(),
-                                    )
-                                    .await
-                                {
-                                    error!("Failed to write artifact: {}", e);
-                                } else {
-                                    info!("Artifact written for {}", msg.source);
-                                }
-                            }
-                            Err(e2) => {
-                                error!("Failed after approval handling: {}", e2);
-                                return;
-                            }
-                        }
-                    } else {
-                        error!("OwnerCommand failed: {}", e);
-                    }
-                }
+                Err(e) => error!("OwnerCommand failed: {}", e),
             }
         }
         TrustLevel::TrustedEvent => {
@@ -268,6 +177,34 @@ pub async fn process_ingress_message(
                 return;
             }
 
+            if msg.payload == crate::scheduler::CONSOLIDATE_MEMORY {
+                // Like the heartbeat, skip rather than race a turn editing MEMORY.md
+                let _ws_guard = match WorkspaceLock::new().and_then(|lock| lock.try_acquire()) {
+                    Ok(Some(guard)) => guard,
+                    Ok(None) => {
+                        info!("Skipping memory consolidation: workspace is locked");
+                        return;
+                    }
+                    Err(e) => {
+                        error!("Memory consolidation: workspace lock failed: {}", e);
+                        return;
+                    }
+                };
+                let options = ConsolidationOptions::from_config(&config);
+                let client = SmartClient::new((*config).clone(), options.model.clone());
+                match memory.consolidate(&client, &options).await {
+                    Ok(Some(run)) => info!(
+                        "Memory consolidation run {}: {} ({} logs)",
+                        run.id,
+                        run.status.as_str(),
+                        run.logs.len()
+                    ),
+                    Ok(None) => info!("Memory consolidation: no new daily logs"),
+                    Err(e) => error!("Memory consolidation failed: {}", e),
+                }
+                return;
+            }
+
             if msg.payload.starts_with("EXECUTE_JOB: ") {
                 let prompt_ref = msg.payload.trim_start_matches("EXECUTE_JOB: ");
                 if let Some(prompt) = prompts.get(prompt_ref) {
@@ -281,15 +218,17 @@ pub async fn process_ingress_message(
                     let job_name = msg.source.strip_prefix("scheduler:").unwrap_or("");
                     let job_config = jobs.iter().find(|j| j.name == job_name);
 
-                    // Scope tools based on job config
+                    // Scope tools based on job config; owner-only tools are never scoped in
                     let scoped_tools = if let Some(job) = job_config {
+                        let job_tools = base_tools
+                            .iter()
+                            .filter(|t: &&Arc<dyn Tool>| !OWNER_ONLY_TOOLS.contains(&t.name()));
                         if job.tool_ref == "all" {
-                            base_tools.clone()
+                            job_tools.cloned().collect()
                         } else {
                             let allowed_names: Vec<&str> =
                                 job.tool_ref.split(',').map(|s| s.trim()).collect();
-                            base_tools
-                                .iter()
+                            job_tools
                                 .filter(|t: &&Arc<dyn Tool>| allowed_names.contains(&t.name()))
                                 .cloned()
                                 .collect()
@@ -306,7 +245,20 @@ pub async fn process_ingress_message(
                         let _ = base_agent.new_session().await;
                     }
 
-                    match base_agent.chat("Execute job.").await {
+                    // Bash runs under the `[sandbox.bash] trusted` profile; approvals
+                    // are asked wherever the owner was last active
+                    let turn =
+                        with_trust(TrustLevel::TrustedEvent, base_agent.chat("Execute job.")).await;
+                    let turn = settle_approvals(
+                        &mut base_agent,
+                        turn,
+                        &approval_coord,
+                        &msg.source,
+                        None,
+                        TrustLevel::TrustedEvent,
+                    )
+                    .await;
+                    match turn {
                         Ok(response) => {
                             info!("Job response: {}", response);
                             let _ = artifact_writer
@@ -361,3 +313,40 @@ pub async fn process_ingress_message(
         }
     }
 }
+
+/// Put calls that need approval to the owner through `approvals` and
+/// continue the turn with each answer, until it finishes or fails otherwise
+async fn settle_approvals(
+    agent: &mut Agent,
+    mut result: anyhow::Result<String>,
+    approvals: &ApprovalCoordinator,
+    source: &str,
+    channel: Option<&str>,
+    trust: TrustLevel,
+) -> anyhow::Result<String> {
+    loop {
+        let call = match &result {
+            Err(e) => match e.downcast_ref::<LlmError>() {
+                Some(LlmError::ApprovalRequired(_, call)) => call.clone(),
+                _ => return result,
+            },
+            Ok(_) => return result,
+        };
+        let answer = approvals
+            .ask(ApprovalRequest {
+                call_id: call.id.clone(),
+                tool_name: call.name.clone(),
+                reason: agent.approval_reason(&call),
+                arguments: call.arguments,
+                source: source.to_string(),
+                channel: channel.map(str::to_string),
+                timeout: None,
+            })
+            .await;
+        // A denial reaches the model as the call's result
+        agent
+            .resolve_tool_call(&call.id, answer.decision, answer.reason.as_deref())
+            .await?;
+        result = with_trust(trust, agent.continue_chat()).await;
+    }
+}
@@ -164,6 +164,10 @@ impl TelegramClient {
                 [
                     { "text": "✅ Approve", "callback_data": format!("approve:{}", call_id) },
                     { "text": "❌ Deny", "callback_data": format!("deny:{}", call_id) }
+                ],
+                [
+                    { "text": "🔓 This session", "callback_data": format!("session:{}", call_id) },
+                    { "text": "♾️ Always", "callback_data": format!("always:{}", call_id) }
                 ]
             ]
         });
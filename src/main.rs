@@ -8,6 +8,11 @@ use cli::{Cli, Commands};
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // The egress bridge runs inside sandboxes and must not log or load config
+    if let Commands::EgressBridge(ref args) = cli.command {
+        return cli::egress_bridge::run(args);
+    }
+
     // Supervisor mode: intercept before runtime start
     if cli.supervised {
         return run_supervised();
@@ -109,5 +114,7 @@ async fn async_main(cli: Cli) -> Result<()> {
         Commands::Daemon(args) => cli::daemon::run(args, &cli.agent).await,
         Commands::Memory(args) => cli::memory::run(args, &cli.agent).await,
         Commands::Config(args) => cli::config::run(args).await,
+        Commands::Forget(args) => cli::forget::run(args, &cli.agent).await,
+        Commands::EgressBridge(args) => cli::egress_bridge::run(&args),
     }
 }
@@ -0,0 +1,598 @@
+//! Approximate nearest-neighbor index (HNSW) for vector search without sqlite-vec
+//!
+//! A small in-process implementation of Hierarchical Navigable Small World
+//! graphs over cosine similarity. Vectors are normalized on insert so the
+//! similarity is a dot product. Removals leave tombstones that are skipped in
+//! results and dropped when the graph is compacted.
+//!
+//! The index is persisted in a compact binary file beside the SQLite database.
+//! SQLite stays the source of truth: on load the caller reconciles the graph
+//! with the stored vectors, so a stale, missing or corrupt file only costs
+//! catch-up work.
+
+use anyhow::{anyhow, bail, Result};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Neighbors per node on upper layers
+const M: usize = 16;
+/// Neighbors per node on layer 0
+const M0: usize = 2 * M;
+/// Candidate list size while inserting
+const EF_CONSTRUCTION: usize = 100;
+/// Minimum candidate list size while searching
+pub const EF_SEARCH: usize = 64;
+
+const MAGIC: &[u8; 4] = b"ZANN";
+const VERSION: u32 = 1;
+/// Magic, version, dims, count, entry and RNG state
+const HEADER_LEN: u64 = 28;
+/// Highest layer a node can be on
+const MAX_LEVEL: usize = 16;
+
+struct Node {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbor lists, one per layer the node lives on
+    links: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+/// Similarity-ordered node reference
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// HNSW graph over normalized vectors keyed by chunk id
+pub struct HnswIndex {
+    dims: usize,
+    nodes: Vec<Node>,
+    ids: HashMap<String, u32>,
+    entry: Option<u32>,
+    rng: u64,
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalized(vector: &[f32]) -> Vec<f32> {
+    let norm = dot(vector, vector).sqrt();
+    if norm > 1e-10 {
+        vector.iter().map(|x| x / norm).collect()
+    } else {
+        vector.to_vec()
+    }
+}
+
+impl HnswIndex {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Number of live (non-deleted) vectors
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// Ids of live vectors
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.ids.keys().map(String::as_str)
+    }
+
+    /// Nodes removed but still linked into the graph
+    pub fn tombstones(&self) -> usize {
+        self.nodes.len() - self.ids.len()
+    }
+
+    /// Insert or replace the vector for `id`
+    pub fn insert(&mut self, id: &str, vector: &[f32]) -> Result<()> {
+        if vector.len() != self.dims {
+            bail!(
+                "Vector has {} dimensions, index expects {}",
+                vector.len(),
+                self.dims
+            );
+        }
+        self.remove(id);
+
+        let vector = normalized(vector);
+        let level = self.random_level();
+        let idx = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            id: id.to_string(),
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id.to_string(), idx);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(idx);
+            return Ok(());
+        };
+
+        let query = self.nodes[idx as usize].vector.clone();
+        let top = self.nodes[entry as usize].links.len() - 1;
+
+        // Greedy descent through layers above the new node's level
+        let mut ep = entry;
+        for layer in (level + 1..=top).rev() {
+            ep = self.search_layer(&query, &[ep], 1, layer)[0].1;
+        }
+
+        let mut eps = vec![ep];
+        for layer in (0..=level.min(top)).rev() {
+            let candidates = self.search_layer(&query, &eps, EF_CONSTRUCTION, layer);
+            let max = if layer == 0 { M0 } else { M };
+            let neighbors: Vec<u32> = candidates
+                .iter()
+                .filter(|s| s.1 != idx)
+                .take(M)
+                .map(|s| s.1)
+                .collect();
+
+            self.nodes[idx as usize].links[layer] = neighbors.clone();
+            for n in neighbors {
+                self.nodes[n as usize].links[layer].push(idx);
+                if self.nodes[n as usize].links[layer].len() > max {
+                    self.prune(n, layer, max);
+                }
+            }
+            eps = candidates.into_iter().map(|s| s.1).collect();
+        }
+
+        if level > top {
+            self.entry = Some(idx);
+        }
+        Ok(())
+    }
+
+    /// Remove the vector for `id`; returns whether it was present
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.ids.remove(id) {
+            Some(idx) => {
+                self.nodes[idx as usize].deleted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Top `k` live vectors by cosine similarity, best first
+    pub fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(String, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if query.len() != self.dims || k == 0 {
+            return Vec::new();
+        }
+
+        let query = normalized(query);
+        let mut ep = entry;
+        for layer in (1..self.nodes[entry as usize].links.len()).rev() {
+            ep = self.search_layer(&query, &[ep], 1, layer)[0].1;
+        }
+
+        // Tombstones occupy candidate slots, so widen the search by their share
+        let ef = ef.max(k) + self.tombstones().min(ef);
+        self.search_layer(&query, &[ep], ef, 0)
+            .into_iter()
+            .filter(|s| !self.nodes[s.1 as usize].deleted)
+            .take(k)
+            .map(|s| (self.nodes[s.1 as usize].id.clone(), s.0))
+            .collect()
+    }
+
+    /// Rebuild the graph without tombstones
+    pub fn compact(&mut self) {
+        let live: Vec<(String, Vec<f32>)> = self
+            .nodes
+            .drain(..)
+            .filter(|n| !n.deleted)
+            .map(|n| (n.id, n.vector))
+            .collect();
+
+        self.ids.clear();
+        self.entry = None;
+        for (id, vector) in live {
+            // Same dimension as before; cannot fail
+            let _ = self.insert(&id, &vector);
+        }
+    }
+
+    /// Beam search on one layer; results sorted by similarity, best first
+    fn search_layer(&self, query: &[f32], entry: &[u32], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entry.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = BinaryHeap::new();
+        let mut results: BinaryHeap<std::cmp::Reverse<Scored>> = BinaryHeap::new();
+
+        for &e in entry {
+            let s = Scored(dot(query, &self.nodes[e as usize].vector), e);
+            candidates.push(s);
+            results.push(std::cmp::Reverse(s));
+            if results.len() > ef {
+                results.pop();
+            }
+        }
+
+        while let Some(current) = candidates.pop() {
+            let worst = results.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+            if current.0 < worst && results.len() >= ef {
+                break;
+            }
+            let Some(links) = self.nodes[current.1 as usize].links.get(layer) else {
+                continue;
+            };
+            for &n in links {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let s = Scored(dot(query, &self.nodes[n as usize].vector), n);
+                let worst = results.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+                if results.len() < ef || s.0 > worst {
+                    candidates.push(s);
+                    results.push(std::cmp::Reverse(s));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut out: Vec<Scored> = results.into_iter().map(|r| r.0).collect();
+        out.sort_by(|a, b| b.cmp(a));
+        out
+    }
+
+    /// Keep the `max` closest neighbors of a node on a layer
+    fn prune(&mut self, node: u32, layer: usize, max: usize) {
+        let base = &self.nodes[node as usize].vector;
+        let mut scored: Vec<Scored> = self.nodes[node as usize].links[layer]
+            .iter()
+            .map(|&n| Scored(dot(base, &self.nodes[n as usize].vector), n))
+            .collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(max);
+        self.nodes[node as usize].links[layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+
+    /// Level for a new node: geometric with ratio 1/M (xorshift, deterministic)
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let uniform = ((self.rng >> 11) as f64 / (1u64 << 53) as f64).max(f64::MIN_POSITIVE);
+        let level = (-uniform.ln() / (M as f64).ln()) as usize;
+        level.min(MAX_LEVEL)
+    }
+
+    /// Write the index to `path` (atomically via a temporary file)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        {
+            let mut w = BufWriter::new(File::create(&tmp)?);
+            w.write_all(MAGIC)?;
+            write_u32(&mut w, VERSION)?;
+            write_u32(&mut w, self.dims as u32)?;
+            write_u32(&mut w, self.nodes.len() as u32)?;
+            write_u32(&mut w, self.entry.map(|e| e + 1).unwrap_or(0))?;
+            w.write_all(&self.rng.to_le_bytes())?;
+            for node in &self.nodes {
+                write_u32(&mut w, node.id.len() as u32)?;
+                w.write_all(node.id.as_bytes())?;
+                w.write_all(&[node.deleted as u8])?;
+                for x in &node.vector {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+                write_u32(&mut w, node.links.len() as u32)?;
+                for links in &node.links {
+                    write_u32(&mut w, links.len() as u32)?;
+                    for n in links {
+                        write_u32(&mut w, *n)?;
+                    }
+                }
+            }
+            w.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read an index written by [`HnswIndex::save`]. A file that doesn't
+    /// describe a valid graph is an error, never a panic or a huge allocation.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut r = BufReader::new(file);
+        let corrupt = || anyhow!("Corrupt vector index {}", path.display());
+
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut r)? != VERSION {
+            bail!("{} is not a vector index", path.display());
+        }
+        let dims = read_u32(&mut r)? as usize;
+        let count = read_u32(&mut r)? as usize;
+        let entry = read_u32(&mut r)? as usize;
+        let mut rng = [0u8; 8];
+        r.read_exact(&mut rng)?;
+
+        // Every node takes at least its id length, flag, vector, layer count
+        // and one neighbor count
+        let node_min = (4 * dims as u64).checked_add(13).ok_or_else(corrupt)?;
+        let needed = (count as u64).checked_mul(node_min).ok_or_else(corrupt)?;
+        if needed > size.saturating_sub(HEADER_LEN) {
+            return Err(corrupt());
+        }
+        if entry > count || (entry == 0) != (count == 0) {
+            return Err(corrupt());
+        }
+        // Lengths read below are bounded by the file, and so are allocations
+        let len = |r: &mut BufReader<File>, unit: u64| -> Result<usize> {
+            let n = read_u32(r)? as u64;
+            if n.saturating_mul(unit) > size {
+                return Err(corrupt());
+            }
+            Ok(n as usize)
+        };
+
+        let mut nodes = Vec::with_capacity(count);
+        let mut ids = HashMap::new();
+        for idx in 0..count {
+            let mut id = vec![0u8; len(&mut r, 1)?];
+            r.read_exact(&mut id)?;
+            let id = String::from_utf8(id)?;
+            let mut deleted = [0u8; 1];
+            r.read_exact(&mut deleted)?;
+            let mut vector = vec![0f32; dims];
+            for x in vector.iter_mut() {
+                let mut b = [0u8; 4];
+                r.read_exact(&mut b)?;
+                *x = f32::from_le_bytes(b);
+            }
+            let layers = read_u32(&mut r)? as usize;
+            if layers == 0 || layers > MAX_LEVEL + 1 {
+                return Err(corrupt());
+            }
+            let mut links = Vec::with_capacity(layers);
+            for _ in 0..layers {
+                let n = len(&mut r, 4)?;
+                let mut layer = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let neighbor = read_u32(&mut r)?;
+                    if neighbor as usize >= count {
+                        return Err(corrupt());
+                    }
+                    layer.push(neighbor);
+                }
+                links.push(layer);
+            }
+            if deleted[0] == 0 {
+                ids.insert(id.clone(), idx as u32);
+            }
+            nodes.push(Node {
+                id,
+                vector,
+                links,
+                deleted: deleted[0] != 0,
+            });
+        }
+
+        // Insertion follows links on a layer into the neighbor's same layer
+        for node in &nodes {
+            for (layer, links) in node.links.iter().enumerate() {
+                if links
+                    .iter()
+                    .any(|&n| nodes[n as usize].links.len() <= layer)
+                {
+                    return Err(corrupt());
+                }
+            }
+        }
+
+        Ok(Self {
+            dims,
+            nodes,
+            ids,
+            entry: (entry as u32).checked_sub(1),
+            rng: u64::from_le_bytes(rng),
+        })
+    }
+}
+
+fn write_u32(w: &mut impl Write, v: u32) -> std::io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn read_u32(r: &mut impl Read) -> std::io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vectors(n: usize, dims: usize) -> Vec<Vec<f32>> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        (0..n)
+            .map(|_| {
+                (0..dims)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        ((state >> 33) as f32 / u32::MAX as f32) - 0.25
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn exact(data: &[Vec<f32>], query: &[f32], k: usize) -> Vec<String> {
+        let q = normalized(query);
+        let mut scored: Vec<(f32, usize)> = data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (dot(&q, &normalized(v)), i))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored
+            .into_iter()
+            .take(k)
+            .map(|(_, i)| i.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_recall_against_exact_search() {
+        let data = vectors(2000, 32);
+        let mut index = HnswIndex::new(32);
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&i.to_string(), v).unwrap();
+        }
+
+        let queries = vectors(50, 32);
+        let mut hits = 0;
+        for q in &queries {
+            let expected: HashSet<String> = exact(&data, q, 10).into_iter().collect();
+            hits += index
+                .search(q, 10, EF_SEARCH)
+                .into_iter()
+                .filter(|(id, _)| expected.contains(id))
+                .count();
+        }
+        let recall = hits as f32 / (queries.len() * 10) as f32;
+        assert!(recall > 0.9, "recall {}", recall);
+    }
+
+    #[test]
+    fn test_remove_save_load() -> Result<()> {
+        let data = vectors(300, 8);
+        let mut index = HnswIndex::new(8);
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&i.to_string(), v)?;
+        }
+        assert!(index.insert("bad", &[1.0]).is_err());
+
+        // The best match for a stored vector is itself, until it is removed
+        assert_eq!(index.search(&data[7], 1, EF_SEARCH)[0].0, "7");
+        assert!(index.remove("7"));
+        assert!(index
+            .search(&data[7], 5, EF_SEARCH)
+            .iter()
+            .all(|(id, _)| id != "7"));
+        assert_eq!(index.len(), 299);
+        assert_eq!(index.tombstones(), 1);
+
+        let temp = TempDir::new()?;
+        let path = temp.path().join("index.hnsw");
+        index.save(&path)?;
+        let mut loaded = HnswIndex::load(&path)?;
+        assert_eq!(loaded.len(), 299);
+        assert!(!loaded.contains("7"));
+        assert_eq!(loaded.search(&data[42], 1, EF_SEARCH)[0].0, "42");
+
+        loaded.compact();
+        assert_eq!(loaded.tombstones(), 0);
+        assert_eq!(loaded.search(&data[42], 1, EF_SEARCH)[0].0, "42");
+
+        std::fs::write(&path, b"garbage")?;
+        assert!(HnswIndex::load(&path).is_err());
+        Ok(())
+    }
+
+    /// File bytes for one-dimensional nodes with the given links per layer
+    fn index_bytes(count: u32, entry: u32, nodes: &[Vec<Vec<u32>>]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        for v in [VERSION, 1, count, entry] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        for (i, layers) in nodes.iter().enumerate() {
+            let id = i.to_string();
+            bytes.extend_from_slice(&(id.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(id.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&1f32.to_le_bytes());
+            bytes.extend_from_slice(&(layers.len() as u32).to_le_bytes());
+            for links in layers {
+                bytes.extend_from_slice(&(links.len() as u32).to_le_bytes());
+                for n in links {
+                    bytes.extend_from_slice(&n.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_corrupt_files_are_rejected() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("index.hnsw");
+        let load = |bytes: Vec<u8>| {
+            std::fs::write(&path, bytes).unwrap();
+            HnswIndex::load(&path)
+        };
+
+        let pair = vec![vec![vec![1]], vec![vec![0]]];
+        let mut index = load(index_bytes(2, 1, &pair))?;
+        assert_eq!(index.len(), 2);
+        index.insert("2", &[1.0])?;
+        assert_eq!(index.search(&[1.0], 3, EF_SEARCH).len(), 3);
+
+        // More nodes than the file can hold
+        assert!(load(index_bytes(u32::MAX, 1, &pair)).is_err());
+        // Entry point past the last node, or missing
+        assert!(load(index_bytes(2, 3, &pair)).is_err());
+        assert!(load(index_bytes(2, 0, &pair)).is_err());
+        // Neighbor id past the last node
+        assert!(load(index_bytes(2, 1, &[vec![vec![5]], vec![vec![0]]])).is_err());
+        // A node without layers
+        assert!(load(index_bytes(2, 1, &[vec![], vec![vec![0]]])).is_err());
+        // A layer-1 link to a node only on layer 0
+        assert!(load(index_bytes(2, 1, &[vec![vec![1], vec![1]], vec![vec![0]]])).is_err());
+        // Truncated
+        let mut bytes = index_bytes(2, 1, &pair);
+        bytes.truncate(bytes.len() - 3);
+        assert!(load(bytes).is_err());
+        Ok(())
+    }
+}
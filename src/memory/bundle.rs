@@ -0,0 +1,534 @@
+//! Portable memory bundles
+//!
+//! `memory export` writes a gzip-compressed JSON Lines file. The first record
+//! is a manifest, then one record per workspace file (markdown and
+//! `skills/`), then index metadata the files alone can't recreate: relations
+//! asserted by the agent, daily logs already consolidated and, optionally,
+//! cached embeddings so the importing side doesn't pay to embed them again.
+//!
+//! ```text
+//! {"type":"manifest","format":"zier-alpha-memory-bundle","version":1,...}
+//! {"type":"file","path":"MEMORY.md","text":"# Memory\n..."}
+//! {"type":"relation","subject":"Alice","predicate":"works_on","object":"Alpha",...}
+//! {"type":"embedding","provider":"openai","model":"text-embedding-3-small",...}
+//! ```
+//!
+//! Imports never delete anything. Files that exist with other content are
+//! handled by a [`ConflictPolicy`].
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Component, Path};
+use tokio::task;
+
+use super::embeddings::{deserialize_embedding, serialize_embedding};
+use super::index::MemoryIndex;
+
+pub const BUNDLE_FORMAT: &str = "zier-alpha-memory-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// One line of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum Record {
+    Manifest {
+        format: String,
+        version: u32,
+        created_at: String,
+        agent: String,
+        /// Embedding models of the exported index
+        #[serde(default)]
+        models: Vec<String>,
+    },
+    File {
+        path: String,
+        /// UTF-8 content
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        /// Base64 content of binary files
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base64: Option<String>,
+    },
+    Relation {
+        subject: String,
+        predicate: String,
+        object: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject_kind: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        object_kind: Option<String>,
+    },
+    ConsolidatedLog {
+        path: String,
+        hash: String,
+    },
+    Embedding {
+        provider: String,
+        model: String,
+        #[serde(default)]
+        provider_key: String,
+        hash: String,
+        embedding: Vec<f32>,
+    },
+}
+
+/// What to do with an imported file that exists with different content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Keep the existing file
+    #[default]
+    Skip,
+    /// Replace it with the imported one
+    Overwrite,
+    /// Write the imported file next to it as `<name>.imported.<ext>`
+    Rename,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "rename" => Ok(ConflictPolicy::Rename),
+            other => bail!(
+                "Unknown conflict policy '{}' (expected skip, overwrite or rename)",
+                other
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    pub on_conflict: ConflictPolicy,
+    /// Report what would change without writing
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportStats {
+    pub files: usize,
+    pub relations: usize,
+    pub consolidated_logs: usize,
+    pub embeddings: usize,
+}
+
+/// Outcome of an import. Paths are relative to the workspace.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// New or replaced files
+    pub written: Vec<String>,
+    /// Files already present with the same content
+    pub unchanged: usize,
+    /// Files left alone because they differ (`skip`)
+    pub conflicts: Vec<String>,
+    /// (imported path, path written instead) under `rename`
+    pub renamed: Vec<(String, String)>,
+    pub relations: usize,
+    pub embeddings: usize,
+    /// Session transcripts copied (OpenClaw imports)
+    pub sessions: usize,
+    /// Embedding models of the source index
+    pub models: Vec<String>,
+}
+
+/// Workspace files that belong in a bundle: markdown anywhere outside hidden
+/// directories, and everything under `skills/`
+pub(super) fn collect_workspace_files(workspace: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let pattern = format!("{}/**/*", workspace.display());
+    let mut files = Vec::new();
+    for entry in glob::glob(&pattern)?.filter_map(|r| r.ok()) {
+        if !entry.is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.strip_prefix(workspace) else {
+            continue;
+        };
+        let hidden = relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        let is_markdown = relative.extension().is_some_and(|e| e == "md");
+        let is_skill = relative.starts_with("skills");
+        if hidden || !(is_markdown || is_skill) {
+            continue;
+        }
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((name, fs::read(&entry)?));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reject absolute paths and `..` so imports stay inside the workspace
+fn check_relative(path: &str) -> Result<()> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        bail!(
+            "Refusing to import '{}': not a relative workspace path",
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Write one imported file under `workspace` according to `options`
+pub(super) fn place_file(
+    workspace: &Path,
+    path: &str,
+    content: &[u8],
+    options: &ImportOptions,
+    report: &mut ImportReport,
+) -> Result<()> {
+    check_relative(path)?;
+    let target = workspace.join(path);
+
+    let destination = match fs::read(&target) {
+        Err(_) => path.to_string(),
+        Ok(existing) if existing == content => {
+            report.unchanged += 1;
+            return Ok(());
+        }
+        Ok(_) => match options.on_conflict {
+            ConflictPolicy::Skip => {
+                report.conflicts.push(path.to_string());
+                return Ok(());
+            }
+            ConflictPolicy::Overwrite => path.to_string(),
+            ConflictPolicy::Rename => {
+                let renamed = free_name(workspace, path);
+                report.renamed.push((path.to_string(), renamed.clone()));
+                renamed
+            }
+        },
+    };
+
+    if !options.dry_run {
+        let target = workspace.join(&destination);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, content)?;
+    }
+    if destination == path {
+        report.written.push(destination);
+    }
+    Ok(())
+}
+
+/// `notes.md` -> `notes.imported.md`, then `notes.imported-2.md`, ...
+fn free_name(workspace: &Path, path: &str) -> String {
+    let (stem, ext) = match path.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.contains('/') => (stem, format!(".{}", ext)),
+        _ => (path, String::new()),
+    };
+    (1..)
+        .map(|n| match n {
+            1 => format!("{}.imported{}", stem, ext),
+            n => format!("{}.imported-{}{}", stem, n, ext),
+        })
+        .find(|candidate| !workspace.join(candidate).exists())
+        .expect("unbounded candidates")
+}
+
+/// Write records as gzip-compressed JSON Lines
+pub(super) fn write_bundle(path: &Path, records: &[Record]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let file = fs::File::create(path)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    for record in records {
+        serde_json::to_writer(&mut encoder, record)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+/// Read and validate a bundle written by [`write_bundle`]
+pub(super) fn read_bundle(path: &Path) -> Result<Vec<Record>> {
+    let file = fs::File::open(path)?;
+    let reader = BufReader::new(GzDecoder::new(file));
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| anyhow!("{} is not a memory bundle: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = serde_json::from_str(&line)
+            .map_err(|e| anyhow!("Bad record on line {} of {}: {}", i + 1, path.display(), e))?;
+        records.push(record);
+    }
+
+    match records.first() {
+        Some(Record::Manifest {
+            format, version, ..
+        }) if format == BUNDLE_FORMAT => {
+            if *version > BUNDLE_VERSION {
+                bail!(
+                    "Bundle version {} is newer than this build supports ({})",
+                    version,
+                    BUNDLE_VERSION
+                );
+            }
+        }
+        _ => bail!("{} is not a memory bundle", path.display()),
+    }
+    Ok(records)
+}
+
+/// File content as a bundle record
+pub(super) fn file_record(path: String, content: Vec<u8>) -> Record {
+    match String::from_utf8(content) {
+        Ok(text) => Record::File {
+            path,
+            text: Some(text),
+            base64: None,
+        },
+        Err(e) => Record::File {
+            path,
+            text: None,
+            base64: Some(base64::engine::general_purpose::STANDARD.encode(e.into_bytes())),
+        },
+    }
+}
+
+/// Content of a file record
+pub(super) fn file_content(text: &Option<String>, encoded: &Option<String>) -> Result<Vec<u8>> {
+    match (text, encoded) {
+        (Some(text), _) => Ok(text.clone().into_bytes()),
+        (None, Some(encoded)) => Ok(base64::engine::general_purpose::STANDARD.decode(encoded)?),
+        (None, None) => Ok(Vec::new()),
+    }
+}
+
+impl MemoryIndex {
+    /// Index metadata for a bundle: agent-asserted relations, consolidated
+    /// logs and, with `embeddings`, cached vectors of the current chunks
+    pub(super) async fn export_metadata(&self, embeddings: bool) -> Result<Vec<Record>> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let mut records = Vec::new();
+
+            let mut stmt = conn.prepare(
+                r#"SELECT DISTINCT
+                          COALESCE(s.label, r.subject), r.predicate, COALESCE(o.label, r.object),
+                          CASE WHEN s.kind IN ('note', 'mention') THEN NULL ELSE s.kind END,
+                          CASE WHEN o.kind IN ('note', 'mention') THEN NULL ELSE o.kind END
+                   FROM relations r
+                   LEFT JOIN entities s ON s.name = r.subject
+                   LEFT JOIN entities o ON o.name = r.object
+                   WHERE r.origin = 'agent'
+                   ORDER BY r.updated_at"#,
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(Record::Relation {
+                    subject: row.get(0)?,
+                    predicate: row.get(1)?,
+                    object: row.get(2)?,
+                    subject_kind: row.get(3)?,
+                    object_kind: row.get(4)?,
+                })
+            })?;
+            for row in rows {
+                records.push(row?);
+            }
+
+            let mut stmt =
+                conn.prepare("SELECT path, hash FROM consolidated_logs ORDER BY path")?;
+            let rows = stmt.query_map([], |row| {
+                Ok(Record::ConsolidatedLog {
+                    path: row.get(0)?,
+                    hash: row.get(1)?,
+                })
+            })?;
+            for row in rows {
+                records.push(row?);
+            }
+
+            if embeddings {
+                let mut stmt = conn.prepare(
+                    r#"SELECT provider, model, provider_key, hash, embedding FROM embedding_cache
+                       WHERE hash IN (SELECT hash FROM chunks)
+                       ORDER BY model, hash"#,
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok(Record::Embedding {
+                        provider: row.get(0)?,
+                        model: row.get(1)?,
+                        provider_key: row.get(2)?,
+                        hash: row.get(3)?,
+                        embedding: deserialize_embedding(&row.get::<_, String>(4)?),
+                    })
+                })?;
+                for row in rows {
+                    records.push(row?);
+                }
+            }
+
+            Ok(records)
+        })
+        .await?
+    }
+
+    /// Add imported consolidated logs and cached embeddings without replacing
+    /// existing rows. Returns the number of embeddings added.
+    pub(super) async fn import_metadata(&self, records: Vec<Record>) -> Result<usize> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let now = chrono::Utc::now().timestamp();
+            let tx = conn.transaction()?;
+            let mut embeddings = 0;
+
+            for record in records {
+                match record {
+                    Record::ConsolidatedLog { path, hash } => {
+                        tx.execute(
+                            r#"INSERT OR IGNORE INTO consolidated_logs (path, hash, run_id, processed_at)
+                               VALUES (?1, ?2, 0, ?3)"#,
+                            params![path, hash, now],
+                        )?;
+                    }
+                    Record::Embedding {
+                        provider,
+                        model,
+                        provider_key,
+                        hash,
+                        embedding,
+                    } => {
+                        if embedding.is_empty() {
+                            continue;
+                        }
+                        embeddings += tx.execute(
+                            r#"INSERT OR IGNORE INTO embedding_cache
+                               (provider, model, provider_key, hash, embedding, dims, updated_at)
+                               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+                            params![
+                                provider,
+                                model,
+                                provider_key,
+                                hash,
+                                serialize_embedding(&embedding),
+                                embedding.len() as i64,
+                                now
+                            ],
+                        )?;
+                    }
+                    _ => {}
+                }
+            }
+
+            tx.commit()?;
+            Ok(embeddings)
+        })
+        .await?
+    }
+
+    /// Embedding models registered in the index
+    pub(super) async fn model_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .embedding_models()
+            .await?
+            .into_iter()
+            .map(|m| m.model)
+            .collect())
+    }
+}
+
+/// Cached embeddings of another (OpenClaw) index as bundle records
+pub(super) fn read_embedding_cache(db_path: &Path) -> Result<Vec<Record>> {
+    let conn =
+        rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let has_cache = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = 'embedding_cache'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if !has_cache {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT provider, model, COALESCE(provider_key, ''), hash, embedding FROM embedding_cache",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Record::Embedding {
+            provider: row.get(0)?,
+            model: row.get(1)?,
+            provider_key: row.get(2)?,
+            hash: row.get(3)?,
+            embedding: deserialize_embedding(&row.get::<_, String>(4)?),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_place_file_conflicts() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        fs::write(workspace.join("MEMORY.md"), "mine")?;
+
+        let mut report = ImportReport::default();
+        let skip = ImportOptions::default();
+        place_file(workspace, "MEMORY.md", b"mine", &skip, &mut report)?;
+        place_file(workspace, "MEMORY.md", b"theirs", &skip, &mut report)?;
+        place_file(workspace, "memory/new.md", b"new", &skip, &mut report)?;
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.conflicts, vec!["MEMORY.md"]);
+        assert_eq!(report.written, vec!["memory/new.md"]);
+        assert_eq!(fs::read_to_string(workspace.join("MEMORY.md"))?, "mine");
+
+        let rename = ImportOptions {
+            on_conflict: ConflictPolicy::Rename,
+            dry_run: false,
+        };
+        place_file(workspace, "MEMORY.md", b"theirs", &rename, &mut report)?;
+        place_file(workspace, "MEMORY.md", b"again", &rename, &mut report)?;
+        assert_eq!(
+            fs::read_to_string(workspace.join("MEMORY.imported.md"))?,
+            "theirs"
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.join("MEMORY.imported-2.md"))?,
+            "again"
+        );
+
+        assert!(place_file(workspace, "../escape.md", b"x", &skip, &mut report).is_err());
+        assert!(place_file(workspace, "/etc/passwd", b"x", &skip, &mut report).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,759 @@
+//! Memory consolidation
+//!
+//! Daily logs (`memory/*.md`) collect session summaries and notes, but nothing
+//! promotes what they contain into MEMORY.md. A consolidation run reads the
+//! logs that have not been processed yet, asks the model for durable facts,
+//! preferences and decisions (and for MEMORY.md entries the logs show to be
+//! stale), and turns the answer into a diff against MEMORY.md. Logs are
+//! sent whole, over several prompts when they don't fit in one, and private
+//! content (`memory.private_paths`, `<!-- private -->` blocks) never is.
+//!
+//! Additions go through the same duplicate/contradiction checks as
+//! `memory_write`. The diff is applied directly when `auto_apply` is set and
+//! otherwise kept as a pending run for the owner to review. Processed logs are
+//! recorded with their content hash, so a log is only read again once it
+//! changes.
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::task;
+
+use super::embeddings::hash_text;
+use super::index::MemoryIndex;
+use super::write::{self, ConflictMode, WriteOutcome};
+use crate::agent::{LLMProvider, LLMResponseContent, Message, Role};
+use crate::config::Config;
+
+/// Session id recorded in provenance comments for consolidated facts
+pub const CONSOLIDATION_SESSION: &str = "consolidation";
+
+/// Section used when the model does not name one
+const DEFAULT_SECTION: &str = "Notes";
+
+/// Maximum characters of a log (or part of one) in a prompt
+const MAX_LOG_CHARS: usize = 12_000;
+
+/// Maximum characters of logs in one prompt; a run sends as many prompts as
+/// its logs need
+const MAX_TOTAL_CHARS: usize = 48_000;
+
+const EXTRACTION_PROMPT: &str = "You maintain the user's long-term memory file (MEMORY.md). \
+You are given the current MEMORY.md and recent daily logs. Return a JSON array of changes:\n\
+- {\"action\": \"add\", \"section\": \"<MEMORY.md section>\", \"fact\": \"<one self-contained fact>\"} \
+for durable facts, preferences and decisions worth remembering beyond this week;\n\
+- {\"action\": \"remove\", \"fact\": \"<an existing MEMORY.md bullet, verbatim>\", \"reason\": \"<why>\"} \
+for entries the logs show are outdated or wrong.\n\
+Reuse existing section names where they fit. Skip small talk, one-off tasks and anything \
+MEMORY.md already says. Return [] if nothing qualifies. Output only the JSON array.";
+
+/// What a candidate asks for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CandidateAction {
+    #[default]
+    Add,
+    Remove,
+}
+
+/// A change proposed by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    #[serde(default)]
+    pub action: CandidateAction,
+    #[serde(default)]
+    pub section: Option<String>,
+    pub fact: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// State of a consolidation run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    /// The diff was written to MEMORY.md
+    Applied,
+    /// The diff awaits owner review
+    Pending,
+    /// Nothing worth changing was found
+    Empty,
+    /// The owner rejected the diff
+    Discarded,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Applied => "applied",
+            RunStatus::Pending => "pending",
+            RunStatus::Empty => "empty",
+            RunStatus::Discarded => "discarded",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "applied" => RunStatus::Applied,
+            "pending" => RunStatus::Pending,
+            "discarded" => RunStatus::Discarded,
+            _ => RunStatus::Empty,
+        }
+    }
+}
+
+/// Options for a single run
+#[derive(Debug, Clone)]
+pub struct ConsolidationOptions {
+    /// Only consider daily logs dated within the last N days
+    pub lookback_days: usize,
+    /// Write the diff to MEMORY.md instead of leaving it pending
+    pub auto_apply: bool,
+    /// Compute the diff without recording the run or marking logs processed
+    pub dry_run: bool,
+    /// Model doing the extraction (recorded in the review artifact)
+    pub model: String,
+}
+
+impl ConsolidationOptions {
+    /// Options from `[memory.consolidation]`
+    pub fn from_config(config: &Config) -> Self {
+        let settings = &config.memory.consolidation;
+        Self {
+            lookback_days: settings.lookback_days,
+            auto_apply: settings.auto_apply,
+            dry_run: false,
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| config.agent.default_model.clone()),
+        }
+    }
+}
+
+/// A recorded consolidation run
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationRun {
+    pub id: i64,
+    /// Unix timestamp
+    pub created_at: i64,
+    pub status: RunStatus,
+    /// Daily logs read by this run (relative to workspace)
+    pub logs: Vec<String>,
+    /// Unified diff against MEMORY.md
+    pub diff: String,
+    /// One line per candidate describing what happened to it
+    pub notes: Vec<String>,
+}
+
+/// A pending run's proposal, as stored in the index
+pub(super) struct Proposal {
+    pub base_hash: String,
+    pub proposed: String,
+}
+
+/// The diff computed from a set of candidates
+#[derive(Debug, Clone)]
+pub struct ConsolidationPlan {
+    pub original: String,
+    pub proposed: String,
+    pub notes: Vec<String>,
+}
+
+impl ConsolidationPlan {
+    pub fn has_changes(&self) -> bool {
+        self.original != self.proposed
+    }
+
+    pub fn diff(&self) -> String {
+        if !self.has_changes() {
+            return String::new();
+        }
+        similar::TextDiff::from_lines(&self.original, &self.proposed)
+            .unified_diff()
+            .context_radius(2)
+            .header("a/MEMORY.md", "b/MEMORY.md")
+            .to_string()
+    }
+}
+
+/// Parse the model's answer, tolerating code fences and surrounding prose
+pub fn parse_candidates(text: &str) -> Result<Vec<Candidate>> {
+    let start = text
+        .find('[')
+        .ok_or_else(|| anyhow!("No JSON array in consolidation response"))?;
+    let end = text
+        .rfind(']')
+        .filter(|end| *end > start)
+        .ok_or_else(|| anyhow!("Unterminated JSON array in consolidation response"))?;
+    let candidates: Vec<Candidate> = serde_json::from_str(&text[start..=end])?;
+    Ok(candidates
+        .into_iter()
+        .filter(|c| !c.fact.trim().is_empty())
+        .collect())
+}
+
+/// Apply candidates to MEMORY.md content in order.
+///
+/// Additions use the `memory_write` checks with conflicts reported, never
+/// overwritten; removals must match an existing bullet.
+pub fn plan(original: &str, candidates: &[Candidate]) -> ConsolidationPlan {
+    let now = Local::now();
+    let mut content = original.to_string();
+    let mut notes = Vec::new();
+
+    for candidate in candidates {
+        let fact = candidate.fact.trim();
+        match candidate.action {
+            CandidateAction::Add => {
+                let section = candidate
+                    .section
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or(DEFAULT_SECTION);
+                let (updated, outcome) = write::plan_write(
+                    &content,
+                    section,
+                    fact,
+                    ConflictMode::Report,
+                    Some(CONSOLIDATION_SESSION),
+                    now,
+                    &|_| None,
+                );
+                notes.push(match &outcome {
+                    WriteOutcome::Added { section, .. } => format!("add [{}] {}", section, fact),
+                    WriteOutcome::Merged { previous, .. } => {
+                        format!("merge {} (was: {})", fact, previous)
+                    }
+                    WriteOutcome::Replaced { previous, .. } => {
+                        format!("replace {} (was: {})", fact, previous)
+                    }
+                    WriteOutcome::Duplicate { .. } => format!("skip duplicate: {}", fact),
+                    WriteOutcome::Conflict {
+                        existing, reason, ..
+                    } => {
+                        format!("conflict: {} vs {} ({})", fact, existing, reason)
+                    }
+                });
+                if let Some(updated) = updated {
+                    content = updated;
+                }
+            }
+            CandidateAction::Remove => match write::remove_entry(&content, fact) {
+                Some((updated, removed)) => {
+                    notes.push(match &candidate.reason {
+                        Some(reason) => format!("remove {} ({})", removed.text, reason),
+                        None => format!("remove {}", removed.text),
+                    });
+                    content = updated;
+                }
+                None => notes.push(format!("skip remove, no such entry: {}", fact)),
+            },
+        }
+    }
+
+    ConsolidationPlan {
+        original: original.to_string(),
+        proposed: content,
+        notes,
+    }
+}
+
+/// Body of the review artifact written for a pending run
+pub fn review_markdown(run: &ConsolidationRun) -> String {
+    let mut out = format!(
+        "# Memory consolidation #{} (pending review)\n\nLogs read:\n",
+        run.id
+    );
+    for log in &run.logs {
+        out.push_str(&format!("- {}\n", log));
+    }
+    out.push_str("\nCandidates:\n");
+    for note in &run.notes {
+        out.push_str(&format!("- {}\n", note));
+    }
+    out.push_str(&format!(
+        "\n```diff\n{}```\n\nApply with `zier-alpha memory consolidate --apply {}` \
+         or reject with `zier-alpha memory consolidate --discard {}`.\n",
+        run.diff, run.id, run.id
+    ));
+    out
+}
+
+/// Group logs into batches of at most MAX_TOTAL_CHARS for one prompt each.
+/// Logs longer than MAX_LOG_CHARS are split at line breaks into labeled
+/// parts, so every log is sent whole across the batches.
+pub(super) fn batches(logs: &[(String, String)]) -> Vec<Vec<(String, String)>> {
+    let mut parts = Vec::new();
+    for (path, content) in logs {
+        let pieces = split_log(content);
+        let count = pieces.len();
+        for (i, piece) in pieces.into_iter().enumerate() {
+            let label = if count > 1 {
+                format!("{} (part {}/{})", path, i + 1, count)
+            } else {
+                path.clone()
+            };
+            parts.push((label, piece));
+        }
+    }
+
+    let mut batches: Vec<Vec<(String, String)>> = Vec::new();
+    let mut size = 0;
+    for (label, text) in parts {
+        let len = text.chars().count();
+        match batches.last_mut() {
+            Some(batch) if size + len <= MAX_TOTAL_CHARS => batch.push((label, text)),
+            _ => {
+                batches.push(vec![(label, text)]);
+                size = 0;
+            }
+        }
+        size += len;
+    }
+    batches
+}
+
+/// Pieces of at most MAX_LOG_CHARS, cut at line breaks where possible
+fn split_log(content: &str) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut len = 0;
+    for line in content.split_inclusive('\n') {
+        let mut line = line;
+        loop {
+            let line_len = line.chars().count();
+            if len + line_len <= MAX_LOG_CHARS {
+                current.push_str(line);
+                len += line_len;
+                break;
+            }
+            if len > 0 {
+                pieces.push(std::mem::take(&mut current));
+                len = 0;
+                continue;
+            }
+            // A single line longer than a piece
+            let cut = line
+                .char_indices()
+                .nth(MAX_LOG_CHARS)
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            pieces.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+    }
+    if len > 0 {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Ask the model for candidates from one batch of logs (see [`batches`]).
+/// Callers remove private content first.
+pub async fn extract_candidates(
+    provider: &dyn LLMProvider,
+    memory_md: &str,
+    logs: &[(String, String)],
+) -> Result<Vec<Candidate>> {
+    let mut body = format!("## Current MEMORY.md\n\n{}\n\n## Daily logs\n", memory_md);
+    for (label, content) in logs {
+        body.push_str(&format!("\n### {}\n\n{}\n", label, content));
+    }
+
+    let messages = vec![
+        Message {
+            role: Role::System,
+            content: EXTRACTION_PROMPT.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            images: Vec::new(),
+        },
+        Message {
+            role: Role::User,
+            content: body,
+            tool_calls: None,
+            tool_call_id: None,
+            images: Vec::new(),
+        },
+    ];
+
+    let response = provider.chat(&messages, None).await?;
+    match response.content {
+        LLMResponseContent::Text(text) => parse_candidates(&text),
+        LLMResponseContent::ToolCalls(_) => {
+            anyhow::bail!("Unexpected tool call in consolidation response")
+        }
+    }
+}
+
+/// Daily logs under `memory/` dated within the lookback window, with content
+pub(super) fn recent_logs(workspace: &Path, lookback_days: usize) -> Result<Vec<(String, String)>> {
+    let dir = workspace.join("memory");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = (Local::now() - chrono::Duration::days(lookback_days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut logs = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // Daily logs and session dumps are named YYYY-MM-DD[-slug].md
+        let Some(date) = name.get(..10) else {
+            continue;
+        };
+        let is_dated = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok();
+        if !is_dated || !name.ends_with(".md") || date < cutoff.as_str() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        if content.trim().is_empty() {
+            continue;
+        }
+        logs.push((format!("memory/{}", name), content));
+    }
+
+    logs.sort();
+    Ok(logs)
+}
+
+/// Create consolidation tables (called during index initialization)
+pub(super) fn ensure_consolidation_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- One row per consolidation run
+        CREATE TABLE IF NOT EXISTS consolidation_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            logs TEXT NOT NULL,
+            base_hash TEXT,
+            proposed TEXT,
+            diff TEXT NOT NULL DEFAULT '',
+            notes TEXT NOT NULL DEFAULT '[]'
+        );
+
+        -- Daily logs already read, by content hash
+        CREATE TABLE IF NOT EXISTS consolidated_logs (
+            path TEXT PRIMARY KEY,
+            hash TEXT NOT NULL,
+            run_id INTEGER NOT NULL,
+            processed_at INTEGER NOT NULL
+        );
+        "#,
+    )?;
+    Ok(())
+}
+
+fn row_to_run(row: &rusqlite::Row) -> rusqlite::Result<ConsolidationRun> {
+    let logs: String = row.get(3)?;
+    let notes: String = row.get(5)?;
+    let status: String = row.get(2)?;
+    Ok(ConsolidationRun {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        status: RunStatus::parse(&status),
+        logs: serde_json::from_str(&logs).unwrap_or_default(),
+        diff: row.get(4)?,
+        notes: serde_json::from_str(&notes).unwrap_or_default(),
+    })
+}
+
+impl MemoryIndex {
+    /// Drop logs whose current content was already consolidated
+    pub async fn unprocessed_logs(
+        &self,
+        logs: Vec<(String, String)>,
+    ) -> Result<Vec<(String, String)>> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let mut stmt = conn.prepare("SELECT hash FROM consolidated_logs WHERE path = ?1")?;
+            let mut pending = Vec::new();
+            for (path, content) in logs {
+                let seen: Option<String> = stmt
+                    .query_row(params![&path], |row| row.get(0))
+                    .optional()?;
+                if seen.as_deref() != Some(hash_text(&content).as_str()) {
+                    pending.push((path, content));
+                }
+            }
+            Ok(pending)
+        })
+        .await?
+    }
+
+    /// Record a run and mark its logs as processed
+    pub async fn record_consolidation_run(
+        &self,
+        status: RunStatus,
+        logs: &[(String, String)],
+        plan: &ConsolidationPlan,
+    ) -> Result<i64> {
+        let pool = self.pool.clone();
+        let logs: Vec<(String, String)> = logs
+            .iter()
+            .map(|(path, content)| (path.clone(), hash_text(content)))
+            .collect();
+        let base_hash = hash_text(&plan.original);
+        let proposed = (status == RunStatus::Pending).then(|| plan.proposed.clone());
+        let diff = plan.diff();
+        let notes = serde_json::to_string(&plan.notes)?;
+
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let tx = conn.transaction()?;
+            let now = chrono::Utc::now().timestamp();
+            let paths: Vec<&String> = logs.iter().map(|(p, _)| p).collect();
+
+            tx.execute(
+                r#"INSERT INTO consolidation_runs (created_at, status, logs, base_hash, proposed, diff, notes)
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+                params![
+                    now,
+                    status.as_str(),
+                    serde_json::to_string(&paths)?,
+                    base_hash,
+                    proposed,
+                    diff,
+                    notes
+                ],
+            )?;
+            let run_id = tx.last_insert_rowid();
+
+            for (path, hash) in &logs {
+                tx.execute(
+                    r#"INSERT INTO consolidated_logs (path, hash, run_id, processed_at)
+                       VALUES (?1, ?2, ?3, ?4)
+                       ON CONFLICT(path) DO UPDATE SET hash = excluded.hash,
+                           run_id = excluded.run_id, processed_at = excluded.processed_at"#,
+                    params![path, hash, run_id, now],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(run_id)
+        })
+        .await?
+    }
+
+    /// Most recent runs, newest first
+    pub async fn consolidation_runs(&self, limit: usize) -> Result<Vec<ConsolidationRun>> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let mut stmt = conn.prepare(
+                r#"SELECT id, created_at, status, logs, diff, notes
+                   FROM consolidation_runs ORDER BY id DESC LIMIT ?1"#,
+            )?;
+            let runs = stmt
+                .query_map(params![limit as i64], row_to_run)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(runs)
+        })
+        .await?
+    }
+
+    /// A run and, while pending, its proposed MEMORY.md
+    pub(super) async fn consolidation_run(
+        &self,
+        id: i64,
+    ) -> Result<Option<(ConsolidationRun, Option<Proposal>)>> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let run = conn
+                .query_row(
+                    r#"SELECT id, created_at, status, logs, diff, notes, base_hash, proposed
+                       FROM consolidation_runs WHERE id = ?1"#,
+                    params![id],
+                    |row| {
+                        let run = row_to_run(row)?;
+                        let base_hash: Option<String> = row.get(6)?;
+                        let proposed: Option<String> = row.get(7)?;
+                        let proposal =
+                            base_hash
+                                .zip(proposed)
+                                .map(|(base_hash, proposed)| Proposal {
+                                    base_hash,
+                                    proposed,
+                                });
+                        Ok((run, proposal))
+                    },
+                )
+                .optional()?;
+            Ok(run)
+        })
+        .await?
+    }
+
+    /// Close a pending run
+    pub(super) async fn set_consolidation_status(&self, id: i64, status: RunStatus) -> Result<()> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            conn.execute(
+                "UPDATE consolidation_runs SET status = ?1, proposed = NULL WHERE id = ?2",
+                params![status.as_str(), id],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Unix timestamp of the latest run, if any
+    pub async fn last_consolidation_at(&self) -> Result<Option<i64>> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let ts: Option<i64> = conn.query_row(
+                "SELECT MAX(created_at) FROM consolidation_runs",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(ts)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMORY: &str = "# Memory\n\n## Preferences\n\n- Editor: helix\n- Prefers dark mode\n";
+
+    #[test]
+    fn test_parse_candidates() {
+        let text = "Here you go:\n```json\n[{\"action\":\"add\",\"section\":\"People\",\"fact\":\"Alice is the PM\"},\
+                    {\"action\":\"remove\",\"fact\":\"Prefers dark mode\",\"reason\":\"switched\"},\
+                    {\"fact\":\"  \"}]\n```";
+        let candidates = parse_candidates(text).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].action, CandidateAction::Add);
+        assert_eq!(candidates[0].section.as_deref(), Some("People"));
+        assert_eq!(candidates[1].action, CandidateAction::Remove);
+
+        assert!(parse_candidates("[]").unwrap().is_empty());
+        assert!(parse_candidates("nothing to add").is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let candidates = vec![
+            Candidate {
+                action: CandidateAction::Add,
+                section: Some("People".to_string()),
+                fact: "Alice is the PM".to_string(),
+                reason: None,
+            },
+            Candidate {
+                action: CandidateAction::Add,
+                section: Some("Preferences".to_string()),
+                fact: "Editor: helix".to_string(),
+                reason: None,
+            },
+            Candidate {
+                action: CandidateAction::Add,
+                section: None,
+                fact: "Editor: vim".to_string(),
+                reason: None,
+            },
+            Candidate {
+                action: CandidateAction::Remove,
+                section: None,
+                fact: "prefers dark mode".to_string(),
+                reason: Some("switched to light".to_string()),
+            },
+        ];
+
+        let plan = plan(MEMORY, &candidates);
+        assert!(plan.has_changes());
+        assert!(plan
+            .proposed
+            .contains("## People\n\n- Alice is the PM <!-- zier:write session=consolidation"));
+        assert!(!plan.proposed.contains("dark mode"));
+        assert!(!plan.proposed.contains("vim"));
+        assert_eq!(plan.notes.len(), 4);
+        assert!(plan.notes[1].starts_with("skip duplicate"));
+        assert!(plan.notes[2].starts_with("conflict"));
+
+        let diff = plan.diff();
+        assert!(diff.contains("--- a/MEMORY.md"));
+        assert!(diff.contains("-- Prefers dark mode"));
+        assert!(diff.contains("+- Alice is the PM"));
+    }
+
+    #[test]
+    fn test_batches_send_every_log_whole() {
+        let long: String = "a line of the daily log\n".repeat(1000);
+        let logs = vec![
+            ("memory/2026-01-01.md".to_string(), "short\n".to_string()),
+            ("memory/2026-01-02.md".to_string(), long.clone()),
+            ("memory/2026-01-03.md".to_string(), "x".repeat(30_000)),
+        ];
+        let batches = batches(&logs);
+        let parts: Vec<&(String, String)> = batches.iter().flatten().collect();
+        assert!(parts.iter().all(|(_, text)| text.len() <= MAX_LOG_CHARS));
+        assert!(batches
+            .iter()
+            .all(|b| b.iter().map(|(_, t)| t.len()).sum::<usize>() <= MAX_TOTAL_CHARS));
+
+        let sent: String = parts
+            .iter()
+            .filter(|(label, _)| label.starts_with("memory/2026-01-02.md (part "))
+            .map(|(_, text)| text.as_str())
+            .collect();
+        assert_eq!(sent, long);
+        assert_eq!(parts.last().unwrap().0, "memory/2026-01-03.md (part 3/3)");
+    }
+
+    #[test]
+    fn test_recent_logs_skips_undated_names() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("memory");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("日記日記.md"), "notes").unwrap();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        std::fs::write(dir.join(format!("{}.md", today)), "today").unwrap();
+
+        let logs = recent_logs(temp.path(), 7).unwrap();
+        assert_eq!(
+            logs,
+            vec![(format!("memory/{}.md", today), "today".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_without_changes() {
+        let plan = plan(MEMORY, &[]);
+        assert!(!plan.has_changes());
+        assert!(plan.diff().is_empty());
+    }
+}
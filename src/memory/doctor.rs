@@ -0,0 +1,677 @@
+//! Index integrity checks and near-duplicate detection
+//!
+//! The index can drift from the workspace when files change while no watcher
+//! runs, or when an indexing pass is interrupted. [`MemoryIndex::doctor`]
+//! cross-checks the filesystem against `files`, `chunks`, `chunks_fts`, the
+//! per-model vector tables and `embedding_cache`, and can repair what it finds.
+//! [`MemoryIndex::duplicates`] groups memory chunks whose stored vectors (or
+//! content hashes) are near-identical.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tokio::task;
+use tracing::{debug, info};
+
+use super::embeddings::{deserialize_embedding, unpack_embedding};
+use super::extract::SOURCE_MEMORY;
+use super::index::{hash_content, MemoryIndex};
+use super::search::MemoryChunk;
+use super::vectors::vec_table_name;
+
+/// Cosine similarity from which `memory duplicates` groups two chunks
+pub const DUPLICATE_THRESHOLD: f32 = 0.95;
+
+/// Kind of inconsistency found by [`MemoryIndex::doctor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// `files` row for a file that no longer exists
+    MissingFile,
+    /// `files` hash differs from the file on disk
+    StaleFile,
+    /// File that should be indexed but has no `files` row
+    UnindexedFile,
+    /// Chunk whose file is not in `files`
+    OrphanChunk,
+    /// Chunk without a `chunks_fts` row
+    MissingFts,
+    /// `chunks_fts` row without a chunk
+    OrphanFts,
+    /// Vector for a missing chunk or an unknown model
+    OrphanVector,
+    /// Vector whose size doesn't match its model's dimension
+    BadVector,
+    /// Vector missing from its model's sqlite-vec table
+    MissingVecRow,
+    /// Unreadable `embedding_cache` entry
+    BadCacheEntry,
+}
+
+impl IssueKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            IssueKind::MissingFile => "indexed file no longer exists",
+            IssueKind::StaleFile => "file changed since it was indexed",
+            IssueKind::UnindexedFile => "file is not indexed",
+            IssueKind::OrphanChunk => "chunk of a file that isn't indexed",
+            IssueKind::MissingFts => "chunk missing from the full-text index",
+            IssueKind::OrphanFts => "full-text row without a chunk",
+            IssueKind::OrphanVector => "vector without a chunk or model",
+            IssueKind::BadVector => "vector size doesn't match its model",
+            IssueKind::MissingVecRow => "vector missing from the sqlite-vec table",
+            IssueKind::BadCacheEntry => "unreadable embedding cache entry",
+        }
+    }
+}
+
+/// One inconsistency; `subject` is a file path, chunk id or cache key
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub files: usize,
+    pub chunks: usize,
+    pub issues: Vec<Issue>,
+    /// Issues were repaired
+    pub fixed: bool,
+}
+
+impl DoctorReport {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Issue counts per kind, in the order kinds were first found
+    pub fn counts(&self) -> Vec<(IssueKind, usize)> {
+        let mut counts: Vec<(IssueKind, usize)> = Vec::new();
+        for issue in &self.issues {
+            match counts.iter_mut().find(|(kind, _)| *kind == issue.kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((issue.kind, 1)),
+            }
+        }
+        counts
+    }
+
+    fn issue(&mut self, kind: IssueKind, subject: &str) {
+        self.issues.push(Issue {
+            kind,
+            subject: subject.to_string(),
+        });
+    }
+}
+
+/// Chunks whose content is near-identical
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCluster {
+    /// Lowest similarity linking the cluster (1.0 for identical text)
+    pub similarity: f32,
+    pub chunks: Vec<MemoryChunk>,
+}
+
+impl MemoryIndex {
+    /// Check the index against `expected`, the files that should be indexed,
+    /// and repair inconsistencies when `fix` is set. Changed and unindexed
+    /// files are reindexed, deleted ones removed, and orphaned rows dropped.
+    pub async fn doctor(&self, expected: Vec<PathBuf>, fix: bool) -> Result<DoctorReport> {
+        let pool = self.pool.clone();
+        let workspace = self.workspace.clone();
+        let has_vec_extension = self.has_vec_extension;
+
+        let mut report = task::spawn_blocking(move || -> Result<DoctorReport> {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let mut report = DoctorReport::default();
+
+            let indexed: HashMap<String, String> = {
+                let mut stmt = conn.prepare("SELECT path, hash FROM files")?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<rusqlite::Result<_>>()?
+            };
+            report.files = indexed.len();
+            report.chunks = conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| {
+                row.get::<_, i64>(0)
+            })? as usize;
+
+            let expected: HashSet<String> = expected
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(&workspace)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect();
+
+            let mut paths: Vec<&String> = indexed.keys().collect();
+            paths.sort();
+            for path in paths {
+                let full_path = workspace.join(path);
+                if !full_path.exists() {
+                    report.issue(IssueKind::MissingFile, path);
+                } else if let Ok(bytes) = std::fs::read(&full_path) {
+                    if hash_content(&bytes) != indexed[path] {
+                        report.issue(IssueKind::StaleFile, path);
+                    }
+                }
+            }
+            let mut unindexed: Vec<&String> = expected
+                .iter()
+                .filter(|path| !indexed.contains_key(*path))
+                .collect();
+            unindexed.sort();
+            for path in unindexed {
+                report.issue(IssueKind::UnindexedFile, path);
+            }
+
+            for issue in check_rows(&conn, has_vec_extension)? {
+                report.issues.push(issue);
+            }
+            Ok(report)
+        })
+        .await??;
+
+        if !fix || report.is_healthy() {
+            return Ok(report);
+        }
+
+        // Files first: reindexing rebuilds their chunks, FTS rows and links
+        for issue in report.issues.clone() {
+            match issue.kind {
+                IssueKind::MissingFile => self.remove_file(&issue.subject).await?,
+                IssueKind::StaleFile | IssueKind::UnindexedFile => {
+                    let path = self.workspace.join(&issue.subject);
+                    self.index_file(&path, true).await?;
+                }
+                _ => {}
+            }
+        }
+
+        let pool = self.pool.clone();
+        let repaired = task::spawn_blocking(move || -> Result<usize> {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let tx = conn.transaction()?;
+            let issues = check_rows(&tx, has_vec_extension)?;
+            for issue in &issues {
+                repair_row(&tx, issue)?;
+            }
+            tx.commit()?;
+            Ok(issues.len())
+        })
+        .await??;
+        debug!("Repaired {} index rows", repaired);
+
+        info!("Memory doctor fixed {} issue(s)", report.issues.len());
+        report.fixed = true;
+        Ok(report)
+    }
+
+    /// Clusters of near-identical memory chunks. Chunks with identical text
+    /// are always grouped; with `model`, so are chunks whose stored vectors
+    /// have a cosine similarity of at least `threshold`. Overlapping chunks of
+    /// the same file are not compared.
+    pub async fn duplicates(
+        &self,
+        model: Option<&str>,
+        threshold: f32,
+        limit: usize,
+    ) -> Result<Vec<DuplicateCluster>> {
+        let pool = self.pool.clone();
+        let model = model.map(str::to_string);
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let mut stmt = conn.prepare(
+                r#"SELECT c.id, c.path, c.start_line, c.end_line, c.text, c.hash, e.embedding
+                   FROM chunks c
+                   LEFT JOIN chunk_embeddings e ON e.chunk_id = c.id AND e.model = ?2
+                   WHERE c.source = ?1
+                   ORDER BY c.path, c.start_line"#,
+            )?;
+            let rows = stmt.query_map(params![SOURCE_MEMORY, &model], |row| {
+                Ok((
+                    MemoryChunk::new(row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, 0.0),
+                    row.get::<_, String>(5)?,
+                    row.get::<_, Option<Vec<u8>>>(6)?,
+                ))
+            })?;
+
+            let mut chunks = Vec::new();
+            let mut hashes = Vec::new();
+            let mut vectors = Vec::new();
+            for row in rows {
+                let (chunk, hash, blob) = row?;
+                chunks.push(chunk);
+                hashes.push(hash);
+                vectors.push(blob.map(|b| normalized(unpack_embedding(&b))));
+            }
+
+            let overlapping = |a: &MemoryChunk, b: &MemoryChunk| {
+                a.file == b.file && a.line_start <= b.line_end && b.line_start <= a.line_end
+            };
+
+            let mut clusters = UnionFind::new(chunks.len());
+            for i in 0..chunks.len() {
+                for j in (i + 1)..chunks.len() {
+                    if overlapping(&chunks[i], &chunks[j]) {
+                        continue;
+                    }
+                    let similarity = if hashes[i] == hashes[j] {
+                        1.0
+                    } else {
+                        match (&vectors[i], &vectors[j]) {
+                            (Some(a), Some(b)) if a.len() == b.len() => {
+                                a.iter().zip(b).map(|(x, y)| x * y).sum()
+                            }
+                            _ => continue,
+                        }
+                    };
+                    if similarity >= threshold {
+                        clusters.union(i, j, similarity);
+                    }
+                }
+            }
+
+            let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+            for i in 0..chunks.len() {
+                groups.entry(clusters.find(i)).or_default().push(i);
+            }
+            let mut result: Vec<DuplicateCluster> = groups
+                .into_iter()
+                .filter(|(_, members)| members.len() > 1)
+                .map(|(root, members)| DuplicateCluster {
+                    similarity: clusters.min_similarity[root],
+                    chunks: members
+                        .into_iter()
+                        .map(|i| {
+                            let mut chunk = chunks[i].clone();
+                            chunk.score = clusters.min_similarity[root] as f64;
+                            chunk
+                        })
+                        .collect(),
+                })
+                .collect();
+            result.sort_by(|a, b| {
+                b.chunks
+                    .len()
+                    .cmp(&a.chunks.len())
+                    .then(b.similarity.total_cmp(&a.similarity))
+                    .then_with(|| a.chunks[0].file.cmp(&b.chunks[0].file))
+            });
+            result.truncate(limit);
+            Ok(result)
+        })
+        .await?
+    }
+}
+
+/// Row-level consistency between chunks, FTS, vectors and the embedding cache
+fn check_rows(conn: &Connection, has_vec_extension: bool) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    let mut collect = |kind: IssueKind, sql: &str| -> Result<()> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        for subject in rows {
+            issues.push(Issue {
+                kind,
+                subject: subject?,
+            });
+        }
+        Ok(())
+    };
+
+    collect(
+        IssueKind::OrphanChunk,
+        "SELECT id FROM chunks WHERE path NOT IN (SELECT path FROM files)",
+    )?;
+
+    // chunks_fts is missing when SQLite was built without FTS5
+    let has_fts: bool = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = 'chunks_fts'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if has_fts {
+        collect(
+            IssueKind::MissingFts,
+            "SELECT id FROM chunks WHERE id NOT IN (SELECT id FROM chunks_fts)",
+        )?;
+        collect(
+            IssueKind::OrphanFts,
+            "SELECT id FROM chunks_fts WHERE id NOT IN (SELECT id FROM chunks)",
+        )?;
+    }
+
+    collect(
+        IssueKind::OrphanVector,
+        r#"SELECT chunk_id || ' ' || model FROM chunk_embeddings
+           WHERE chunk_id NOT IN (SELECT id FROM chunks)
+              OR model NOT IN (SELECT model FROM embedding_models)"#,
+    )?;
+    collect(
+        IssueKind::BadVector,
+        r#"SELECT e.chunk_id || ' ' || e.model FROM chunk_embeddings e
+           JOIN embedding_models m ON m.model = e.model
+           WHERE e.chunk_id IN (SELECT id FROM chunks)
+             AND m.dims IS NOT NULL AND length(e.embedding) != m.dims * 4"#,
+    )?;
+
+    if has_vec_extension {
+        let models: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT model FROM embedding_models")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        for model in models {
+            let table = vec_table_name(&model);
+            let exists = conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE name = ?1",
+                    params![&table],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            if !exists {
+                continue;
+            }
+            let quoted = model.replace('\'', "''");
+            collect(
+                IssueKind::OrphanVector,
+                &format!(
+                    "SELECT id || ' {q}' FROM {t} WHERE id NOT IN
+                     (SELECT chunk_id FROM chunk_embeddings WHERE model = '{q}')",
+                    t = table,
+                    q = quoted
+                ),
+            )?;
+            collect(
+                IssueKind::MissingVecRow,
+                &format!(
+                    "SELECT chunk_id || ' ' || model FROM chunk_embeddings
+                     WHERE model = '{q}' AND chunk_id NOT IN (SELECT id FROM {t})",
+                    t = table,
+                    q = quoted
+                ),
+            )?;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT provider, model, provider_key, hash, embedding, dims FROM embedding_cache",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+        ))
+    })?;
+    for row in rows {
+        let (provider, model, provider_key, hash, json, dims) = row?;
+        let embedding = deserialize_embedding(&json);
+        if embedding.is_empty() || dims.is_some_and(|d| d as usize != embedding.len()) {
+            issues.push(Issue {
+                kind: IssueKind::BadCacheEntry,
+                subject: cache_key(&provider, &model, &provider_key, &hash),
+            });
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Cache entries are addressed as `provider\tmodel\tprovider_key\thash`
+fn cache_key(provider: &str, model: &str, provider_key: &str, hash: &str) -> String {
+    [provider, model, provider_key, hash].join("\t")
+}
+
+fn repair_row(conn: &Connection, issue: &Issue) -> Result<()> {
+    match issue.kind {
+        IssueKind::OrphanChunk => {
+            conn.execute(
+                "DELETE FROM chunks_fts WHERE id = ?1",
+                params![&issue.subject],
+            )
+            .ok();
+            super::vectors::delete_vectors_for_chunk(conn, &issue.subject)?;
+            conn.execute("DELETE FROM chunks WHERE id = ?1", params![&issue.subject])?;
+        }
+        IssueKind::MissingFts => {
+            conn.execute(
+                r#"INSERT INTO chunks_fts (text, id, path, source, model, start_line, end_line)
+                   SELECT text, id, path, source, model, start_line, end_line
+                   FROM chunks WHERE id = ?1"#,
+                params![&issue.subject],
+            )?;
+        }
+        IssueKind::OrphanFts => {
+            conn.execute(
+                "DELETE FROM chunks_fts WHERE id = ?1",
+                params![&issue.subject],
+            )?;
+        }
+        IssueKind::OrphanVector | IssueKind::BadVector | IssueKind::MissingVecRow => {
+            let (chunk_id, model) = issue
+                .subject
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("Malformed vector issue: {}", issue.subject))?;
+            let table = vec_table_name(model);
+            if issue.kind == IssueKind::MissingVecRow {
+                conn.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {} (id, embedding)
+                         SELECT chunk_id, embedding FROM chunk_embeddings
+                         WHERE chunk_id = ?1 AND model = ?2",
+                        table
+                    ),
+                    params![chunk_id, model],
+                )?;
+            } else {
+                // Bad vectors are re-embedded by the next embedding pass
+                conn.execute(
+                    "DELETE FROM chunk_embeddings WHERE chunk_id = ?1 AND model = ?2",
+                    params![chunk_id, model],
+                )?;
+                conn.execute(
+                    &format!("DELETE FROM {} WHERE id = ?1", table),
+                    params![chunk_id],
+                )
+                .ok();
+            }
+        }
+        IssueKind::BadCacheEntry => {
+            let key: Vec<&str> = issue.subject.split('\t').collect();
+            if let [provider, model, provider_key, hash] = key.as_slice() {
+                conn.execute(
+                    r#"DELETE FROM embedding_cache
+                       WHERE provider = ?1 AND model = ?2 AND provider_key = ?3 AND hash = ?4"#,
+                    params![provider, model, provider_key, hash],
+                )?;
+            }
+        }
+        IssueKind::MissingFile | IssueKind::StaleFile | IssueKind::UnindexedFile => {}
+    }
+    Ok(())
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+    vector
+}
+
+/// Disjoint sets of chunk indexes, tracking each set's weakest link
+struct UnionFind {
+    parent: Vec<usize>,
+    min_similarity: Vec<f32>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            min_similarity: vec![1.0; len],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = i;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize, similarity: f32) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        let min = self.min_similarity[ra]
+            .min(self.min_similarity[rb])
+            .min(similarity);
+        self.parent[rb] = ra;
+        self.min_similarity[ra] = min;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_doctor_finds_and_fixes_drift() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let index = MemoryIndex::new_with_db_path(workspace, &workspace.join("index.sqlite"))?;
+
+        let memory = workspace.join("MEMORY.md");
+        let gone = workspace.join("gone.md");
+        let new = workspace.join("new.md");
+        fs::write(&memory, "# Memory\n\nLikes tea.\n")?;
+        fs::write(&gone, "# Gone\n\nSoon deleted.\n")?;
+        index.index_file(&memory, false).await?;
+        index.index_file(&gone, false).await?;
+        index.register_embedding_model("m", "test").await?;
+        let chunk_id: String = {
+            let conn = index.pool.get()?;
+            conn.query_row(
+                "SELECT id FROM chunks WHERE path = 'MEMORY.md'",
+                [],
+                |row| row.get(0),
+            )?
+        };
+        index.store_embedding(&chunk_id, &[1.0, 0.0], "m").await?;
+
+        // Drift: edits without the watcher, deletions and stray rows
+        fs::write(&memory, "# Memory\n\nLikes green tea.\n")?;
+        fs::remove_file(&gone)?;
+        fs::write(&new, "# New\n")?;
+        {
+            let conn = index.pool.get()?;
+            conn.execute("DELETE FROM chunks_fts WHERE id = ?1", params![&chunk_id])?;
+            conn.execute(
+                "INSERT INTO chunk_embeddings (chunk_id, model, embedding, updated_at) VALUES ('ghost', 'm', x'00000000', 0)",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO embedding_cache (provider, model, provider_key, hash, embedding, dims, updated_at) VALUES ('p', 'm', '', 'h', 'oops', 2, 0)",
+                [],
+            )?;
+        }
+
+        let expected = vec![memory.clone(), new.clone()];
+        let report = index.doctor(expected.clone(), false).await?;
+        let kinds: HashSet<IssueKind> = report.issues.iter().map(|i| i.kind).collect();
+        for kind in [
+            IssueKind::MissingFile,
+            IssueKind::StaleFile,
+            IssueKind::UnindexedFile,
+            IssueKind::MissingFts,
+            IssueKind::OrphanVector,
+            IssueKind::BadCacheEntry,
+        ] {
+            assert!(kinds.contains(&kind), "{:?} not reported", kind);
+        }
+        assert!(!report.fixed);
+
+        let report = index.doctor(expected.clone(), true).await?;
+        assert!(report.fixed);
+        let report = index.doctor(expected, false).await?;
+        assert!(report.is_healthy(), "{:?}", report.issues);
+        assert_eq!(index.search("green", 5).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_duplicates() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let index = MemoryIndex::new_with_db_path(workspace, &workspace.join("index.sqlite"))?;
+        fs::create_dir_all(workspace.join("memory"))?;
+        let files = [
+            ("MEMORY.md", "Prefers helix as editor."),
+            ("memory/2026-01-01.md", "Prefers helix as editor."),
+            ("memory/2026-01-02.md", "Uses helix for editing."),
+            ("memory/2026-01-03.md", "Flies to Lisbon in May."),
+        ];
+        index.register_embedding_model("m", "test").await?;
+        let vectors = [[1.0, 0.0], [1.0, 0.0], [0.98, 0.05], [0.0, 1.0]];
+        for ((file, text), vector) in files.iter().zip(vectors) {
+            let path = workspace.join(file);
+            fs::write(&path, text)?;
+            index.index_file(&path, false).await?;
+            let id: String = index.pool.get()?.query_row(
+                "SELECT id FROM chunks WHERE path = ?1",
+                params![file],
+                |row| row.get(0),
+            )?;
+            index.store_embedding(&id, &vector, "m").await?;
+        }
+
+        // Identical text is found without vectors
+        let exact = index.duplicates(None, DUPLICATE_THRESHOLD, 10).await?;
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].chunks.len(), 2);
+        assert_eq!(exact[0].similarity, 1.0);
+
+        let near = index.duplicates(Some("m"), DUPLICATE_THRESHOLD, 10).await?;
+        assert_eq!(near.len(), 1);
+        let files: Vec<&str> = near[0].chunks.iter().map(|c| c.file.as_str()).collect();
+        assert_eq!(
+            files,
+            vec!["MEMORY.md", "memory/2026-01-01.md", "memory/2026-01-02.md"]
+        );
+        assert!(near[0].similarity < 1.0);
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
 //! Embedding providers for semantic search
 //!
-//! Supports OpenAI embeddings API, local embeddings via fastembed (ONNX),
-//! and optional GGUF embeddings via llama.cpp (requires `gguf` feature).
+//! Supports the OpenAI embeddings API (and OpenAI-compatible servers),
+//! Ollama, local embeddings via fastembed (ONNX), and optional GGUF
+//! embeddings via llama.cpp (requires `gguf` feature).
 
 use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::debug;
 
 /// Embedding provider trait
@@ -19,7 +21,7 @@ pub trait EmbeddingProvider: Send + Sync {
     /// Get the model name
     fn model(&self) -> &str;
 
-    /// Get embedding dimensions
+    /// Get embedding dimensions (0 until known for auto-detecting providers)
     fn dimensions(&self) -> usize;
 
     /// Embed a single text
@@ -29,32 +31,42 @@ pub trait EmbeddingProvider: Send + Sync {
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
 }
 
-/// OpenAI embedding provider
+/// OpenAI embedding provider, also used for OpenAI-compatible endpoints
+/// (`providers.extra`, llama.cpp server, LM Studio, ...)
 pub struct OpenAIEmbeddingProvider {
     client: Client,
+    id: String,
     api_key: String,
     base_url: String,
     model: String,
-    dimensions: usize,
+    /// Known for OpenAI models, otherwise detected from the first response
+    dimensions: AtomicUsize,
 }
 
 impl OpenAIEmbeddingProvider {
     pub fn new(api_key: &str, base_url: &str, model: &str) -> Result<Self> {
+        Self::new_compatible("openai", api_key, base_url, model)
+    }
+
+    /// Provider for an OpenAI-compatible endpoint registered under `id`.
+    /// An empty `api_key` sends no Authorization header.
+    pub fn new_compatible(id: &str, api_key: &str, base_url: &str, model: &str) -> Result<Self> {
         // text-embedding-3-small has 1536 dimensions by default
         // text-embedding-3-large has 3072 dimensions by default
         let dimensions = match model {
             "text-embedding-3-small" => 1536,
             "text-embedding-3-large" => 3072,
             "text-embedding-ada-002" => 1536,
-            _ => 1536, // default
+            _ => 0, // detected on first call
         };
 
         Ok(Self {
             client: Client::new(),
+            id: id.to_string(),
             api_key: api_key.to_string(),
-            base_url: base_url.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
             model: model.to_string(),
-            dimensions,
+            dimensions: AtomicUsize::new(dimensions),
         })
     }
 }
@@ -78,7 +90,7 @@ struct EmbeddingData {
 #[async_trait]
 impl EmbeddingProvider for OpenAIEmbeddingProvider {
     fn id(&self) -> &str {
-        "openai"
+        &self.id
     }
 
     fn model(&self) -> &str {
@@ -86,7 +98,7 @@ impl EmbeddingProvider for OpenAIEmbeddingProvider {
     }
 
     fn dimensions(&self) -> usize {
-        self.dimensions
+        self.dimensions.load(Ordering::Relaxed)
     }
 
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
@@ -109,19 +121,19 @@ impl EmbeddingProvider for OpenAIEmbeddingProvider {
 
         debug!("Embedding {} texts with {}", texts.len(), self.model);
 
-        let response = self
+        let mut builder = self
             .client
             .post(format!("{}/embeddings", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+            .header("Content-Type", "application/json");
+        if !self.api_key.is_empty() {
+            builder = builder.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+        let response = builder.json(&request).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("OpenAI API error {}: {}", status, body);
+            anyhow::bail!("{} embeddings API error {}: {}", self.id, status, body);
         }
 
         let response: EmbeddingResponse = response.json().await?;
@@ -133,10 +145,114 @@ impl EmbeddingProvider for OpenAIEmbeddingProvider {
             .map(|d| normalize_embedding(d.embedding))
             .collect();
 
+        detect_dimensions(&self.dimensions, &embeddings, &self.model);
         Ok(embeddings)
     }
 }
 
+/// Maximum texts per Ollama `/api/embed` request
+const OLLAMA_BATCH_SIZE: usize = 32;
+
+/// Ollama embedding provider (`/api/embed`)
+pub struct OllamaEmbeddingProvider {
+    client: Client,
+    endpoint: String,
+    model: String,
+    /// Detected from the first response
+    dimensions: AtomicUsize,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(endpoint: &str, model: &str) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            dimensions: AtomicUsize::new(0),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn id(&self) -> &str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions.load(Ordering::Relaxed)
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let results = self.embed_batch(&[text.to_string()]).await?;
+        results
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No embedding returned"))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(OLLAMA_BATCH_SIZE) {
+            debug!("Embedding {} texts with ollama/{}", batch.len(), self.model);
+
+            let response = self
+                .client
+                .post(format!("{}/api/embed", self.endpoint))
+                .json(&OllamaEmbedRequest {
+                    model: &self.model,
+                    input: batch,
+                })
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("Ollama embed error {}: {}", status, body);
+            }
+
+            let response: OllamaEmbedResponse = response.json().await?;
+            if response.embeddings.len() != batch.len() {
+                anyhow::bail!(
+                    "Ollama returned {} embeddings for {} texts",
+                    response.embeddings.len(),
+                    batch.len()
+                );
+            }
+            embeddings.extend(response.embeddings.into_iter().map(normalize_embedding));
+        }
+
+        detect_dimensions(&self.dimensions, &embeddings, &self.model);
+        Ok(embeddings)
+    }
+}
+
+/// Record the embedding size on first response
+fn detect_dimensions(dimensions: &AtomicUsize, embeddings: &[Vec<f32>], model: &str) {
+    if let Some(first) = embeddings.first() {
+        if dimensions.swap(first.len(), Ordering::Relaxed) != first.len() {
+            debug!("Detected {} dimensions for {}", first.len(), model);
+        }
+    }
+}
+
 /// Normalize embedding to unit vector
 pub fn normalize_embedding(mut vec: Vec<f32>) -> Vec<f32> {
     let magnitude: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
@@ -509,6 +625,22 @@ pub fn deserialize_embedding(json: &str) -> Vec<f32> {
     serde_json::from_str(json).unwrap_or_default()
 }
 
+/// Pack embedding as little-endian f32 bytes (vector storage and sqlite-vec)
+pub fn pack_embedding(embedding: &[f32]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(embedding.len() * 4);
+    for &val in embedding {
+        blob.extend_from_slice(&val.to_le_bytes());
+    }
+    blob
+}
+
+/// Unpack embedding from little-endian f32 bytes
+pub fn unpack_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,6 +663,14 @@ mod tests {
         assert!(cosine_similarity(&a, &c).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_pack_unpack() {
+        let embedding = vec![0.1, -2.5, 3.0e-7];
+        let blob = pack_embedding(&embedding);
+        assert_eq!(blob.len(), 12);
+        assert_eq!(unpack_embedding(&blob), embedding);
+    }
+
     #[cfg(feature = "fastembed")]
     #[test]
     fn test_serialize_deserialize() {
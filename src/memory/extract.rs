@@ -0,0 +1,309 @@
+//! Content extractors for non-markdown index sources
+//!
+//! The index stores plain text, so every indexed file goes through an
+//! [`Extractor`] chosen by file extension. The extractor decides what the
+//! text is (PDF page text, HTML rendered as text, source code split at
+//! top-level symbols) and the value stored in `chunks.source`.
+//!
+//! Locations are lines for everything except PDFs, whose chunks carry page
+//! numbers in `start_line`/`end_line` (see [`is_paged`]).
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// `chunks.source` for markdown (OpenClaw-compatible default)
+pub const SOURCE_MEMORY: &str = "memory";
+pub const SOURCE_TEXT: &str = "text";
+pub const SOURCE_HTML: &str = "html";
+pub const SOURCE_PDF: &str = "pdf";
+pub const SOURCE_CODE: &str = "code";
+
+/// Column width used when rendering HTML to text
+const HTML_WIDTH: usize = 100;
+
+/// Whether chunk locations for `source` are page numbers rather than lines
+pub fn is_paged(source: &str) -> bool {
+    source == SOURCE_PDF
+}
+
+/// Text extracted from a file, ready for chunking
+#[derive(Debug, Clone)]
+pub struct Extracted {
+    /// Value stored in `chunks.source`
+    pub source: &'static str,
+    /// Segments are pages (`start` is a page number) instead of line ranges
+    pub paged: bool,
+    pub segments: Vec<Segment>,
+}
+
+/// A contiguous piece of extracted text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// First line (1-indexed) or page number of the segment
+    pub start: i32,
+    pub text: String,
+}
+
+impl Extracted {
+    fn whole(source: &'static str, text: String) -> Self {
+        Self {
+            source,
+            paged: false,
+            segments: vec![Segment { start: 1, text }],
+        }
+    }
+}
+
+/// Turns raw file bytes into indexable text
+pub trait Extractor: Send + Sync {
+    fn extract(&self, path: &Path, bytes: &[u8]) -> Result<Extracted>;
+}
+
+/// Extractors keyed by lowercase file extension
+#[derive(Clone)]
+pub struct Extractors {
+    by_extension: HashMap<String, Arc<dyn Extractor>>,
+}
+
+impl Extractors {
+    /// Registry without any extractors
+    pub fn empty() -> Self {
+        Self {
+            by_extension: HashMap::new(),
+        }
+    }
+
+    /// Register an extractor for the given extensions (without the dot)
+    pub fn register(&mut self, extensions: &[&str], extractor: Arc<dyn Extractor>) {
+        for ext in extensions {
+            self.by_extension
+                .insert(ext.to_ascii_lowercase(), extractor.clone());
+        }
+    }
+
+    /// Extractor for a path, if its extension is supported
+    pub fn for_path(&self, path: &Path) -> Option<&Arc<dyn Extractor>> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.by_extension.get(&ext)
+    }
+
+    pub fn supports(&self, path: &Path) -> bool {
+        self.for_path(path).is_some()
+    }
+}
+
+impl Default for Extractors {
+    fn default() -> Self {
+        let mut extractors = Self::empty();
+        extractors.register(
+            &["md", "markdown"],
+            Arc::new(PlainTextExtractor(SOURCE_MEMORY)),
+        );
+        extractors.register(
+            &["txt", "text", "org", "rst", "adoc"],
+            Arc::new(PlainTextExtractor(SOURCE_TEXT)),
+        );
+        extractors.register(&["html", "htm", "xhtml"], Arc::new(HtmlExtractor));
+        extractors.register(&["pdf"], Arc::new(PdfExtractor));
+        extractors.register(CODE_EXTENSIONS, Arc::new(CodeExtractor));
+        extractors
+    }
+}
+
+impl std::fmt::Debug for Extractors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut exts: Vec<_> = self.by_extension.keys().collect();
+        exts.sort();
+        f.debug_struct("Extractors")
+            .field("extensions", &exts)
+            .finish()
+    }
+}
+
+fn decode(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Markdown and plain-text notes, indexed as-is
+pub struct PlainTextExtractor(pub &'static str);
+
+impl Extractor for PlainTextExtractor {
+    fn extract(&self, _path: &Path, bytes: &[u8]) -> Result<Extracted> {
+        Ok(Extracted::whole(self.0, decode(bytes)))
+    }
+}
+
+/// HTML pages rendered to text (lines refer to the rendered text)
+pub struct HtmlExtractor;
+
+impl Extractor for HtmlExtractor {
+    fn extract(&self, _path: &Path, bytes: &[u8]) -> Result<Extracted> {
+        let text = html2text::from_read(bytes, HTML_WIDTH)
+            .map_err(|e| anyhow!("Failed to render HTML: {}", e))?;
+        Ok(Extracted::whole(SOURCE_HTML, text))
+    }
+}
+
+/// PDF text, one segment per page
+pub struct PdfExtractor;
+
+impl Extractor for PdfExtractor {
+    fn extract(&self, _path: &Path, bytes: &[u8]) -> Result<Extracted> {
+        let pages = pdf_extract::extract_text_from_mem_by_pages(bytes)
+            .map_err(|e| anyhow!("Failed to extract PDF text: {}", e))?;
+        let segments = pages
+            .into_iter()
+            .enumerate()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(i, text)| Segment {
+                start: i as i32 + 1,
+                text,
+            })
+            .collect();
+        Ok(Extracted {
+            source: SOURCE_PDF,
+            paged: true,
+            segments,
+        })
+    }
+}
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "mjs", "cjs", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cc", "cpp",
+    "hpp", "cs", "rb", "swift", "scala", "php", "sh", "lua",
+];
+
+static SYMBOL_START: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)^(?:
+            (?:pub(?:\([^)]*\))?\s+)?(?:async\s+|const\s+|unsafe\s+|extern\s+(?:\x22[^\x22]*\x22\s+)?)*
+                (?:fn|struct|enum|trait|impl|mod|type|union|macro_rules!)\b
+          | (?:async\s+)?def\s | class\s
+          | (?:export\s+)?(?:default\s+)?(?:async\s+)?(?:function|class|interface|type|enum|const|let|var)\b
+          | func\s
+          | (?:public|private|protected|internal|static|final|abstract|sealed|open|data)\s
+          | (?:module|describe)\b
+        )",
+    )
+    .expect("valid symbol regex")
+});
+
+/// Lines attached to the following symbol (doc comments, attributes, decorators)
+fn is_symbol_preamble(line: &str) -> bool {
+    let line = line.trim_start();
+    ["//", "#", "@", "/*", "*"]
+        .iter()
+        .any(|p| line.starts_with(p))
+}
+
+/// Source code split at top-level symbols
+pub struct CodeExtractor;
+
+impl Extractor for CodeExtractor {
+    fn extract(&self, _path: &Path, bytes: &[u8]) -> Result<Extracted> {
+        let text = decode(bytes);
+        Ok(Extracted {
+            source: SOURCE_CODE,
+            paged: false,
+            segments: split_symbols(&text),
+        })
+    }
+}
+
+/// Split code into segments that each start at a top-level (unindented)
+/// declaration, keeping leading comments and attributes with it.
+/// Anything before the first declaration (imports, headers) is its own segment.
+fn split_symbols(text: &str) -> Vec<Segment> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut starts = vec![0];
+
+    for (i, line) in lines.iter().enumerate() {
+        if !SYMBOL_START.is_match(line) {
+            continue;
+        }
+        let mut start = i;
+        while start > 0 && is_symbol_preamble(lines[start - 1]) {
+            start -= 1;
+        }
+        if start > *starts.last().unwrap_or(&0) {
+            starts.push(start);
+        }
+    }
+    starts.push(lines.len());
+
+    starts
+        .windows(2)
+        .filter_map(|w| {
+            let body = lines[w[0]..w[1]].join("\n");
+            if body.trim().is_empty() {
+                return None;
+            }
+            Some(Segment {
+                start: w[0] as i32 + 1,
+                text: body,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_by_extension() {
+        let extractors = Extractors::default();
+        assert!(extractors.supports(Path::new("notes/a.MD")));
+        assert!(extractors.supports(Path::new("paper.pdf")));
+        assert!(extractors.supports(Path::new("todo.org")));
+        assert!(extractors.supports(Path::new("src/main.rs")));
+        assert!(!extractors.supports(Path::new("image.png")));
+        assert!(!extractors.supports(Path::new("Makefile")));
+
+        let doc = extractors
+            .for_path(Path::new("todo.org"))
+            .unwrap()
+            .extract(Path::new("todo.org"), b"* TODO ship it\n")
+            .unwrap();
+        assert_eq!(doc.source, SOURCE_TEXT);
+        assert!(!doc.paged);
+    }
+
+    #[test]
+    fn test_html_extractor() {
+        let html = b"<html><head><style>p{}</style></head><body><h1>Title</h1><p>Hello <b>world</b></p></body></html>";
+        let doc = HtmlExtractor.extract(Path::new("a.html"), html).unwrap();
+        assert_eq!(doc.source, SOURCE_HTML);
+        let text = &doc.segments[0].text;
+        assert!(text.contains("Title"));
+        assert!(text.contains("Hello"));
+        assert!(!text.contains("<p>"));
+    }
+
+    #[test]
+    fn test_split_symbols() {
+        let code = "use std::fs;\n\n/// Reads it\n#[inline]\npub fn read() {\n    fs::read(\"x\");\n}\n\nstruct Foo {\n    a: u8,\n}\n\nimpl Foo {\n    fn b(&self) {}\n}\n";
+        let segments = split_symbols(code);
+        let starts: Vec<i32> = segments.iter().map(|s| s.start).collect();
+        assert_eq!(starts, vec![1, 3, 9, 13]);
+        assert!(segments[1].text.starts_with("/// Reads it"));
+        assert!(segments[3].text.contains("fn b(&self)"));
+
+        let py = "import os\n\n@cache\ndef load():\n    return 1\n\nclass A:\n    def m(self):\n        pass\n";
+        let starts: Vec<i32> = split_symbols(py).iter().map(|s| s.start).collect();
+        assert_eq!(starts, vec![1, 3, 7]);
+    }
+
+    #[test]
+    fn test_pdf_extractor_rejects_garbage() {
+        assert!(PdfExtractor
+            .extract(Path::new("a.pdf"), b"not a pdf")
+            .is_err());
+        assert!(is_paged(SOURCE_PDF));
+        assert!(!is_paged(SOURCE_CODE));
+    }
+}
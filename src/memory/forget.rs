@@ -0,0 +1,466 @@
+//! Right to forget
+//!
+//! Finds a topic (case-insensitive literal text) everywhere memory persists
+//! it: workspace markdown (MEMORY.md, daily logs, artifacts), session
+//! transcripts, and index rows that reindexing alone wouldn't clear: cached
+//! embeddings, agent-asserted relations and entities, and consolidation
+//! proposals. Markdown matches are redacted in place or their lines deleted.
+//! Transcript entries are always redacted, since dropping them would break
+//! tool call/result pairing. Rewritten files are then reindexed, so
+//! `chunks`, `chunks_fts` and their vectors follow.
+//!
+//! File checkpoints of earlier turns are redacted the same way. Earlier
+//! commits of the workspace repository keep their copies; the report lists
+//! them.
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use tokio::task;
+
+use super::history::Revision;
+use super::index::MemoryIndex;
+use crate::agent::CheckpointMatches;
+
+/// Replaces forgotten text
+pub const FORGOTTEN: &str = "[forgotten]";
+
+/// Shorter queries would match too much to be a deliberate request
+pub const MIN_FORGET_QUERY: usize = 3;
+
+/// How markdown matches are removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForgetMode {
+    /// Replace the matched text with [`FORGOTTEN`]
+    #[default]
+    Redact,
+    /// Delete every line containing a match
+    Delete,
+}
+
+impl std::str::FromStr for ForgetMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "redact" => Ok(ForgetMode::Redact),
+            "delete" => Ok(ForgetMode::Delete),
+            other => bail!(
+                "Unknown forget mode '{}' (expected redact or delete)",
+                other
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ForgetOptions {
+    pub mode: ForgetMode,
+    /// Without this only the report is produced
+    pub apply: bool,
+    /// Session transcripts to search (default: the agent's sessions directory)
+    pub sessions_dir: Option<PathBuf>,
+}
+
+/// Lines of one file that mention the query (1-indexed)
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatches {
+    pub path: String,
+    pub lines: Vec<usize>,
+}
+
+/// Where a query was found, and what was removed when `applied`
+#[derive(Debug, Default, Serialize)]
+pub struct ForgetReport {
+    /// Workspace markdown outside `artifacts/`
+    pub memory: Vec<FileMatches>,
+    pub artifacts: Vec<FileMatches>,
+    /// Session transcripts, by file name
+    pub sessions: Vec<FileMatches>,
+    /// Indexed sources outside the workspace markdown; they are left alone
+    pub external: Vec<String>,
+    /// Index chunks mentioning the query
+    pub chunks: usize,
+    pub embeddings: usize,
+    pub relations: usize,
+    pub consolidation_runs: usize,
+    /// Workspace commits that still contain it; history is not rewritten
+    pub history: Vec<Revision>,
+    /// File checkpoints of earlier turns containing it
+    pub checkpoints: Vec<CheckpointMatches>,
+    pub applied: bool,
+}
+
+impl ForgetReport {
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+            && self.artifacts.is_empty()
+            && self.sessions.is_empty()
+            && self.external.is_empty()
+            && self.chunks == 0
+            && self.embeddings == 0
+            && self.relations == 0
+            && self.consolidation_runs == 0
+            && self.history.is_empty()
+            && self.checkpoints.is_empty()
+    }
+}
+
+/// `path: lines 3, 7` with long line lists shortened
+fn format_files(
+    f: &mut std::fmt::Formatter<'_>,
+    label: &str,
+    files: &[FileMatches],
+) -> std::fmt::Result {
+    if files.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{} ({}):", label, files.len())?;
+    for file in files {
+        let mut lines: Vec<String> = file.lines.iter().take(10).map(|l| l.to_string()).collect();
+        if file.lines.len() > 10 {
+            lines.push(format!("... +{}", file.lines.len() - 10));
+        }
+        writeln!(f, "  {}: line {}", file.path, lines.join(", "))?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for ForgetReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No occurrences found");
+        }
+        format_files(f, "Memory files", &self.memory)?;
+        format_files(f, "Artifacts", &self.artifacts)?;
+        format_files(f, "Session transcripts", &self.sessions)?;
+        let verb = if self.applied { "removed" } else { "to remove" };
+        writeln!(
+            f,
+            "Index: {} chunk(s), {} cached embedding(s), {} relation(s), {} consolidation run(s) {}",
+            self.chunks, self.embeddings, self.relations, self.consolidation_runs, verb
+        )?;
+        if !self.external.is_empty() {
+            writeln!(
+                f,
+                "Indexed sources outside the workspace still contain it (not modified): {}",
+                self.external.join(", ")
+            )?;
+        }
+        if !self.history.is_empty() {
+            writeln!(
+                f,
+                "Workspace git history still contains it in {} commit(s) (not rewritten; purge them with a history rewrite such as git filter-repo):",
+                self.history.len()
+            )?;
+            for revision in self.history.iter().take(10) {
+                writeln!(f, "  {} {}", revision.id, revision.summary)?;
+            }
+            if self.history.len() > 10 {
+                writeln!(f, "  ... +{}", self.history.len() - 10)?;
+            }
+        }
+        if !self.checkpoints.is_empty() {
+            let verb = if self.applied {
+                "redacted"
+            } else {
+                "to redact"
+            };
+            writeln!(f, "File checkpoints {} ({}):", verb, self.checkpoints.len())?;
+            for session in &self.checkpoints {
+                let files: Vec<String> = session
+                    .files
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                writeln!(f, "  session {}: {}", session.session_id, files.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Index rows mentioning a query
+#[derive(Debug, Default)]
+pub(super) struct IndexMatches {
+    /// Paths of matching chunks
+    pub paths: BTreeSet<String>,
+    pub chunks: usize,
+    pub embeddings: usize,
+    pub relations: usize,
+    pub consolidation_runs: usize,
+}
+
+/// Case-insensitive literal matcher for a forget query
+pub(super) fn matcher(query: &str) -> Result<Regex> {
+    let query = query.trim();
+    if query.chars().count() < MIN_FORGET_QUERY {
+        bail!(
+            "Query must be at least {} characters to forget",
+            MIN_FORGET_QUERY
+        );
+    }
+    Ok(Regex::new(&format!("(?i){}", regex::escape(query)))?)
+}
+
+/// Forget matches in markdown. Returns the new content and matching lines.
+pub(super) fn forget_in_markdown(
+    content: &str,
+    re: &Regex,
+    mode: ForgetMode,
+) -> (String, Vec<usize>) {
+    let mut lines = Vec::new();
+    let mut out = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if !re.is_match(line) {
+            out.push(line.to_string());
+            continue;
+        }
+        lines.push(i + 1);
+        if mode == ForgetMode::Redact {
+            out.push(re.replace_all(line, FORGOTTEN).into_owned());
+        }
+    }
+
+    let mut new_content = out.join("\n");
+    if content.ends_with('\n') && !new_content.is_empty() {
+        new_content.push('\n');
+    }
+    (new_content, lines)
+}
+
+/// Redact matches inside the string values of JSON Lines, keeping every
+/// entry and the file valid. Returns the new content and matching lines.
+pub(super) fn forget_in_jsonl(content: &str, re: &Regex) -> (String, Vec<usize>) {
+    let mut lines = Vec::new();
+    let mut out = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        // Compare decoded strings, so escaped text matches too
+        let redacted = match serde_json::from_str::<Value>(line) {
+            Ok(value) => {
+                let mut redacted = value.clone();
+                redact_value(&mut redacted, re);
+                (redacted != value).then(|| redacted.to_string())
+            }
+            Err(_) => re
+                .is_match(line)
+                .then(|| re.replace_all(line, FORGOTTEN).into_owned()),
+        };
+        match redacted {
+            Some(redacted) => {
+                lines.push(i + 1);
+                out.push(redacted);
+            }
+            None => out.push(line.to_string()),
+        }
+    }
+
+    let mut new_content = out.join("\n");
+    if content.ends_with('\n') {
+        new_content.push('\n');
+    }
+    (new_content, lines)
+}
+
+/// Redact matches in every string (and object key) of a JSON value
+fn redact_value(value: &mut Value, re: &Regex) {
+    match value {
+        Value::String(s) if re.is_match(s) => {
+            *s = re.replace_all(s, FORGOTTEN).into_owned();
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact_value(v, re)),
+        Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (key, mut v) in entries {
+                redact_value(&mut v, re);
+                map.insert(re.replace_all(&key, FORGOTTEN).into_owned(), v);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl MemoryIndex {
+    /// Find, and with `apply` remove, index rows mentioning the query that a
+    /// reindex wouldn't: cached embeddings of matching chunks, agent-asserted
+    /// relations and entities, and consolidation proposals (redacted).
+    /// Matching chunks are only counted; reindexing rewritten files drops them.
+    pub(super) async fn forget_rows(&self, re: Regex, apply: bool) -> Result<IndexMatches> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let tx = conn.transaction()?;
+            let mut matches = IndexMatches::default();
+
+            let mut hashes = BTreeSet::new();
+            {
+                let mut stmt = tx.prepare("SELECT path, hash, text FROM chunks")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?;
+                for row in rows {
+                    let (path, hash, text) = row?;
+                    if re.is_match(&text) {
+                        matches.chunks += 1;
+                        matches.paths.insert(path);
+                        hashes.insert(hash);
+                    }
+                }
+            }
+            for hash in &hashes {
+                matches.embeddings += if apply {
+                    tx.execute("DELETE FROM embedding_cache WHERE hash = ?1", params![hash])?
+                } else {
+                    tx.query_row(
+                        "SELECT COUNT(*) FROM embedding_cache WHERE hash = ?1",
+                        params![hash],
+                        |row| row.get::<_, i64>(0),
+                    )? as usize
+                };
+            }
+
+            let relations: Vec<i64> = {
+                let mut stmt = tx.prepare(
+                    "SELECT rowid, subject, predicate, object FROM relations WHERE origin = 'agent'",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        format!(
+                            "{} {} {}",
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                            row.get::<_, String>(3)?
+                        ),
+                    ))
+                })?;
+                rows.filter_map(|r| r.ok())
+                    .filter(|(_, text)| re.is_match(text))
+                    .map(|(id, _)| id)
+                    .collect()
+            };
+            matches.relations = relations.len();
+            let entities: Vec<String> = {
+                let mut stmt =
+                    tx.prepare("SELECT name, label FROM entities WHERE kind != 'note'")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+                rows.filter_map(|r| r.ok())
+                    .filter(|(name, label)| re.is_match(name) || re.is_match(label))
+                    .map(|(name, _)| name)
+                    .collect()
+            };
+
+            let runs: Vec<(i64, Option<String>, String, String)> = {
+                let mut stmt =
+                    tx.prepare("SELECT id, proposed, diff, notes FROM consolidation_runs")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                })?;
+                rows.filter_map(|r| r.ok())
+                    .filter(|(_, proposed, diff, notes)| {
+                        proposed.as_deref().is_some_and(|p| re.is_match(p))
+                            || re.is_match(diff)
+                            || re.is_match(notes)
+                    })
+                    .collect()
+            };
+            matches.consolidation_runs = runs.len();
+
+            if apply {
+                for id in relations {
+                    tx.execute("DELETE FROM relations WHERE rowid = ?1", params![id])?;
+                }
+                for name in entities {
+                    tx.execute("DELETE FROM entities WHERE name = ?1", params![name])?;
+                }
+                for (id, proposed, diff, notes) in runs {
+                    let proposed = proposed.map(|p| re.replace_all(&p, FORGOTTEN).into_owned());
+                    let diff = re.replace_all(&diff, FORGOTTEN).into_owned();
+                    let notes = match serde_json::from_str::<Value>(&notes) {
+                        Ok(mut value) => {
+                            redact_value(&mut value, &re);
+                            value.to_string()
+                        }
+                        Err(_) => re.replace_all(&notes, FORGOTTEN).into_owned(),
+                    };
+                    tx.execute(
+                        "UPDATE consolidation_runs SET proposed = ?1, diff = ?2, notes = ?3 WHERE id = ?4",
+                        params![proposed, diff, notes, id],
+                    )?;
+                }
+            }
+
+            tx.commit()?;
+            Ok(matches)
+        })
+        .await?
+    }
+
+    /// Merge FTS segments and rewrite the database file so deleted text
+    /// doesn't linger in free pages
+    pub(super) async fn compact(&self) -> Result<()> {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            conn.execute("INSERT INTO chunks_fts(chunks_fts) VALUES('optimize')", [])?;
+            conn.execute_batch("VACUUM")?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forget_in_markdown_and_jsonl() {
+        let re = matcher("hunter2").unwrap();
+        let content = "# Memory\n\n- Password is HUNTER2, oops\n- Likes tea\n";
+
+        let (redacted, lines) = forget_in_markdown(content, &re, ForgetMode::Redact);
+        assert_eq!(lines, vec![3]);
+        assert_eq!(
+            redacted,
+            "# Memory\n\n- Password is [forgotten], oops\n- Likes tea\n"
+        );
+
+        let (deleted, _) = forget_in_markdown(content, &re, ForgetMode::Delete);
+        assert_eq!(deleted, "# Memory\n\n- Likes tea\n");
+
+        let jsonl = "{\"type\":\"session\"}\n{\"message\":{\"content\":[{\"text\":\"my pw: hunt\\u0065r2\"}]}}\n";
+        let (redacted, lines) = forget_in_jsonl(jsonl, &re);
+        assert_eq!(lines, vec![2]);
+        assert!(redacted.contains("my pw: [forgotten]"));
+        for line in redacted.lines() {
+            serde_json::from_str::<Value>(line).unwrap();
+        }
+
+        assert!(matcher(" a ").is_err());
+        // Regex syntax in the query is literal
+        assert!(matcher("a.b*c").unwrap().is_match("x a.b*c y"));
+        assert!(!matcher("a.b*c").unwrap().is_match("axbbbc"));
+    }
+}
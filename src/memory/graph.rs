@@ -0,0 +1,734 @@
+//! Memory graph: wikilinks, entities and relations
+//!
+//! Extracts `[[wikilinks]]` and inline `predicate:: [[Target]]` fields from
+//! indexed files and stores them next to `chunks` in the memory SQLite.
+//! File-backed nodes are keyed by their relative path without `.md`
+//! (case-insensitive), so `a/notes.md` and `b/notes.md` stay distinct. A bare
+//! `[[Alice]]` resolves to `people/Alice.md` wherever it lives, as long as
+//! only one note has that name.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use tokio::task;
+
+use super::index::MemoryIndex;
+
+/// Relation name used for plain `[[wikilinks]]`
+pub const LINKS_TO: &str = "links_to";
+
+/// Maximum depth accepted by neighbor and path queries
+pub const MAX_GRAPH_DEPTH: usize = 6;
+
+/// Version of the extracted graph data. Bumping it re-extracts every
+/// markdown file on the next sync, unchanged ones included.
+const GRAPH_VERSION: &str = "2";
+
+/// A `[[wikilink]]` found in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Link target as written (without alias or heading)
+    pub target: String,
+    /// Optional display alias (`[[Target|alias]]`)
+    pub alias: Option<String>,
+    /// Optional heading anchor (`[[Target#Heading]]`)
+    pub heading: Option<String>,
+    /// Line number (1-indexed)
+    pub line: i32,
+}
+
+/// A typed relation written inline as `predicate:: [[Target]]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineRelation {
+    pub predicate: String,
+    pub target: String,
+    pub line: i32,
+}
+
+/// A graph node (entity)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Normalized node name
+    pub name: String,
+    /// Display label (original casing)
+    pub label: String,
+    /// Entity kind: "note" for indexed files, "mention" for unresolved links,
+    /// or a kind assigned by the agent (e.g. "person", "project")
+    pub kind: String,
+    /// File path relative to workspace, if the node is backed by a file
+    pub path: Option<String>,
+}
+
+/// A directed edge between two nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: String,
+    /// File the edge was extracted from (None for agent-asserted relations)
+    pub path: Option<String>,
+    pub line: Option<i32>,
+    /// "wikilink", "inline" or "agent"
+    pub origin: String,
+}
+
+/// Result of a path query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphPath {
+    pub nodes: Vec<String>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Normalize a note name or path into a graph node name.
+///
+/// `people/Alice.md` maps to `people/alice`; `Alice` and `alice` map to
+/// `alice`. Bare names are matched against notes by [`resolve_node`].
+pub fn normalize_node(name: &str) -> String {
+    let name = name.trim().replace('\\', "/");
+    let name = name.trim_start_matches("./");
+    let name = name
+        .strip_suffix(".md")
+        .or_else(|| name.strip_suffix(".MD"))
+        .unwrap_or(name);
+    name.trim().to_lowercase()
+}
+
+/// Resolve a note name or path to a node: a bare name that matches exactly
+/// one note (by file name, in any folder) resolves to that note, anything
+/// else to its normalized form
+fn resolve_node(conn: &Connection, name: &str) -> Result<String> {
+    let key = normalize_node(name);
+    if key.contains('/') {
+        return Ok(key);
+    }
+
+    let suffix = format!("/{}", key);
+    let mut stmt = conn.prepare(
+        "SELECT name FROM entities WHERE path IS NOT NULL AND (name = ?1 OR substr(name, -length(?2)) = ?2) LIMIT 2",
+    )?;
+    let matches = stmt
+        .query_map(params![&key, &suffix], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(match matches.as_slice() {
+        [only] => only.clone(),
+        _ => key,
+    })
+}
+
+/// Extract `[[wikilinks]]` from markdown, skipping fenced code blocks
+pub fn extract_wikilinks(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for (i, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find("[[") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("]]") else {
+                break;
+            };
+            let inner = &after[..end];
+            rest = &after[end + 2..];
+
+            let (target_part, alias) = match inner.split_once('|') {
+                Some((t, a)) => (t, Some(a.trim().to_string())),
+                None => (inner, None),
+            };
+            let (target, heading) = match target_part.split_once('#') {
+                Some((t, h)) => (t.trim(), Some(h.trim().to_string())),
+                None => (target_part.trim(), None),
+            };
+
+            if target.is_empty() || target.contains('[') {
+                continue;
+            }
+
+            links.push(WikiLink {
+                target: target.to_string(),
+                alias: alias.filter(|a| !a.is_empty()),
+                heading: heading.filter(|h| !h.is_empty()),
+                line: (i + 1) as i32,
+            });
+        }
+    }
+
+    links
+}
+
+/// Extract inline relations of the form `predicate:: [[Target]]`.
+///
+/// List markers are allowed before the predicate (`- works_on:: [[Alpha]]`),
+/// and a line may reference several targets.
+pub fn extract_inline_relations(content: &str) -> Vec<InlineRelation> {
+    let mut relations = Vec::new();
+
+    for link in extract_wikilinks(content) {
+        let Some(line) = content.lines().nth((link.line - 1) as usize) else {
+            continue;
+        };
+        let Some((lhs, rhs)) = line.split_once("::") else {
+            continue;
+        };
+        if !rhs.contains(&format!("[[{}", link.target)) {
+            continue;
+        }
+
+        let predicate = lhs
+            .trim()
+            .trim_start_matches(['-', '*', '+'])
+            .trim()
+            .to_lowercase()
+            .replace(' ', "_");
+        if predicate.is_empty()
+            || !predicate
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            continue;
+        }
+
+        relations.push(InlineRelation {
+            predicate,
+            target: link.target,
+            line: link.line,
+        });
+    }
+
+    relations
+}
+
+/// Create graph tables (called during index initialization)
+pub(super) fn ensure_graph_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Wikilinks extracted from indexed files
+        CREATE TABLE IF NOT EXISTS links (
+            source_path TEXT NOT NULL,
+            source TEXT NOT NULL,
+            target TEXT NOT NULL,
+            target_raw TEXT NOT NULL,
+            heading TEXT,
+            line INTEGER NOT NULL
+        );
+
+        -- Graph nodes (files, link targets, agent-declared entities)
+        CREATE TABLE IF NOT EXISTS entities (
+            name TEXT PRIMARY KEY,
+            label TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'mention',
+            path TEXT,
+            updated_at INTEGER NOT NULL
+        );
+
+        -- Typed relations (inline fields or asserted by the agent)
+        CREATE TABLE IF NOT EXISTS relations (
+            subject TEXT NOT NULL,
+            predicate TEXT NOT NULL,
+            object TEXT NOT NULL,
+            path TEXT,
+            line INTEGER,
+            origin TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_links_source ON links(source);
+        CREATE INDEX IF NOT EXISTS idx_links_target ON links(target);
+        CREATE INDEX IF NOT EXISTS idx_links_path ON links(source_path);
+        CREATE INDEX IF NOT EXISTS idx_relations_subject ON relations(subject);
+        CREATE INDEX IF NOT EXISTS idx_relations_object ON relations(object);
+        CREATE INDEX IF NOT EXISTS idx_entities_path ON entities(path);
+        "#,
+    )?;
+
+    // Graph data only gets extracted when a file's hash changes. After an
+    // upgrade, drop what older versions extracted and clear the markdown
+    // hashes so the next sync extracts every file once.
+    let version: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'graph_version'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if version.as_deref() != Some(GRAPH_VERSION) {
+        conn.execute_batch(
+            r#"
+            DELETE FROM links;
+            DELETE FROM relations WHERE origin = 'inline';
+            UPDATE entities SET path = NULL, kind = CASE WHEN kind = 'note' THEN 'mention' ELSE kind END;
+            UPDATE files SET hash = '' WHERE source = 'memory';
+            "#,
+        )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('graph_version', ?1)",
+            params![GRAPH_VERSION],
+        )?;
+    }
+    Ok(())
+}
+
+/// Replace graph data extracted from a file (runs inside the index transaction)
+pub(super) fn index_links(conn: &Connection, path: &str, content: &str, now: i64) -> Result<()> {
+    delete_links_for_path(conn, path)?;
+
+    let source = normalize_node(path);
+    let label = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+
+    conn.execute(
+        r#"INSERT INTO entities (name, label, kind, path, updated_at) VALUES (?1, ?2, 'note', ?3, ?4)
+           ON CONFLICT(name) DO UPDATE SET
+               label = excluded.label,
+               path = excluded.path,
+               kind = CASE WHEN entities.kind = 'mention' THEN 'note' ELSE entities.kind END,
+               updated_at = excluded.updated_at"#,
+        params![&source, &label, path, now],
+    )?;
+    adopt_mentions(conn, &source, now)?;
+
+    for link in extract_wikilinks(content) {
+        let target = resolve_node(conn, &link.target)?;
+        conn.execute(
+            "INSERT INTO links (source_path, source, target, target_raw, heading, line) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![path, &source, &target, &link.target, &link.heading, link.line],
+        )?;
+        upsert_mention(conn, &target, &link.target, now)?;
+    }
+
+    for rel in extract_inline_relations(content) {
+        let object = resolve_node(conn, &rel.target)?;
+        conn.execute(
+            "INSERT INTO relations (subject, predicate, object, path, line, origin, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, 'inline', ?6)",
+            params![&source, &rel.predicate, &object, path, rel.line, now],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Remove graph data extracted from a file
+pub(super) fn delete_links_for_path(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute("DELETE FROM links WHERE source_path = ?1", params![path])?;
+    conn.execute(
+        "DELETE FROM relations WHERE path = ?1 AND origin = 'inline'",
+        params![path],
+    )?;
+    Ok(())
+}
+
+/// Detach entities from a deleted file (they stay as mentions)
+pub(super) fn forget_file_entity(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE entities SET path = NULL, kind = CASE WHEN kind = 'note' THEN 'mention' ELSE kind END WHERE path = ?1",
+        params![path],
+    )?;
+    Ok(())
+}
+
+/// Point edges recorded against a note's bare name at the note itself, once
+/// the name resolves to it (the note was indexed after the links to it)
+fn adopt_mentions(conn: &Connection, note: &str, now: i64) -> Result<()> {
+    let Some((_, bare)) = note.rsplit_once('/') else {
+        return Ok(());
+    };
+    if resolve_node(conn, bare)? != note {
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE links SET target = ?1 WHERE target = ?2",
+        params![note, bare],
+    )?;
+    conn.execute(
+        "UPDATE relations SET subject = ?1 WHERE subject = ?2",
+        params![note, bare],
+    )?;
+    conn.execute(
+        "UPDATE relations SET object = ?1 WHERE object = ?2",
+        params![note, bare],
+    )?;
+    // Keep a kind the agent gave the mention
+    conn.execute(
+        r#"UPDATE entities SET
+               kind = (SELECT kind FROM entities WHERE name = ?2),
+               updated_at = ?3
+           WHERE name = ?1 AND kind = 'note'
+             AND EXISTS (SELECT 1 FROM entities WHERE name = ?2 AND kind NOT IN ('mention', 'note'))"#,
+        params![note, bare, now],
+    )?;
+    conn.execute(
+        "DELETE FROM entities WHERE name = ?1 AND path IS NULL",
+        params![bare],
+    )?;
+    Ok(())
+}
+
+fn upsert_mention(conn: &Connection, name: &str, label: &str, now: i64) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO entities (name, label, kind, path, updated_at) VALUES (?1, ?2, 'mention', NULL, ?3)",
+        params![name, label, now],
+    )?;
+    Ok(())
+}
+
+/// All edges touching a node (both directions)
+fn edges_for_node(conn: &Connection, node: &str) -> Result<Vec<GraphEdge>> {
+    let mut edges = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT source, target, source_path, line FROM links WHERE source = ?1 OR target = ?1",
+    )?;
+    let rows = stmt.query_map(params![node], |row| {
+        Ok(GraphEdge {
+            from: row.get(0)?,
+            to: row.get(1)?,
+            relation: LINKS_TO.to_string(),
+            path: row.get(2)?,
+            line: row.get(3)?,
+            origin: "wikilink".to_string(),
+        })
+    })?;
+    for row in rows {
+        edges.push(row?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT subject, object, predicate, path, line, origin FROM relations WHERE subject = ?1 OR object = ?1",
+    )?;
+    let rows = stmt.query_map(params![node], |row| {
+        Ok(GraphEdge {
+            from: row.get(0)?,
+            to: row.get(1)?,
+            relation: row.get(2)?,
+            path: row.get(3)?,
+            line: row.get(4)?,
+            origin: row.get(5)?,
+        })
+    })?;
+    for row in rows {
+        edges.push(row?);
+    }
+
+    Ok(edges)
+}
+
+fn other_end<'a>(edge: &'a GraphEdge, node: &str) -> &'a str {
+    if edge.from == node {
+        &edge.to
+    } else {
+        &edge.from
+    }
+}
+
+impl MemoryIndex {
+    /// Look up a node by name or path
+    pub async fn graph_node(&self, name: &str) -> Result<Option<GraphNode>> {
+        let pool = self.pool.clone();
+        let name = name.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let name = resolve_node(&conn, &name)?;
+            let node = conn
+                .query_row(
+                    "SELECT name, label, kind, path FROM entities WHERE name = ?1",
+                    params![&name],
+                    |row| {
+                        Ok(GraphNode {
+                            name: row.get(0)?,
+                            label: row.get(1)?,
+                            kind: row.get(2)?,
+                            path: row.get(3)?,
+                        })
+                    },
+                )
+                .optional()?;
+            Ok(node)
+        })
+        .await?
+    }
+
+    /// Edges reachable from a node within `depth` hops (undirected traversal)
+    pub async fn graph_neighbors(&self, name: &str, depth: usize) -> Result<Vec<GraphEdge>> {
+        let pool = self.pool.clone();
+        let name = name.to_string();
+        let depth = depth.clamp(1, MAX_GRAPH_DEPTH);
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let start = resolve_node(&conn, &name)?;
+
+            let mut visited: HashSet<String> = HashSet::from([start.clone()]);
+            let mut frontier = vec![start];
+            let mut seen_edges = HashSet::new();
+            let mut result = Vec::new();
+
+            for _ in 0..depth {
+                let mut next = Vec::new();
+                for node in &frontier {
+                    for edge in edges_for_node(&conn, node)? {
+                        let key = (
+                            edge.from.clone(),
+                            edge.to.clone(),
+                            edge.relation.clone(),
+                            edge.path.clone(),
+                            edge.line,
+                        );
+                        let other = other_end(&edge, node).to_string();
+                        if seen_edges.insert(key) {
+                            result.push(edge);
+                        }
+                        if visited.insert(other.clone()) {
+                            next.push(other);
+                        }
+                    }
+                }
+                if next.is_empty() {
+                    break;
+                }
+                frontier = next;
+            }
+
+            Ok(result)
+        })
+        .await?
+    }
+
+    /// Files and notes linking to a node
+    pub async fn graph_backlinks(&self, name: &str) -> Result<Vec<GraphEdge>> {
+        let pool = self.pool.clone();
+        let name = name.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let target = resolve_node(&conn, &name)?;
+
+            Ok(edges_for_node(&conn, &target)?
+                .into_iter()
+                .filter(|e| e.to == target && e.from != target)
+                .collect())
+        })
+        .await?
+    }
+
+    /// Shortest path between two nodes (undirected BFS, bounded by MAX_GRAPH_DEPTH)
+    pub async fn graph_path(&self, from: &str, to: &str) -> Result<Option<GraphPath>> {
+        let pool = self.pool.clone();
+        let (from, to) = (from.to_string(), to.to_string());
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let from = resolve_node(&conn, &from)?;
+            let to = resolve_node(&conn, &to)?;
+
+            if from == to {
+                return Ok(Some(GraphPath {
+                    nodes: vec![from],
+                    edges: Vec::new(),
+                }));
+            }
+
+            // node -> (previous node, edge used to reach it)
+            let mut came_from: HashMap<String, (String, GraphEdge)> = HashMap::new();
+            let mut depth_of: HashMap<String, usize> = HashMap::from([(from.clone(), 0)]);
+            let mut queue = VecDeque::from([from.clone()]);
+
+            while let Some(node) = queue.pop_front() {
+                let depth = depth_of[&node];
+                if depth >= MAX_GRAPH_DEPTH {
+                    continue;
+                }
+                for edge in edges_for_node(&conn, &node)? {
+                    let other = other_end(&edge, &node).to_string();
+                    if depth_of.contains_key(&other) {
+                        continue;
+                    }
+                    depth_of.insert(other.clone(), depth + 1);
+                    came_from.insert(other.clone(), (node.clone(), edge));
+
+                    if other == to {
+                        let mut nodes = vec![to.clone()];
+                        let mut edges = Vec::new();
+                        let mut cursor = to.clone();
+                        while let Some((prev, edge)) = came_from.remove(&cursor) {
+                            edges.push(edge);
+                            nodes.push(prev.clone());
+                            cursor = prev;
+                        }
+                        nodes.reverse();
+                        edges.reverse();
+                        return Ok(Some(GraphPath { nodes, edges }));
+                    }
+                    queue.push_back(other);
+                }
+            }
+
+            Ok(None)
+        })
+        .await?
+    }
+
+    /// Record a relation asserted by the agent (survives reindexing).
+    /// Optional kinds tag the subject/object entities (e.g. "person", "project").
+    pub async fn graph_relate(
+        &self,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+        subject_kind: Option<&str>,
+        object_kind: Option<&str>,
+    ) -> Result<()> {
+        let pool = self.pool.clone();
+        let subject_label = subject.trim().to_string();
+        let object_label = object.trim().to_string();
+        let predicate = predicate.trim().to_lowercase().replace(' ', "_");
+        let subject_kind = subject_kind.map(|s| s.to_string());
+        let object_kind = object_kind.map(|s| s.to_string());
+
+        if subject_label.is_empty() || object_label.is_empty() || predicate.is_empty() {
+            anyhow::bail!("subject, predicate and object are required");
+        }
+
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs() as i64;
+
+            let tx = conn.transaction()?;
+            let subject = resolve_node(&tx, &subject_label)?;
+            let object = resolve_node(&tx, &object_label)?;
+
+            for (name, label, kind) in [
+                (&subject, &subject_label, &subject_kind),
+                (&object, &object_label, &object_kind),
+            ] {
+                upsert_mention(&tx, name, label, now)?;
+                if let Some(kind) = kind {
+                    tx.execute(
+                        "UPDATE entities SET kind = ?1, updated_at = ?2 WHERE name = ?3",
+                        params![kind, now, name],
+                    )?;
+                }
+            }
+
+            tx.execute(
+                "DELETE FROM relations WHERE subject = ?1 AND predicate = ?2 AND object = ?3 AND origin = 'agent'",
+                params![&subject, &predicate, &object],
+            )?;
+            tx.execute(
+                "INSERT INTO relations (subject, predicate, object, path, line, origin, updated_at) VALUES (?1, ?2, ?3, NULL, NULL, 'agent', ?4)",
+                params![&subject, &predicate, &object, now],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_wikilinks() {
+        let content = "See [[Alice]] and [[Project Alpha|alpha]].\n\
+                       ```\n[[NotALink]]\n```\n\
+                       Heading link: [[Notes#Decisions]]";
+        let links = extract_wikilinks(content);
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].target, "Alice");
+        assert_eq!(links[1].target, "Project Alpha");
+        assert_eq!(links[1].alias.as_deref(), Some("alpha"));
+        assert_eq!(links[2].target, "Notes");
+        assert_eq!(links[2].heading.as_deref(), Some("Decisions"));
+        assert_eq!(links[2].line, 5);
+    }
+
+    #[test]
+    fn test_extract_inline_relations() {
+        let content = "- works_on:: [[Project Alpha]]\n- plain [[Bob]]\nManager :: [[Carol]]";
+        let relations = extract_inline_relations(content);
+
+        assert_eq!(relations.len(), 2);
+        assert_eq!(relations[0].predicate, "works_on");
+        assert_eq!(relations[0].target, "Project Alpha");
+        assert_eq!(relations[1].predicate, "manager");
+    }
+
+    #[test]
+    fn test_normalize_node() {
+        assert_eq!(normalize_node("people/Alice.md"), "people/alice");
+        assert_eq!(normalize_node("./people/Alice.md"), "people/alice");
+        assert_eq!(normalize_node(" Alice "), "alice");
+        assert_eq!(normalize_node("MEMORY.md"), "memory");
+    }
+
+    #[tokio::test]
+    async fn test_graph_queries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let workspace = temp_dir.path();
+
+        let alice = workspace.join("Alice.md");
+        let alpha = workspace.join("Alpha.md");
+        fs::write(&alice, "# Alice\n\n- works_on:: [[Alpha]]\n")?;
+        fs::write(&alpha, "# Alpha\n\nKickoff with [[Bob]].\n")?;
+
+        let index = MemoryIndex::new(workspace)?;
+        index.index_file(&alice, false).await?;
+        index.index_file(&alpha, false).await?;
+
+        let backlinks = index.graph_backlinks("Alpha").await?;
+        assert!(backlinks.iter().any(|e| e.from == "alice"));
+
+        let neighbors = index.graph_neighbors("alpha", 1).await?;
+        assert!(neighbors.iter().any(|e| e.to == "bob"));
+        assert!(neighbors
+            .iter()
+            .any(|e| e.relation == "works_on" && e.from == "alice"));
+
+        let path = index.graph_path("Alice", "Bob").await?.expect("path");
+        assert_eq!(path.nodes, vec!["alice", "alpha", "bob"]);
+
+        index
+            .graph_relate("Bob", "reports_to", "Carol", Some("person"), None)
+            .await?;
+        let bob = index.graph_node("bob").await?.expect("node");
+        assert_eq!(bob.kind, "person");
+        assert!(index.graph_path("alice", "carol").await?.is_some());
+
+        // Reindexing a file replaces its links but keeps agent relations
+        fs::write(&alpha, "# Alpha\n\nNo links anymore.\n")?;
+        index.index_file(&alpha, false).await?;
+        assert!(index.graph_backlinks("bob").await?.is_empty());
+        assert!(!index.graph_neighbors("bob", 1).await?.is_empty());
+
+        Ok(())
+    }
+}
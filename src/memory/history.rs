@@ -0,0 +1,468 @@
+//! Git-backed versioning of workspace cognitive files
+//!
+//! After each agent turn that changed MEMORY.md, SOUL.md, daily logs and the
+//! other cognitive files, the kernel commits them to a repository in the
+//! workspace. Commits are built from HEAD plus the cognitive files, never
+//! from the on-disk index, so anything else the user keeps or stages in the
+//! workspace is left alone. Afterwards only the committed paths are synced
+//! into the on-disk index, so `git status` stays clean and a later plain
+//! `git commit` doesn't revert them. Everything goes through libgit2, so no
+//! git executable is needed.
+
+use anyhow::{anyhow, Context, Result};
+use git2::{
+    DiffFormat, DiffOptions, ErrorCode, Index, IndexEntry, IndexTime, ObjectType, Oid, Repository,
+    Signature, TreeWalkMode, TreeWalkResult,
+};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::agent::tools::is_cognitive_path;
+
+const AUTHOR_NAME: &str = "zier-alpha";
+const AUTHOR_EMAIL: &str = "zier-alpha@localhost";
+
+/// A commit that touched a file
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    /// Abbreviated commit id
+    pub id: String,
+    /// Unix timestamp
+    pub time: i64,
+    pub summary: String,
+}
+
+/// Versioning handle for a workspace repository
+pub struct WorkspaceHistory {
+    repo: Repository,
+    workspace: PathBuf,
+}
+
+impl WorkspaceHistory {
+    /// Open the repository at the workspace root
+    pub fn open(workspace: &Path) -> Result<Self> {
+        let repo = Repository::open(workspace)
+            .with_context(|| format!("{} is not a git repository", workspace.display()))?;
+        Ok(Self {
+            repo,
+            workspace: workspace.to_path_buf(),
+        })
+    }
+
+    /// Open the repository at the workspace root, creating it if missing
+    pub fn open_or_init(workspace: &Path) -> Result<Self> {
+        let repo = match Repository::open(workspace) {
+            Ok(repo) => repo,
+            Err(e) if e.code() == ErrorCode::NotFound => Repository::init(workspace)?,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            repo,
+            workspace: workspace.to_path_buf(),
+        })
+    }
+
+    /// Commit changed cognitive files on top of HEAD.
+    /// Returns the abbreviated commit id, or None when nothing changed.
+    pub fn commit_cognitive(&self, message: &str) -> Result<Option<String>> {
+        let changed = self.changed_cognitive_files()?;
+        if changed.is_empty() {
+            return Ok(None);
+        }
+
+        // Build the tree in memory from HEAD plus the cognitive files, so
+        // whatever the user has staged in the real index stays out of the commit
+        let parent = self.head_commit()?;
+        let mut index = Index::new()?;
+        if let Some(ref parent) = parent {
+            index.read_tree(&parent.tree()?)?;
+        }
+        for path in &changed {
+            let full = self.workspace.join(path);
+            if full.is_file() {
+                let id = self.repo.blob_path(&full)?;
+                index.add(&index_entry(path, id, std::fs::metadata(&full)?.len()))?;
+            } else {
+                index.remove_path(Path::new(path))?;
+            }
+        }
+
+        let tree = self.repo.find_tree(index.write_tree_to(&self.repo)?)?;
+        if let Some(ref parent) = parent {
+            if parent.tree_id() == tree.id() {
+                return Ok(None);
+            }
+        }
+
+        let signature = Signature::now(AUTHOR_NAME, AUTHOR_EMAIL)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+        self.sync_index(&changed)?;
+        Ok(Some(self.short_id(oid)?))
+    }
+
+    /// Stage `paths` as just committed in the on-disk index, leaving every
+    /// other entry (whatever the user staged) untouched
+    fn sync_index(&self, paths: &[String]) -> Result<()> {
+        let mut index = self.repo.index()?;
+        for path in paths {
+            let path = Path::new(path);
+            if self.workspace.join(path).is_file() {
+                index.add_path(path)?;
+            } else if index.get_path(path, 0).is_some() {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    /// Commits that changed `file`, newest first
+    pub fn history(&self, file: &str, limit: usize) -> Result<Vec<Revision>> {
+        let path = Path::new(file);
+        let mut revisions = Vec::new();
+        if self.head_commit()?.is_none() {
+            return Ok(revisions);
+        }
+
+        let mut walk = self.repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            let current = blob_id(&commit, path);
+            let previous = match commit.parent(0) {
+                Ok(parent) => blob_id(&parent, path),
+                Err(_) => None,
+            };
+            if current != previous {
+                revisions.push(Revision {
+                    id: self.short_id(commit.id())?,
+                    time: commit.time().seconds(),
+                    summary: commit.summary().unwrap_or("").to_string(),
+                });
+                if revisions.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(revisions)
+    }
+
+    /// Commits reachable from HEAD with a file matching `re`, newest first
+    pub fn commits_containing(&self, re: &Regex) -> Result<Vec<Revision>> {
+        let mut revisions = Vec::new();
+        if self.head_commit()?.is_none() {
+            return Ok(revisions);
+        }
+
+        let mut walk = self.repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        // Commits share most blobs, so each one is read once
+        let mut matched: HashMap<Oid, bool> = HashMap::new();
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            let mut found = false;
+            commit.tree()?.walk(TreeWalkMode::PreOrder, |_, entry| {
+                if found || entry.kind() != Some(ObjectType::Blob) {
+                    return TreeWalkResult::Ok;
+                }
+                found = *matched.entry(entry.id()).or_insert_with(|| {
+                    self.repo
+                        .find_blob(entry.id())
+                        .map(|blob| re.is_match(&String::from_utf8_lossy(blob.content())))
+                        .unwrap_or(false)
+                });
+                TreeWalkResult::Ok
+            })?;
+            if found {
+                revisions.push(Revision {
+                    id: self.short_id(commit.id())?,
+                    time: commit.time().seconds(),
+                    summary: commit.summary().unwrap_or("").to_string(),
+                });
+            }
+        }
+        Ok(revisions)
+    }
+
+    /// Unified diff of the working tree against `rev` (default HEAD),
+    /// limited to `file` or to cognitive files when no file is given
+    pub fn diff(&self, file: Option<&str>, rev: Option<&str>) -> Result<String> {
+        let tree = match rev {
+            Some(rev) => Some(self.resolve(rev)?.tree()?),
+            None => self.head_commit()?.map(|c| c.tree()).transpose()?,
+        };
+
+        let mut options = DiffOptions::new();
+        options.include_untracked(true).show_untracked_content(true);
+        match file {
+            Some(file) => {
+                options.pathspec(file);
+            }
+            None => {
+                for path in self.cognitive_pathspecs() {
+                    options.pathspec(path);
+                }
+            }
+        }
+
+        let diff = self
+            .repo
+            .diff_tree_to_workdir(tree.as_ref(), Some(&mut options))?;
+        let mut out = String::new();
+        diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                out.push(line.origin());
+            }
+            out.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+        Ok(out)
+    }
+
+    /// Content of `file` at `rev`
+    pub fn show(&self, file: &str, rev: &str) -> Result<Vec<u8>> {
+        let commit = self.resolve(rev)?;
+        let entry = commit
+            .tree()?
+            .get_path(Path::new(file))
+            .map_err(|_| anyhow!("{} does not exist at {}", file, rev))?;
+        let blob = entry
+            .to_object(&self.repo)?
+            .peel_to_blob()
+            .map_err(|_| anyhow!("{} is not a file at {}", file, rev))?;
+        Ok(blob.content().to_vec())
+    }
+
+    /// Overwrite `file` in the workspace with its content at `rev` and commit the
+    /// restore (cognitive files only). Returns the commit id if one was made.
+    pub fn restore(&self, file: &str, rev: &str) -> Result<Option<String>> {
+        let content = self.show(file, rev)?;
+        let path = self.workspace.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+
+        if is_cognitive_path(file) {
+            let short = self.short_id(self.resolve(rev)?.id())?;
+            self.commit_cognitive(&format!("Restore {} to {}", file, short))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn resolve(&self, rev: &str) -> Result<git2::Commit<'_>> {
+        self.repo
+            .revparse_single(rev)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| anyhow!("Unknown revision '{}': {}", rev, e.message()))
+    }
+
+    fn head_commit(&self) -> Result<Option<git2::Commit<'_>>> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?)),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn short_id(&self, oid: Oid) -> Result<String> {
+        let object = self.repo.find_object(oid, Some(ObjectType::Commit))?;
+        Ok(object
+            .short_id()?
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| oid.to_string()))
+    }
+
+    /// Pathspecs covering the cognitive files
+    fn cognitive_pathspecs(&self) -> Vec<&'static str> {
+        vec![
+            "MEMORY.md",
+            "SOUL.md",
+            "HEARTBEAT.md",
+            "IDENTITY.md",
+            "USER.md",
+            "AGENTS.md",
+            "TOOLS.md",
+            "memory/",
+        ]
+    }
+
+    /// Cognitive files that are new, modified or deleted relative to HEAD.
+    /// The on-disk index is never consulted, since commits bypass it.
+    fn changed_cognitive_files(&self) -> Result<Vec<String>> {
+        let tree = self.head_commit()?.map(|c| c.tree()).transpose()?;
+        let mut options = DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        for path in self.cognitive_pathspecs() {
+            options.pathspec(path);
+        }
+
+        let diff = self
+            .repo
+            .diff_tree_to_workdir(tree.as_ref(), Some(&mut options))?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())
+                    .and_then(Path::to_str)
+                    .map(str::to_string)
+            })
+            .filter(|path| is_cognitive_path(path))
+            .collect())
+    }
+}
+
+/// Index entry for a regular file whose blob is already in the object database
+fn index_entry(path: &str, id: Oid, size: u64) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: 0o100644,
+        uid: 0,
+        gid: 0,
+        file_size: size as u32,
+        id,
+        flags: path.len().min(0xfff) as u16,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    }
+}
+
+/// Blob id of `path` in a commit's tree, if present
+fn blob_id(commit: &git2::Commit<'_>, path: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()
+        .and_then(|tree| tree.get_path(path).ok())
+        .map(|entry| entry.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_commit_history_diff_restore() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let history = WorkspaceHistory::open_or_init(workspace)?;
+
+        // Nothing to commit in an empty workspace
+        assert!(history.commit_cognitive("empty")?.is_none());
+
+        std::fs::write(workspace.join("MEMORY.md"), "# Memory\n\n- Editor: vim\n")?;
+        std::fs::write(workspace.join("notes.txt"), "not cognitive\n")?;
+        let first = history.commit_cognitive("first")?.expect("commit");
+
+        std::fs::write(workspace.join("MEMORY.md"), "# Memory\n\n- Editor: helix\n")?;
+        std::fs::create_dir_all(workspace.join("memory"))?;
+        std::fs::write(workspace.join("memory/2026-01-01.md"), "log\n")?;
+        let diff = history.diff(None, None)?;
+        assert!(diff.contains("-- Editor: vim"));
+        assert!(diff.contains("+- Editor: helix"));
+        assert!(!diff.contains("not cognitive"));
+
+        history.commit_cognitive("second")?.expect("commit");
+        assert!(history.commit_cognitive("noop")?.is_none());
+
+        let revisions = history.history("MEMORY.md", 10)?;
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].summary, "second");
+        assert_eq!(revisions[1].id, first);
+        assert_eq!(history.history("memory/2026-01-01.md", 10)?.len(), 1);
+        let vim = history.commits_containing(&Regex::new("(?i)VIM")?)?;
+        assert_eq!(vim.len(), 1);
+        assert_eq!(vim[0].id, first);
+
+        // Untracked non-cognitive files are never committed
+        assert!(history.show("notes.txt", "HEAD").is_err());
+
+        history
+            .restore("MEMORY.md", &first)?
+            .expect("restore commit");
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("MEMORY.md"))?,
+            "# Memory\n\n- Editor: vim\n"
+        );
+        assert!(history.history("MEMORY.md", 10)?[0]
+            .summary
+            .starts_with("Restore MEMORY.md"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_leaves_staged_changes_alone() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let history = WorkspaceHistory::open_or_init(workspace)?;
+
+        // The user stages a file of their own
+        std::fs::write(workspace.join("draft.txt"), "work in progress\n")?;
+        let mut index = history.repo.index()?;
+        index.add_path(Path::new("draft.txt"))?;
+        index.write()?;
+
+        std::fs::write(workspace.join("MEMORY.md"), "# Memory\n")?;
+        history.commit_cognitive("memory")?.expect("commit");
+
+        assert!(history.show("MEMORY.md", "HEAD").is_ok());
+        assert!(history.show("draft.txt", "HEAD").is_err());
+
+        // Still staged, and MEMORY.md matches HEAD in the index and on disk
+        let statuses = history.repo.statuses(None)?;
+        let status = |path: &str| {
+            statuses
+                .iter()
+                .find(|s| s.path() == Some(path))
+                .map(|s| s.status())
+        };
+        assert_eq!(status("draft.txt"), Some(git2::Status::INDEX_NEW));
+        assert_eq!(status("MEMORY.md"), None);
+
+        // Later edits and deletions are committed and synced the same way
+        std::fs::write(workspace.join("MEMORY.md"), "# Memory\n\n- Editor: helix\n")?;
+        history.commit_cognitive("edit")?.expect("commit");
+        let index = history.repo.index()?;
+        let staged = index.get_path(Path::new("MEMORY.md"), 0).expect("staged");
+        assert_eq!(
+            Some(staged.id),
+            blob_id(&history.resolve("HEAD")?, Path::new("MEMORY.md"))
+        );
+
+        std::fs::remove_file(workspace.join("MEMORY.md"))?;
+        history.commit_cognitive("delete")?.expect("commit");
+        let statuses = history.repo.statuses(None)?;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses.get(0).unwrap().path(), Some("draft.txt"));
+        assert!(history
+            .repo
+            .index()?
+            .get_path(Path::new("draft.txt"), 0)
+            .is_some());
+
+        Ok(())
+    }
+}
@@ -3,27 +3,41 @@ use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{params, Connection};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::task;
-use tracing::{debug, warn};
+use tracing::debug;
 use uuid::Uuid;
 
-use super::embeddings::{cosine_similarity, deserialize_embedding, serialize_embedding};
-use super::search::MemoryChunk;
+use super::consolidate;
+use super::embeddings::{deserialize_embedding, serialize_embedding};
+use super::extract::{Extracted, Extractors, SOURCE_MEMORY};
+use super::graph;
+use super::search::{MemoryChunk, SearchFilter};
+use super::vectors;
 
 #[derive(Clone)]
 pub struct MemoryIndex {
-    pool: Pool<SqliteConnectionManager>,
-    workspace: PathBuf,
+    pub(super) pool: Pool<SqliteConnectionManager>,
+    pub(super) workspace: PathBuf,
     db_path: PathBuf,
     /// Whether sqlite-vec extension is loaded for fast vector search
-    has_vec_extension: bool,
+    pub(super) has_vec_extension: bool,
     /// Token count per chunk (default: 400)
     chunk_size: usize,
     /// Token overlap between chunks (default: 80)
     chunk_overlap: usize,
+    /// Content extractors keyed by file extension
+    extractors: Arc<Extractors>,
+    /// HNSW indexes used for vector search without sqlite-vec
+    pub(super) ann: Arc<vectors::AnnStore>,
+    /// Vector count from which the HNSW index replaces a full scan
+    pub(super) ann_min_vectors: usize,
+    /// Mirror the active model's vectors as JSON into `chunks.embedding`
+    pub(super) json_mirror: bool,
 }
 
 #[derive(Debug)]
@@ -36,11 +50,7 @@ pub struct ReindexStats {
 
 impl MemoryIndex {
     /// Create a new memory index with database at the specified path
-    pub fn new_with_db_path(
-        workspace: &Path,
-        db_path: &Path,
-        dimension: Option<usize>,
-    ) -> Result<Self> {
+    pub fn new_with_db_path(workspace: &Path, db_path: &Path) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             fs::create_dir_all(parent)?;
@@ -124,21 +134,20 @@ impl MemoryIndex {
         // Create FTS5 table (OpenClaw-compatible with UNINDEXED columns)
         Self::ensure_fts_table(&conn)?;
 
+        // Link graph tables (links, entities, relations)
+        graph::ensure_graph_tables(&conn)?;
+
+        // Consolidation run tracking
+        consolidate::ensure_consolidation_tables(&conn)?;
+
+        // Per-model vector storage (imports legacy chunk embeddings)
+        vectors::ensure_vector_tables(&conn)?;
+
         // Check if sqlite-vec is actually available
-        let mut has_vec_extension = Self::check_vec_extension(&conn);
+        let has_vec_extension = Self::check_vec_extension(&conn);
 
         if has_vec_extension {
             debug!("sqlite-vec extension loaded successfully");
-            match Self::ensure_vec_table(&conn, dimension) {
-                Ok(_) => {}
-                Err(e) => {
-                    warn!(
-                        "Vector table validation/creation failed: {}. Falling back to in-memory vector search.",
-                        e
-                    );
-                    has_vec_extension = false;
-                }
-            }
         } else {
             debug!("sqlite-vec extension not available, using in-memory vector search");
         }
@@ -150,6 +159,10 @@ impl MemoryIndex {
             has_vec_extension,
             chunk_size: 400,
             chunk_overlap: 80,
+            extractors: Arc::new(Extractors::default()),
+            ann: Arc::new(vectors::AnnStore::new(db_path)),
+            ann_min_vectors: vectors::ANN_MIN_VECTORS,
+            json_mirror: false,
         })
     }
 
@@ -160,6 +173,36 @@ impl MemoryIndex {
         self
     }
 
+    /// Set the vector count from which searches use the HNSW index (builder pattern)
+    pub fn with_ann_min_vectors(mut self, min_vectors: usize) -> Self {
+        self.ann_min_vectors = min_vectors;
+        self
+    }
+
+    /// Keep (or drop) the OpenClaw JSON copy of the active model's vectors in
+    /// `chunks.embedding` (builder pattern); the column is brought in line
+    /// right away
+    pub fn with_json_mirror(mut self, enabled: bool) -> Result<Self> {
+        self.json_mirror = enabled;
+        let conn = self
+            .pool
+            .get()
+            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+        vectors::sync_json_mirror(&conn, enabled)?;
+        Ok(self)
+    }
+
+    /// Replace the content extractors (builder pattern)
+    pub fn with_extractors(mut self, extractors: Extractors) -> Self {
+        self.extractors = Arc::new(extractors);
+        self
+    }
+
+    /// Whether the file type has a content extractor
+    pub fn supports(&self, path: &Path) -> bool {
+        self.extractors.supports(path)
+    }
+
     /// Try to load sqlite-vec extension
     #[allow(unsafe_code)]
     fn try_load_sqlite_vec(conn: &Connection) -> bool {
@@ -203,56 +246,55 @@ impl MemoryIndex {
             .is_ok()
     }
 
-    /// Create virtual table for vector search (requires sqlite-vec)
-    fn ensure_vec_table(conn: &Connection, dimension: Option<usize>) -> Result<()> {
-        let target_dim = dimension.unwrap_or(1536);
-
-        // Check if table exists
-        let sql: Option<String> = conn
-            .query_row(
-                "SELECT sql FROM sqlite_master WHERE name='chunks_vec'",
-                [],
-                |row| row.get(0),
-            )
-            .ok();
-
-        if let Some(sql) = sql {
-            // Table exists, check dimension
-            let pattern = format!("float[{}]", target_dim);
-            if !sql.contains(&pattern) {
-                if let Some(req_dim) = dimension {
-                    return Err(anyhow::anyhow!(
-                        "Existing vector table has different dimension than requested {}. SQL: {}",
-                        req_dim,
-                        sql
-                    ));
-                }
-            }
-        } else {
-            // Create chunks_vec virtual table if sqlite-vec is available
-            let sql = format!(
-                "CREATE VIRTUAL TABLE chunks_vec USING vec0(id TEXT PRIMARY KEY, embedding float[{}])",
-                target_dim
-            );
-            let result = conn.execute(&sql, []);
-            match result {
-                Ok(_) => debug!("chunks_vec table created with dimension {}", target_dim),
-                Err(e) => return Err(anyhow::anyhow!("Failed to create chunks_vec table: {}", e)),
-            }
-        }
-        Ok(())
-    }
-
     /// Create a new memory index with database in workspace (legacy path)
     pub fn new(workspace: &Path) -> Result<Self> {
         let db_path = workspace.join("memory.sqlite");
-        Self::new_with_db_path(workspace, &db_path, None)
+        Self::new_with_db_path(workspace, &db_path)
     }
 
-    /// Index a file, returning true if it was updated
+    /// Open another agent's existing index for searching only.
+    /// Connections are `query_only` and the schema is left untouched; the
+    /// ANN index is never used, so nothing is written next to the database.
+    pub fn open_read_only(workspace: &Path, db_path: &Path) -> Result<Self> {
+        if !db_path.exists() {
+            return Err(anyhow!("No memory index at {}", db_path.display()));
+        }
+
+        let manager = SqliteConnectionManager::file(db_path).with_init(|conn| {
+            conn.execute_batch("PRAGMA query_only = ON;")?;
+            let _ = Self::try_load_sqlite_vec(conn);
+            Ok(())
+        });
+        let pool =
+            Pool::new(manager).map_err(|e| anyhow!("Failed to create connection pool: {}", e))?;
+        let conn = pool
+            .get()
+            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+        let has_vec_extension = Self::check_vec_extension(&conn);
+
+        Ok(Self {
+            pool,
+            workspace: workspace.to_path_buf(),
+            db_path: db_path.to_path_buf(),
+            has_vec_extension,
+            chunk_size: 400,
+            chunk_overlap: 80,
+            extractors: Arc::new(Extractors::default()),
+            ann: Arc::new(vectors::AnnStore::new(db_path)),
+            ann_min_vectors: usize::MAX,
+            json_mirror: false,
+        })
+    }
+
+    /// Index a file, returning true if it was updated.
+    /// Files without a content extractor for their extension are skipped.
     pub async fn index_file(&self, path: &Path, force: bool) -> Result<bool> {
-        let content = fs::read_to_string(path)?;
-        let file_hash = hash_content(&content);
+        let Some(extractor) = self.extractors.for_path(path).cloned() else {
+            debug!("No extractor for {}, skipping", path.display());
+            return Ok(false);
+        };
+        let bytes = fs::read(path)?;
+        let file_hash = hash_content(&bytes);
         let metadata = fs::metadata(path)?;
         let mtime = metadata
             .modified()?
@@ -269,6 +311,7 @@ impl MemoryIndex {
         let pool = self.pool.clone();
         let chunk_size = self.chunk_size;
         let chunk_overlap = self.chunk_overlap;
+        let path = path.to_path_buf();
 
         task::spawn_blocking(move || {
             let mut conn = pool.get()
@@ -294,21 +337,24 @@ impl MemoryIndex {
 
             debug!("Indexing file: {}", relative_path);
 
+            let doc = extractor.extract(&path, &bytes)?;
+            let source = doc.source;
+
             let now = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs() as i64;
 
             // Update file record (OpenClaw-compatible columns)
             tx.execute(
-                "INSERT OR REPLACE INTO files (path, source, hash, mtime, size) VALUES (?1, 'memory', ?2, ?3, ?4)",
-                params![&relative_path, &file_hash, mtime, size],
+                "INSERT OR REPLACE INTO files (path, source, hash, mtime, size) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![&relative_path, source, &file_hash, mtime, size],
             )?;
 
             // Delete existing chunks and their FTS entries
             Self::delete_chunks_for_path(&tx, &relative_path)?;
 
             // Create new chunks (OpenClaw-compatible)
-            let chunks = chunk_text(&content, chunk_size, chunk_overlap);
+            let chunks = chunk_document(&doc, chunk_size, chunk_overlap);
 
             for chunk in chunks.iter() {
                 let chunk_id = Uuid::new_v4().to_string();
@@ -316,8 +362,8 @@ impl MemoryIndex {
 
                 tx.execute(
                     r#"INSERT INTO chunks (id, path, source, start_line, end_line, hash, model, text, embedding, updated_at)
-                   VALUES (?1, ?2, 'memory', ?3, ?4, ?5, '', ?6, '', ?7)"#,
-                    params![&chunk_id, &relative_path, chunk.line_start, chunk.line_end, &chunk_hash, &chunk.content, now],
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, '', ?7, '', ?8)"#,
+                    params![&chunk_id, &relative_path, source, chunk.line_start, chunk.line_end, &chunk_hash, &chunk.content, now],
                 )?;
 
                 // Insert into FTS
@@ -325,7 +371,7 @@ impl MemoryIndex {
                     &tx,
                     &chunk_id,
                     &relative_path,
-                    "memory",
+                    source,
                     "",
                     chunk.line_start,
                     chunk.line_end,
@@ -333,6 +379,12 @@ impl MemoryIndex {
                 )?;
             }
 
+            // Refresh wikilinks and inline relations for markdown files
+            if source == SOURCE_MEMORY {
+                let content = &doc.segments[0].text;
+                graph::index_links(&tx, &relative_path, content, now)?;
+            }
+
             tx.commit()?;
             Ok(true)
         }).await?
@@ -349,8 +401,7 @@ impl MemoryIndex {
 
         for chunk_id in chunk_ids {
             let _ = conn.execute("DELETE FROM chunks_fts WHERE id = ?1", params![&chunk_id]);
-            // Also try to delete from vector table (might fail if table doesn't exist, which is fine)
-            let _ = conn.execute("DELETE FROM chunks_vec WHERE id = ?1", params![&chunk_id]);
+            vectors::delete_vectors_for_chunk(conn, &chunk_id)?;
         }
 
         // Delete chunks
@@ -370,6 +421,8 @@ impl MemoryIndex {
 
             let tx = conn.transaction()?;
             Self::delete_chunks_for_path(&tx, &relative_path)?;
+            graph::delete_links_for_path(&tx, &relative_path)?;
+            graph::forget_file_entity(&tx, &relative_path)?;
             tx.execute("DELETE FROM files WHERE path = ?1", params![&relative_path])?;
             tx.commit()?;
 
@@ -421,6 +474,68 @@ impl MemoryIndex {
 
     /// Search using FTS5
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryChunk>> {
+        self.search_fts(query, limit, None).await
+    }
+
+    /// FTS search with a filter (min_score applies to the BM25 score)
+    pub async fn search_with_filter(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<MemoryChunk>> {
+        let paths = self.allowed_paths(filter).await?;
+        let mut results = self.search_fts(query, limit, paths).await?;
+        if let Some(min_score) = filter.min_score {
+            results.retain(|r| r.score >= min_score);
+        }
+        Ok(results)
+    }
+
+    /// Indexed paths passing the filter's file criteria, or None if it has none
+    pub(super) async fn allowed_paths(
+        &self,
+        filter: &SearchFilter,
+    ) -> Result<Option<Arc<HashSet<String>>>> {
+        if !filter.restricts_files() {
+            return Ok(None);
+        }
+        let pool = self.pool.clone();
+        let filter = filter.clone();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let mut stmt = conn.prepare("SELECT path, source, mtime FROM files")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?;
+
+            let mut paths = HashSet::new();
+            for row in rows {
+                let (path, source, mtime) = row?;
+                if filter.matches_file(&path, &source, mtime) {
+                    paths.insert(path);
+                }
+            }
+            Ok(Some(Arc::new(paths)))
+        })
+        .await?
+    }
+
+    /// FTS search, restricted to `paths` when given
+    async fn search_fts(
+        &self,
+        query: &str,
+        limit: usize,
+        paths: Option<Arc<HashSet<String>>>,
+    ) -> Result<Vec<MemoryChunk>> {
         let fts_query = match build_fts_query(query) {
             Some(q) => q,
             None => return Ok(Vec::new()),
@@ -428,6 +543,7 @@ impl MemoryIndex {
 
         let pool = self.pool.clone();
         let query_clone = fts_query.clone();
+        let paths_json = paths_param(paths.as_deref());
 
         task::spawn_blocking(move || {
             let conn = pool
@@ -437,21 +553,25 @@ impl MemoryIndex {
             // OpenClaw-compatible: use 'path', 'start_line', 'end_line', 'text' columns
             let mut stmt = conn.prepare(
                 r#"
-                SELECT fts.path, fts.start_line, fts.end_line, fts.text, bm25(chunks_fts) as score
+                SELECT fts.path, fts.start_line, fts.end_line, fts.text, bm25(chunks_fts) as score,
+                       fts.source
                 FROM chunks_fts fts
                 WHERE chunks_fts MATCH ?1
+                  AND (?3 IS NULL OR fts.path IN (SELECT value FROM json_each(?3)))
                 ORDER BY score
                 LIMIT ?2
                 "#,
             )?;
 
-            let rows = stmt.query_map(params![&query_clone, limit as i64], |row| {
+            let rows = stmt.query_map(params![&query_clone, limit as i64, &paths_json], |row| {
                 Ok(MemoryChunk {
                     file: row.get(0)?,
                     line_start: row.get(1)?,
                     line_end: row.get(2)?,
                     content: row.get(3)?,
                     score: row.get::<_, f64>(4)?.abs(), // BM25 returns negative scores
+                    source: row.get(5)?,
+                    origin: None,
                 })
             })?;
 
@@ -502,6 +622,44 @@ impl MemoryIndex {
         .await?
     }
 
+    /// Get the first chunks of an indexed file (ordered by line)
+    pub async fn chunks_for_path(
+        &self,
+        relative_path: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryChunk>> {
+        let pool = self.pool.clone();
+        let relative_path = relative_path.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let mut stmt = conn.prepare(
+                "SELECT path, start_line, end_line, text, source FROM chunks WHERE path = ?1 ORDER BY start_line LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![&relative_path, limit as i64], |row| {
+                Ok(MemoryChunk {
+                    file: row.get(0)?,
+                    line_start: row.get(1)?,
+                    line_end: row.get(2)?,
+                    content: row.get(3)?,
+                    score: 0.0,
+                    source: row.get(4)?,
+                    origin: None,
+                })
+            })?;
+
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+        .await?
+    }
+
     /// Get database size in bytes
     pub fn size_bytes(&self) -> Result<u64> {
         if self.db_path.exists() {
@@ -542,75 +700,6 @@ impl MemoryIndex {
         Ok(())
     }
 
-    /// Get chunks that need embeddings (OpenClaw-compatible: id is TEXT, text column)
-    pub async fn chunks_without_embeddings(&self, limit: usize) -> Result<Vec<(String, String)>> {
-        let pool = self.pool.clone();
-
-        task::spawn_blocking(move || {
-            let conn = pool
-                .get()
-                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-
-            let mut stmt = conn.prepare(
-                "SELECT id, text FROM chunks WHERE embedding = '' OR embedding IS NULL LIMIT ?1",
-            )?;
-
-            let rows = stmt.query_map(params![limit as i64], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-            })?;
-
-            let mut results = Vec::new();
-            for row in rows {
-                results.push(row?);
-            }
-
-            Ok(results)
-        })
-        .await?
-    }
-
-    /// Store embedding for a chunk (OpenClaw-compatible: id is TEXT, model column)
-    pub async fn store_embedding(
-        &self,
-        chunk_id: &str,
-        embedding: &[f32],
-        model: &str,
-    ) -> Result<()> {
-        let pool = self.pool.clone();
-        let chunk_id = chunk_id.to_string();
-        let embedding = embedding.to_vec();
-        let model = model.to_string();
-        let has_vec_extension = self.has_vec_extension;
-
-        task::spawn_blocking(move || {
-            let conn = pool
-                .get()
-                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-
-            let embedding_json = serialize_embedding(&embedding);
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs() as i64;
-
-            conn.execute(
-                "UPDATE chunks SET embedding = ?1, model = ?2, updated_at = ?3 WHERE id = ?4",
-                params![&embedding_json, &model, now, &chunk_id],
-            )?;
-
-            // Also store in vec table if sqlite-vec is available
-            if has_vec_extension {
-                let embedding_blob = embedding_to_blob(&embedding);
-                let _ = conn.execute(
-                    "INSERT OR REPLACE INTO chunks_vec (id, embedding) VALUES (?1, ?2)",
-                    params![chunk_id, &embedding_blob],
-                );
-            }
-
-            Ok(())
-        })
-        .await?
-    }
-
     // ========================================================================
     // Embedding Cache (OpenClaw-compatible)
     // ========================================================================
@@ -684,137 +773,9 @@ impl MemoryIndex {
         self.has_vec_extension
     }
 
-    /// Vector search using embeddings (OpenClaw-compatible columns)
-    /// Uses sqlite-vec if available for fast search, otherwise falls back to in-memory scan
-    pub async fn search_vector(
-        &self,
-        query_embedding: &[f32],
-        model: &str,
-        limit: usize,
-    ) -> Result<Vec<MemoryChunk>> {
-        let pool = self.pool.clone();
-        let query_embedding = query_embedding.to_vec();
-        let model = model.to_string();
-        let has_vec_extension = self.has_vec_extension;
-
-        task::spawn_blocking(move || {
-            let conn = pool
-                .get()
-                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-
-            // Try sqlite-vec fast path if available
-            if has_vec_extension {
-                if let Ok(results) =
-                    Self::search_vector_fast(&conn, &query_embedding, &model, limit)
-                {
-                    return Ok(results);
-                }
-                warn!("sqlite-vec search failed, falling back to in-memory scan");
-            }
-
-            // Fallback: in-memory scan (slower but always works)
-            Self::search_vector_scan(&conn, &query_embedding, &model, limit)
-        })
-        .await?
-    }
-
-    /// Fast vector search using sqlite-vec extension
-    fn search_vector_fast(
-        conn: &Connection,
-        query_embedding: &[f32],
-        model: &str,
-        limit: usize,
-    ) -> Result<Vec<MemoryChunk>> {
-        let query_blob = embedding_to_blob(query_embedding);
-
-        // sqlite-vec uses vec_distance_cosine for cosine distance (1 - similarity)
-        let mut stmt = conn.prepare(
-            r#"
-            SELECT c.path, c.start_line, c.end_line, c.text,
-                   1.0 - vec_distance_cosine(v.embedding, ?1) AS score
-            FROM chunks_vec v
-            JOIN chunks c ON c.id = v.id
-            WHERE c.model = ?2
-            ORDER BY score DESC
-            LIMIT ?3
-            "#,
-        )?;
-
-        let rows = stmt.query_map(params![&query_blob, model, limit as i64], |row| {
-            Ok(MemoryChunk {
-                file: row.get(0)?,
-                line_start: row.get(1)?,
-                line_end: row.get(2)?,
-                content: row.get(3)?,
-                score: row.get(4)?,
-            })
-        })?;
-
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row?);
-        }
-        Ok(results)
-    }
-
-    /// In-memory vector scan (fallback when sqlite-vec not available)
-    fn search_vector_scan(
-        conn: &Connection,
-        query_embedding: &[f32],
-        model: &str,
-        limit: usize,
-    ) -> Result<Vec<MemoryChunk>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, path, start_line, end_line, text, embedding
-             FROM chunks
-             WHERE embedding != '' AND embedding IS NOT NULL AND model = ?1",
-        )?;
-
-        let rows = stmt.query_map(params![model], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, i32>(2)?,
-                row.get::<_, i32>(3)?,
-                row.get::<_, String>(4)?,
-                row.get::<_, String>(5)?,
-            ))
-        })?;
-
-        // Compute similarities and sort
-        let mut scored: Vec<(f32, MemoryChunk)> = Vec::new();
-
-        for row in rows {
-            let (_, path, start_line, end_line, text, embedding_json) = row?;
-            let embedding = deserialize_embedding(&embedding_json);
-
-            if embedding.len() == query_embedding.len() {
-                let similarity = cosine_similarity(query_embedding, &embedding);
-                scored.push((
-                    similarity,
-                    MemoryChunk {
-                        file: path,
-                        line_start: start_line,
-                        line_end: end_line,
-                        content: text,
-                        score: similarity as f64,
-                    },
-                ));
-            }
-        }
-
-        // Sort by similarity (descending)
-        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Take top results
-        Ok(scored
-            .into_iter()
-            .take(limit)
-            .map(|(_, chunk)| chunk)
-            .collect())
-    }
-
-    /// Hybrid search: combine FTS and vector results
+    /// Hybrid search: combine FTS and vector results.
+    /// `filter` restricts candidate files; min_score applies to the combined score.
+    #[allow(clippy::too_many_arguments)]
     pub async fn search_hybrid(
         &self,
         query: &str,
@@ -823,13 +784,17 @@ impl MemoryIndex {
         limit: usize,
         text_weight: f32,
         vector_weight: f32,
+        filter: &SearchFilter,
     ) -> Result<Vec<MemoryChunk>> {
+        let paths = self.allowed_paths(filter).await?;
+
         // Get FTS results
-        let fts_results = self.search(query, limit * 2).await?;
+        let fts_results = self.search_fts(query, limit * 2, paths.clone()).await?;
 
         // Get vector results if embedding provided
         let vector_results = if let Some(embedding) = query_embedding {
-            self.search_vector(embedding, model, limit * 2).await?
+            self.search_vector_in(embedding, model, limit * 2, paths)
+                .await?
         } else {
             Vec::new()
         };
@@ -869,46 +834,24 @@ impl MemoryIndex {
 
         Ok(results
             .into_iter()
+            .filter(|(score, _)| filter.min_score.is_none_or(|min| *score as f64 >= min))
             .take(limit)
             .map(|(_, chunk)| chunk)
             .collect())
     }
+}
 
-    /// Count chunks with embeddings (OpenClaw-compatible: model column)
-    pub async fn embedded_chunk_count(&self, model: &str) -> Result<usize> {
-        let pool = self.pool.clone();
-        let model = model.to_string();
-
-        task::spawn_blocking(move || {
-            let conn = pool.get()
-                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-
-            let count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM chunks WHERE embedding != '' AND embedding IS NOT NULL AND model = ?1",
-                params![&model],
-                |row| row.get(0),
-            )?;
-
-            Ok(count as usize)
-        }).await?
-    }
+/// JSON array of paths for `json_each` restrictions (NULL when unrestricted)
+pub(super) fn paths_param(paths: Option<&HashSet<String>>) -> Option<String> {
+    paths.map(|p| serde_json::to_string(p).unwrap_or_else(|_| "[]".to_string()))
 }
 
-fn hash_content(content: &str) -> String {
+pub(super) fn hash_content(content: impl AsRef<[u8]>) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(content.as_ref());
     format!("{:x}", hasher.finalize())
 }
 
-/// Convert f32 embedding to binary blob for sqlite-vec
-fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
-    let mut blob = Vec::with_capacity(embedding.len() * 4);
-    for &val in embedding {
-        blob.extend_from_slice(&val.to_le_bytes());
-    }
-    blob
-}
-
 /// Build FTS5 query from raw input (OpenClaw-compatible)
 /// Tokenizes input and joins with AND so all terms must appear (in any order)
 fn build_fts_query(raw: &str) -> Option<String> {
@@ -937,6 +880,60 @@ struct ChunkInfo {
     content: String,
 }
 
+/// Chunk extracted text. Line-based segments (code symbols) are packed
+/// together while they fit and split when larger than a chunk; page-based
+/// segments are chunked per page and keep the page number as location.
+fn chunk_document(doc: &Extracted, target_tokens: usize, overlap_tokens: usize) -> Vec<ChunkInfo> {
+    let mut chunks = Vec::new();
+
+    if doc.paged {
+        for page in &doc.segments {
+            for chunk in chunk_text(&page.text, target_tokens, overlap_tokens) {
+                chunks.push(ChunkInfo {
+                    line_start: page.start,
+                    line_end: page.start,
+                    content: chunk.content,
+                });
+            }
+        }
+        return chunks;
+    }
+
+    let target_chars = target_tokens * 4;
+    let mut pending: Option<(i32, String)> = None;
+    let flush = |pending: &mut Option<(i32, String)>, chunks: &mut Vec<ChunkInfo>| {
+        if let Some((start, text)) = pending.take() {
+            for chunk in chunk_text(&text, target_tokens, overlap_tokens) {
+                chunks.push(ChunkInfo {
+                    line_start: chunk.line_start + start - 1,
+                    line_end: chunk.line_end + start - 1,
+                    content: chunk.content,
+                });
+            }
+        }
+    };
+
+    for segment in &doc.segments {
+        match pending.as_mut() {
+            // Only merge segments that are contiguous in the source
+            Some((start, text))
+                if text.len() + segment.text.len() < target_chars
+                    && *start + text.split('\n').count() as i32 == segment.start =>
+            {
+                text.push('\n');
+                text.push_str(&segment.text);
+            }
+            _ => {
+                flush(&mut pending, &mut chunks);
+                pending = Some((segment.start, segment.text.clone()));
+            }
+        }
+    }
+    flush(&mut pending, &mut chunks);
+
+    chunks
+}
+
 fn chunk_text(text: &str, target_tokens: usize, overlap_tokens: usize) -> Vec<ChunkInfo> {
     let lines: Vec<&str> = text.lines().collect();
     let mut chunks = Vec::new();
@@ -995,7 +992,9 @@ fn chunk_text(text: &str, target_tokens: usize, overlap_tokens: usize) -> Vec<Ch
 
 #[cfg(test)]
 mod tests {
+    use super::super::extract::Segment;
     use super::*;
+    use chrono::NaiveDate;
     use tempfile::TempDir;
 
     #[test]
@@ -1029,4 +1028,158 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_chunk_document_packs_symbols() {
+        let doc = Extracted {
+            source: "code",
+            paged: false,
+            segments: vec![
+                Segment {
+                    start: 1,
+                    text: "use a;\n".to_string(),
+                },
+                Segment {
+                    start: 3,
+                    text: "fn b() {}".to_string(),
+                },
+                Segment {
+                    start: 10,
+                    text: "fn c() {}".to_string(),
+                },
+            ],
+        };
+        let chunks = chunk_document(&doc, 400, 80);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].line_start, chunks[0].line_end), (1, 3));
+        assert_eq!((chunks[1].line_start, chunks[1].line_end), (10, 10));
+
+        let pdf = Extracted {
+            source: "pdf",
+            paged: true,
+            segments: vec![Segment {
+                start: 4,
+                text: "page four\nsecond line".to_string(),
+            }],
+        };
+        let chunks = chunk_document(&pdf, 400, 80);
+        assert_eq!((chunks[0].line_start, chunks[0].line_end), (4, 4));
+    }
+
+    #[tokio::test]
+    async fn test_index_non_markdown_sources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let workspace = temp_dir.path();
+
+        let code = workspace.join("lib.rs");
+        fs::write(
+            &code,
+            "use std::fs;\n\n/// Parses the widget\npub fn parse_widget() {}\n",
+        )?;
+        let html = workspace.join("saved.html");
+        fs::write(&html, "<p>Quarterly <b>zebra</b> report</p>")?;
+        let image = workspace.join("photo.png");
+        fs::write(&image, [0u8, 1, 2])?;
+
+        let index = MemoryIndex::new(workspace)?;
+        assert!(index.index_file(&code, false).await?);
+        assert!(index.index_file(&html, false).await?);
+        assert!(!index.index_file(&image, false).await?);
+
+        let results = index.search("parse_widget", 10).await?;
+        assert_eq!(results[0].source, "code");
+        assert_eq!(results[0].location(), "lib.rs:1-4");
+
+        let results = index.search("zebra", 10).await?;
+        assert_eq!(results[0].source, "html");
+        assert_eq!(results[0].file, "saved.html");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_filters() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let workspace = temp_dir.path();
+        fs::create_dir_all(workspace.join("memory"))?;
+        fs::create_dir_all(workspace.join("projects"))?;
+
+        let files = [
+            "memory/2026-01-02.md",
+            "memory/2026-01-10.md",
+            "projects/otters.md",
+        ];
+        let index = MemoryIndex::new(workspace)?;
+        index.register_embedding_model("test", "local").await?;
+        for file in files {
+            let path = workspace.join(file);
+            fs::write(&path, "# Notes\n\nThe otter colony grew again.")?;
+            index.index_file(&path, false).await?;
+        }
+        for (id, _) in index.chunks_without_embeddings("test", 10).await? {
+            index.store_embedding(&id, &[1.0, 0.0], "test").await?;
+        }
+
+        let search = |filter: SearchFilter| {
+            let index = &index;
+            async move {
+                let mut files: Vec<String> = index
+                    .search_hybrid("otter", Some(&[1.0, 0.0]), "test", 10, 0.3, 0.7, &filter)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|c| c.file)
+                    .collect();
+                files.sort();
+                files
+            }
+        };
+
+        assert_eq!(search(SearchFilter::default()).await.len(), 3);
+        assert_eq!(
+            search(SearchFilter {
+                paths: vec!["memory/*".to_string()],
+                since: Some(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()),
+                ..Default::default()
+            })
+            .await,
+            vec!["memory/2026-01-10.md"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                exclude: vec!["memory/*".to_string()],
+                ..Default::default()
+            })
+            .await,
+            vec!["projects/otters.md"]
+        );
+        assert!(search(SearchFilter {
+            sources: vec!["pdf".to_string()],
+            ..Default::default()
+        })
+        .await
+        .is_empty());
+        assert!(search(SearchFilter {
+            min_score: Some(2.0),
+            ..Default::default()
+        })
+        .await
+        .is_empty());
+
+        let fts = index
+            .search_with_filter(
+                "otter",
+                10,
+                &SearchFilter {
+                    until: Some(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()),
+                    paths: vec!["memory/*".to_string()],
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(fts.len(), 1);
+        assert_eq!(fts[0].file, "memory/2026-01-02.md");
+
+        Ok(())
+    }
 }
@@ -1,31 +1,83 @@
+mod ann;
 pub mod artifact;
+mod bundle;
+mod consolidate;
+mod doctor;
 mod embeddings;
+mod extract;
+mod forget;
+mod graph;
+mod history;
 mod index;
+mod openclaw;
+mod privacy;
 mod search;
+mod sections;
+mod vectors;
 mod watcher;
 mod workspace;
+mod write;
 
+pub use ann::HnswIndex;
 pub use artifact::ArtifactWriter;
+pub use bundle::{
+    ConflictPolicy, ExportStats, ImportOptions, ImportReport, BUNDLE_FORMAT, BUNDLE_VERSION,
+};
+pub use consolidate::{
+    parse_candidates, Candidate, CandidateAction, ConsolidationOptions, ConsolidationRun, RunStatus,
+};
+pub use doctor::{DoctorReport, DuplicateCluster, Issue, IssueKind, DUPLICATE_THRESHOLD};
 #[cfg(feature = "fastembed")]
 pub use embeddings::FastEmbedProvider;
 #[cfg(feature = "gguf")]
 pub use embeddings::LlamaCppProvider;
-pub use embeddings::{hash_text, EmbeddingProvider, OpenAIEmbeddingProvider};
+pub use embeddings::{
+    hash_text, EmbeddingProvider, OllamaEmbeddingProvider, OpenAIEmbeddingProvider,
+};
+pub use extract::{
+    is_paged, CodeExtractor, Extracted, Extractor, Extractors, HtmlExtractor, PdfExtractor,
+    PlainTextExtractor, Segment,
+};
+pub use forget::{
+    FileMatches, ForgetMode, ForgetOptions, ForgetReport, FORGOTTEN, MIN_FORGET_QUERY,
+};
+pub use graph::{
+    extract_wikilinks, normalize_node, GraphEdge, GraphNode, GraphPath, WikiLink, MAX_GRAPH_DEPTH,
+};
+pub use history::{Revision, WorkspaceHistory};
 pub use index::{MemoryIndex, ReindexStats};
-pub use search::MemoryChunk;
+pub use openclaw::OpenClawImport;
+pub use privacy::{
+    private_line_ranges, redact_lines, redact_private, PrivacyPolicy, PRIVATE_END, PRIVATE_START,
+    WITHHELD,
+};
+pub use search::{file_date, parse_date_bound, MemoryChunk, SearchFilter};
+pub use sections::{find_section, parse_headings, replace_section, split_anchor, Heading, Section};
+pub use vectors::{EmbeddingModel, ModelState};
 pub use watcher::MemoryWatcher;
 pub use workspace::{init_state_dir, init_workspace};
+pub use write::{ConflictMode, FactWrite, WriteOutcome};
 
 use anyhow::Result;
 use chrono::Local;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 use tokio::fs;
+use tokio::sync::OwnedMutexGuard;
 
 use tracing::{debug, info, warn};
 
+use crate::agent::{CheckpointMatches, CheckpointStore, LLMProvider};
 use crate::config::{Config, MemoryConfig};
+use crate::security::AuditLog;
+
+/// Chunks embedded per provider call during background re-embedding
+const EMBEDDING_BATCH_SIZE: usize = 50;
+
+/// Ollama model used when `memory.embedding_model` is left at its default
+const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
 
 #[derive(Clone)]
 pub struct MemoryManager {
@@ -35,10 +87,33 @@ pub struct MemoryManager {
     config: MemoryConfig,
     /// Optional embedding provider for semantic search
     embedding_provider: Option<Arc<dyn EmbeddingProvider>>,
+    /// App config, for recreating the provider of a previous embedding model
+    app_config: Option<Arc<Config>>,
+    /// Provider for the active model while the configured one is still being built
+    serving_provider: Arc<Mutex<Option<ServingProvider>>>,
+    /// Other workspaces' indexes, searched read-only
+    federation: Arc<Vec<FederatedIndex>>,
+    /// Local-only paths (`memory.private_paths`)
+    privacy: PrivacyPolicy,
+    /// Records withheld private content
+    audit: AuditLog,
     /// True if this was a brand new workspace (first run)
     is_brand_new: bool,
 }
 
+/// Another agent's or profile's index (`[[memory.federation]]`)
+struct FederatedIndex {
+    /// Origin label put on results
+    name: String,
+    workspace: PathBuf,
+    index: MemoryIndex,
+    /// Results may be sent to non-local LLM providers
+    allow_cloud: bool,
+}
+
+/// Provider recreated for the active embedding model during a migration
+type ServingProvider = (String, Option<Arc<dyn EmbeddingProvider>>);
+
 #[derive(Debug)]
 pub struct MemoryStats {
     pub workspace: String,
@@ -46,6 +121,22 @@ pub struct MemoryStats {
     pub total_chunks: usize,
     pub index_size_kb: u64,
     pub files: Vec<FileStats>,
+    pub embeddings: EmbeddingStatus,
+}
+
+/// Embedding models and migration progress
+#[derive(Debug, Default)]
+pub struct EmbeddingStatus {
+    /// Model serving vector queries
+    pub active: Option<String>,
+    /// Configured model
+    pub target: Option<String>,
+    /// Chunks embedded with the configured model
+    pub embedded: usize,
+    /// Chunks in the index
+    pub total: usize,
+    /// The configured model is being built while `active` serves queries
+    pub migrating: bool,
 }
 
 #[derive(Debug)]
@@ -85,121 +176,32 @@ impl MemoryManager {
         // Initialize workspace with templates if needed, returns true if brand new
         let is_brand_new = init_workspace(&workspace)?;
 
-        // Database goes in state_dir/memory/{agentId}.sqlite (OpenClaw-compatible)
-        let state_dir = workspace
-            .parent()
-            .ok_or_else(|| anyhow::anyhow!("Workspace has no parent directory"))?;
-        let memory_dir = state_dir.join("memory");
-        std::fs::create_dir_all(&memory_dir)?; // Sync creation is fine during init
-        let db_path = memory_dir.join(format!("{}.sqlite", agent_id));
-
-        // Create embedding provider based on config
-        let embedding_provider: Option<Arc<dyn EmbeddingProvider>> = match memory_config
-            .embedding_provider
-            .as_str()
-        {
-            #[cfg(feature = "fastembed")]
-            "local" => {
-                let model_name = if memory_config.embedding_model.is_empty()
-                    || memory_config.embedding_model == "text-embedding-3-small"
-                {
-                    None // Use default local model
-                } else {
-                    Some(memory_config.embedding_model.as_str())
-                };
-                let cache_dir = if memory_config.embedding_cache_dir.is_empty() {
-                    None
-                } else {
-                    Some(memory_config.embedding_cache_dir.as_str())
-                };
-                match FastEmbedProvider::new_with_cache_dir(model_name, cache_dir) {
-                    Ok(provider) => {
-                        info!("Using local embedding provider: {}", provider.model());
-                        Some(Arc::new(provider))
-                    }
-                    Err(e) => {
-                        warn!("Failed to initialize local embeddings: {}. Falling back to FTS-only search.", e);
-                        None
-                    }
-                }
-            }
-            #[cfg(not(feature = "fastembed"))]
-            "local" => {
-                warn!("Local embedding provider requested but 'fastembed' feature is not enabled. Build with --features fastembed to enable ONNX/CoreML local embeddings. Falling back to FTS-only search.");
-                None
-            }
-            "openai" => {
-                // Need OpenAI config for API key
-                if let Some(config) = app_config {
-                    if let Some(ref openai) = config.providers.openai {
-                        match OpenAIEmbeddingProvider::new(
-                            &openai.api_key,
-                            &openai.base_url,
-                            &memory_config.embedding_model,
-                        ) {
-                            Ok(provider) => {
-                                info!("Using OpenAI embedding provider: {}", provider.model());
-                                Some(Arc::new(provider))
-                            }
-                            Err(e) => {
-                                warn!("Failed to initialize OpenAI embeddings: {}. Falling back to FTS-only search.", e);
-                                None
-                            }
-                        }
-                    } else {
-                        warn!("OpenAI embedding provider requested but no OpenAI config found. Falling back to FTS-only search.");
-                        None
-                    }
-                } else {
-                    warn!("OpenAI embedding provider requested but no app config provided. Falling back to FTS-only search.");
-                    None
-                }
-            }
-            #[cfg(feature = "gguf")]
-            "gguf" => {
-                let cache_dir = if memory_config.embedding_cache_dir.is_empty() {
-                    None
-                } else {
-                    Some(memory_config.embedding_cache_dir.as_str())
-                };
-                match LlamaCppProvider::new(&memory_config.embedding_model, cache_dir) {
-                    Ok(provider) => {
-                        info!("Using GGUF embedding provider: {}", provider.model());
-                        Some(Arc::new(provider))
-                    }
-                    Err(e) => {
-                        warn!("Failed to initialize GGUF embeddings: {}. Falling back to FTS-only search.", e);
-                        None
-                    }
-                }
-            }
-            #[cfg(not(feature = "gguf"))]
-            "gguf" => {
-                warn!("GGUF embedding provider requested but 'gguf' feature is not enabled. Build with --features gguf. Falling back to FTS-only search.");
-                None
-            }
-            "none" => {
-                debug!("Embeddings disabled, using FTS-only search");
-                None
-            }
-            other => {
-                warn!(
-                    "Unknown embedding provider '{}'. Falling back to FTS-only search.",
-                    other
-                );
-                None
-            }
-        };
+        let db_path = index_db_path(&workspace, agent_id)?;
+        if let Some(memory_dir) = db_path.parent() {
+            std::fs::create_dir_all(memory_dir)?; // Sync creation is fine during init
+        }
 
-        let dimension = embedding_provider.as_ref().map(|p| p.dimensions());
-        let index = MemoryIndex::new_with_db_path(&workspace, &db_path, dimension)?;
+        let embedding_provider = create_embedding_provider(
+            &memory_config.embedding_provider,
+            &memory_config.embedding_model,
+            memory_config,
+            app_config,
+        );
+        let index = MemoryIndex::new_with_db_path(&workspace, &db_path)?
+            .with_json_mirror(memory_config.openclaw_embeddings)?;
+        let federation = open_federation(memory_config, &workspace, &db_path);
 
         Ok(Self {
+            privacy: PrivacyPolicy::new(&memory_config.private_paths),
+            audit: AuditLog::for_workspace(&workspace),
             workspace,
             db_path,
             index,
             config: memory_config.clone(),
             embedding_provider,
+            app_config: app_config.cloned().map(Arc::new),
+            serving_provider: Arc::new(Mutex::new(None)),
+            federation: Arc::new(federation),
             is_brand_new,
         })
     }
@@ -219,6 +221,87 @@ impl MemoryManager {
         &self.workspace
     }
 
+    /// Local-only paths of this workspace
+    pub fn privacy(&self) -> &PrivacyPolicy {
+        &self.privacy
+    }
+
+    /// Audit log for withheld private content
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Drop or redact private content from results bound for a non-local
+    /// provider. Every withheld chunk is audit-logged under `context`.
+    pub fn withhold_private(&self, chunks: Vec<MemoryChunk>, context: &str) -> Vec<MemoryChunk> {
+        chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                let workspace = match &chunk.origin {
+                    None => &self.workspace,
+                    Some(origin) => self
+                        .federation
+                        .iter()
+                        .find(|f| &f.name == origin)
+                        .map(|f| &f.workspace)?,
+                };
+                let file = chunk.file.clone();
+                let origin = chunk.origin.clone();
+                let lines = format!("{}-{}", chunk.line_start, chunk.line_end);
+                let (kept, withheld) = self.privacy.redact_chunk(workspace, chunk);
+                if withheld > 0 {
+                    self.audit.record(
+                        "private_withheld",
+                        serde_json::json!({
+                            "context": context,
+                            "file": file,
+                            "origin": origin,
+                            "lines": lines,
+                            "blocks": withheld,
+                            "dropped": kept.is_none(),
+                            "session": crate::agent::tool_executor::current_session_id(),
+                        }),
+                    );
+                }
+                kept
+            })
+            .collect()
+    }
+
+    /// Content of a workspace file for a prompt: unchanged for local
+    /// providers, otherwise without private blocks, or None if the whole
+    /// file is private. Withheld content is audit-logged under `context`.
+    pub fn shareable_content(
+        &self,
+        file: &str,
+        content: &str,
+        local_provider: bool,
+        context: &str,
+    ) -> Option<String> {
+        if local_provider {
+            return Some(content.to_string());
+        }
+        let (shared, withheld) = if self.privacy.is_private_path(file) {
+            (None, 1)
+        } else {
+            let (redacted, withheld) = redact_private(content);
+            (Some(redacted), withheld)
+        };
+        if withheld > 0 {
+            self.audit.record(
+                "private_withheld",
+                serde_json::json!({
+                    "context": context,
+                    "file": file,
+                    "blocks": withheld,
+                    "dropped": shared.is_none(),
+                    "session": crate::agent::tool_executor::current_session_id(),
+                }),
+            );
+        }
+        shared
+    }
+
     /// Read the main MEMORY.md file
     pub async fn read_memory_file(&self) -> Result<String> {
         let path = self.workspace.join("MEMORY.md");
@@ -325,32 +408,535 @@ impl MemoryManager {
 
     /// Search memory using hybrid search (FTS + semantic if available)
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryChunk>> {
+        self.search_filtered(query, limit, &SearchFilter::default())
+            .await
+    }
+
+    /// Hybrid search restricted by path, source, date and minimum score.
+    ///
+    /// Federated workspaces are searched too and their results labeled with
+    /// `origin`; with `filter.cloud_bound`, only those that allow cloud use.
+    pub async fn search_filtered(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<MemoryChunk>> {
         // If we have an embedding provider, try hybrid search
-        if let Some(ref provider) = self.embedding_provider {
+        let mut query_embedding = None;
+        if let Some(provider) = self.serving_provider().await {
             // Try to get query embedding (may fail if no API key, rate limited, etc.)
-            let provider = provider.clone();
-            let query_string = query.to_string();
-            let model = provider.model().to_string();
-
-            // Run embedding (embedding provider is likely async)
-            if let Ok(embedding) = provider.embed(&query_string).await {
+            if let Ok(embedding) = provider.embed(query).await {
                 debug!("Using hybrid search with {} dimensions", embedding.len());
-                return self
-                    .index
-                    .search_hybrid(
-                        query,
-                        Some(&embedding),
-                        &model,
-                        limit,
-                        0.3, // FTS weight
-                        0.7, // Vector weight
-                    )
-                    .await;
+                query_embedding = Some((embedding, provider.model().to_string()));
             }
         }
+        let embedding = query_embedding
+            .as_ref()
+            .map(|(embedding, model)| (embedding.as_slice(), model.as_str()));
 
-        // Fallback to FTS-only search
-        self.index.search(query, limit).await
+        let mut results = search_index(&self.index, query, embedding, limit, filter).await?;
+        if self.federation.is_empty() {
+            return Ok(self.cloud_safe(results, filter));
+        }
+
+        for federated in self.federation.iter() {
+            if filter.cloud_bound && !federated.allow_cloud {
+                debug!(
+                    "Skipping federated workspace '{}': not allowed for cloud providers",
+                    federated.name
+                );
+                continue;
+            }
+            match search_index(&federated.index, query, embedding, limit, filter).await {
+                Ok(chunks) => results.extend(
+                    chunks
+                        .into_iter()
+                        .map(|chunk| chunk.with_origin(&federated.name)),
+                ),
+                Err(e) => warn!(
+                    "Search in federated workspace '{}' failed: {}",
+                    federated.name, e
+                ),
+            }
+        }
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(self.cloud_safe(results, filter))
+    }
+
+    /// Withhold private content when results are bound for the cloud
+    fn cloud_safe(&self, results: Vec<MemoryChunk>, filter: &SearchFilter) -> Vec<MemoryChunk> {
+        if filter.cloud_bound {
+            self.withhold_private(results, "memory_search")
+        } else {
+            results
+        }
+    }
+
+    /// Provider for the model that serves vector queries: the configured one,
+    /// or the previous model's while the configured one is still being built.
+    /// None means FTS-only search.
+    async fn serving_provider(&self) -> Option<Arc<dyn EmbeddingProvider>> {
+        let target = self.embedding_provider.clone()?;
+        let active = match self.index.active_embedding_model().await {
+            Ok(Some(active)) if active.model != target.model() => active,
+            Ok(_) => return Some(target),
+            Err(e) => {
+                debug!("Failed to read active embedding model: {}", e);
+                return Some(target);
+            }
+        };
+
+        let mut cached = self.serving_provider.lock().unwrap();
+        if let Some((model, provider)) = cached.as_ref() {
+            if *model == active.model {
+                return provider.clone();
+            }
+        }
+
+        let provider = if active.provider.is_empty() {
+            None
+        } else {
+            create_embedding_provider(
+                &active.provider,
+                &active.model,
+                &self.config,
+                self.app_config.as_deref(),
+            )
+        };
+        if provider.is_none() {
+            warn!(
+                "Cannot serve queries with previous embedding model {} during migration; using FTS-only search",
+                active.model
+            );
+        }
+        *cached = Some((active.model, provider.clone()));
+        provider
+    }
+
+    /// Search memory, then add chunks from notes linked to the results.
+    ///
+    /// Graph neighbors (within `depth` hops of each result file) are appended
+    /// after the direct hits with a decayed score, up to `limit` extra chunks.
+    /// Neighbors are subject to the same `filter` as the direct hits.
+    pub async fn search_with_graph(
+        &self,
+        query: &str,
+        limit: usize,
+        depth: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<MemoryChunk>> {
+        let mut results = self.search_filtered(query, limit, filter).await?;
+        let allowed = self.index.allowed_paths(filter).await?;
+        let mut seen_files: std::collections::HashSet<String> =
+            results.iter().map(|r| r.file.clone()).collect();
+        let mut expanded = Vec::new();
+
+        // Federated results are not expanded: the graph is this workspace's
+        for result in results.iter().filter(|r| r.origin.is_none()) {
+            let node = normalize_node(&result.file);
+            for edge in self.index.graph_neighbors(&node, depth).await? {
+                for neighbor in [&edge.from, &edge.to] {
+                    if expanded.len() >= limit {
+                        break;
+                    }
+                    let Some(path) = self.index.graph_node(neighbor).await?.and_then(|n| n.path)
+                    else {
+                        continue;
+                    };
+                    if allowed.as_ref().is_some_and(|a| !a.contains(&path))
+                        || !seen_files.insert(path.clone())
+                    {
+                        continue;
+                    }
+                    let score = result.score * 0.5;
+                    if filter.min_score.is_some_and(|min| score < min) {
+                        continue;
+                    }
+                    for mut chunk in self.index.chunks_for_path(&path, 1).await? {
+                        chunk.score = score;
+                        expanded.push(chunk);
+                    }
+                }
+            }
+        }
+
+        if !expanded.is_empty() {
+            debug!("Graph expansion added {} chunks", expanded.len());
+        }
+        results.extend(expanded);
+        Ok(results)
+    }
+
+    /// Edges within `depth` hops of a note or entity
+    pub async fn graph_neighbors(&self, node: &str, depth: usize) -> Result<Vec<GraphEdge>> {
+        self.index.graph_neighbors(node, depth).await
+    }
+
+    /// Notes linking to a note or entity
+    pub async fn graph_backlinks(&self, node: &str) -> Result<Vec<GraphEdge>> {
+        self.index.graph_backlinks(node).await
+    }
+
+    /// Shortest link path between two notes or entities
+    pub async fn graph_path(&self, from: &str, to: &str) -> Result<Option<GraphPath>> {
+        self.index.graph_path(from, to).await
+    }
+
+    /// Look up a graph node
+    pub async fn graph_node(&self, node: &str) -> Result<Option<GraphNode>> {
+        self.index.graph_node(node).await
+    }
+
+    /// Record an entity relation (LLM-assisted extraction goes through here)
+    pub async fn graph_relate(
+        &self,
+        subject: &str,
+        predicate: &str,
+        object: &str,
+        subject_kind: Option<&str>,
+        object_kind: Option<&str>,
+    ) -> Result<()> {
+        self.index
+            .graph_relate(subject, predicate, object, subject_kind, object_kind)
+            .await
+    }
+
+    /// Append a fact under a MEMORY.md section, checking existing entries first.
+    ///
+    /// Near-identical facts are rejected, facts that extend an entry replace
+    /// it, and contradictions are reported unless `on_conflict` says otherwise.
+    pub async fn write_fact(&self, request: FactWrite) -> Result<WriteOutcome> {
+        let fact = request
+            .fact
+            .trim()
+            .trim_start_matches(['-', '*', '+'])
+            .trim();
+        if fact.is_empty() {
+            anyhow::bail!("Fact is empty");
+        }
+        let fact = fact.replace('\n', " ");
+        let section = request.section.trim().trim_start_matches('#').trim();
+        if section.is_empty() {
+            anyhow::bail!("Section is empty");
+        }
+
+        // Concurrent writers must not each start from the same old content
+        let _guard = self.lock_memory_md().await;
+        let content = self.read_memory_file().await?;
+        let entries = write::parse_entries(&content);
+        let similarities = self.entry_similarities(&fact, &entries).await;
+        let (updated, outcome) = write::plan_write(
+            &content,
+            section,
+            &fact,
+            request.on_conflict,
+            request.session_id.as_deref(),
+            Local::now(),
+            &|e| {
+                similarities
+                    .iter()
+                    .find(|(line, _)| *line == e.line)
+                    .map(|(_, s)| *s)
+            },
+        );
+        let Some(updated) = updated else {
+            return Ok(outcome);
+        };
+
+        self.write_memory_md(&updated).await?;
+        info!("memory_write: {:?}", outcome);
+
+        Ok(outcome)
+    }
+
+    /// Embedding similarity between a fact and the MEMORY.md entries the index
+    /// considers relevant. Empty without an embedding provider.
+    async fn entry_similarities(
+        &self,
+        fact: &str,
+        entries: &[write::MemoryEntry],
+    ) -> Vec<(usize, f32)> {
+        let Some(ref provider) = self.embedding_provider else {
+            return Vec::new();
+        };
+        let hits = match self.search(fact, 10).await {
+            Ok(hits) => hits,
+            Err(e) => {
+                debug!("memory_write similarity search failed: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let candidates: Vec<&write::MemoryEntry> = entries
+            .iter()
+            .filter(|e| {
+                let line = e.line as i32 + 1;
+                hits.iter()
+                    .any(|h| h.file == "MEMORY.md" && h.line_start <= line && line <= h.line_end)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        let mut texts = vec![fact.to_string()];
+        texts.extend(candidates.iter().map(|e| e.text.clone()));
+        match provider.embed_batch(&texts).await {
+            Ok(embeddings) if embeddings.len() == texts.len() => candidates
+                .iter()
+                .zip(&embeddings[1..])
+                .map(|(e, emb)| (e.line, embeddings::cosine_similarity(&embeddings[0], emb)))
+                .collect(),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                debug!("memory_write embedding failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Promote facts from unprocessed daily logs into MEMORY.md.
+    ///
+    /// Returns `None` when no daily log changed since the last run. Otherwise
+    /// the run is recorded (unless `dry_run`) and, when the diff is left for
+    /// review, a review artifact is written to `artifacts/`.
+    pub async fn consolidate(
+        &self,
+        provider: &dyn LLMProvider,
+        options: &ConsolidationOptions,
+    ) -> Result<Option<ConsolidationRun>> {
+        let logs: Vec<(String, String)> =
+            consolidate::recent_logs(&self.workspace, options.lookback_days)?
+                .into_iter()
+                .filter(|(path, _)| !self.privacy.is_private_path(path))
+                .collect();
+        let logs = self.index.unprocessed_logs(logs).await?;
+        if logs.is_empty() {
+            debug!("Consolidation: no new daily logs");
+            return Ok(None);
+        }
+
+        // The consolidation model may not run on this machine
+        let memory_md = self
+            .shareable_content(
+                "MEMORY.md",
+                &self.read_memory_file().await?,
+                false,
+                "consolidation",
+            )
+            .unwrap_or_default();
+        let shared: Vec<(String, String)> = logs
+            .iter()
+            .filter_map(|(path, content)| {
+                self.shareable_content(path, content, false, "consolidation")
+                    .map(|content| (path.clone(), content))
+            })
+            .collect();
+        let mut candidates = Vec::new();
+        for batch in consolidate::batches(&shared) {
+            candidates.extend(consolidate::extract_candidates(provider, &memory_md, &batch).await?);
+        }
+
+        // Plan against a fresh read in case MEMORY.md changed during extraction
+        let _guard = self.lock_memory_md().await;
+        let plan = consolidate::plan(&self.read_memory_file().await?, &candidates);
+        let status = if !plan.has_changes() {
+            RunStatus::Empty
+        } else if options.auto_apply {
+            RunStatus::Applied
+        } else {
+            RunStatus::Pending
+        };
+
+        let mut run = ConsolidationRun {
+            id: 0,
+            created_at: chrono::Utc::now().timestamp(),
+            status,
+            logs: logs.iter().map(|(path, _)| path.clone()).collect(),
+            diff: plan.diff(),
+            notes: plan.notes.clone(),
+        };
+        if options.dry_run {
+            return Ok(Some(run));
+        }
+
+        if status == RunStatus::Applied {
+            self.write_memory_md(&plan.proposed).await?;
+        }
+        run.id = self
+            .index
+            .record_consolidation_run(status, &logs, &plan)
+            .await?;
+        info!(
+            "Consolidation run {}: {} logs, {} ({} candidates)",
+            run.id,
+            run.logs.len(),
+            status.as_str(),
+            candidates.len()
+        );
+
+        if status == RunStatus::Pending {
+            let writer = ArtifactWriter::new(self.workspace.join("artifacts"));
+            let path = writer
+                .write(
+                    &consolidate::review_markdown(&run),
+                    "memory_consolidation",
+                    "TrustedEvent",
+                    &options.model,
+                )
+                .await?;
+            info!("Consolidation diff awaiting review: {}", path.display());
+        }
+
+        Ok(Some(run))
+    }
+
+    /// Apply a pending consolidation run to MEMORY.md
+    pub async fn apply_consolidation(&self, id: i64) -> Result<ConsolidationRun> {
+        let (mut run, proposal) = self
+            .index
+            .consolidation_run(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No consolidation run {}", id))?;
+        let proposal = match (run.status, proposal) {
+            (RunStatus::Pending, Some(proposal)) => proposal,
+            (status, _) => anyhow::bail!("Run {} is {}, not pending", id, status.as_str()),
+        };
+
+        let _guard = self.lock_memory_md().await;
+        if hash_text(&self.read_memory_file().await?) != proposal.base_hash {
+            anyhow::bail!(
+                "MEMORY.md changed since run {} was proposed; discard it and consolidate again",
+                id
+            );
+        }
+
+        self.write_memory_md(&proposal.proposed).await?;
+        self.index
+            .set_consolidation_status(id, RunStatus::Applied)
+            .await?;
+        run.status = RunStatus::Applied;
+        Ok(run)
+    }
+
+    /// Reject a pending consolidation run (its logs stay processed)
+    pub async fn discard_consolidation(&self, id: i64) -> Result<()> {
+        match self.index.consolidation_run(id).await? {
+            Some((run, _)) if run.status == RunStatus::Pending => {
+                self.index
+                    .set_consolidation_status(id, RunStatus::Discarded)
+                    .await
+            }
+            Some((run, _)) => anyhow::bail!("Run {} is {}, not pending", id, run.status.as_str()),
+            None => anyhow::bail!("No consolidation run {}", id),
+        }
+    }
+
+    /// Most recent consolidation runs, newest first
+    pub async fn consolidation_runs(&self, limit: usize) -> Result<Vec<ConsolidationRun>> {
+        self.index.consolidation_runs(limit).await
+    }
+
+    /// Whether at least `interval` has passed since the last consolidation run
+    pub async fn consolidation_due(&self, interval: Duration) -> Result<bool> {
+        Ok(match self.index.last_consolidation_at().await? {
+            Some(last) => chrono::Utc::now().timestamp() - last >= interval.as_secs() as i64,
+            None => true,
+        })
+    }
+
+    /// Hold MEMORY.md for a read-modify-write cycle. The lock is shared by
+    /// every manager of the workspace in this process.
+    pub async fn lock_memory_md(&self) -> OwnedMutexGuard<()> {
+        Self::lock_file(&self.workspace.join("MEMORY.md")).await
+    }
+
+    /// Hold `path` for a read-modify-write cycle, for writers without a
+    /// manager such as `edit_section`. Same lock as [`Self::lock_memory_md`]
+    /// for MEMORY.md.
+    pub async fn lock_file(path: &Path) -> OwnedMutexGuard<()> {
+        type FileLocks = Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>;
+        static LOCKS: OnceLock<FileLocks> = OnceLock::new();
+        // One key per file, however the path is spelled and whether or not
+        // the file exists yet
+        let path = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) => std::fs::canonicalize(dir)
+                .map(|dir| dir.join(name))
+                .unwrap_or_else(|_| path.to_path_buf()),
+            _ => path.to_path_buf(),
+        };
+        let lock = {
+            let mut locks = LOCKS
+                .get_or_init(Default::default)
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            Arc::clone(locks.entry(path).or_default())
+        };
+        lock.lock_owned().await
+    }
+
+    async fn write_memory_md(&self, content: &str) -> Result<()> {
+        let path = self.workspace.join("MEMORY.md");
+        fs::write(&path, content).await?;
+        if let Err(e) = self.index.index_file(&path, true).await {
+            warn!("Failed to reindex MEMORY.md after write: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Commit changed cognitive files to the workspace repository, creating it
+    /// if needed. Returns the commit id, or None when nothing changed or
+    /// `memory.auto_commit` is off.
+    pub async fn commit_workspace(&self, message: &str) -> Result<Option<String>> {
+        if !self.config.auto_commit {
+            return Ok(None);
+        }
+        let workspace = self.workspace.clone();
+        let message = message.to_string();
+        tokio::task::spawn_blocking(move || {
+            WorkspaceHistory::open_or_init(&workspace)?.commit_cognitive(&message)
+        })
+        .await?
+    }
+
+    /// Commits that changed a workspace file, newest first
+    pub async fn file_history(&self, file: &str, limit: usize) -> Result<Vec<Revision>> {
+        let workspace = self.workspace.clone();
+        let file = file.to_string();
+        tokio::task::spawn_blocking(move || {
+            WorkspaceHistory::open(&workspace)?.history(&file, limit)
+        })
+        .await?
+    }
+
+    /// Uncommitted changes (or changes since `rev`) to a file or to all cognitive files
+    pub async fn workspace_diff(&self, file: Option<&str>, rev: Option<&str>) -> Result<String> {
+        let workspace = self.workspace.clone();
+        let file = file.map(str::to_string);
+        let rev = rev.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            WorkspaceHistory::open(&workspace)?.diff(file.as_deref(), rev.as_deref())
+        })
+        .await?
+    }
+
+    /// Restore a workspace file to its content at `rev` and reindex it
+    pub async fn restore_file(&self, file: &str, rev: &str) -> Result<Option<String>> {
+        let workspace = self.workspace.clone();
+        let (file_owned, rev) = (file.to_string(), rev.to_string());
+        let commit = tokio::task::spawn_blocking(move || {
+            WorkspaceHistory::open(&workspace)?.restore(&file_owned, &rev)
+        })
+        .await??;
+
+        let path = self.workspace.join(file);
+        if let Err(e) = self.index.index_file(&path, true).await {
+            warn!("Failed to reindex {} after restore: {}", file, e);
+        }
+        Ok(commit)
     }
 
     /// Search memory using FTS only (faster, no API calls)
@@ -379,22 +965,32 @@ impl MemoryManager {
             info!("Removed {} deleted files from index", files_removed);
         }
 
-        // Index all .md files recursively under workspace
+        for entry in self.indexable_files() {
+            stats.files_processed += 1;
+            if self.index.index_file(&entry, force).await? {
+                stats.files_updated += 1;
+            }
+        }
+
+        stats.chunks_indexed = self.index.chunk_count().await?;
+        stats.duration = start.elapsed();
+
+        info!("Reindex complete: {:?}", stats);
+        Ok(stats)
+    }
+
+    /// Files the index should cover: workspace markdown plus configured paths
+    fn indexable_files(&self) -> Vec<PathBuf> {
+        // All .md files recursively under workspace
         let pattern = format!("{}/**/*.md", self.workspace.display());
-        for entry in glob::glob(&pattern)
+        let mut files: Vec<PathBuf> = glob::glob(&pattern)
             .into_iter()
             .flatten()
             .filter_map(|r| r.ok())
-        {
-            if entry.is_file() {
-                stats.files_processed += 1;
-                if self.index.index_file(&entry, force).await? {
-                    stats.files_updated += 1;
-                }
-            }
-        }
+            .filter(|entry| entry.is_file())
+            .collect();
 
-        // Index configured external paths (outside workspace)
+        // Configured paths (external, or non-markdown sources inside the workspace)
         for index_path in &self.config.paths {
             let base_path = if index_path.path.starts_with('~') || index_path.path.starts_with('/')
             {
@@ -403,38 +999,372 @@ impl MemoryManager {
                 self.workspace.join(&index_path.path)
             };
 
-            // Skip paths inside workspace (already covered by recursive glob above)
-            if base_path.starts_with(&self.workspace) {
-                continue;
-            }
-
             if !base_path.exists() {
                 debug!("Skipping non-existent index path: {}", base_path.display());
                 continue;
             }
 
             let pattern = format!("{}/{}", base_path.display(), index_path.pattern);
-            debug!("Indexing external path with pattern: {}", pattern);
+            debug!("Indexing path with pattern: {}", pattern);
+
+            files.extend(
+                glob::glob(&pattern)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|r| r.ok())
+                    .filter(|entry| entry.is_file() && self.is_extra_source(entry)),
+            );
+        }
 
-            for entry in glob::glob(&pattern)
+        files
+    }
+
+    /// Check the index against the workspace, repairing drift when `fix` is set
+    pub async fn doctor(&self, fix: bool) -> Result<DoctorReport> {
+        self.index.doctor(self.indexable_files(), fix).await
+    }
+
+    /// Clusters of near-identical memory chunks, compared with the active
+    /// embedding model's stored vectors (identical text only without one)
+    pub async fn duplicates(&self, threshold: f32, limit: usize) -> Result<Vec<DuplicateCluster>> {
+        let model = self.index.active_embedding_model().await?.map(|m| m.model);
+        self.index
+            .duplicates(model.as_deref(), threshold, limit)
+            .await
+    }
+
+    /// Write the workspace (markdown and skills) and index metadata to a
+    /// portable bundle, with cached embeddings of current chunks if asked
+    pub async fn export_bundle(&self, path: &Path, embeddings: bool) -> Result<ExportStats> {
+        let mut records = vec![bundle::Record::Manifest {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            agent: self.agent_id(),
+            models: self.index.model_names().await?,
+        }];
+
+        let workspace = self.workspace.clone();
+        let files =
+            tokio::task::spawn_blocking(move || bundle::collect_workspace_files(&workspace))
+                .await??;
+        let mut stats = ExportStats {
+            files: files.len(),
+            ..Default::default()
+        };
+        records.extend(
+            files
                 .into_iter()
-                .flatten()
-                .filter_map(|r| r.ok())
-            {
-                if entry.is_file() {
-                    stats.files_processed += 1;
-                    if self.index.index_file(&entry, force).await? {
-                        stats.files_updated += 1;
+                .map(|(path, content)| bundle::file_record(path, content)),
+        );
+
+        for record in self.index.export_metadata(embeddings).await? {
+            match record {
+                bundle::Record::Relation { .. } => stats.relations += 1,
+                bundle::Record::ConsolidatedLog { .. } => stats.consolidated_logs += 1,
+                bundle::Record::Embedding { .. } => stats.embeddings += 1,
+                _ => {}
+            }
+            records.push(record);
+        }
+
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || bundle::write_bundle(&path, &records)).await??;
+        Ok(stats)
+    }
+
+    /// Restore a bundle written by [`export_bundle`](Self::export_bundle) and
+    /// reindex. Nothing is deleted; conflicting files follow `options`.
+    pub async fn import_bundle(
+        &self,
+        path: &Path,
+        options: &ImportOptions,
+    ) -> Result<ImportReport> {
+        let path = path.to_path_buf();
+        let records = tokio::task::spawn_blocking(move || bundle::read_bundle(&path)).await??;
+
+        let workspace = self.workspace.clone();
+        let opts = options.clone();
+        let (mut report, metadata) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut report = ImportReport::default();
+            let mut metadata = Vec::new();
+            for record in records {
+                match record {
+                    bundle::Record::Manifest { models, .. } => report.models = models,
+                    bundle::Record::File { path, text, base64 } => {
+                        let content = bundle::file_content(&text, &base64)?;
+                        bundle::place_file(&workspace, &path, &content, &opts, &mut report)?;
                     }
+                    other => metadata.push(other),
+                }
+            }
+            Ok((report, metadata))
+        })
+        .await??;
+
+        if options.dry_run {
+            for record in &metadata {
+                match record {
+                    bundle::Record::Relation { .. } => report.relations += 1,
+                    bundle::Record::Embedding { .. } => report.embeddings += 1,
+                    _ => {}
                 }
             }
+            return Ok(report);
         }
 
-        stats.chunks_indexed = self.index.chunk_count().await?;
-        stats.duration = start.elapsed();
+        for record in &metadata {
+            if let bundle::Record::Relation {
+                subject,
+                predicate,
+                object,
+                subject_kind,
+                object_kind,
+            } = record
+            {
+                self.index
+                    .graph_relate(
+                        subject,
+                        predicate,
+                        object,
+                        subject_kind.as_deref(),
+                        object_kind.as_deref(),
+                    )
+                    .await?;
+                report.relations += 1;
+            }
+        }
+        report.embeddings = self.index.import_metadata(metadata).await?;
+        self.reindex(false).await?;
+        Ok(report)
+    }
 
-        info!("Reindex complete: {:?}", stats);
-        Ok(stats)
+    /// Import an OpenClaw state directory or workspace: files, sessions of
+    /// every agent and the cached embeddings of their indexes, then reindex
+    pub async fn import_openclaw(
+        &self,
+        source: &Path,
+        import: &OpenClawImport,
+    ) -> Result<ImportReport> {
+        let source = source.to_path_buf();
+        let workspace = self.workspace.clone();
+        let job = import.clone();
+        let (mut report, embeddings) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut report = ImportReport::default();
+            let embeddings = openclaw::import_files(&source, &workspace, &job, &mut report)?;
+            Ok((report, embeddings))
+        })
+        .await??;
+
+        for (agent, records) in embeddings {
+            if import.options.dry_run {
+                report.embeddings += records.len();
+                continue;
+            }
+            let db_path = self.db_path.with_file_name(format!("{}.sqlite", agent));
+            if db_path == self.db_path {
+                report.embeddings += self.index.import_metadata(records).await?;
+            } else {
+                let index = MemoryIndex::new_with_db_path(&self.workspace, &db_path)?;
+                report.embeddings += index.import_metadata(records).await?;
+            }
+        }
+
+        if !import.options.dry_run {
+            self.reindex(false).await?;
+        }
+        Ok(report)
+    }
+
+    /// Find a topic in memory files, artifacts, session transcripts and the
+    /// index. With `options.apply` it is redacted or deleted everywhere, the
+    /// affected files are reindexed and a tombstone (a hash of the query, not
+    /// the query) is written to the audit log. File checkpoints are redacted
+    /// too; copies left in workspace git history are reported, not removed.
+    pub async fn forget(&self, query: &str, options: &ForgetOptions) -> Result<ForgetReport> {
+        let re = forget::matcher(query)?;
+        let sessions_dir = match &options.sessions_dir {
+            Some(dir) => dir.clone(),
+            None => crate::agent::get_sessions_dir_for_agent(&self.agent_id())?,
+        };
+
+        // Memory writes must not race the rewrite of MEMORY.md
+        let _guard = if options.apply {
+            Some(self.lock_memory_md().await)
+        } else {
+            None
+        };
+        let workspace = self.workspace.clone();
+        let (mode, apply) = (options.mode, options.apply);
+        let matcher = re.clone();
+        let (mut report, rewritten) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut report = ForgetReport::default();
+            let mut rewritten = Vec::new();
+
+            let pattern = format!("{}/**/*.md", workspace.display());
+            for path in glob::glob(&pattern)?.filter_map(|r| r.ok()) {
+                let Ok(relative) = path.strip_prefix(&workspace) else {
+                    continue;
+                };
+                let hidden = relative
+                    .components()
+                    .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+                if hidden {
+                    continue;
+                }
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                let (new_content, lines) = forget::forget_in_markdown(&content, &matcher, mode);
+                if lines.is_empty() {
+                    continue;
+                }
+                if apply {
+                    std::fs::write(&path, new_content)?;
+                    rewritten.push(path.clone());
+                }
+                let matches = FileMatches {
+                    path: relative.to_string_lossy().to_string(),
+                    lines,
+                };
+                if relative.starts_with("artifacts") {
+                    report.artifacts.push(matches);
+                } else {
+                    report.memory.push(matches);
+                }
+            }
+
+            let pattern = format!("{}/*.jsonl", sessions_dir.display());
+            for path in glob::glob(&pattern)?.filter_map(|r| r.ok()) {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                let (new_content, lines) = forget::forget_in_jsonl(&content, &matcher);
+                if lines.is_empty() {
+                    continue;
+                }
+                if apply {
+                    std::fs::write(&path, new_content)?;
+                }
+                report.sessions.push(FileMatches {
+                    path: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    lines,
+                });
+            }
+
+            Ok((report, rewritten))
+        })
+        .await??;
+
+        // Before reindexing, while the matching chunks are still there
+        let matches = self.index.forget_rows(re.clone(), apply).await?;
+        let in_files: std::collections::HashSet<&str> = report
+            .memory
+            .iter()
+            .chain(&report.artifacts)
+            .map(|f| f.path.as_str())
+            .collect();
+        report.external = matches
+            .paths
+            .iter()
+            .filter(|p| !in_files.contains(p.as_str()))
+            .cloned()
+            .collect();
+        report.chunks = matches.chunks;
+        report.embeddings = matches.embeddings;
+        report.relations = matches.relations;
+        report.consolidation_runs = matches.consolidation_runs;
+
+        if !apply {
+            (report.history, report.checkpoints) = self.retained_copies(&re, None).await?;
+            return Ok(report);
+        }
+
+        for path in &rewritten {
+            self.index.index_file(path, true).await?;
+        }
+        self.index.compact().await?;
+        report.applied = true;
+
+        let files = report.memory.len() + report.artifacts.len();
+        if files > 0 {
+            if let Err(e) = self
+                .commit_workspace(&format!("Forget: rewrote {} file(s)", files))
+                .await
+            {
+                warn!("Failed to commit workspace after forget: {}", e);
+            }
+        }
+        // After the forget commit, which no longer contains it
+        (report.history, report.checkpoints) = self.retained_copies(&re, Some(mode)).await?;
+        self.audit.record(
+            "forget",
+            serde_json::json!({
+                "query_sha256": hash_text(&query.trim().to_lowercase()),
+                "mode": match mode {
+                    ForgetMode::Redact => "redact",
+                    ForgetMode::Delete => "delete",
+                },
+                "memory_files": report.memory.len(),
+                "artifacts": report.artifacts.len(),
+                "sessions": report.sessions.len(),
+                "chunks": report.chunks,
+                "embeddings": report.embeddings,
+                "relations": report.relations,
+                "consolidation_runs": report.consolidation_runs,
+                "external": report.external.len(),
+                "history_commits": report.history.len(),
+                "checkpoint_sessions": report.checkpoints.len(),
+            }),
+        );
+
+        Ok(report)
+    }
+
+    /// Workspace commits and file checkpoints holding text matching `re`.
+    /// With a `mode` the checkpoints are redacted the way markdown is.
+    async fn retained_copies(
+        &self,
+        re: &regex::Regex,
+        mode: Option<ForgetMode>,
+    ) -> Result<(Vec<Revision>, Vec<CheckpointMatches>)> {
+        let workspace = self.workspace.clone();
+        let re = re.clone();
+        tokio::task::spawn_blocking(move || -> Result<_> {
+            let history = match WorkspaceHistory::open(&workspace) {
+                Ok(history) => history.commits_containing(&re)?,
+                Err(_) => Vec::new(),
+            };
+            let store = CheckpointStore::for_workspace(&workspace);
+            let checkpoints = match mode {
+                Some(mode) => {
+                    store.redact(&re, |text| forget::forget_in_markdown(text, &re, mode).0)?
+                }
+                None => store.find(&re)?,
+            };
+            Ok((history, checkpoints))
+        })
+        .await?
+    }
+
+    /// Agent id this manager's index belongs to
+    fn agent_id(&self) -> String {
+        self.db_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Whether a file matched by a configured index path should be indexed from
+    /// there: it needs an extractor, and workspace markdown is already covered
+    /// by the workspace glob.
+    fn is_extra_source(&self, path: &std::path::Path) -> bool {
+        let workspace_markdown = path.starts_with(&self.workspace)
+            && path.extension().map(|e| e == "md").unwrap_or(false);
+        self.index.supports(path) && !workspace_markdown
     }
 
     /// Remove files from index that no longer exist on disk
@@ -485,7 +1415,7 @@ impl MemoryManager {
             }
         }
 
-        // Configured external paths (outside workspace)
+        // Configured paths (external, or non-markdown sources inside the workspace)
         for index_path in &self.config.paths {
             let base_path = if index_path.path.starts_with('~') || index_path.path.starts_with('/')
             {
@@ -494,11 +1424,6 @@ impl MemoryManager {
                 self.workspace.join(&index_path.path)
             };
 
-            // Skip paths inside workspace (already covered above)
-            if base_path.starts_with(&self.workspace) {
-                continue;
-            }
-
             if !base_path.exists() {
                 continue;
             }
@@ -510,9 +1435,12 @@ impl MemoryManager {
                 .flatten()
                 .filter_map(|r| r.ok())
             {
-                if entry.is_file() {
-                    let content = fs::read_to_string(&entry).await?;
-                    let lines = content.lines().count();
+                if entry.is_file() && self.is_extra_source(&entry) {
+                    // Lines of the raw file; binary sources (PDF) report 0
+                    let lines = match String::from_utf8(fs::read(&entry).await?) {
+                        Ok(content) => content.lines().count(),
+                        Err(_) => 0,
+                    };
                     let chunks = self.index.file_chunk_count(&entry).await?;
                     total_chunks += chunks;
 
@@ -539,6 +1467,7 @@ impl MemoryManager {
             total_chunks,
             index_size_kb: index_size,
             files,
+            embeddings: self.embedding_status().await?,
         })
     }
 
@@ -610,13 +1539,19 @@ impl MemoryManager {
 
         let provider_id = provider.id().to_string();
         let model = provider.model().to_string();
+        self.index
+            .register_embedding_model(&model, &provider_id)
+            .await?;
         let mut total_processed = 0;
         let mut total_embedded = 0;
         let mut cache_hits = 0;
 
         loop {
             // Get chunks without embeddings
-            let chunks = self.index.chunks_without_embeddings(batch_size).await?;
+            let chunks = self
+                .index
+                .chunks_without_embeddings(&model, batch_size)
+                .await?;
             if chunks.is_empty() {
                 break;
             }
@@ -718,6 +1653,11 @@ impl MemoryManager {
             total_processed, total_embedded, cache_hits
         );
 
+        self.index.flush_vector_index().await?;
+        if self.index.complete_migration(&model).await? {
+            *self.serving_provider.lock().unwrap() = None;
+        }
+
         Ok((total_processed, total_embedded))
     }
 
@@ -730,4 +1670,268 @@ impl MemoryManager {
             .unwrap_or_default();
         self.index.embedded_chunk_count(&model).await
     }
+
+    /// Active and configured embedding models and re-embedding progress
+    pub async fn embedding_status(&self) -> Result<EmbeddingStatus> {
+        let active = self.index.active_embedding_model().await?.map(|m| m.model);
+        let target = self
+            .embedding_provider
+            .as_ref()
+            .map(|p| p.model().to_string());
+        let embedded = match target {
+            Some(ref model) => self.index.embedded_chunk_count(model).await?,
+            None => 0,
+        };
+        let migrating = matches!((&active, &target), (Some(a), Some(t)) if a != t);
+
+        Ok(EmbeddingStatus {
+            active,
+            target,
+            embedded,
+            total: self.index.chunk_count().await?,
+            migrating,
+        })
+    }
+
+    /// Re-embed the index with the configured model in the background when it
+    /// differs from the model serving queries. Queries keep using the old
+    /// model until every chunk is embedded.
+    pub async fn spawn_embedding_migration(&self) -> Result<Option<tokio::task::JoinHandle<()>>> {
+        let status = self.embedding_status().await?;
+        if !status.migrating {
+            return Ok(None);
+        }
+
+        info!(
+            "Re-embedding memory with {} ({}/{} chunks done); serving {} meanwhile",
+            status.target.as_deref().unwrap_or_default(),
+            status.embedded,
+            status.total,
+            status.active.as_deref().unwrap_or_default()
+        );
+        let memory = self.clone();
+        Ok(Some(tokio::spawn(async move {
+            if let Err(e) = memory.generate_embeddings(EMBEDDING_BATCH_SIZE).await {
+                warn!("Embedding migration failed: {}", e);
+            }
+        })))
+    }
+}
+
+/// Index database of an agent: {state_dir}/memory/{agent_id}.sqlite (OpenClaw-compatible)
+fn index_db_path(workspace: &Path, agent_id: &str) -> Result<PathBuf> {
+    let state_dir = workspace
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Workspace has no parent directory"))?;
+    Ok(state_dir
+        .join("memory")
+        .join(format!("{}.sqlite", agent_id)))
+}
+
+/// Open the configured federated indexes. Missing or unreadable ones are
+/// skipped with a warning.
+fn open_federation(config: &MemoryConfig, workspace: &Path, own_db: &Path) -> Vec<FederatedIndex> {
+    let mut federation = Vec::new();
+    for entry in &config.federation {
+        let other_workspace = entry.workspace_path(workspace);
+        let db_path = match index_db_path(&other_workspace, &entry.agent) {
+            Ok(path) if path != own_db => path,
+            Ok(_) => {
+                warn!(
+                    "Federated workspace '{}' is this agent's own index, skipping",
+                    entry.name
+                );
+                continue;
+            }
+            Err(e) => {
+                warn!("Federated workspace '{}': {}", entry.name, e);
+                continue;
+            }
+        };
+        match MemoryIndex::open_read_only(&other_workspace, &db_path) {
+            Ok(index) => federation.push(FederatedIndex {
+                name: entry.name.clone(),
+                workspace: other_workspace,
+                index,
+                allow_cloud: entry.allow_cloud,
+            }),
+            Err(e) => warn!("Federated workspace '{}' unavailable: {}", entry.name, e),
+        }
+    }
+    federation
+}
+
+/// Search one index, hybrid when a query embedding is available
+async fn search_index(
+    index: &MemoryIndex,
+    query: &str,
+    embedding: Option<(&[f32], &str)>,
+    limit: usize,
+    filter: &SearchFilter,
+) -> Result<Vec<MemoryChunk>> {
+    match embedding {
+        Some((embedding, model)) => {
+            index
+                .search_hybrid(
+                    query,
+                    Some(embedding),
+                    model,
+                    limit,
+                    0.3, // FTS weight
+                    0.7, // Vector weight
+                    filter,
+                )
+                .await
+        }
+        None => index.search_with_filter(query, limit, filter).await,
+    }
+}
+
+/// Create an embedding provider by id ("openai", "ollama", "local", "gguf",
+/// "none", or the name of an OpenAI-compatible `[providers.<name>]` entry).
+/// Returns None (FTS-only search) when the provider is unavailable.
+#[cfg_attr(
+    not(any(feature = "fastembed", feature = "gguf")),
+    allow(unused_variables)
+)]
+fn create_embedding_provider(
+    provider: &str,
+    model: &str,
+    memory_config: &MemoryConfig,
+    app_config: Option<&Config>,
+) -> Option<Arc<dyn EmbeddingProvider>> {
+    match provider {
+        #[cfg(feature = "fastembed")]
+        "local" => {
+            let model_name = if model.is_empty() || model == "text-embedding-3-small" {
+                None // Use default local model
+            } else {
+                Some(model)
+            };
+            let cache_dir = if memory_config.embedding_cache_dir.is_empty() {
+                None
+            } else {
+                Some(memory_config.embedding_cache_dir.as_str())
+            };
+            match FastEmbedProvider::new_with_cache_dir(model_name, cache_dir) {
+                Ok(provider) => {
+                    info!("Using local embedding provider: {}", provider.model());
+                    Some(Arc::new(provider))
+                }
+                Err(e) => {
+                    warn!("Failed to initialize local embeddings: {}. Falling back to FTS-only search.", e);
+                    None
+                }
+            }
+        }
+        #[cfg(not(feature = "fastembed"))]
+        "local" => {
+            warn!("Local embedding provider requested but 'fastembed' feature is not enabled. Build with --features fastembed to enable ONNX/CoreML local embeddings. Falling back to FTS-only search.");
+            None
+        }
+        "openai" => {
+            // Need OpenAI config for API key
+            if let Some(config) = app_config {
+                if let Some(ref openai) = config.providers.openai {
+                    match OpenAIEmbeddingProvider::new(&openai.api_key, &openai.base_url, model) {
+                        Ok(provider) => {
+                            info!("Using OpenAI embedding provider: {}", provider.model());
+                            Some(Arc::new(provider))
+                        }
+                        Err(e) => {
+                            warn!("Failed to initialize OpenAI embeddings: {}. Falling back to FTS-only search.", e);
+                            None
+                        }
+                    }
+                } else {
+                    warn!("OpenAI embedding provider requested but no OpenAI config found. Falling back to FTS-only search.");
+                    None
+                }
+            } else {
+                warn!("OpenAI embedding provider requested but no app config provided. Falling back to FTS-only search.");
+                None
+            }
+        }
+        #[cfg(feature = "gguf")]
+        "gguf" => {
+            let cache_dir = if memory_config.embedding_cache_dir.is_empty() {
+                None
+            } else {
+                Some(memory_config.embedding_cache_dir.as_str())
+            };
+            match LlamaCppProvider::new(model, cache_dir) {
+                Ok(provider) => {
+                    info!("Using GGUF embedding provider: {}", provider.model());
+                    Some(Arc::new(provider))
+                }
+                Err(e) => {
+                    warn!("Failed to initialize GGUF embeddings: {}. Falling back to FTS-only search.", e);
+                    None
+                }
+            }
+        }
+        #[cfg(not(feature = "gguf"))]
+        "gguf" => {
+            warn!("GGUF embedding provider requested but 'gguf' feature is not enabled. Build with --features gguf. Falling back to FTS-only search.");
+            None
+        }
+        "ollama" => {
+            let endpoint = app_config
+                .and_then(|c| c.providers.ollama.as_ref())
+                .map(|o| o.endpoint.clone())
+                .unwrap_or_else(|| "http://localhost:11434".to_string());
+            // The fastembed default has no Ollama equivalent under that name
+            let model = if model.is_empty() || model == "all-MiniLM-L6-v2" {
+                DEFAULT_OLLAMA_EMBEDDING_MODEL
+            } else {
+                model
+            };
+            match OllamaEmbeddingProvider::new(&endpoint, model) {
+                Ok(provider) => {
+                    info!("Using Ollama embedding provider: {}", provider.model());
+                    Some(Arc::new(provider))
+                }
+                Err(e) => {
+                    warn!("Failed to initialize Ollama embeddings: {}. Falling back to FTS-only search.", e);
+                    None
+                }
+            }
+        }
+        "none" => {
+            debug!("Embeddings disabled, using FTS-only search");
+            None
+        }
+        other => {
+            // OpenAI-compatible endpoint from [providers.<name>]
+            let Some(extra) = app_config.and_then(|c| c.providers.extra.get(other)) else {
+                warn!(
+                    "Unknown embedding provider '{}'. Falling back to FTS-only search.",
+                    other
+                );
+                return None;
+            };
+            match OpenAIEmbeddingProvider::new_compatible(
+                other,
+                extra.api_key.as_deref().unwrap_or_default(),
+                &extra.base_url,
+                model,
+            ) {
+                Ok(provider) => {
+                    info!(
+                        "Using OpenAI-compatible embedding provider {}: {}",
+                        other,
+                        provider.model()
+                    );
+                    Some(Arc::new(provider))
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to initialize {} embeddings: {}. Falling back to FTS-only search.",
+                        other, e
+                    );
+                    None
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! Import from an existing OpenClaw installation
+//!
+//! OpenClaw uses the layout zier-alpha inherited: a workspace of markdown
+//! files, `agents/<id>/sessions/` with JSONL transcripts and a
+//! `sessions.json` store, and `memory/<id>.sqlite` indexes. Workspace files
+//! are copied like bundle files, transcripts and session entries are added
+//! per agent (optionally under another agent id), and cached embeddings are
+//! copied into the matching index so reindexing doesn't embed everything
+//! again. Chunks themselves are rebuilt by the reindex that follows.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use super::bundle::{
+    collect_workspace_files, place_file, read_embedding_cache, ConflictPolicy, ImportOptions,
+    ImportReport, Record,
+};
+
+/// Where an OpenClaw import goes
+#[derive(Debug, Clone)]
+pub struct OpenClawImport {
+    pub options: ImportOptions,
+    /// OpenClaw agent id -> zier-alpha agent id; unmapped agents keep their id
+    pub agents: HashMap<String, String>,
+    /// zier-alpha state directory that receives `agents/<id>/sessions`
+    pub state_dir: PathBuf,
+}
+
+impl OpenClawImport {
+    pub fn agent<'a>(&'a self, id: &'a str) -> &'a str {
+        self.agents.get(id).map(String::as_str).unwrap_or(id)
+    }
+}
+
+/// Resolve an OpenClaw path to (state dir, workspace). Accepts the state
+/// directory (`~/.openclaw`) or the workspace itself.
+pub(super) fn locate(source: &Path) -> Result<(PathBuf, PathBuf)> {
+    if !source.is_dir() {
+        bail!("{} is not a directory", source.display());
+    }
+    if source.join("workspace").is_dir() {
+        return Ok((source.to_path_buf(), source.join("workspace")));
+    }
+    if source.join("MEMORY.md").exists() || source.join("memory").is_dir() {
+        let state = source.parent().unwrap_or(source).to_path_buf();
+        return Ok((state, source.to_path_buf()));
+    }
+    bail!(
+        "{} doesn't look like an OpenClaw state directory or workspace",
+        source.display()
+    )
+}
+
+/// Copy workspace files and sessions. Returns cached embeddings per
+/// (mapped) agent for the caller to add to its indexes.
+pub(super) fn import_files(
+    source: &Path,
+    workspace: &Path,
+    import: &OpenClawImport,
+    report: &mut ImportReport,
+) -> Result<HashMap<String, Vec<Record>>> {
+    let (state, source_workspace) = locate(source)?;
+
+    for (path, content) in collect_workspace_files(&source_workspace)? {
+        place_file(workspace, &path, &content, &import.options, report)?;
+    }
+
+    let agents_dir = state.join("agents");
+    if agents_dir.is_dir() {
+        for entry in fs::read_dir(&agents_dir)?.filter_map(|e| e.ok()) {
+            let sessions = entry.path().join("sessions");
+            if !sessions.is_dir() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            let target = import
+                .state_dir
+                .join("agents")
+                .join(import.agent(&id))
+                .join("sessions");
+            import_sessions(&sessions, &target, &import.options, report)?;
+        }
+    }
+
+    let mut embeddings = HashMap::new();
+    let pattern = format!("{}/memory/*.sqlite", state.display());
+    for db in glob::glob(&pattern)?.filter_map(|r| r.ok()) {
+        let Some(id) = db.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        match read_embedding_cache(&db) {
+            Ok(records) if !records.is_empty() => {
+                embeddings
+                    .entry(import.agent(&id).to_string())
+                    .or_insert_with(Vec::new)
+                    .extend(records);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Skipping OpenClaw index {}: {}", db.display(), e),
+        }
+    }
+
+    Ok(embeddings)
+}
+
+/// Copy transcripts and merge `sessions.json`. A session id can't be renamed
+/// without breaking its store entry, so `rename` keeps existing ones like `skip`.
+fn import_sessions(
+    source: &Path,
+    target: &Path,
+    options: &ImportOptions,
+    report: &mut ImportReport,
+) -> Result<()> {
+    if !options.dry_run {
+        fs::create_dir_all(target)?;
+    }
+    let overwrite = options.on_conflict == ConflictPolicy::Overwrite;
+
+    for entry in fs::read_dir(source)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().map(|e| e != "jsonl").unwrap_or(true) {
+            continue;
+        }
+        let destination = target.join(entry.file_name());
+        let content = fs::read(&path)?;
+        match fs::read(&destination) {
+            Ok(existing) if existing == content => continue,
+            Ok(_) if !overwrite => {
+                report.conflicts.push(destination.display().to_string());
+                continue;
+            }
+            _ => {}
+        }
+        if !options.dry_run {
+            fs::write(&destination, content)?;
+        }
+        report.sessions += 1;
+    }
+
+    let store = source.join("sessions.json");
+    if store.exists() {
+        let theirs: HashMap<String, serde_json::Value> =
+            serde_json::from_str(&fs::read_to_string(&store)?)?;
+        let target_store = target.join("sessions.json");
+        let mut ours: HashMap<String, serde_json::Value> = match fs::read_to_string(&target_store) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(_) => HashMap::new(),
+        };
+        let before = ours.clone();
+        for (key, entry) in theirs {
+            if overwrite || !ours.contains_key(&key) {
+                ours.insert(key, entry);
+            }
+        }
+        if ours != before && !options.dry_run {
+            let tmp = target_store.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(&ours)?)?;
+            fs::rename(&tmp, &target_store)?;
+        }
+    }
+
+    Ok(())
+}
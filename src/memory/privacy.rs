@@ -0,0 +1,234 @@
+//! Local-only memory content
+//!
+//! Files and folders listed in `memory.private_paths`, and inline blocks
+//! between `<!-- private -->` and `<!-- /private -->`, must not reach an LLM
+//! provider that runs off this machine. Marking works on whole lines: a line
+//! holding the opening marker starts a block, the line holding the closing
+//! marker ends it, and an unclosed block runs to the end of the file (so a
+//! marker on the first line makes the whole file private).
+
+use std::path::Path;
+use tracing::warn;
+
+use super::extract::{SOURCE_MEMORY, SOURCE_TEXT};
+use super::search::MemoryChunk;
+
+pub const PRIVATE_START: &str = "<!-- private -->";
+pub const PRIVATE_END: &str = "<!-- /private -->";
+
+/// Placeholder left where private lines were removed
+pub const WITHHELD: &str = "[private content withheld]";
+
+/// Which workspace paths are local-only
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyPolicy {
+    patterns: Vec<glob::Pattern>,
+}
+
+impl PrivacyPolicy {
+    /// Build from globs relative to the workspace (`journal/**`, `health.md`).
+    /// A plain folder name covers everything below it.
+    pub fn new(private_paths: &[String]) -> Self {
+        let patterns = private_paths
+            .iter()
+            .filter_map(|p| match glob::Pattern::new(p.trim_end_matches('/')) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    warn!("Ignoring invalid private path pattern '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether a workspace-relative path is marked private as a whole
+    pub fn is_private_path(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        self.patterns.iter().any(|pattern| {
+            pattern.matches(path)
+                || Path::new(path)
+                    .ancestors()
+                    .skip(1)
+                    .any(|dir| pattern.matches(&dir.to_string_lossy()))
+        })
+    }
+
+    /// Redact a search result for a non-local provider, reading its file under
+    /// `workspace` to locate private blocks. Returns None when nothing of the
+    /// chunk may be shared, and the number of withheld blocks otherwise.
+    pub fn redact_chunk(
+        &self,
+        workspace: &Path,
+        mut chunk: MemoryChunk,
+    ) -> (Option<MemoryChunk>, usize) {
+        if self.is_private_path(&chunk.file) {
+            return (None, 1);
+        }
+        let Ok(content) = std::fs::read_to_string(workspace.join(&chunk.file)) else {
+            // Binary sources (PDF) carry no inline markers
+            return (Some(chunk), 0);
+        };
+        let ranges = private_line_ranges(&content);
+        if ranges.is_empty() {
+            return (Some(chunk), 0);
+        }
+        // Rendered sources (HTML) don't keep the file's line numbers
+        if chunk.source != SOURCE_MEMORY && chunk.source != SOURCE_TEXT {
+            return (None, 1);
+        }
+
+        let (redacted, withheld) =
+            redact_lines(&chunk.content, chunk.line_start.max(1) as usize, &ranges);
+        if withheld == 0 {
+            return (Some(chunk), 0);
+        }
+        if redacted
+            .lines()
+            .all(|l| l.trim().is_empty() || l == WITHHELD)
+        {
+            return (None, withheld);
+        }
+        chunk.content = redacted;
+        (Some(chunk), withheld)
+    }
+}
+
+/// Line ranges (1-indexed, inclusive) of private blocks, markers included
+pub fn private_line_ranges(content: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut last = 0;
+    for (i, line) in content.lines().enumerate() {
+        let n = i + 1;
+        last = n;
+        if start.is_none() && line.contains(PRIVATE_START) {
+            start = Some(n);
+        }
+        if let Some(s) = start {
+            // The closing marker must follow the opening one on a shared line
+            let after_open = if s == n {
+                line.find(PRIVATE_START)
+                    .map(|i| &line[i + PRIVATE_START.len()..])
+                    .unwrap_or(line)
+            } else {
+                line
+            };
+            if after_open.contains(PRIVATE_END) {
+                ranges.push((s, n));
+                start = None;
+            }
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, last));
+    }
+    ranges
+}
+
+/// Replace private lines of an excerpt starting at file line `first_line`.
+/// Each run of private lines becomes one [`WITHHELD`] line. Returns the
+/// redacted text and the number of runs withheld.
+pub fn redact_lines(
+    excerpt: &str,
+    first_line: usize,
+    ranges: &[(usize, usize)],
+) -> (String, usize) {
+    let mut out = Vec::new();
+    let mut withheld = 0;
+    let mut in_run = false;
+    for (i, line) in excerpt.lines().enumerate() {
+        let n = first_line + i;
+        if ranges.iter().any(|&(s, e)| n >= s && n <= e) {
+            if !in_run {
+                out.push(WITHHELD);
+                withheld += 1;
+                in_run = true;
+            }
+        } else {
+            out.push(line);
+            in_run = false;
+        }
+    }
+    if withheld == 0 {
+        return (excerpt.to_string(), 0);
+    }
+    (out.join("\n"), withheld)
+}
+
+/// Remove all private blocks from a whole file
+pub fn redact_private(content: &str) -> (String, usize) {
+    redact_lines(content, 1, &private_line_ranges(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_private_blocks() {
+        let content = "# Notes\nPublic fact\n<!-- private -->\nBank PIN 1234\n<!-- /private -->\nMore public\nInline <!-- private -->secret<!-- /private -->\nEnd";
+        assert_eq!(private_line_ranges(content), vec![(3, 5), (7, 7)]);
+
+        let (redacted, withheld) = redact_private(content);
+        assert_eq!(withheld, 2);
+        assert!(!redacted.contains("1234"));
+        assert!(!redacted.contains("secret"));
+        assert_eq!(
+            redacted,
+            format!(
+                "# Notes\nPublic fact\n{w}\nMore public\n{w}\nEnd",
+                w = WITHHELD
+            )
+        );
+
+        // An unclosed block runs to the end of the file
+        let (redacted, _) = redact_private("<!-- private -->\nall\nof it");
+        assert_eq!(redacted, WITHHELD);
+    }
+
+    #[test]
+    fn test_private_paths() {
+        let policy = PrivacyPolicy::new(&["journal".to_string(), "health/*.md".to_string()]);
+        assert!(policy.is_private_path("journal/2026-01-01.md"));
+        assert!(policy.is_private_path("journal"));
+        assert!(policy.is_private_path("health/labs.md"));
+        assert!(!policy.is_private_path("MEMORY.md"));
+        assert!(!policy.is_private_path("journals.md"));
+    }
+
+    #[test]
+    fn test_redact_chunk() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("MEMORY.md"),
+            "# Memory\nLikes tea\n<!-- private -->\nDiagnosis: X\nMedication: Y\n<!-- /private -->\n",
+        )
+        .unwrap();
+        let policy = PrivacyPolicy::default();
+
+        // A chunk straddling the block keeps its public lines
+        let chunk = MemoryChunk::new(
+            "MEMORY.md".to_string(),
+            2,
+            4,
+            "Likes tea\n<!-- private -->\nDiagnosis: X".to_string(),
+            1.0,
+        );
+        let (chunk, withheld) = policy.redact_chunk(temp.path(), chunk);
+        assert_eq!(withheld, 1);
+        assert_eq!(chunk.unwrap().content, format!("Likes tea\n{}", WITHHELD));
+
+        // A chunk inside the block, without markers, is withheld entirely
+        let chunk = MemoryChunk::new(
+            "MEMORY.md".to_string(),
+            5,
+            5,
+            "Medication: Y".to_string(),
+            1.0,
+        );
+        let (chunk, withheld) = policy.redact_chunk(temp.path(), chunk);
+        assert!(chunk.is_none());
+        assert_eq!(withheld, 1);
+    }
+}
@@ -1,7 +1,13 @@
 //! Memory search types and utilities
 
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use super::extract::{is_paged, SOURCE_MEMORY};
+
 /// A chunk of memory content returned from search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryChunk {
@@ -19,6 +25,18 @@ pub struct MemoryChunk {
 
     /// Relevance score (higher is better)
     pub score: f64,
+
+    /// Content type the chunk was extracted from (`memory`, `text`, `html`, `pdf`, `code`)
+    #[serde(default = "default_source")]
+    pub source: String,
+
+    /// Federated workspace the chunk came from (None for this workspace)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+fn default_source() -> String {
+    SOURCE_MEMORY.to_string()
 }
 
 impl MemoryChunk {
@@ -30,9 +48,23 @@ impl MemoryChunk {
             line_end,
             content,
             score,
+            source: default_source(),
+            origin: None,
         }
     }
 
+    /// Set the source type (builder pattern)
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Label the chunk with its federated origin (builder pattern)
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
     /// Get a preview of the content (first N characters)
     pub fn preview(&self, max_len: usize) -> String {
         if self.content.len() <= max_len {
@@ -42,8 +74,15 @@ impl MemoryChunk {
         }
     }
 
-    /// Get the location string (file:line)
+    /// Get the location string (file:line, or file#page=N for paged sources)
     pub fn location(&self) -> String {
+        if is_paged(&self.source) {
+            return if self.line_start == self.line_end {
+                format!("{}#page={}", self.file, self.line_start)
+            } else {
+                format!("{}#page={}-{}", self.file, self.line_start, self.line_end)
+            };
+        }
         if self.line_start == self.line_end {
             format!("{}:{}", self.file, self.line_start)
         } else {
@@ -52,6 +91,114 @@ impl MemoryChunk {
     }
 }
 
+/// Restricts which chunks a search may return
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    /// Glob patterns; a result's path must match at least one (`projects/*`)
+    pub paths: Vec<String>,
+    /// Glob patterns for paths to leave out (`HEARTBEAT.md`)
+    pub exclude: Vec<String>,
+    /// Allowed `chunks.source` values (`memory`, `pdf`, ...)
+    pub sources: Vec<String>,
+    /// Earliest file date (inclusive)
+    pub since: Option<NaiveDate>,
+    /// Latest file date (inclusive)
+    pub until: Option<NaiveDate>,
+    /// Minimum result score
+    pub min_score: Option<f64>,
+    /// Results will be sent to a non-local LLM provider: leave out federated
+    /// workspaces that don't allow it and withhold private content
+    pub cloud_bound: bool,
+}
+
+static FILE_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\d{4}-\d{2}-\d{2})").expect("valid date regex"));
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the filter restricts which files can match
+    pub fn restricts_files(&self) -> bool {
+        !self.paths.is_empty()
+            || !self.exclude.is_empty()
+            || !self.sources.is_empty()
+            || self.since.is_some()
+            || self.until.is_some()
+    }
+
+    /// Check an indexed file against the path, source and date criteria.
+    /// `mtime` (unix seconds) dates files without a date in their name.
+    pub fn matches_file(&self, path: &str, source: &str, mtime: i64) -> bool {
+        let glob_match = |pattern: &String| {
+            glob::Pattern::new(pattern)
+                .map(|p| p.matches(path))
+                .unwrap_or(false)
+        };
+        if !self.paths.is_empty() && !self.paths.iter().any(glob_match) {
+            return false;
+        }
+        if self.exclude.iter().any(glob_match) {
+            return false;
+        }
+        if !self.sources.is_empty() && !self.sources.iter().any(|s| s == source) {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(date) = file_date(path, mtime) else {
+                return false;
+            };
+            if self.since.is_some_and(|since| date < since)
+                || self.until.is_some_and(|until| date > until)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Date of a file: from a `YYYY-MM-DD` file name (daily logs), else its mtime
+pub fn file_date(path: &str, mtime: i64) -> Option<NaiveDate> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if let Some(m) = FILE_DATE.captures(name) {
+        if let Ok(date) = NaiveDate::parse_from_str(&m[1], "%Y-%m-%d") {
+            return Some(date);
+        }
+    }
+    Local
+        .timestamp_opt(mtime, 0)
+        .single()
+        .map(|t| t.date_naive())
+}
+
+/// Parse a date filter bound: `YYYY-MM-DD`, `today`, `yesterday`, or a
+/// relative `Nd` / `Nw` (days or weeks ago)
+pub fn parse_date_bound(value: &str) -> Result<NaiveDate> {
+    let value = value.trim();
+    let today = Local::now().date_naive();
+    match value {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    let days = match value.char_indices().last() {
+        Some((i, 'd')) => value[..i].parse::<i64>().ok(),
+        Some((i, 'w')) => value[..i].parse::<i64>().ok().map(|w| w * 7),
+        _ => None,
+    };
+    days.map(|d| today - Duration::days(d)).ok_or_else(|| {
+        anyhow!(
+            "Invalid date '{}': use YYYY-MM-DD, today, yesterday, Nd or Nw",
+            value
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,4 +229,68 @@ mod tests {
 
         assert_eq!(chunk.location(), "test.md:10");
     }
+
+    #[test]
+    fn test_memory_chunk_page_location() {
+        let chunk = MemoryChunk::new("paper.pdf".to_string(), 3, 3, "Abstract".to_string(), 0.5)
+            .with_source("pdf");
+        assert_eq!(chunk.location(), "paper.pdf#page=3");
+
+        let chunk = chunk.with_source("code");
+        assert_eq!(chunk.location(), "paper.pdf:3");
+    }
+
+    #[test]
+    fn test_search_filter_matches_file() {
+        let filter = SearchFilter {
+            paths: vec!["memory/*".to_string(), "projects/*".to_string()],
+            exclude: vec!["projects/archive/*".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches_file("memory/2026-01-05.md", "memory", 0));
+        assert!(filter.matches_file("projects/zier/notes.md", "memory", 0));
+        assert!(!filter.matches_file("projects/archive/old.md", "memory", 0));
+        assert!(!filter.matches_file("HEARTBEAT.md", "memory", 0));
+
+        let filter = SearchFilter {
+            sources: vec!["pdf".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches_file("papers/a.pdf", "pdf", 0));
+        assert!(!filter.matches_file("MEMORY.md", "memory", 0));
+    }
+
+    #[test]
+    fn test_search_filter_dates() {
+        let filter = SearchFilter {
+            since: Some(NaiveDate::from_ymd_opt(2026, 1, 3).unwrap()),
+            until: Some(NaiveDate::from_ymd_opt(2026, 1, 9).unwrap()),
+            ..Default::default()
+        };
+        assert!(!filter.matches_file("memory/2026-01-02.md", "memory", 0));
+        assert!(filter.matches_file("memory/2026-01-03.md", "memory", 0));
+        assert!(filter.matches_file("memory/2026-01-09-standup.md", "memory", 0));
+        assert!(!filter.matches_file("memory/2026-01-10.md", "memory", 0));
+
+        // Files without a dated name use their mtime
+        let mtime = Local
+            .with_ymd_and_hms(2026, 1, 5, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert!(filter.matches_file("MEMORY.md", "memory", mtime));
+        assert!(!filter.matches_file("MEMORY.md", "memory", 0));
+    }
+
+    #[test]
+    fn test_parse_date_bound() {
+        let today = Local::now().date_naive();
+        assert_eq!(
+            parse_date_bound("2026-02-01").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 2, 1).unwrap()
+        );
+        assert_eq!(parse_date_bound("today").unwrap(), today);
+        assert_eq!(parse_date_bound("7d").unwrap(), today - Duration::days(7));
+        assert_eq!(parse_date_bound("2w").unwrap(), today - Duration::days(14));
+        assert!(parse_date_bound("last tuesday").is_err());
+    }
 }
@@ -0,0 +1,256 @@
+//! Heading-addressed sections of markdown files
+//!
+//! A section is an ATX heading (`## Editor`) plus everything below it up to
+//! the next heading of the same or a higher level, so it includes its
+//! subsections. Sections are addressed by heading path, `Preferences/Editor`,
+//! usually appended to a file as an anchor: `MEMORY.md#Preferences/Editor`.
+//! The path only has to name the innermost headings: `Editor` matches
+//! `# Memory > ## Preferences > ### Editor` as long as it is unambiguous.
+
+use anyhow::{bail, Result};
+
+/// A markdown heading
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: usize,
+    pub title: String,
+    /// 1-indexed line of the heading
+    pub line: usize,
+}
+
+/// A section located in a file. Line numbers are 1-indexed and inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Titles from the outermost enclosing heading down to this one
+    pub path: Vec<String>,
+    pub level: usize,
+    pub heading_line: usize,
+    pub end_line: usize,
+}
+
+impl Section {
+    pub fn anchor(&self) -> String {
+        self.path.join("/")
+    }
+}
+
+/// Split `file#Heading/Sub` into the file and the heading path
+pub fn split_anchor(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('#') {
+        Some((file, anchor)) if !anchor.trim().is_empty() => (file, Some(anchor)),
+        Some((file, _)) => (file, None),
+        None => (path, None),
+    }
+}
+
+/// All ATX headings outside fenced code blocks
+pub fn parse_headings(content: &str) -> Vec<Heading> {
+    let mut in_code_block = false;
+    let mut headings = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.len() - trimmed.len() > 3 {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if level == 0 || level > 6 {
+            continue;
+        }
+        let rest = &trimmed[level..];
+        if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+            continue;
+        }
+        // Optional closing sequence: `## Title ##`
+        let title = rest.trim().trim_end_matches('#').trim_end();
+        headings.push(Heading {
+            level,
+            title: title.to_string(),
+            line: i + 1,
+        });
+    }
+
+    headings
+}
+
+/// Every section of a file, in document order
+pub fn sections(content: &str) -> Vec<Section> {
+    let headings = parse_headings(content);
+    let total_lines = content.lines().count();
+    let mut stack: Vec<&Heading> = Vec::new();
+
+    headings
+        .iter()
+        .enumerate()
+        .map(|(i, heading)| {
+            while stack.last().is_some_and(|h| h.level >= heading.level) {
+                stack.pop();
+            }
+            stack.push(heading);
+            let end_line = headings[i + 1..]
+                .iter()
+                .find(|h| h.level <= heading.level)
+                .map(|h| h.line - 1)
+                .unwrap_or(total_lines);
+            Section {
+                path: stack.iter().map(|h| h.title.clone()).collect(),
+                level: heading.level,
+                heading_line: heading.line,
+                end_line,
+            }
+        })
+        .collect()
+}
+
+fn normalize(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Locate the section a heading path points at. Titles are compared
+/// case-insensitively and the path may omit outer headings.
+pub fn find_section(content: &str, anchor: &str) -> Result<Section> {
+    let wanted: Vec<String> = anchor.trim_matches('/').split('/').map(normalize).collect();
+    let all = sections(content);
+    let matches: Vec<&Section> = all
+        .iter()
+        .filter(|s| {
+            s.path.len() >= wanted.len()
+                && s.path[s.path.len() - wanted.len()..]
+                    .iter()
+                    .map(|t| normalize(t))
+                    .eq(wanted.iter().cloned())
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [section] => Ok((*section).clone()),
+        [] => {
+            let available: Vec<String> = all.iter().take(20).map(|s| s.anchor()).collect();
+            if available.is_empty() {
+                bail!("No section '{}': the file has no headings", anchor)
+            }
+            bail!(
+                "No section '{}'. Available: {}",
+                anchor,
+                available.join(", ")
+            )
+        }
+        several => {
+            let candidates: Vec<String> = several.iter().map(|s| s.anchor()).collect();
+            bail!(
+                "Section '{}' is ambiguous, use a longer path: {}",
+                anchor,
+                candidates.join(", ")
+            )
+        }
+    }
+}
+
+/// Replace the body of a section (everything below its heading, subsections
+/// included) and leave the rest of the file untouched. Returns the new
+/// content and the section as it is after the edit.
+pub fn replace_section(content: &str, anchor: &str, body: &str) -> Result<(String, Section)> {
+    let section = find_section(content, anchor)?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let before = &lines[..section.heading_line];
+    let after = &lines[section.end_line..];
+    let body = body.trim_matches('\n');
+    // The heading stays; a body that repeats it doesn't duplicate it
+    let body = body
+        .lines()
+        .next()
+        .filter(|first| {
+            parse_headings(first)
+                .first()
+                .is_some_and(|h| h.level == section.level)
+        })
+        .map(|_| {
+            body.split_once('\n')
+                .map(|(_, rest)| rest.trim_start_matches('\n'))
+                .unwrap_or("")
+        })
+        .unwrap_or(body);
+
+    let mut out: Vec<&str> = before.to_vec();
+    if body.lines().next().is_some() {
+        // Keep the file's spacing below the heading
+        if section.end_line > section.heading_line && lines[section.heading_line].trim().is_empty()
+        {
+            out.push("");
+        }
+        out.extend(body.lines());
+    }
+    if !after.is_empty() {
+        out.push("");
+    }
+    let updated = Section {
+        end_line: out.len(),
+        ..section
+    };
+    out.extend(after);
+
+    let mut new_content = out.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        new_content.push('\n');
+    }
+    Ok((new_content, updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Memory\n\nIntro\n\n## Preferences\n\n### Editor\n\n- helix\n\n### Shell\n\n- fish\n\n## Projects\n\n```md\n## Not a heading\n```\n\n### Editor\n\nzed plugin\n";
+
+    #[test]
+    fn test_find_section() {
+        let section = find_section(DOC, "Preferences/Editor").unwrap();
+        assert_eq!(section.path, vec!["Memory", "Preferences", "Editor"]);
+        assert_eq!((section.heading_line, section.end_line), (7, 10));
+
+        let section = find_section(DOC, "preferences").unwrap();
+        assert_eq!((section.heading_line, section.end_line), (5, 14));
+
+        let section = find_section(DOC, "Projects").unwrap();
+        assert_eq!(section.end_line, DOC.lines().count());
+
+        assert!(find_section(DOC, "Editor")
+            .unwrap_err()
+            .to_string()
+            .contains("ambiguous"));
+        assert!(find_section(DOC, "Not a heading").is_err());
+        assert_eq!(split_anchor("MEMORY.md#A/B"), ("MEMORY.md", Some("A/B")));
+        assert_eq!(split_anchor("MEMORY.md"), ("MEMORY.md", None));
+    }
+
+    #[test]
+    fn test_replace_section() {
+        let (updated, section) = replace_section(
+            DOC,
+            "Preferences/Editor",
+            "- neovim\n- helix for quick edits\n",
+        )
+        .unwrap();
+        assert!(updated.contains("### Editor\n\n- neovim\n- helix for quick edits\n\n### Shell"));
+        assert!(!updated.contains("- helix\n"));
+        assert_eq!((section.heading_line, section.end_line), (7, 11));
+        // Everything outside the section is unchanged
+        assert_eq!(
+            updated.replace("- neovim\n- helix for quick edits\n", "- helix\n"),
+            DOC
+        );
+
+        // Repeating the heading is accepted, the last section keeps the final newline
+        let (updated, _) = replace_section(DOC, "Projects/Editor", "### Editor\nzed").unwrap();
+        assert!(updated.ends_with("### Editor\n\nzed\n"));
+    }
+}
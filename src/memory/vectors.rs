@@ -0,0 +1,1192 @@
+//! Per-model vector storage and embedding model migration
+//!
+//! Vectors live in `chunk_embeddings`, keyed by chunk and model, so several
+//! embedding models can coexist in one index. With sqlite-vec loaded, each
+//! model also gets its own `vec0` table sized to its dimension.
+//!
+//! One model is `active` and serves vector queries. When the configured model
+//! changes, the new one is registered as `building`. Chunks are re-embedded in
+//! the background while queries keep using the active model. Once every chunk
+//! has a vector for the new model it becomes active and the old model's
+//! vectors are dropped. `chunk_embeddings` stores packed little-endian f32
+//! blobs, the only copy of each vector by default. With
+//! `memory.openclaw_embeddings`, `chunks.embedding`/`chunks.model` also mirror
+//! the active model as JSON for OpenClaw.
+//!
+//! Without sqlite-vec, models with many vectors are searched through an HNSW
+//! index ([`super::ann`]) kept in memory, updated as embeddings are stored and
+//! persisted beside the database.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::task;
+use tracing::{debug, info, warn};
+
+use super::ann::{HnswIndex, EF_SEARCH};
+use super::embeddings::{
+    cosine_similarity, deserialize_embedding, pack_embedding, serialize_embedding, unpack_embedding,
+};
+use super::index::{paths_param, MemoryIndex};
+use super::search::MemoryChunk;
+
+/// Vector count from which a model is searched through the ANN index
+/// instead of a full scan (when sqlite-vec is unavailable)
+pub const ANN_MIN_VECTORS: usize = 2_000;
+
+/// Persist an ANN index after this many incremental changes
+const ANN_SAVE_EVERY: usize = 256;
+
+/// Lifecycle of an embedding model in the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelState {
+    /// Serves vector queries
+    Active,
+    /// Being backfilled; takes over once every chunk is embedded
+    Building,
+}
+
+impl ModelState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelState::Active => "active",
+            ModelState::Building => "building",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "active" => ModelState::Active,
+            _ => ModelState::Building,
+        }
+    }
+}
+
+/// An embedding model registered in the index
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingModel {
+    pub model: String,
+    /// Embedding provider id ("openai", "local", "gguf"); empty for legacy rows
+    pub provider: String,
+    pub dims: Option<usize>,
+    pub state: ModelState,
+    /// Chunks with a vector for this model
+    pub embedded: usize,
+}
+
+pub(super) fn ensure_vector_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Embedding models known to the index
+        CREATE TABLE IF NOT EXISTS embedding_models (
+            model TEXT PRIMARY KEY,
+            provider TEXT NOT NULL DEFAULT '',
+            dims INTEGER,
+            state TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            activated_at INTEGER
+        );
+
+        -- Chunk vectors per model (little-endian f32)
+        CREATE TABLE IF NOT EXISTS chunk_embeddings (
+            chunk_id TEXT NOT NULL,
+            model TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (chunk_id, model)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_chunk_embeddings_model ON chunk_embeddings(model);
+        "#,
+    )?;
+
+    import_legacy_embeddings(conn)?;
+    pack_json_embeddings(conn)
+}
+
+/// Convert JSON vectors (legacy import, older indexes) to packed blobs
+fn pack_json_embeddings(conn: &Connection) -> Result<()> {
+    let rows: Vec<(String, String, String)> = {
+        let mut stmt = conn.prepare(
+            r#"SELECT chunk_id, model, embedding FROM chunk_embeddings
+               WHERE typeof(embedding) = 'text'"#,
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut stmt = conn
+        .prepare("UPDATE chunk_embeddings SET embedding = ?3 WHERE chunk_id = ?1 AND model = ?2")?;
+    for (chunk_id, model, json) in &rows {
+        stmt.execute(params![
+            chunk_id,
+            model,
+            pack_embedding(&deserialize_embedding(json))
+        ])?;
+    }
+    debug!("Packed {} JSON embeddings", rows.len());
+    Ok(())
+}
+
+/// Adopt vectors written before per-model storage existed: the model with the
+/// most embedded chunks becomes active, other models' vectors are discarded.
+fn import_legacy_embeddings(conn: &Connection) -> Result<()> {
+    let registered: i64 = conn.query_row("SELECT COUNT(*) FROM embedding_models", [], |row| {
+        row.get(0)
+    })?;
+    if registered > 0 {
+        return Ok(());
+    }
+
+    let legacy: Option<(String, String)> = conn
+        .query_row(
+            r#"SELECT model, MIN(embedding) FROM chunks
+               WHERE embedding != '' AND model != ''
+               GROUP BY model ORDER BY COUNT(*) DESC LIMIT 1"#,
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((model, sample)) = legacy else {
+        return Ok(());
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let dims = deserialize_embedding(&sample).len() as i64;
+    conn.execute(
+        r#"INSERT INTO embedding_models (model, provider, dims, state, created_at, activated_at)
+           VALUES (?1, '', ?2, 'active', ?3, ?3)"#,
+        params![&model, dims, now],
+    )?;
+    let imported = conn.execute(
+        r#"INSERT OR IGNORE INTO chunk_embeddings (chunk_id, model, embedding, updated_at)
+           SELECT id, model, embedding, updated_at FROM chunks
+           WHERE model = ?1 AND embedding != ''"#,
+        params![&model],
+    )?;
+    conn.execute(
+        "UPDATE chunks SET embedding = '', model = '' WHERE model != ?1",
+        params![&model],
+    )?;
+    // The single fixed-dimension table is superseded by per-model tables
+    let _ = conn.execute("DROP TABLE IF EXISTS chunks_vec", []);
+
+    info!(
+        "Imported {} legacy embeddings for model {} ({} dims)",
+        imported, model, dims
+    );
+    Ok(())
+}
+
+/// Bring the OpenClaw JSON columns of `chunks` in line with the setting:
+/// fill in the active model's vectors where missing, or clear them all
+pub(super) fn sync_json_mirror(conn: &Connection, enabled: bool) -> Result<()> {
+    if !enabled {
+        let cleared = conn.execute(
+            "UPDATE chunks SET embedding = '', model = '' WHERE embedding != '' OR model != ''",
+            [],
+        )?;
+        if cleared > 0 {
+            debug!("Dropped {} JSON embedding copies", cleared);
+        }
+        return Ok(());
+    }
+
+    let active: Option<String> = conn
+        .query_row(
+            "SELECT model FROM embedding_models WHERE state = 'active'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(model) = active else {
+        return Ok(());
+    };
+    let rows: Vec<(String, Vec<u8>)> = {
+        let mut stmt = conn.prepare(
+            r#"SELECT e.chunk_id, e.embedding FROM chunk_embeddings e
+               JOIN chunks c ON c.id = e.chunk_id
+               WHERE e.model = ?1 AND (c.embedding = '' OR c.model != ?1)"#,
+        )?;
+        let rows = stmt.query_map(params![&model], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let mut update = conn.prepare("UPDATE chunks SET model = ?2, embedding = ?3 WHERE id = ?1")?;
+    for (chunk_id, blob) in &rows {
+        update.execute(params![
+            chunk_id,
+            &model,
+            serialize_embedding(&unpack_embedding(blob))
+        ])?;
+    }
+    Ok(())
+}
+
+/// Short stable key for a model name, usable in table and file names
+fn model_key(model: &str) -> String {
+    let digest = Sha256::digest(model.as_bytes());
+    digest
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Name of the sqlite-vec table holding a model's vectors
+pub(super) fn vec_table_name(model: &str) -> String {
+    format!("chunks_vec_{}", model_key(model))
+}
+
+/// Create the model's vec0 table if needed, backfilling it from `chunk_embeddings`
+fn ensure_model_vec_table(conn: &Connection, model: &str, dims: usize) -> Result<String> {
+    let table = vec_table_name(model);
+    let exists: bool = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = ?1",
+            params![&table],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if exists {
+        return Ok(table);
+    }
+
+    conn.execute(
+        &format!(
+            "CREATE VIRTUAL TABLE {} USING vec0(id TEXT PRIMARY KEY, embedding float[{}])",
+            table, dims
+        ),
+        [],
+    )?;
+    debug!("Created {} for {} ({} dims)", table, model, dims);
+
+    let mut stmt =
+        conn.prepare("SELECT chunk_id, embedding FROM chunk_embeddings WHERE model = ?1")?;
+    let rows = stmt.query_map(params![model], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+    })?;
+    for row in rows {
+        let (id, blob) = row?;
+        if blob.len() == dims * 4 {
+            conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} (id, embedding) VALUES (?1, ?2)",
+                    table
+                ),
+                params![id, blob],
+            )?;
+        }
+    }
+    Ok(table)
+}
+
+fn load_models(conn: &Connection) -> Result<Vec<EmbeddingModel>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT m.model, m.provider, m.dims, m.state,
+                  (SELECT COUNT(*) FROM chunk_embeddings e WHERE e.model = m.model)
+           FROM embedding_models m ORDER BY m.created_at, m.rowid"#,
+    )?;
+    let models = stmt
+        .query_map([], |row| {
+            Ok(EmbeddingModel {
+                model: row.get(0)?,
+                provider: row.get(1)?,
+                dims: row.get::<_, Option<i64>>(2)?.map(|d| d as usize),
+                state: ModelState::parse(&row.get::<_, String>(3)?),
+                embedded: row.get::<_, i64>(4)? as usize,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(models)
+}
+
+/// Delete a chunk's vectors for every model
+pub(super) fn delete_vectors_for_chunk(conn: &Connection, chunk_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM chunk_embeddings WHERE chunk_id = ?1",
+        params![chunk_id],
+    )?;
+    let mut stmt = conn.prepare("SELECT model FROM embedding_models")?;
+    let models: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+    for model in models {
+        // Fails harmlessly when sqlite-vec is not loaded or the table is missing
+        let _ = conn.execute(
+            &format!("DELETE FROM {} WHERE id = ?1", vec_table_name(&model)),
+            params![chunk_id],
+        );
+    }
+    Ok(())
+}
+
+/// In-memory HNSW indexes per model, persisted beside the database
+pub(super) struct AnnStore {
+    db_path: PathBuf,
+    indexes: Mutex<HashMap<String, LoadedAnn>>,
+}
+
+struct LoadedAnn {
+    index: HnswIndex,
+    /// Changes since the index was last saved
+    dirty: usize,
+}
+
+impl AnnStore {
+    pub(super) fn new(db_path: &Path) -> Self {
+        Self {
+            db_path: db_path.to_path_buf(),
+            indexes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `<db file>.<model key>.hnsw` next to the database
+    fn path(&self, model: &str) -> PathBuf {
+        let name = self
+            .db_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.db_path
+            .with_file_name(format!("{}.{}.hnsw", name, model_key(model)))
+    }
+
+    fn is_loaded(&self, model: &str) -> bool {
+        self.indexes.lock().unwrap().contains_key(model)
+    }
+
+    /// Run `f` on the model's index, loading and reconciling it first if needed
+    fn with_index<R>(
+        &self,
+        conn: &Connection,
+        model: &str,
+        f: impl FnOnce(&mut LoadedAnn) -> R,
+    ) -> Result<R> {
+        let mut indexes = self.indexes.lock().unwrap();
+        if !indexes.contains_key(model) {
+            let loaded = self.load(conn, model)?;
+            indexes.insert(model.to_string(), loaded);
+        }
+        let loaded = indexes.get_mut(model).expect("index loaded above");
+        let result = f(loaded);
+        if loaded.dirty >= ANN_SAVE_EVERY {
+            self.save(model, loaded);
+        }
+        Ok(result)
+    }
+
+    /// Load the persisted index and bring it in line with `chunk_embeddings`
+    fn load(&self, conn: &Connection, model: &str) -> Result<LoadedAnn> {
+        let dims: Option<i64> = conn
+            .query_row(
+                "SELECT dims FROM embedding_models WHERE model = ?1",
+                params![model],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        let Some(dims) = dims.map(|d| d as usize) else {
+            return Err(anyhow!("No vectors stored for {}", model));
+        };
+
+        let path = self.path(model);
+        let mut index = match HnswIndex::load(&path) {
+            Ok(index) if index.dims() == dims => index,
+            Ok(_) => HnswIndex::new(dims),
+            Err(e) => {
+                if path.exists() {
+                    warn!("Rebuilding vector index {}: {}", path.display(), e);
+                }
+                HnswIndex::new(dims)
+            }
+        };
+
+        let stored: HashSet<String> = {
+            let mut stmt =
+                conn.prepare("SELECT chunk_id FROM chunk_embeddings WHERE model = ?1")?;
+            let rows = stmt.query_map(params![model], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let stale: Vec<String> = index
+            .ids()
+            .filter(|id| !stored.contains(*id))
+            .map(str::to_string)
+            .collect();
+        for id in &stale {
+            index.remove(id);
+        }
+
+        let mut added = 0;
+        let mut stmt = conn
+            .prepare("SELECT embedding FROM chunk_embeddings WHERE chunk_id = ?1 AND model = ?2")?;
+        let missing: Vec<&String> = stored.iter().filter(|id| !index.contains(id)).collect();
+        for id in missing {
+            let blob: Vec<u8> = stmt.query_row(params![id, model], |row| row.get(0))?;
+            let vector = unpack_embedding(&blob);
+            if vector.len() == dims {
+                index.insert(id, &vector)?;
+                added += 1;
+            }
+        }
+
+        let mut loaded = LoadedAnn { index, dirty: 0 };
+        if added > 0 || !stale.is_empty() {
+            info!(
+                "Vector index for {}: {} vectors ({} added, {} removed)",
+                model,
+                loaded.index.len(),
+                added,
+                stale.len()
+            );
+            loaded.dirty = added + stale.len();
+            self.save(model, &mut loaded);
+        }
+        Ok(loaded)
+    }
+
+    fn save(&self, model: &str, loaded: &mut LoadedAnn) {
+        if loaded.index.tombstones() > loaded.index.len() {
+            loaded.index.compact();
+        }
+        match loaded.index.save(&self.path(model)) {
+            Ok(()) => loaded.dirty = 0,
+            Err(e) => warn!("Failed to save vector index for {}: {}", model, e),
+        }
+    }
+
+    /// Apply a stored vector to the model's index if it is loaded.
+    /// Unloaded indexes catch up from `chunk_embeddings` when loaded.
+    fn insert(&self, model: &str, chunk_id: &str, vector: &[f32]) {
+        let mut indexes = self.indexes.lock().unwrap();
+        if let Some(loaded) = indexes.get_mut(model) {
+            match loaded.index.insert(chunk_id, vector) {
+                Ok(()) => loaded.dirty += 1,
+                Err(e) => debug!("Vector not added to index for {}: {}", model, e),
+            }
+            if loaded.dirty >= ANN_SAVE_EVERY {
+                self.save(model, loaded);
+            }
+        }
+    }
+
+    /// Save indexes with unsaved changes
+    fn flush(&self) {
+        let mut indexes = self.indexes.lock().unwrap();
+        for (model, loaded) in indexes.iter_mut() {
+            if loaded.dirty > 0 {
+                self.save(model, loaded);
+            }
+        }
+    }
+
+    /// Forget a model's index and delete its file
+    fn drop_model(&self, model: &str) {
+        self.indexes.lock().unwrap().remove(model);
+        let _ = std::fs::remove_file(self.path(model));
+    }
+}
+
+impl MemoryIndex {
+    /// Register the configured embedding model. The first model becomes
+    /// active immediately; later ones start building alongside it.
+    pub async fn register_embedding_model(
+        &self,
+        model: &str,
+        provider: &str,
+    ) -> Result<ModelState> {
+        let pool = self.pool.clone();
+        let model = model.to_string();
+        let provider = provider.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let existing: Option<String> = conn
+                .query_row(
+                    "SELECT state FROM embedding_models WHERE model = ?1",
+                    params![&model],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(state) = existing {
+                // Legacy rows don't know their provider
+                conn.execute(
+                    "UPDATE embedding_models SET provider = ?2 WHERE model = ?1 AND provider = ''",
+                    params![&model, &provider],
+                )?;
+                return Ok(ModelState::parse(&state));
+            }
+
+            let has_active: bool = conn
+                .query_row(
+                    "SELECT 1 FROM embedding_models WHERE state = 'active'",
+                    [],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            let state = if has_active {
+                ModelState::Building
+            } else {
+                ModelState::Active
+            };
+            let now = chrono::Utc::now().timestamp();
+            conn.execute(
+                r#"INSERT INTO embedding_models (model, provider, state, created_at, activated_at)
+                   VALUES (?1, ?2, ?3, ?4, CASE WHEN ?3 = 'active' THEN ?4 END)"#,
+                params![&model, &provider, state.as_str(), now],
+            )?;
+            if state == ModelState::Building {
+                info!(
+                    "Embedding model {} registered; re-embedding in background",
+                    model
+                );
+            }
+            Ok(state)
+        })
+        .await?
+    }
+
+    /// All registered embedding models, oldest first
+    pub async fn embedding_models(&self) -> Result<Vec<EmbeddingModel>> {
+        let pool = self.pool.clone();
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            load_models(&conn)
+        })
+        .await?
+    }
+
+    /// The model currently serving vector queries
+    pub async fn active_embedding_model(&self) -> Result<Option<EmbeddingModel>> {
+        Ok(self
+            .embedding_models()
+            .await?
+            .into_iter()
+            .find(|m| m.state == ModelState::Active))
+    }
+
+    /// Promote a building model once every chunk has a vector for it, then
+    /// drop the previous model's vectors. Returns true if it was promoted.
+    pub async fn complete_migration(&self, model: &str) -> Result<bool> {
+        let pool = self.pool.clone();
+        let model = model.to_string();
+        let ann = self.ann.clone();
+        let json_mirror = self.json_mirror;
+
+        task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            let tx = conn.transaction()?;
+
+            let state: Option<String> = tx
+                .query_row(
+                    "SELECT state FROM embedding_models WHERE model = ?1",
+                    params![&model],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if state.as_deref() != Some("building") {
+                return Ok(false);
+            }
+
+            let missing: i64 = tx.query_row(
+                r#"SELECT COUNT(*) FROM chunks c WHERE NOT EXISTS
+                   (SELECT 1 FROM chunk_embeddings e WHERE e.chunk_id = c.id AND e.model = ?1)"#,
+                params![&model],
+                |row| row.get(0),
+            )?;
+            if missing > 0 {
+                return Ok(false);
+            }
+
+            let retired: Vec<String> = {
+                let mut stmt =
+                    tx.prepare("SELECT model FROM embedding_models WHERE model != ?1")?;
+                let rows = stmt.query_map(params![&model], |row| row.get(0))?;
+                rows.filter_map(|r| r.ok()).collect()
+            };
+            for old in &retired {
+                tx.execute(
+                    "DELETE FROM chunk_embeddings WHERE model = ?1",
+                    params![old],
+                )?;
+                tx.execute(
+                    "DELETE FROM embedding_models WHERE model = ?1",
+                    params![old],
+                )?;
+                let _ = tx.execute(&format!("DROP TABLE IF EXISTS {}", vec_table_name(old)), []);
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            tx.execute(
+                "UPDATE embedding_models SET state = 'active', activated_at = ?2 WHERE model = ?1",
+                params![&model, now],
+            )?;
+            sync_json_mirror(&tx, false)?;
+            if json_mirror {
+                sync_json_mirror(&tx, true)?;
+            }
+            tx.commit()?;
+            for old in &retired {
+                ann.drop_model(old);
+            }
+
+            info!(
+                "Embedding migration complete: {} is active, dropped {}",
+                model,
+                if retired.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    retired.join(", ")
+                }
+            );
+            Ok(true)
+        })
+        .await?
+    }
+
+    /// Get chunks without a vector for `model` (OpenClaw-compatible: id is TEXT, text column)
+    pub async fn chunks_without_embeddings(
+        &self,
+        model: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, String)>> {
+        let pool = self.pool.clone();
+        let model = model.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let mut stmt = conn.prepare(
+                r#"SELECT c.id, c.text FROM chunks c WHERE NOT EXISTS
+                   (SELECT 1 FROM chunk_embeddings e WHERE e.chunk_id = c.id AND e.model = ?1)
+                   LIMIT ?2"#,
+            )?;
+
+            let rows = stmt.query_map(params![&model, limit as i64], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+
+            Ok(results)
+        })
+        .await?
+    }
+
+    /// Store a chunk's vector for `model`
+    pub async fn store_embedding(
+        &self,
+        chunk_id: &str,
+        embedding: &[f32],
+        model: &str,
+    ) -> Result<()> {
+        let pool = self.pool.clone();
+        let chunk_id = chunk_id.to_string();
+        let embedding = embedding.to_vec();
+        let model = model.to_string();
+        let has_vec_extension = self.has_vec_extension;
+        let ann = self.ann.clone();
+        let json_mirror = self.json_mirror;
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let blob = pack_embedding(&embedding);
+            let now = chrono::Utc::now().timestamp();
+
+            conn.execute(
+                r#"INSERT OR REPLACE INTO chunk_embeddings (chunk_id, model, embedding, updated_at)
+                   VALUES (?1, ?2, ?3, ?4)"#,
+                params![&chunk_id, &model, &blob, now],
+            )?;
+            conn.execute(
+                "UPDATE embedding_models SET dims = ?2 WHERE model = ?1 AND dims IS NULL",
+                params![&model, embedding.len() as i64],
+            )?;
+
+            // Mirror the active model into the OpenClaw-compatible columns
+            if json_mirror {
+                conn.execute(
+                    r#"UPDATE chunks SET embedding = ?1, model = ?2, updated_at = ?3
+                       WHERE id = ?4 AND EXISTS
+                       (SELECT 1 FROM embedding_models WHERE model = ?2 AND state = 'active')"#,
+                    params![serialize_embedding(&embedding), &model, now, &chunk_id],
+                )?;
+            }
+
+            // Also store in the model's vec table if sqlite-vec is available
+            if has_vec_extension {
+                match ensure_model_vec_table(&conn, &model, embedding.len()) {
+                    Ok(table) => {
+                        let _ = conn.execute(
+                            &format!(
+                                "INSERT OR REPLACE INTO {} (id, embedding) VALUES (?1, ?2)",
+                                table
+                            ),
+                            params![chunk_id, blob],
+                        );
+                    }
+                    Err(e) => debug!("No vec table for {}: {}", model, e),
+                }
+            } else {
+                ann.insert(&model, &chunk_id, &embedding);
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Count chunks with a vector for `model`
+    pub async fn embedded_chunk_count(&self, model: &str) -> Result<usize> {
+        let pool = self.pool.clone();
+        let model = model.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM chunk_embeddings WHERE model = ?1",
+                params![&model],
+                |row| row.get(0),
+            )?;
+
+            Ok(count as usize)
+        })
+        .await?
+    }
+
+    /// Save vector indexes with unsaved incremental updates
+    pub async fn flush_vector_index(&self) -> Result<()> {
+        let ann = self.ann.clone();
+        task::spawn_blocking(move || ann.flush()).await?;
+        Ok(())
+    }
+
+    /// Vector search using embeddings of `model`.
+    /// Uses the model's sqlite-vec table if available, then the ANN index for
+    /// large models, otherwise scans in memory.
+    pub async fn search_vector(
+        &self,
+        query_embedding: &[f32],
+        model: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryChunk>> {
+        self.search_vector_in(query_embedding, model, limit, None)
+            .await
+    }
+
+    /// Vector search restricted to chunks of `paths` when given
+    pub(super) async fn search_vector_in(
+        &self,
+        query_embedding: &[f32],
+        model: &str,
+        limit: usize,
+        paths: Option<Arc<HashSet<String>>>,
+    ) -> Result<Vec<MemoryChunk>> {
+        let pool = self.pool.clone();
+        let query_embedding = query_embedding.to_vec();
+        let model = model.to_string();
+        let has_vec_extension = self.has_vec_extension;
+        let ann = self.ann.clone();
+        let ann_min_vectors = self.ann_min_vectors;
+
+        task::spawn_blocking(move || {
+            let conn = pool
+                .get()
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+            // Try sqlite-vec fast path if available
+            if has_vec_extension {
+                match search_vector_fast(&conn, &query_embedding, &model, limit, paths.as_deref()) {
+                    Ok(results) => return Ok(results),
+                    Err(e) => warn!(
+                        "sqlite-vec search failed ({}), falling back to in-memory scan",
+                        e
+                    ),
+                }
+            } else if ann.is_loaded(&model) || {
+                let count: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM chunk_embeddings WHERE model = ?1",
+                    params![&model],
+                    |row| row.get(0),
+                )?;
+                count as usize >= ann_min_vectors
+            } {
+                match search_vector_ann(
+                    &conn,
+                    &ann,
+                    &query_embedding,
+                    &model,
+                    limit,
+                    paths.as_deref(),
+                ) {
+                    // A narrow path filter can starve the over-fetched candidates
+                    Ok(results) if paths.is_none() || results.len() >= limit => return Ok(results),
+                    Ok(_) => {}
+                    Err(e) => warn!("ANN search failed ({}), falling back to full scan", e),
+                }
+            }
+
+            // Fallback: in-memory scan (slower but always works)
+            search_vector_scan(&conn, &query_embedding, &model, limit, paths.as_deref())
+        })
+        .await?
+    }
+}
+
+/// Fast vector search using the model's sqlite-vec table
+fn search_vector_fast(
+    conn: &Connection,
+    query_embedding: &[f32],
+    model: &str,
+    limit: usize,
+    paths: Option<&HashSet<String>>,
+) -> Result<Vec<MemoryChunk>> {
+    let query_blob = pack_embedding(query_embedding);
+    let paths_json = paths_param(paths);
+
+    // sqlite-vec uses vec_distance_cosine for cosine distance (1 - similarity)
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT c.path, c.start_line, c.end_line, c.text,
+               1.0 - vec_distance_cosine(v.embedding, ?1) AS score, c.source
+        FROM {} v
+        JOIN chunks c ON c.id = v.id
+        WHERE ?3 IS NULL OR c.path IN (SELECT value FROM json_each(?3))
+        ORDER BY score DESC
+        LIMIT ?2
+        "#,
+        vec_table_name(model)
+    ))?;
+
+    let rows = stmt.query_map(params![&query_blob, limit as i64, &paths_json], |row| {
+        Ok(MemoryChunk {
+            file: row.get(0)?,
+            line_start: row.get(1)?,
+            line_end: row.get(2)?,
+            content: row.get(3)?,
+            score: row.get(4)?,
+            source: row.get(5)?,
+            origin: None,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+/// Vector search through the model's HNSW index
+fn search_vector_ann(
+    conn: &Connection,
+    ann: &AnnStore,
+    query_embedding: &[f32],
+    model: &str,
+    limit: usize,
+    paths: Option<&HashSet<String>>,
+) -> Result<Vec<MemoryChunk>> {
+    // Over-fetch: hits for chunks deleted since the index was updated, or
+    // outside the allowed paths, are skipped
+    let fetch = if paths.is_some() {
+        limit * 8
+    } else {
+        limit * 2
+    };
+    let hits = ann.with_index(conn, model, |loaded| {
+        loaded
+            .index
+            .search(query_embedding, fetch, EF_SEARCH.max(fetch * 2))
+    })?;
+
+    let mut stmt =
+        conn.prepare("SELECT path, start_line, end_line, text, source FROM chunks WHERE id = ?1")?;
+    let mut results = Vec::new();
+    let mut stale = Vec::new();
+    for (id, score) in hits {
+        if results.len() >= limit {
+            break;
+        }
+        let chunk = stmt
+            .query_row(params![&id], |row| {
+                Ok(MemoryChunk {
+                    file: row.get(0)?,
+                    line_start: row.get(1)?,
+                    line_end: row.get(2)?,
+                    content: row.get(3)?,
+                    score: score as f64,
+                    source: row.get(4)?,
+                    origin: None,
+                })
+            })
+            .optional()?;
+        match chunk {
+            Some(chunk) if paths.is_none_or(|p| p.contains(&chunk.file)) => results.push(chunk),
+            Some(_) => {}
+            None => stale.push(id),
+        }
+    }
+
+    if !stale.is_empty() {
+        ann.with_index(conn, model, |loaded| {
+            for id in &stale {
+                if loaded.index.remove(id) {
+                    loaded.dirty += 1;
+                }
+            }
+        })?;
+    }
+    Ok(results)
+}
+
+/// In-memory vector scan (fallback when sqlite-vec not available)
+fn search_vector_scan(
+    conn: &Connection,
+    query_embedding: &[f32],
+    model: &str,
+    limit: usize,
+    paths: Option<&HashSet<String>>,
+) -> Result<Vec<MemoryChunk>> {
+    let paths_json = paths_param(paths);
+    let mut stmt = conn.prepare(
+        r#"SELECT c.path, c.start_line, c.end_line, c.text, e.embedding, c.source
+           FROM chunk_embeddings e
+           JOIN chunks c ON c.id = e.chunk_id
+           WHERE e.model = ?1
+             AND (?2 IS NULL OR c.path IN (SELECT value FROM json_each(?2)))"#,
+    )?;
+
+    let rows = stmt.query_map(params![model, &paths_json], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i32>(1)?,
+            row.get::<_, i32>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Vec<u8>>(4)?,
+            row.get::<_, String>(5)?,
+        ))
+    })?;
+
+    // Compute similarities and sort
+    let mut scored: Vec<(f32, MemoryChunk)> = Vec::new();
+
+    for row in rows {
+        let (path, start_line, end_line, text, blob, source) = row?;
+        let embedding = unpack_embedding(&blob);
+
+        if embedding.len() == query_embedding.len() {
+            let similarity = cosine_similarity(query_embedding, &embedding);
+            scored.push((
+                similarity,
+                MemoryChunk {
+                    file: path,
+                    line_start: start_line,
+                    line_end: end_line,
+                    content: text,
+                    score: similarity as f64,
+                    source,
+                    origin: None,
+                },
+            ));
+        }
+    }
+
+    // Sort by similarity (descending)
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Take top results
+    Ok(scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, chunk)| chunk)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_model_migration() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        fs::write(workspace.join("a.md"), "# A\n\nalpha")?;
+        fs::write(workspace.join("b.md"), "# B\n\nbeta")?;
+
+        let index = MemoryIndex::new(workspace)?;
+        index.index_file(&workspace.join("a.md"), false).await?;
+        index.index_file(&workspace.join("b.md"), false).await?;
+
+        // First model is active right away
+        assert_eq!(
+            index.register_embedding_model("old", "openai").await?,
+            ModelState::Active
+        );
+        for (id, _) in index.chunks_without_embeddings("old", 10).await? {
+            index.store_embedding(&id, &[1.0, 0.0], "old").await?;
+        }
+
+        // A new model with another dimension builds alongside it
+        assert_eq!(
+            index.register_embedding_model("new", "local").await?,
+            ModelState::Building
+        );
+        let pending = index.chunks_without_embeddings("new", 10).await?;
+        assert_eq!(pending.len(), 2);
+        index
+            .store_embedding(&pending[0].0, &[0.0, 1.0, 0.0], "new")
+            .await?;
+
+        // Not complete yet: old model still serves queries
+        assert!(!index.complete_migration("new").await?);
+        assert_eq!(index.active_embedding_model().await?.unwrap().model, "old");
+        assert_eq!(index.search_vector(&[1.0, 0.0], "old", 5).await?.len(), 2);
+        assert_eq!(index.embedded_chunk_count("new").await?, 1);
+
+        index
+            .store_embedding(&pending[1].0, &[0.0, 1.0, 0.0], "new")
+            .await?;
+        assert!(index.complete_migration("new").await?);
+
+        let models = index.embedding_models().await?;
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model, "new");
+        assert_eq!(models[0].dims, Some(3));
+        assert_eq!(index.embedded_chunk_count("old").await?, 0);
+        assert_eq!(
+            index.search_vector(&[0.0, 1.0, 0.0], "new", 5).await?.len(),
+            2
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_mirror_is_opt_in() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let db_path = workspace.join("index.sqlite");
+        fs::write(workspace.join("a.md"), "# A\n\nalpha")?;
+
+        let index = MemoryIndex::new_with_db_path(workspace, &db_path)?;
+        index.index_file(&workspace.join("a.md"), false).await?;
+        index.register_embedding_model("m", "openai").await?;
+        for (id, _) in index.chunks_without_embeddings("m", 10).await? {
+            index.store_embedding(&id, &[1.0, 0.0], "m").await?;
+        }
+
+        let json_copies = |index: &MemoryIndex| -> Result<i64> {
+            let conn = index.pool.get()?;
+            Ok(conn.query_row(
+                "SELECT COUNT(*) FROM chunks WHERE embedding != ''",
+                [],
+                |row| row.get(0),
+            )?)
+        };
+        // Packed blobs only by default
+        assert_eq!(json_copies(&index)?, 0);
+        assert_eq!(index.search_vector(&[1.0, 0.0], "m", 5).await?.len(), 1);
+
+        // Opting in fills the OpenClaw columns, opting out clears them again
+        let index = MemoryIndex::new_with_db_path(workspace, &db_path)?.with_json_mirror(true)?;
+        assert_eq!(json_copies(&index)?, 1);
+        let index = MemoryIndex::new_with_db_path(workspace, &db_path)?.with_json_mirror(false)?;
+        assert_eq!(json_copies(&index)?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ann_search_and_persistence() -> Result<()> {
+        let temp = TempDir::new()?;
+        let workspace = temp.path();
+        let db_path = workspace.join("ann.sqlite");
+        for (name, text) in [("a.md", "alpha"), ("b.md", "beta"), ("c.md", "gamma")] {
+            fs::write(workspace.join(name), format!("# {}\n\n{}", name, text))?;
+        }
+
+        let index = MemoryIndex::new_with_db_path(workspace, &db_path)?.with_ann_min_vectors(1);
+        for name in ["a.md", "b.md", "c.md"] {
+            index.index_file(&workspace.join(name), false).await?;
+        }
+        index.register_embedding_model("m", "openai").await?;
+        let axes = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        for (id, text) in index.chunks_without_embeddings("m", 10).await? {
+            let axis = if text.contains("alpha") {
+                0
+            } else if text.contains("beta") {
+                1
+            } else {
+                2
+            };
+            index.store_embedding(&id, &axes[axis], "m").await?;
+        }
+
+        // Vectors are stored packed, four bytes per dimension
+        let conn = index.pool.get()?;
+        let size: i64 = conn.query_row(
+            "SELECT length(embedding) FROM chunk_embeddings LIMIT 1",
+            [],
+            |r| r.get(0),
+        )?;
+        assert_eq!(size, 12);
+
+        let hits = index.search_vector(&[0.1, 1.0, 0.0], "m", 1).await?;
+        assert_eq!(hits[0].file, "b.md");
+        index.flush_vector_index().await?;
+        assert!(index.ann.path("m").exists());
+
+        // Deleted chunks drop out of results; a fresh index reconciles with the DB
+        index.remove_file("b.md").await?;
+        let hits = index.search_vector(&[0.1, 1.0, 0.0], "m", 3).await?;
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.file != "b.md"));
+
+        let reopened = MemoryIndex::new_with_db_path(workspace, &db_path)?.with_ann_min_vectors(1);
+        let hits = reopened.search_vector(&[1.0, 0.1, 0.0], "m", 3).await?;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].file, "a.md");
+
+        // A corrupt file is rebuilt from chunk_embeddings
+        let mut bytes = fs::read(index.ann.path("m"))?;
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(index.ann.path("m"), bytes)?;
+        let rebuilt = MemoryIndex::new_with_db_path(workspace, &db_path)?.with_ann_min_vectors(1);
+        let hits = rebuilt.search_vector(&[1.0, 0.1, 0.0], "m", 3).await?;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].file, "a.md");
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+use super::extract::Extractors;
 use super::MemoryIndex;
 use crate::config::MemoryConfig;
 
@@ -24,15 +25,35 @@ impl MemoryWatcher {
         // Create a channel for receiving events
         let (tx, mut rx) = mpsc::channel(100);
 
+        // Configured index paths; supported non-markdown files are only
+        // picked up under these (markdown is watched everywhere)
+        let index_bases: Vec<PathBuf> = config
+            .paths
+            .iter()
+            .map(|index_path| {
+                if index_path.path.starts_with('~') || index_path.path.starts_with('/') {
+                    PathBuf::from(shellexpand::tilde(&index_path.path).to_string())
+                } else {
+                    workspace.join(&index_path.path)
+                }
+            })
+            .collect();
+        let extractors = Extractors::default();
+        let bases_for_events = index_bases.clone();
+
         // Create watcher with debounce
         let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
             match res {
                 Ok(event) => {
-                    // Filter for modify/create events on .md files
+                    // Filter for modify/create events on indexable files
                     match event.kind {
                         EventKind::Modify(_) | EventKind::Create(_) => {
                             for path in event.paths {
-                                if path.extension().map(|e| e == "md").unwrap_or(false) {
+                                let is_markdown =
+                                    path.extension().map(|e| e == "md").unwrap_or(false);
+                                let is_extra_source = extractors.supports(&path)
+                                    && bases_for_events.iter().any(|b| path.starts_with(b));
+                                if is_markdown || is_extra_source {
                                     if let Err(e) = tx.blocking_send(path.clone()) {
                                         warn!("Failed to send event: {}", e);
                                     }
@@ -52,14 +73,7 @@ impl MemoryWatcher {
 
         // Watch configured paths
         let mut watched_paths = vec![workspace.clone()];
-        for index_path in &config.paths {
-            let base_path = if index_path.path.starts_with('~') || index_path.path.starts_with('/')
-            {
-                PathBuf::from(shellexpand::tilde(&index_path.path).to_string())
-            } else {
-                workspace.join(&index_path.path)
-            };
-
+        for base_path in index_bases {
             // Skip if already watching (subdirectory of workspace)
             if base_path.starts_with(&workspace) {
                 continue;
@@ -84,14 +98,14 @@ impl MemoryWatcher {
         let chunk_overlap = config.chunk_overlap;
 
         tokio::spawn(async move {
-            let index =
-                match MemoryIndex::new_with_db_path(&workspace_for_task, &db_path_for_task, None) {
-                    Ok(idx) => idx.with_chunk_config(chunk_size, chunk_overlap),
-                    Err(e) => {
-                        warn!("Failed to create memory index for watcher: {}", e);
-                        return;
-                    }
-                };
+            let index = match MemoryIndex::new_with_db_path(&workspace_for_task, &db_path_for_task)
+            {
+                Ok(idx) => idx.with_chunk_config(chunk_size, chunk_overlap),
+                Err(e) => {
+                    warn!("Failed to create memory index for watcher: {}", e);
+                    return;
+                }
+            };
 
             // Debounce logic is harder with async loop, but let's simplify.
             // Just process events. Maybe add a small delay/buffer if needed.
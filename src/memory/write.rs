@@ -0,0 +1,571 @@
+//! Structured memory writes
+//!
+//! `memory_write` appends a fact as a bullet under a `## Section` heading of
+//! MEMORY.md instead of letting the agent edit the file freely. Before writing,
+//! the fact is compared against the existing bullets (semantically when an
+//! embedding provider is configured, lexically otherwise) and the write is
+//! applied, merged into an entry it extends, rejected as a duplicate, or
+//! reported back as a contradiction.
+//!
+//! Every change carries a provenance comment that markdown renders invisibly:
+//!
+//! ```text
+//! - Editor: helix <!-- zier:write session=4f1c… ts=2026-03-01T09:12:44+00:00 op=add -->
+//! ```
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Marker that starts a provenance comment
+pub const PROVENANCE_MARKER: &str = "<!-- zier:write";
+
+/// Jaccard similarity above which two bullets are the same fact
+const LEXICAL_DUPLICATE_THRESHOLD: f64 = 0.8;
+
+/// Embedding cosine similarity above which two bullets are the same fact
+const SEMANTIC_DUPLICATE_THRESHOLD: f32 = 0.92;
+
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "don't", "doesn't", "didn't", "isn't", "aren't", "wasn't", "won't",
+    "can't", "cannot", "dislikes", "hates", "avoids",
+];
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "is", "are", "was", "to", "of", "and", "or", "in", "on", "for", "with", "do",
+    "does", "did", "uses", "use", "user", "user's", "their", "they",
+];
+
+/// What to do when a fact contradicts an existing entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    /// Leave the file unchanged and report the contradiction
+    #[default]
+    Report,
+    /// Overwrite the contradicted entry
+    Replace,
+    /// Record the new fact next to the old one
+    Keep,
+}
+
+impl std::str::FromStr for ConflictMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "report" => Ok(Self::Report),
+            "replace" => Ok(Self::Replace),
+            "keep" => Ok(Self::Keep),
+            other => anyhow::bail!("Unknown on_conflict mode: {}", other),
+        }
+    }
+}
+
+/// A fact to persist in MEMORY.md
+#[derive(Debug, Clone)]
+pub struct FactWrite {
+    pub section: String,
+    pub fact: String,
+    pub on_conflict: ConflictMode,
+    /// Session that produced the fact (recorded as provenance)
+    pub session_id: Option<String>,
+}
+
+/// Result of a structured memory write. Line numbers are 1-indexed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WriteOutcome {
+    Added {
+        section: String,
+        line: usize,
+    },
+    Merged {
+        line: usize,
+        previous: String,
+    },
+    Replaced {
+        line: usize,
+        previous: String,
+    },
+    Duplicate {
+        line: usize,
+        existing: String,
+    },
+    Conflict {
+        line: usize,
+        existing: String,
+        reason: String,
+    },
+}
+
+/// A bullet already present in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryEntry {
+    pub section: String,
+    /// 0-indexed line in the file
+    pub line: usize,
+    /// Bullet text without marker and provenance
+    pub text: String,
+}
+
+/// How a new fact relates to an existing entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum FactRelation {
+    Duplicate,
+    /// The new fact restates the entry with more detail
+    Supersedes,
+    Contradicts(String),
+}
+
+/// Collect bullets (`-`, `*`, `+`) with the `##` section they belong to
+pub fn parse_entries(content: &str) -> Vec<MemoryEntry> {
+    let mut section = String::new();
+    let mut in_code_block = false;
+    let mut entries = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            section = heading.trim().to_string();
+            continue;
+        }
+        if let Some(text) = bullet_text(line) {
+            let text = strip_provenance(text).trim().to_string();
+            if !text.is_empty() {
+                entries.push(MemoryEntry {
+                    section: section.clone(),
+                    line: i,
+                    text,
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+fn bullet_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+}
+
+/// Remove a trailing provenance comment from a bullet
+pub fn strip_provenance(text: &str) -> &str {
+    match text.find(PROVENANCE_MARKER) {
+        Some(pos) => text[..pos].trim_end(),
+        None => text,
+    }
+}
+
+/// Build the provenance comment for a change
+pub fn provenance(session_id: Option<&str>, op: &str, now: DateTime<Local>) -> String {
+    format!(
+        "{} session={} ts={} op={} -->",
+        PROVENANCE_MARKER,
+        session_id.unwrap_or("unknown"),
+        now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        op
+    )
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '-' || c == '.'))
+        .map(|w| w.trim_matches(|c: char| c == '.' || c == '\'' || c == '-'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
+
+fn content_tokens(text: &str) -> HashSet<String> {
+    words(text)
+        .into_iter()
+        .filter(|w| !STOPWORDS.contains(&w.as_str()) && !NEGATIONS.contains(&w.as_str()))
+        .map(|w| stem(&w))
+        .collect()
+}
+
+/// Crude plural/third-person folding so "likes" matches "like"
+fn stem(word: &str) -> String {
+    match word.strip_suffix('s') {
+        Some(base) if word.len() > 3 && !base.ends_with('s') => base.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+fn is_negated(text: &str) -> bool {
+    words(text)
+        .iter()
+        .filter(|w| NEGATIONS.contains(&w.as_str()))
+        .count()
+        % 2
+        == 1
+}
+
+/// Token Jaccard similarity, ignoring stopwords and negations
+pub fn lexical_similarity(a: &str, b: &str) -> f64 {
+    let a = content_tokens(a);
+    let b = content_tokens(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let intersection = a.intersection(&b).count() as f64;
+    let union = a.union(&b).count() as f64;
+    intersection / union
+}
+
+/// Split `Key: value` facts; keys are short labels, not sentences
+fn split_key_value(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once(':')?;
+    let key = key.trim().trim_matches('*').trim();
+    let value = value.trim();
+    if key.is_empty() || value.is_empty() || key.split_whitespace().count() > 4 {
+        return None;
+    }
+    Some((key.to_lowercase(), value.to_string()))
+}
+
+/// Decide how `new` relates to `existing`.
+///
+/// `semantic` is the embedding cosine similarity when available.
+pub fn classify(existing: &str, new: &str, semantic: Option<f32>) -> Option<FactRelation> {
+    if words(existing) == words(new) {
+        return Some(FactRelation::Duplicate);
+    }
+
+    if let (Some((old_key, old_value)), Some((new_key, new_value))) =
+        (split_key_value(existing), split_key_value(new))
+    {
+        if old_key == new_key {
+            let (old_tokens, new_tokens) = (content_tokens(&old_value), content_tokens(&new_value));
+            if old_tokens == new_tokens {
+                return Some(FactRelation::Duplicate);
+            }
+            if old_tokens.is_subset(&new_tokens) && is_negated(&old_value) == is_negated(&new_value)
+            {
+                return Some(FactRelation::Supersedes);
+            }
+            return Some(FactRelation::Contradicts(format!(
+                "'{}' is already recorded as '{}'",
+                old_key, old_value
+            )));
+        }
+    }
+
+    let old_tokens = content_tokens(existing);
+    let new_tokens = content_tokens(new);
+    if old_tokens.is_empty() || new_tokens.is_empty() {
+        return None;
+    }
+
+    let same_polarity = is_negated(existing) == is_negated(new);
+    if !same_polarity && (old_tokens == new_tokens || lexical_similarity(existing, new) >= 0.6) {
+        return Some(FactRelation::Contradicts(
+            "the existing entry states the opposite".to_string(),
+        ));
+    }
+    if !same_polarity {
+        return None;
+    }
+
+    if new_tokens.is_subset(&old_tokens) {
+        return Some(FactRelation::Duplicate);
+    }
+    if old_tokens.is_subset(&new_tokens) {
+        return Some(FactRelation::Supersedes);
+    }
+    if lexical_similarity(existing, new) >= LEXICAL_DUPLICATE_THRESHOLD
+        || semantic.is_some_and(|s| s >= SEMANTIC_DUPLICATE_THRESHOLD)
+    {
+        return Some(FactRelation::Duplicate);
+    }
+
+    None
+}
+
+/// Find the most relevant existing entry for a new fact.
+///
+/// Contradictions win over duplicates, duplicates over merges, so the model
+/// always hears about a conflict first.
+pub fn find_related<'a>(
+    entries: &'a [MemoryEntry],
+    fact: &str,
+    semantic: &dyn Fn(&MemoryEntry) -> Option<f32>,
+) -> Option<(&'a MemoryEntry, FactRelation)> {
+    let rank = |r: &FactRelation| match r {
+        FactRelation::Contradicts(_) => 0,
+        FactRelation::Duplicate => 1,
+        FactRelation::Supersedes => 2,
+    };
+
+    entries
+        .iter()
+        .filter_map(|e| classify(&e.text, fact, semantic(e)).map(|r| (e, r)))
+        .min_by_key(|(_, r)| rank(r))
+}
+
+/// Work out how writing `fact` changes `content`, without touching disk.
+///
+/// Returns the updated content (`None` when the file stays as it is) and the
+/// outcome to report.
+pub fn plan_write(
+    content: &str,
+    section: &str,
+    fact: &str,
+    on_conflict: ConflictMode,
+    session_id: Option<&str>,
+    now: DateTime<Local>,
+    semantic: &dyn Fn(&MemoryEntry) -> Option<f32>,
+) -> (Option<String>, WriteOutcome) {
+    let entries = parse_entries(content);
+    let bullet = |op: &str| format!("- {} {}", fact, provenance(session_id, op, now));
+
+    match find_related(&entries, fact, semantic) {
+        Some((entry, FactRelation::Duplicate)) => (
+            None,
+            WriteOutcome::Duplicate {
+                line: entry.line + 1,
+                existing: entry.text.clone(),
+            },
+        ),
+        Some((entry, FactRelation::Contradicts(reason))) if on_conflict == ConflictMode::Report => {
+            (
+                None,
+                WriteOutcome::Conflict {
+                    line: entry.line + 1,
+                    existing: entry.text.clone(),
+                    reason,
+                },
+            )
+        }
+        Some((entry, FactRelation::Contradicts(_))) if on_conflict == ConflictMode::Replace => (
+            Some(replace_line(content, entry.line, &bullet("replace"))),
+            WriteOutcome::Replaced {
+                line: entry.line + 1,
+                previous: entry.text.clone(),
+            },
+        ),
+        Some((entry, FactRelation::Supersedes)) => (
+            Some(replace_line(content, entry.line, &bullet("merge"))),
+            WriteOutcome::Merged {
+                line: entry.line + 1,
+                previous: entry.text.clone(),
+            },
+        ),
+        _ => {
+            let (updated, line) = insert_bullet(content, section, &bullet("add"));
+            (
+                Some(updated),
+                WriteOutcome::Added {
+                    section: section.to_string(),
+                    line: line + 1,
+                },
+            )
+        }
+    }
+}
+
+/// Remove the bullet whose text matches `text` (ignoring case and punctuation)
+pub fn remove_entry(content: &str, text: &str) -> Option<(String, MemoryEntry)> {
+    let wanted = words(strip_provenance(
+        text.trim().trim_start_matches(['-', '*', '+']),
+    ));
+    let entry = parse_entries(content)
+        .into_iter()
+        .find(|e| words(&e.text) == wanted)?;
+
+    let mut out = content
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| *i != entry.line)
+        .map(|(_, l)| l)
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.push('\n');
+    Some((out, entry))
+}
+
+/// Insert a bullet at the end of `## section`, creating the section if needed.
+///
+/// Returns the new content and the 0-indexed line of the bullet.
+pub fn insert_bullet(content: &str, section: &str, bullet: &str) -> (String, usize) {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let heading = lines.iter().position(|l| {
+        l.trim_start()
+            .strip_prefix("## ")
+            .is_some_and(|h| h.trim().eq_ignore_ascii_case(section))
+    });
+
+    let line = match heading {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|l| {
+                    let t = l.trim_start();
+                    t.starts_with("# ") || t.starts_with("## ")
+                })
+                .map(|p| start + 1 + p)
+                .unwrap_or(lines.len());
+            // Place after the last non-blank line of the section
+            let mut at = end;
+            while at > start + 1 && lines[at - 1].trim().is_empty() {
+                at -= 1;
+            }
+            if at == start + 1 {
+                lines.insert(at, String::new());
+                at += 1;
+            }
+            lines.insert(at, bullet.to_string());
+            at
+        }
+        None => {
+            while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                lines.pop();
+            }
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("## {}", section));
+            lines.push(String::new());
+            lines.push(bullet.to_string());
+            lines.len() - 1
+        }
+    };
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    (out, line)
+}
+
+/// Replace the 0-indexed line, keeping the original bullet indentation
+pub fn replace_line(content: &str, line: usize, bullet: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    if let Some(existing) = lines.get_mut(line) {
+        let indent: String = existing.chars().take_while(|c| c.is_whitespace()).collect();
+        *existing = format!("{}{}", indent, bullet);
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Memory\n\n## Preferences\n\n- Editor: helix\n- Prefers dark mode <!-- zier:write session=s1 ts=2026-01-01T00:00:00+00:00 op=add -->\n\n## Projects\n\n- Works on zier-alpha\n";
+
+    #[test]
+    fn test_parse_entries() {
+        let entries = parse_entries(SAMPLE);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].section, "Preferences");
+        assert_eq!(entries[0].text, "Editor: helix");
+        assert_eq!(entries[1].text, "Prefers dark mode");
+        assert_eq!(entries[2].section, "Projects");
+        assert_eq!(entries[2].line, 9);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("Prefers dark mode", "prefers dark mode.", None),
+            Some(FactRelation::Duplicate)
+        );
+        assert!(matches!(
+            classify("Editor: helix", "Editor: vim", None),
+            Some(FactRelation::Contradicts(_))
+        ));
+        assert!(matches!(
+            classify("Likes coffee", "Does not like coffee", None),
+            Some(FactRelation::Contradicts(_))
+        ));
+        assert_eq!(
+            classify(
+                "Works on zier-alpha",
+                "Works on zier-alpha memory subsystem",
+                None
+            ),
+            Some(FactRelation::Supersedes)
+        );
+        assert_eq!(classify("Lives in Berlin", "Has a dog", None), None);
+        assert_eq!(
+            classify("Lives in Berlin", "Resides in Berlin", Some(0.95)),
+            Some(FactRelation::Duplicate)
+        );
+    }
+
+    #[test]
+    fn test_insert_bullet_existing_section() {
+        let (out, line) = insert_bullet(SAMPLE, "preferences", "- Uses fish shell");
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[line], "- Uses fish shell");
+        assert_eq!(line, 6);
+        assert_eq!(lines[7], "");
+        assert_eq!(lines[8], "## Projects");
+    }
+
+    #[test]
+    fn test_insert_bullet_new_section() {
+        let (out, line) = insert_bullet(SAMPLE, "People", "- Alice is the PM");
+        assert!(out.ends_with("## People\n\n- Alice is the PM\n"));
+        assert_eq!(out.lines().nth(line), Some("- Alice is the PM"));
+
+        let (out, line) = insert_bullet("", "People", "- Alice is the PM");
+        assert_eq!(out, "## People\n\n- Alice is the PM\n");
+        assert_eq!(line, 2);
+    }
+
+    #[test]
+    fn test_plan_write_and_remove() {
+        let now = Local::now();
+        let (updated, outcome) = plan_write(
+            SAMPLE,
+            "Preferences",
+            "Editor: vim",
+            ConflictMode::Replace,
+            Some("s2"),
+            now,
+            &|_| None,
+        );
+        assert_eq!(
+            outcome,
+            WriteOutcome::Replaced {
+                line: 5,
+                previous: "Editor: helix".to_string()
+            }
+        );
+        assert!(updated
+            .unwrap()
+            .contains("- Editor: vim <!-- zier:write session=s2"));
+
+        let (updated, removed) = remove_entry(SAMPLE, "prefers dark mode.").unwrap();
+        assert_eq!(removed.line, 5);
+        assert!(!updated.contains("dark mode"));
+        assert!(remove_entry(SAMPLE, "Has a cat").is_none());
+    }
+
+    #[test]
+    fn test_replace_line_and_provenance() {
+        let out = replace_line(SAMPLE, 4, "- Editor: vim");
+        assert!(out.contains("- Editor: vim\n"));
+        assert!(!out.contains("helix"));
+
+        let now = Local::now();
+        let note = provenance(Some("abc"), "merge", now);
+        assert!(note.starts_with(PROVENANCE_MARKER));
+        assert!(note.contains("session=abc"));
+        assert!(note.contains("op=merge"));
+        assert_eq!(strip_provenance(&format!("Fact {}", note)), "Fact");
+    }
+}
@@ -8,6 +8,9 @@ use tracing::{error, info};
 
 pub mod dispatcher;
 
+/// Payload of the built-in memory consolidation job
+pub const CONSOLIDATE_MEMORY: &str = "CONSOLIDATE_MEMORY";
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct JobConfig {
     pub name: String,
@@ -122,6 +125,29 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Schedule memory consolidation (`[memory.consolidation] schedule`)
+    pub async fn register_consolidation_job(&self, schedule: &str) -> Result<()> {
+        let bus = self.bus.clone();
+
+        let job = Job::new_async(schedule, move |_uuid, _l| {
+            let bus = bus.clone();
+            Box::pin(async move {
+                let msg = crate::ingress::IngressMessage::new(
+                    "scheduler:memory_consolidation".to_string(),
+                    CONSOLIDATE_MEMORY.to_string(),
+                    crate::ingress::TrustLevel::TrustedEvent,
+                );
+                if let Err(e) = bus.push(msg).await {
+                    error!("Failed to push memory consolidation job: {}", e);
+                }
+            })
+        })?;
+
+        self.scheduler.add(job).await?;
+        info!("Scheduled memory consolidation ({})", schedule);
+        Ok(())
+    }
+
     pub async fn start(&self) -> Result<()> {
         self.scheduler.start().await?;
         Ok(())
@@ -4,6 +4,8 @@ use crate::config::{Config, SandboxPolicy, WorkdirStrategy};
 use crate::ingress::{IngressBus, IngressMessage, TrustLevel};
 use crate::scheduler::Scheduler;
 use crate::scripting::safety::{CommandSafety, SafetyPolicy};
+use crate::security::egress::Egress;
+use crate::security::AuditLog;
 use deno_core::error::AnyError;
 use deno_core::{op2, v8, JsRuntime, ModuleSpecifier, OpState, RuntimeOptions};
 use serde::{Deserialize, Serialize};
@@ -302,18 +304,39 @@ pub async fn op_fetch(
     state: Rc<RefCell<OpState>>,
     #[string] url: String,
 ) -> Result<String, std::io::Error> {
-    {
+    // Without full network access, only sandbox.allow_domains hosts
+    let allowlist = {
         let state = state.borrow();
         let sandbox = state.borrow::<SandboxState>();
-        if !sandbox.capabilities.net {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::PermissionDenied,
-                "Network access not allowed",
-            ));
+        match Egress::of(&sandbox.policy) {
+            _ if sandbox.capabilities.net => None,
+            Egress::Allowlist(allowlist) => {
+                Some(allowlist.with_audit(AuditLog::for_workspace(&sandbox.workspace)))
+            }
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "Network access not allowed",
+                ))
+            }
         }
-    }
+    };
 
-    let body = reqwest::get(&url)
+    let request = match &allowlist {
+        Some(allowlist) => {
+            let url = allowlist.check_url(&url).map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::PermissionDenied, e.to_string())
+            })?;
+            reqwest::Client::builder()
+                .redirect(allowlist.redirect_policy())
+                .build()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?
+                .get(url)
+                .send()
+        }
+        None => reqwest::Client::new().get(&url).send(),
+    };
+    let body = request
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?
         .text()
@@ -358,7 +381,7 @@ pub async fn op_zier_exec(
         "[DEBUG op_zier_exec] cmd: {:?}, opts.env: {:?}",
         cmd, opts.env
     );
-    let (safety_result, project_dir, sandbox_policy) = {
+    let (safety_result, project_dir, sandbox_policy, audit) = {
         let state = state.borrow();
         let sandbox = state.borrow::<SandboxState>();
         if !sandbox.capabilities.exec {
@@ -376,6 +399,7 @@ pub async fn op_zier_exec(
                 })?,
             sandbox.project_dir.clone(),
             sandbox.policy.clone(),
+            AuditLog::for_workspace(&sandbox.workspace),
         )
     };
 
@@ -447,6 +471,7 @@ pub async fn op_zier_exec(
         &target_cwd,
         Some(merged_env.clone()),
         &sandbox_policy,
+        Some(&audit),
     )
     .await
     .map_err(|e| std::io::Error::other(e.to_string()))?;
@@ -527,11 +552,14 @@ pub async fn op_zier_scheduler_register(
 #[op2(async)]
 pub async fn op_zier_mcp_initialize(
     state: Rc<RefCell<OpState>>,
-    #[serde] configs: Vec<ServerConfig>,
+    #[serde] mut configs: Vec<ServerConfig>,
 ) -> Result<(), std::io::Error> {
     let manager = {
         let state = state.borrow();
         let sandbox = state.borrow::<SandboxState>();
+        for config in &mut configs {
+            config.limits = sandbox.policy.limits_for(&config.name);
+        }
         sandbox.mcp_manager.clone()
     };
 
@@ -972,11 +1000,15 @@ impl DenoRuntime {
                     }
                 }
 
-                // Verify net
+                // Verify net; an allow_domains policy grants the listed hosts only
+                let mut declared_caps = declared_caps;
                 if declared_caps.net && !policy.allow_network {
-                    return Err(anyhow::anyhow!(
-                        "Script declared capability 'net' which is not allowed by sandbox policy"
-                    ));
+                    if policy.allow_domains.is_empty() {
+                        return Err(anyhow::anyhow!(
+                            "Script declared capability 'net' which is not allowed by sandbox policy"
+                        ));
+                    }
+                    declared_caps.net = false;
                 }
 
                 // Verify env
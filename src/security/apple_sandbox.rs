@@ -1,6 +1,17 @@
 use crate::config::SandboxPolicy;
 
 pub fn compile_profile(policy: &SandboxPolicy, executable_path: &str, script_path: &str) -> String {
+    compile_profile_with_proxy(policy, executable_path, script_path, None)
+}
+
+/// Like [`compile_profile`], also allowing TCP to the egress proxy on
+/// `localhost:proxy_port`
+pub fn compile_profile_with_proxy(
+    policy: &SandboxPolicy,
+    executable_path: &str,
+    script_path: &str,
+    proxy_port: Option<u16>,
+) -> String {
     let mut sbpl = String::from("(version 1)\n(deny default)\n(debug deny)\n");
 
     // Base Allowances
@@ -18,6 +29,12 @@ pub fn compile_profile(policy: &SandboxPolicy, executable_path: &str, script_pat
     if policy.allow_network {
         sbpl.push_str("(allow network*)\n");
         sbpl.push_str("(allow system-socket)\n");
+    } else if let Some(port) = proxy_port {
+        sbpl.push_str(&format!(
+            "(allow network-outbound (remote tcp \"localhost:{}\"))\n",
+            port
+        ));
+        sbpl.push_str("(allow system-socket)\n");
     }
 
     // Always allow reading the executable itself and the script being executed
@@ -75,6 +92,7 @@ mod tests {
             allow_write: vec![],
             allow_env: false,
             enable_os_sandbox: true,
+            ..Default::default()
         };
         let profile = compile_profile(&policy, "/usr/bin/python3", "/tmp/script.py");
 
@@ -86,6 +104,18 @@ mod tests {
         assert!(!profile.contains("(allow network*)"));
     }
 
+    #[test]
+    fn test_compile_profile_proxy() {
+        let policy = SandboxPolicy {
+            allow_domains: vec!["api.github.com".to_string()],
+            ..Default::default()
+        };
+        let profile =
+            compile_profile_with_proxy(&policy, "/bin/bash", "/tmp/script.sh", Some(4321));
+        assert!(profile.contains("(remote tcp \"localhost:4321\")"));
+        assert!(!profile.contains("(allow network*)"));
+    }
+
     #[test]
     fn test_compile_profile_network() {
         let policy = SandboxPolicy {
@@ -94,6 +124,7 @@ mod tests {
             allow_write: vec![],
             allow_env: false,
             enable_os_sandbox: true,
+            ..Default::default()
         };
         let profile = compile_profile(&policy, "/bin/bash", "/tmp/script.sh");
 
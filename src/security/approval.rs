@@ -0,0 +1,550 @@
+//! Argument-aware approval rules and saved grants
+//!
+//! `tools.require_approval` only names tools. [`ApprovalRule`]s also match
+//! the call's arguments (command regex, path glob or prefix, URL host) and
+//! decide allow, ask or deny. When the user approves a call "for this
+//! session" or "always", [`grant_for`] derives an allow rule from it: the
+//! command's program for `bash`, the directory of a `path`, the host of a
+//! `url`, else the whole tool. Session grants live in memory; "always"
+//! grants are saved per agent in `{state_dir}/agents/<id>/approvals.json`.
+//!
+//! Paths are compared as the file the tool will touch, never as typed: the
+//! tool resolves them ([`Tool::target_path`](crate::agent::Tool::target_path)),
+//! `.` and `..` are removed, and symlinks in the existing part are
+//! followed. So `notes/../../.bashrc` is not under a grant for `notes`.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+use super::egress::EgressAllowlist;
+use crate::agent::ToolCall;
+use crate::config::{ApprovalRule, RuleAction};
+
+/// How far an approval reaches
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GrantScope {
+    /// This call only
+    #[default]
+    Once,
+    /// Similar calls until the agent exits
+    Session,
+    /// Similar calls from now on, saved for the agent
+    Always,
+}
+
+/// Resolves a `path` argument the way the called tool will; `None` when
+/// the tool doesn't say (the path is then only normalized)
+pub type PathResolver<'a> = &'a dyn Fn(&str) -> Option<PathBuf>;
+
+/// Rule with its patterns compiled
+struct CompiledRule {
+    rule: ApprovalRule,
+    tool: glob::Pattern,
+    command: Option<Regex>,
+    host: Option<EgressAllowlist>,
+}
+
+impl CompiledRule {
+    fn new(rule: ApprovalRule) -> Result<Self> {
+        let tool = glob::Pattern::new(&rule.tool)
+            .with_context(|| format!("Invalid tool pattern: {}", rule.tool))?;
+        let command = match &rule.command {
+            Some(re) => {
+                Some(Regex::new(re).with_context(|| format!("Invalid command regex: {}", re))?)
+            }
+            None => None,
+        };
+        let host = rule
+            .host
+            .as_ref()
+            .map(|h| EgressAllowlist::new(std::slice::from_ref(h)));
+        Ok(Self {
+            rule,
+            tool,
+            command,
+            host,
+        })
+    }
+
+    fn matches(&self, call: &ToolCall, args: &Value, resolve: PathResolver) -> bool {
+        if !self.tool.matches(&call.name) {
+            return false;
+        }
+        if let Some(re) = &self.command {
+            match args["command"].as_str() {
+                Some(command) if re.is_match(command) => {}
+                _ => return false,
+            }
+        }
+        if let Some(pattern) = &self.rule.path {
+            match args["path"].as_str() {
+                Some(path) if path_matches(pattern, path, resolve) => {}
+                _ => return false,
+            }
+        }
+        if let Some(host) = &self.host {
+            match args["url"].as_str().and_then(|u| Url::parse(u).ok()) {
+                Some(url) if host.allows_url(&url) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Glob match, or directory prefix when `pattern` has no wildcards. Both
+/// sides are resolved with [`effective_path`], so a relative pattern means
+/// what the same relative path would mean to the tool.
+fn path_matches(pattern: &str, path: &str, resolve: PathResolver) -> bool {
+    let path = effective_path(path, resolve);
+    let glob = pattern.contains(['*', '?', '[']);
+    let pattern = effective_path(pattern, resolve);
+    if glob {
+        glob::Pattern::new(&pattern.to_string_lossy()).is_ok_and(|p| p.matches_path(&path))
+    } else {
+        path.starts_with(pattern)
+    }
+}
+
+/// The file `path` names: resolved for the tool (else `~` expanded), with
+/// `.` and `..` removed and, when absolute, symlinks in its existing part
+/// followed
+fn effective_path(path: &str, resolve: PathResolver) -> PathBuf {
+    let path = resolve(path).unwrap_or_else(|| PathBuf::from(shellexpand::tilde(path).as_ref()));
+    let path = normalize(&path);
+    if !path.is_absolute() {
+        return path;
+    }
+    // Canonicalize the deepest ancestor that exists, keep the rest
+    let mut rest = Vec::new();
+    let mut existing = path.as_path();
+    loop {
+        if let Ok(real) = existing.canonicalize() {
+            return rest.iter().rev().fold(real, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+/// `path` with `.` dropped and `..` applied lexically. A relative path keeps
+/// the `..`s it can't resolve; an absolute one stops at the root.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Allow rule covering `call` and calls like it
+pub fn grant_for(call: &ToolCall, resolve: PathResolver) -> ApprovalRule {
+    let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+    let mut rule = ApprovalRule {
+        tool: glob::Pattern::escape(&call.name),
+        command: None,
+        path: None,
+        host: None,
+        action: RuleAction::Allow,
+        reason: None,
+    };
+
+    if let Some(command) = args["command"].as_str() {
+        let command = command.trim();
+        // Chained or redirected commands are only granted verbatim
+        rule.command = Some(
+            if command.contains(['&', ';', '|', '`', '$', '<', '>', '\n']) {
+                format!("^{}$", regex::escape(command))
+            } else {
+                let program = command.split_whitespace().next().unwrap_or_default();
+                format!(r"^{}(\s|$)", regex::escape(program))
+            },
+        );
+    } else if let Some(path) = args["path"].as_str() {
+        let path = path.split('#').next().unwrap_or(path);
+        let path = effective_path(path, resolve);
+        rule.path = Some(match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+            _ => glob::Pattern::escape(&path.to_string_lossy()),
+        });
+    } else if let Some(url) = args["url"].as_str().and_then(|u| Url::parse(u).ok()) {
+        rule.host = url.host_str().map(|host| match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        });
+    }
+    rule
+}
+
+/// Short description of what a rule covers, for prompts
+pub fn describe(rule: &ApprovalRule) -> String {
+    let mut text = format!("'{}'", rule.tool);
+    if let Some(command) = &rule.command {
+        text.push_str(&format!(" commands matching /{}/", command));
+    }
+    if let Some(path) = &rule.path {
+        text.push_str(&format!(" on {}", path));
+    }
+    if let Some(host) = &rule.host {
+        text.push_str(&format!(" to {}", host));
+    }
+    text
+}
+
+/// Configured rules plus the grants given from approval prompts
+pub struct ApprovalPolicy {
+    rules: Vec<CompiledRule>,
+    session: Mutex<Vec<CompiledRule>>,
+    saved: Mutex<Vec<CompiledRule>>,
+    /// Where "always" grants are kept; none keeps them in memory
+    store: Option<PathBuf>,
+}
+
+impl ApprovalPolicy {
+    /// Invalid rules are logged and skipped
+    pub fn new(rules: &[ApprovalRule]) -> Self {
+        Self {
+            rules: compile(rules.iter().cloned()),
+            session: Mutex::new(Vec::new()),
+            saved: Mutex::new(Vec::new()),
+            store: None,
+        }
+    }
+
+    /// Load and save "always" grants of `agent_id` next to the workspace
+    pub fn with_store(mut self, workspace: &Path, agent_id: &str) -> Self {
+        let state_dir = workspace.parent().unwrap_or(workspace);
+        let path = state_dir
+            .join("agents")
+            .join(agent_id)
+            .join("approvals.json");
+        let saved: Vec<ApprovalRule> = match std::fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable grants in {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        self.saved = Mutex::new(compile(saved));
+        self.store = Some(path);
+        self
+    }
+
+    /// Outcome for `call`: a matching deny rule, else any grant (allow), else
+    /// the first matching rule. `None` when nothing matches.
+    pub fn evaluate(
+        &self,
+        call: &ToolCall,
+        resolve: PathResolver,
+    ) -> Option<(RuleAction, Option<String>)> {
+        let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+        let outcome = |r: &CompiledRule| (r.rule.action, r.rule.reason.clone());
+
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|r| r.rule.action == RuleAction::Deny && r.matches(call, &args, resolve))
+        {
+            return Some(outcome(rule));
+        }
+        for grants in [&self.session, &self.saved] {
+            if grants
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|r| r.matches(call, &args, resolve))
+            {
+                return Some((RuleAction::Allow, None));
+            }
+        }
+        self.rules
+            .iter()
+            .find(|r| r.matches(call, &args, resolve))
+            .map(outcome)
+    }
+
+    /// Allow calls like `call` for `scope`; returns the rule granted
+    pub fn grant(
+        &self,
+        call: &ToolCall,
+        scope: GrantScope,
+        resolve: PathResolver,
+    ) -> Result<Option<ApprovalRule>> {
+        let rule = grant_for(call, resolve);
+        let compiled = CompiledRule::new(rule.clone())?;
+        match scope {
+            GrantScope::Once => return Ok(None),
+            GrantScope::Session => self.session.lock().unwrap().push(compiled),
+            GrantScope::Always => {
+                let mut saved = self.saved.lock().unwrap();
+                if !saved.iter().any(|r| r.rule == rule) {
+                    saved.push(compiled);
+                    if let Some(path) = &self.store {
+                        let rules: Vec<&ApprovalRule> = saved.iter().map(|r| &r.rule).collect();
+                        if let Some(parent) = path.parent() {
+                            std::fs::create_dir_all(parent)?;
+                        }
+                        std::fs::write(path, serde_json::to_string_pretty(&rules)?)?;
+                    }
+                }
+            }
+        }
+        Ok(Some(rule))
+    }
+}
+
+fn compile(rules: impl IntoIterator<Item = ApprovalRule>) -> Vec<CompiledRule> {
+    rules
+        .into_iter()
+        .filter_map(|rule| match CompiledRule::new(rule) {
+            Ok(rule) => Some(rule),
+            Err(e) => {
+                warn!("Skipping approval rule: {:#}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        }
+    }
+
+    fn none(_: &str) -> Option<PathBuf> {
+        None
+    }
+
+    fn rule(tool: &str, action: RuleAction) -> ApprovalRule {
+        ApprovalRule {
+            tool: tool.to_string(),
+            command: None,
+            path: None,
+            host: None,
+            action,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_rules_match_arguments() {
+        let policy = ApprovalPolicy::new(&[
+            ApprovalRule {
+                command: Some(r"\brm\s+-[a-z]*r".to_string()),
+                ..rule("bash", RuleAction::Deny)
+            },
+            ApprovalRule {
+                command: Some(r"^(ls|git status)\b".to_string()),
+                ..rule("bash", RuleAction::Allow)
+            },
+            ApprovalRule {
+                path: Some("/etc".to_string()),
+                ..rule("write_file", RuleAction::Deny)
+            },
+            ApprovalRule {
+                host: Some("*.github.com".to_string()),
+                ..rule("web_fetch", RuleAction::Allow)
+            },
+            rule("bash", RuleAction::Ask),
+        ]);
+        let bash = |c: &str| call("bash", serde_json::json!({ "command": c }));
+
+        assert_eq!(
+            policy.evaluate(&bash("ls -la"), &none).unwrap().0,
+            RuleAction::Allow
+        );
+        assert_eq!(
+            policy.evaluate(&bash("make"), &none).unwrap().0,
+            RuleAction::Ask
+        );
+        assert_eq!(
+            policy
+                .evaluate(&bash("ls && rm -rf /tmp/x"), &none)
+                .unwrap()
+                .0,
+            RuleAction::Deny
+        );
+        let write = call("write_file", serde_json::json!({ "path": "/etc/hosts" }));
+        assert_eq!(policy.evaluate(&write, &none).unwrap().0, RuleAction::Deny);
+        let write = call("write_file", serde_json::json!({ "path": "/etcetera" }));
+        assert!(policy.evaluate(&write, &none).is_none());
+        let fetch = call(
+            "web_fetch",
+            serde_json::json!({ "url": "https://api.github.com/x" }),
+        );
+        assert_eq!(policy.evaluate(&fetch, &none).unwrap().0, RuleAction::Allow);
+        let fetch = call(
+            "web_fetch",
+            serde_json::json!({ "url": "https://github.com.evil/" }),
+        );
+        assert!(policy.evaluate(&fetch, &none).is_none());
+    }
+
+    #[test]
+    fn test_grant_derivation() {
+        let bash = call(
+            "bash",
+            serde_json::json!({ "command": "git status --short" }),
+        );
+        let grant = grant_for(&bash, &none);
+        assert_eq!(grant.command.as_deref(), Some(r"^git(\s|$)"));
+
+        let chained = call("bash", serde_json::json!({ "command": "git pull && make" }));
+        assert_eq!(
+            grant_for(&chained, &none).command.as_deref(),
+            Some(r"^git pull \&\& make$")
+        );
+
+        let write = call("write_file", serde_json::json!({ "path": "src/main.rs" }));
+        assert_eq!(grant_for(&write, &none).path.as_deref(), Some("src"));
+
+        let fetch = call(
+            "web_fetch",
+            serde_json::json!({ "url": "http://localhost:8080/a" }),
+        );
+        assert_eq!(
+            grant_for(&fetch, &none).host.as_deref(),
+            Some("localhost:8080")
+        );
+    }
+
+    #[test]
+    fn test_grants_persist_per_agent() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        let policy = ApprovalPolicy::new(&[
+            ApprovalRule {
+                command: Some(r"\bpush\b".to_string()),
+                ..rule("bash", RuleAction::Deny)
+            },
+            rule("bash", RuleAction::Ask),
+        ])
+        .with_store(&workspace, "main");
+        let status = call("bash", serde_json::json!({ "command": "git status" }));
+        let push = call("bash", serde_json::json!({ "command": "git push" }));
+
+        policy.grant(&status, GrantScope::Always, &none).unwrap();
+        assert_eq!(
+            policy.evaluate(&status, &none).unwrap().0,
+            RuleAction::Allow
+        );
+        // Grants never override deny rules
+        assert_eq!(policy.evaluate(&push, &none).unwrap().0, RuleAction::Deny);
+
+        let reloaded =
+            ApprovalPolicy::new(&[rule("bash", RuleAction::Ask)]).with_store(&workspace, "main");
+        assert_eq!(
+            reloaded.evaluate(&status, &none).unwrap().0,
+            RuleAction::Allow
+        );
+        let other =
+            ApprovalPolicy::new(&[rule("bash", RuleAction::Ask)]).with_store(&workspace, "other");
+        assert_eq!(other.evaluate(&status, &none).unwrap().0, RuleAction::Ask);
+    }
+
+    #[test]
+    fn test_paths_are_resolved_before_matching() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().canonicalize().unwrap();
+        let project = home.join("project");
+        std::fs::create_dir_all(project.join("notes")).unwrap();
+        std::fs::create_dir_all(home.join(".ssh")).unwrap();
+        // How the file tools resolve relative paths
+        let resolve = |p: &str| Some(project.join(p));
+        let write = |p: &str| call("write_file", serde_json::json!({ "path": p }));
+
+        // A grant for notes/ doesn't reach outside it
+        let policy = ApprovalPolicy::new(&[rule("write_file", RuleAction::Ask)]);
+        let grant = policy
+            .grant(&write("notes/a.md"), GrantScope::Session, &resolve)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            grant.path.as_deref(),
+            Some(project.join("notes").to_str().unwrap())
+        );
+        let allowed = |p: &str| policy.evaluate(&write(p), &resolve).unwrap().0;
+        assert_eq!(allowed("notes/b.md"), RuleAction::Allow);
+        assert_eq!(allowed("./notes/new/c.md"), RuleAction::Allow);
+        assert_eq!(allowed("notes/../../.bashrc"), RuleAction::Ask);
+        assert_eq!(allowed("notes/../secrets.md"), RuleAction::Ask);
+        let absolute = project.join("notes/d.md");
+        assert_eq!(allowed(absolute.to_str().unwrap()), RuleAction::Allow);
+
+        // A deny rule holds for every spelling of the path
+        let ssh = home.join(".ssh");
+        let policy = ApprovalPolicy::new(&[ApprovalRule {
+            path: Some(ssh.to_string_lossy().to_string()),
+            ..rule("write_file", RuleAction::Deny)
+        }]);
+        let denied = |p: &str| {
+            policy
+                .evaluate(&write(p), &resolve)
+                .is_some_and(|(action, _)| action == RuleAction::Deny)
+        };
+        let sneaky = home.join("x/../.ssh/authorized_keys");
+        assert!(denied(sneaky.to_str().unwrap()));
+        assert!(denied("../.ssh/authorized_keys"));
+        assert!(denied("notes/../../.ssh/config"));
+        assert!(!denied("notes/ssh.md"));
+
+        // Relative patterns mean what the tool makes of them
+        let policy = ApprovalPolicy::new(&[ApprovalRule {
+            path: Some("notes".to_string()),
+            ..rule("write_file", RuleAction::Deny)
+        }]);
+        let absolute = project.join("notes/e.md");
+        assert!(policy
+            .evaluate(&write(absolute.to_str().unwrap()), &resolve)
+            .is_some());
+        assert!(policy.evaluate(&write("notes2/e.md"), &resolve).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_paths_follow_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("secret")).unwrap();
+        std::os::unix::fs::symlink(root.join("secret"), root.join("link")).unwrap();
+        let policy = ApprovalPolicy::new(&[ApprovalRule {
+            path: Some(root.join("secret").to_string_lossy().to_string()),
+            ..rule("write_file", RuleAction::Deny)
+        }]);
+        let via_link = root.join("link/new.txt");
+        let write = call(
+            "write_file",
+            serde_json::json!({ "path": via_link.to_str().unwrap() }),
+        );
+        assert_eq!(policy.evaluate(&write, &none).unwrap().0, RuleAction::Deny);
+    }
+}
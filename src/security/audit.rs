@@ -0,0 +1,71 @@
+//! Append-only audit log of security-relevant decisions
+//!
+//! Each record is one JSON line: `{"ts": ..., "event": ..., ...details}`.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Audit log file, `{state_dir}/logs/audit.jsonl` by default
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Audit log of the state directory that holds `workspace`
+    pub fn for_workspace(workspace: &Path) -> Self {
+        let state_dir = workspace.parent().unwrap_or(workspace);
+        Self::new(state_dir.join("logs").join("audit.jsonl"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record. Failures are logged, never surfaced.
+    pub fn record(&self, event: &str, details: Value) {
+        let mut entry = json!({
+            "ts": Utc::now().to_rfc3339(),
+            "event": event,
+        });
+        if let (Some(entry), Value::Object(details)) = (entry.as_object_mut(), details) {
+            entry.extend(details);
+        }
+        info!(target: "audit", "{}", entry);
+
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+            })
+            .and_then(|mut file| writeln!(file, "{}", entry));
+        if let Err(e) = result {
+            warn!("Failed to write audit log {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Read all records (oldest first), skipping malformed lines
+    pub fn entries(&self) -> Vec<Value> {
+        std::fs::read_to_string(&self.path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,543 @@
+//! Domain-allowlisted network egress
+//!
+//! `SandboxPolicy.allow_domains` lets a policy reach listed hosts without
+//! `allow_network`. In-process clients (`op_fetch`, `web_fetch`) check URLs
+//! and redirects against the [`EgressAllowlist`] directly. Sandboxed
+//! commands get no network of their own and go through a local HTTP(S)
+//! CONNECT proxy that enforces the list and logs every connection. There is
+//! one proxy per distinct allowlist and audit log, served on a background
+//! runtime.
+//!
+//! A command in a network namespace can't reach the host's loopback, so it
+//! runs under `zier-alpha egress-bridge`: inside the namespace the bridge
+//! listens on `127.0.0.1:`[`BRIDGE_PORT`] and forwards to the proxy's Unix
+//! socket, which is bind-mounted into the sandbox.
+
+use anyhow::{bail, Context, Result};
+use reqwest::Url;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{debug, info, warn};
+
+use super::AuditLog;
+use crate::config::SandboxPolicy;
+
+/// Port the bridge listens on inside a sandbox's network namespace
+pub const BRIDGE_PORT: u16 = 3128;
+
+/// Largest request head the proxy reads before giving up
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Ports an entry without `:port` allows
+const DEFAULT_PORTS: &[u16] = &[80, 443];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Rule {
+    host: String,
+    /// `*.example.com`: subdomains of `host`, not `host` itself
+    wildcard: bool,
+    port: Option<u16>,
+}
+
+impl Rule {
+    fn parse(entry: &str) -> Option<Self> {
+        let entry = entry.trim();
+        let (host, port) = match split_host_port(entry) {
+            Some((host, port)) => (host, Some(port)),
+            None => (normalize_host(entry), None),
+        };
+        let (host, wildcard) = match host.strip_prefix("*.") {
+            Some(rest) => (rest.to_string(), true),
+            None => (host, false),
+        };
+        (!host.is_empty()).then_some(Self {
+            host,
+            wildcard,
+            port,
+        })
+    }
+
+    fn matches(&self, host: &str, port: u16) -> bool {
+        let host_ok = if self.wildcard {
+            host.strip_suffix(self.host.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+        } else {
+            host == self.host
+        };
+        let port_ok = match self.port {
+            Some(p) => p == port,
+            None => DEFAULT_PORTS.contains(&port),
+        };
+        host_ok && port_ok
+    }
+}
+
+/// Hosts (and ports) a policy may connect to: `api.github.com`,
+/// `*.githubusercontent.com`, `example.com:8443`. Entries without a port
+/// allow 80 and 443.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EgressAllowlist {
+    rules: Vec<Rule>,
+    /// Where this list's decisions are recorded
+    audit: Option<AuditLog>,
+}
+
+impl EgressAllowlist {
+    pub fn new(entries: &[String]) -> Self {
+        let mut rules: Vec<Rule> = entries
+            .iter()
+            .filter_map(|e| {
+                let rule = Rule::parse(e);
+                if rule.is_none() {
+                    warn!("Ignoring invalid allow_domains entry: {:?}", e);
+                }
+                rule
+            })
+            .collect();
+        rules.sort_by(|a, b| (&a.host, a.wildcard, a.port).cmp(&(&b.host, b.wildcard, b.port)));
+        rules.dedup();
+        Self { rules, audit: None }
+    }
+
+    /// Record allowed and denied connections in `audit`
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        let host = normalize_host(host);
+        self.rules.iter().any(|r| r.matches(&host, port))
+    }
+
+    pub fn allows_url(&self, url: &Url) -> bool {
+        matches!(url.scheme(), "http" | "https")
+            && match (url.host_str(), url.port_or_known_default()) {
+                (Some(host), Some(port)) => self.allows(host, port),
+                _ => false,
+            }
+    }
+
+    /// Parse `url` and refuse it unless allowed
+    pub fn check_url(&self, url: &str) -> Result<Url> {
+        let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {}", url))?;
+        if !self.allows_url(&parsed) {
+            self.record(
+                "fetch",
+                parsed.host_str().unwrap_or(""),
+                parsed.port_or_known_default().unwrap_or(0),
+                false,
+            );
+            bail!(
+                "Blocked by egress policy: {} is not in sandbox.allow_domains",
+                parsed.host_str().unwrap_or(url)
+            );
+        }
+        Ok(parsed)
+    }
+
+    /// Redirect policy following only redirects to allowed URLs
+    pub fn redirect_policy(&self) -> reqwest::redirect::Policy {
+        let allowlist = self.clone();
+        reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= 10 {
+                attempt.error("too many redirects")
+            } else if allowlist.allows_url(attempt.url()) {
+                attempt.follow()
+            } else {
+                let blocked = format!("Blocked by egress policy: redirect to {}", attempt.url());
+                attempt.error(blocked)
+            }
+        })
+    }
+
+    /// Log a decision, and audit it when the list has an audit log
+    fn record(&self, via: &str, host: &str, port: u16, allowed: bool) {
+        if allowed {
+            info!(target: "egress", "{} {}:{} allowed", via, host, port);
+        } else {
+            warn!(target: "egress", "{} {}:{} denied", via, host, port);
+        }
+        if let Some(audit) = &self.audit {
+            audit.record(
+                "egress",
+                json!({ "via": via, "host": host, "port": port, "allowed": allowed }),
+            );
+        }
+    }
+}
+
+/// Network access a sandbox policy grants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Egress {
+    /// `allow_network`: anything
+    Open,
+    /// Only the `allow_domains` hosts
+    Allowlist(EgressAllowlist),
+    /// No network
+    Closed,
+}
+
+impl Egress {
+    pub fn of(policy: &SandboxPolicy) -> Self {
+        if policy.allow_network {
+            return Egress::Open;
+        }
+        let allowlist = EgressAllowlist::new(&policy.allow_domains);
+        if allowlist.is_empty() {
+            Egress::Closed
+        } else {
+            Egress::Allowlist(allowlist)
+        }
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+/// `host:port` or `[v6]:port`
+fn split_host_port(target: &str) -> Option<(String, u16)> {
+    let (host, port) = if let Some(rest) = target.strip_prefix('[') {
+        let (host, port) = rest.split_once("]:")?;
+        (host, port)
+    } else {
+        target.rsplit_once(':')?
+    };
+    Some((normalize_host(host), port.parse().ok()?))
+}
+
+/// Environment pointing HTTP clients at a proxy on `127.0.0.1:port`
+pub fn proxy_env(port: u16) -> HashMap<String, String> {
+    let url = format!("http://127.0.0.1:{}", port);
+    let mut env = HashMap::new();
+    for key in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+        env.insert(key.to_string(), url.clone());
+        env.insert(key.to_ascii_lowercase(), url.clone());
+    }
+    env.insert("NO_PROXY".to_string(), String::new());
+    env.insert("no_proxy".to_string(), String::new());
+    env
+}
+
+/// A running proxy for one allowlist
+#[derive(Debug)]
+pub struct EgressProxy {
+    /// Loopback TCP listener, for sandboxes sharing the host network
+    pub addr: SocketAddr,
+    /// Unix socket, for the bridge inside a network namespace
+    pub socket: PathBuf,
+    /// Private (0700) directory holding `socket`, removed on drop
+    _dir: tempfile::TempDir,
+}
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("egress-proxy")
+            .enable_all()
+            .build()
+            .expect("Failed to start egress proxy runtime")
+    })
+}
+
+/// Proxy enforcing `allowlist`, started on first use
+#[cfg(unix)]
+pub fn proxy_for(allowlist: &EgressAllowlist) -> Result<Arc<EgressProxy>> {
+    static PROXIES: OnceLock<Mutex<HashMap<EgressAllowlist, Arc<EgressProxy>>>> = OnceLock::new();
+    let mut proxies = PROXIES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(proxy) = proxies.get(allowlist) {
+        return Ok(proxy.clone());
+    }
+
+    let tcp = std::net::TcpListener::bind("127.0.0.1:0")?;
+    tcp.set_nonblocking(true)?;
+    let addr = tcp.local_addr()?;
+
+    // A new directory with a random name that only we can enter, so no
+    // other user can plant or swap the socket. An existing path is never
+    // reused.
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::Builder::new()
+        .prefix("zier-egress-")
+        .permissions(std::fs::Permissions::from_mode(0o700))
+        .tempdir()
+        .context("Failed to create the egress proxy socket directory")?;
+    let socket = dir.path().join("proxy.sock");
+    let unix = std::os::unix::net::UnixListener::bind(&socket)
+        .with_context(|| format!("Failed to bind {}", socket.display()))?;
+    unix.set_nonblocking(true)?;
+
+    let allowlist = Arc::new(allowlist.clone());
+    let list = allowlist.clone();
+    runtime().spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(tcp) {
+            Ok(l) => l,
+            Err(e) => return warn!("Egress proxy listener failed: {}", e),
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle(stream, list.clone()));
+        }
+    });
+    let list = allowlist.clone();
+    runtime().spawn(async move {
+        let listener = match tokio::net::UnixListener::from_std(unix) {
+            Ok(l) => l,
+            Err(e) => return warn!("Egress proxy socket failed: {}", e),
+        };
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle(stream, list.clone()));
+        }
+    });
+
+    info!(
+        "Egress proxy on {} and {} for {} rule(s)",
+        addr,
+        socket.display(),
+        allowlist.rules.len()
+    );
+    let proxy = Arc::new(EgressProxy {
+        addr,
+        socket,
+        _dir: dir,
+    });
+    proxies.insert((*allowlist).clone(), proxy.clone());
+    Ok(proxy)
+}
+
+async fn handle<S>(client: S, allowlist: Arc<EgressAllowlist>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Err(e) = serve(client, &allowlist).await {
+        debug!("Egress proxy connection ended: {}", e);
+    }
+}
+
+async fn serve<S>(mut client: S, allowlist: &EgressAllowlist) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut buf = Vec::with_capacity(4096);
+    let head_end = loop {
+        let mut chunk = [0u8; 4096];
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            bail!("request head too large");
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let (request_line, rest) = head.split_once("\r\n").unwrap_or((&head, ""));
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return respond(&mut client, "400 Bad Request", "Malformed request").await;
+    };
+
+    // CONNECT tunnels as-is; plain HTTP is forwarded in origin form
+    let (host, port, forward) = if method.eq_ignore_ascii_case("CONNECT") {
+        let Some((host, port)) = split_host_port(target) else {
+            return respond(&mut client, "400 Bad Request", "Malformed CONNECT target").await;
+        };
+        (host, port, None)
+    } else {
+        let url = match Url::parse(target) {
+            Ok(url) if url.scheme() == "http" => url,
+            _ => {
+                let msg = "Only absolute http:// URLs and CONNECT are proxied";
+                return respond(&mut client, "400 Bad Request", msg).await;
+            }
+        };
+        let host = normalize_host(url.host_str().unwrap_or(""));
+        let port = url.port_or_known_default().unwrap_or(80);
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        let mut request = format!("{} {} {}\r\n{}", method, path, version, rest).into_bytes();
+        request.extend_from_slice(&buf[head_end..]);
+        (host, port, Some(request))
+    };
+
+    let via = if forward.is_some() { "http" } else { "connect" };
+    let allowed = allowlist.allows(&host, port);
+    allowlist.record(via, &host, port, allowed);
+    if !allowed {
+        let msg = format!("Blocked by egress policy: {}:{}", host, port);
+        return respond(&mut client, "403 Forbidden", &msg).await;
+    }
+
+    let mut upstream = match TcpStream::connect((host.as_str(), port)).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            let msg = format!("Failed to connect to {}:{}: {}", host, port, e);
+            return respond(&mut client, "502 Bad Gateway", &msg).await;
+        }
+    };
+    match forward {
+        Some(request) => upstream.write_all(&request).await?,
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+            upstream.write_all(&buf[head_end..]).await?;
+        }
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+async fn respond<S>(client: &mut S, status: &str, message: &str) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        message.len(),
+        message
+    );
+    client.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Executable providing the `egress-bridge` subcommand: `$ZIER_ALPHA_BIN`,
+/// else the running binary
+pub fn bridge_executable() -> Result<PathBuf> {
+    match std::env::var_os("ZIER_ALPHA_BIN") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => std::env::current_exe().context("Failed to locate the zier-alpha executable"),
+    }
+}
+
+/// Arguments running `command` under the bridge
+pub fn bridge_args(socket: &Path, command: &str, args: &[String]) -> Vec<String> {
+    let mut bridged = vec![
+        "egress-bridge".to_string(),
+        "--socket".to_string(),
+        socket.to_string_lossy().to_string(),
+        "--port".to_string(),
+        BRIDGE_PORT.to_string(),
+        "--".to_string(),
+        command.to_string(),
+    ];
+    bridged.extend_from_slice(args);
+    bridged
+}
+
+/// Body of `zier-alpha egress-bridge`: forward `127.0.0.1:port` to the
+/// proxy at `socket` while `command` runs. Returns its exit code.
+#[cfg(unix)]
+pub async fn run_bridge(socket: &Path, port: u16, command: &str, args: &[String]) -> Result<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    #[cfg(target_os = "linux")]
+    loopback_up();
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to listen on 127.0.0.1:{}", port))?;
+    let socket = socket.to_path_buf();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let socket = socket.clone();
+            tokio::spawn(async move {
+                if let Ok(mut proxy) = tokio::net::UnixStream::connect(&socket).await {
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut proxy).await;
+                }
+            });
+        }
+    });
+
+    let status = tokio::process::Command::new(command)
+        .args(args)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run {}", command))?;
+    Ok(status
+        .code()
+        .unwrap_or_else(|| 128 + status.signal().unwrap_or(0)))
+}
+
+/// A fresh network namespace has `lo` down; bwrap brings it up, unshare doesn't
+#[cfg(target_os = "linux")]
+fn loopback_up() {
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        if fd < 0 {
+            return;
+        }
+        let mut req: libc::ifreq = std::mem::zeroed();
+        for (i, b) in b"lo".iter().enumerate() {
+            req.ifr_name[i] = *b as libc::c_char;
+        }
+        if libc::ioctl(fd, libc::SIOCGIFFLAGS as _, &mut req) == 0 {
+            req.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short;
+            libc::ioctl(fd, libc::SIOCSIFFLAGS as _, &req);
+        }
+        libc::close(fd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_rules() {
+        let list = EgressAllowlist::new(&[
+            "api.github.com".to_string(),
+            "*.githubusercontent.com".to_string(),
+            "example.com:8443".to_string(),
+        ]);
+        assert!(list.allows("api.github.com", 443));
+        assert!(list.allows("API.GitHub.com.", 80));
+        assert!(!list.allows("api.github.com", 22));
+        assert!(!list.allows("github.com", 443));
+        assert!(list.allows("raw.githubusercontent.com", 443));
+        assert!(!list.allows("githubusercontent.com", 443));
+        assert!(!list.allows("evilgithubusercontent.com", 443));
+        assert!(list.allows("example.com", 8443));
+        assert!(!list.allows("example.com", 443));
+
+        assert!(list.check_url("https://api.github.com/repos").is_ok());
+        assert!(list.check_url("https://example.org/").is_err());
+        assert!(list.check_url("file:///etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_egress_of_policy() {
+        let mut policy = SandboxPolicy::default();
+        assert_eq!(Egress::of(&policy), Egress::Closed);
+        policy.allow_domains = vec!["api.github.com".to_string()];
+        assert!(matches!(Egress::of(&policy), Egress::Allowlist(_)));
+        policy.allow_network = true;
+        assert_eq!(Egress::of(&policy), Egress::Open);
+    }
+}
@@ -0,0 +1,366 @@
+//! Resource limits for tool processes
+//!
+//! [`ResourceLimits`] are enforced on the child: rlimits set between `fork`
+//! and `exec`, and on Linux a cgroup v2 child group (`memory.max`,
+//! `pids.max`) when the daemon's own cgroup delegates those controllers.
+//! Output is read as it's produced into buffers capped at
+//! `max_output_bytes`, so a chatty command can't exhaust memory.
+//! Reading stops shortly after the command exits or is killed, even when a
+//! descendant that left the process group still holds the pipes.
+
+use anyhow::{Context, Result};
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::config::ResourceLimits;
+
+/// How long output is still read after the command exited or was killed
+const PIPE_GRACE: Duration = Duration::from_secs(1);
+
+/// Keeps per-command enforcement state (the cgroup) alive. Dropping it kills
+/// whatever is left in the cgroup and removes it.
+#[derive(Default)]
+pub struct LimitGuard {
+    #[cfg(target_os = "linux")]
+    cgroup: Option<cgroup::Cgroup>,
+}
+
+/// Install `limits` on `cmd`. Keep the guard until the child has exited.
+pub fn apply(cmd: &mut Command, limits: &ResourceLimits) -> LimitGuard {
+    #[allow(unused_mut)]
+    let mut guard = LimitGuard::default();
+
+    #[cfg(target_os = "linux")]
+    {
+        guard.cgroup = cgroup::Cgroup::create(limits);
+        if let Some(cgroup) = &guard.cgroup {
+            let fd = cgroup.procs_fd();
+            unsafe {
+                cmd.pre_exec(move || {
+                    // "0" moves the writing process
+                    if libc::write(fd, b"0".as_ptr().cast(), 1) != 1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        #[cfg(target_os = "linux")]
+        let pids_in_cgroup = guard.cgroup.is_some();
+        #[cfg(not(target_os = "linux"))]
+        let pids_in_cgroup = false;
+
+        let rlimits = rlimits(limits, pids_in_cgroup);
+        if !rlimits.is_empty() {
+            unsafe {
+                cmd.pre_exec(move || {
+                    for (resource, value) in &rlimits {
+                        let limit = libc::rlimit {
+                            rlim_cur: *value,
+                            rlim_max: *value,
+                        };
+                        if libc::setrlimit(*resource, &limit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    guard
+}
+
+#[cfg(target_os = "linux")]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(target_os = "linux")))]
+type Resource = libc::c_int;
+
+#[cfg(unix)]
+fn rlimits(limits: &ResourceLimits, pids_in_cgroup: bool) -> Vec<(Resource, libc::rlim_t)> {
+    let mut rlimits = Vec::new();
+    if let Some(mb) = limits.memory_mb {
+        rlimits.push((libc::RLIMIT_AS, (mb * 1024 * 1024) as libc::rlim_t));
+    }
+    if let Some(seconds) = limits.cpu_seconds {
+        rlimits.push((libc::RLIMIT_CPU, seconds as libc::rlim_t));
+    }
+    if let (Some(max), false) = (limits.max_processes, pids_in_cgroup) {
+        rlimits.push((libc::RLIMIT_NPROC, max as libc::rlim_t));
+    }
+    rlimits
+}
+
+/// Run `cmd` to completion under `limits`, like `Command::output` but with
+/// capped output. Past the wall clock limit the process group is killed and
+/// what it printed so far is returned, with a marker on stderr. Pipes still
+/// open [`PIPE_GRACE`] after the exit (a daemonized grandchild) are dropped.
+pub async fn output(mut cmd: Command, limits: &ResourceLimits) -> Result<Output> {
+    let _guard = apply(&mut cmd, limits);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn().context("Failed to spawn command")?;
+    let mut stdout = child.stdout.take().context("Failed to capture stdout")?;
+    let mut stderr = child.stderr.take().context("Failed to capture stderr")?;
+    let mut out = Capped::new(limits.output_cap());
+    let mut err = Capped::new(limits.output_cap());
+
+    let (exited_tx, exited_rx) = tokio::sync::oneshot::channel::<()>();
+    let wait = async {
+        let result = match limits.wall_clock_secs {
+            None => (child.wait().await, false),
+            Some(secs) => tokio::select! {
+                status = child.wait() => (status, false),
+                _ = tokio::time::sleep(Duration::from_secs(secs)) => {
+                    kill_group(&mut child);
+                    (child.wait().await, true)
+                }
+            },
+        };
+        let _ = exited_tx.send(());
+        result
+    };
+    let reads = async {
+        let both =
+            async { tokio::try_join!(out.read_from(&mut stdout), err.read_from(&mut stderr)) };
+        let grace = async {
+            let _ = exited_rx.await;
+            tokio::time::sleep(PIPE_GRACE).await;
+        };
+        tokio::select! {
+            result = both => result.map(|_| ()),
+            _ = grace => {
+                debug!("Command exited but its output pipes are still open; not waiting");
+                Ok(())
+            }
+        }
+    };
+    let (read, (status, killed)) = tokio::join!(reads, wait);
+    read?;
+
+    let mut stderr = err.finish();
+    if killed {
+        let secs = limits.wall_clock_secs.unwrap_or_default();
+        stderr.extend_from_slice(
+            format!("\n[killed: wall clock limit of {}s exceeded]\n", secs).as_bytes(),
+        );
+    }
+    Ok(Output {
+        status: status?,
+        stdout: out.finish(),
+        stderr,
+    })
+}
+
+fn kill_group(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+        return;
+    }
+    let _ = child.start_kill();
+}
+
+/// Read `reader` to the end, keeping at most `cap` bytes. Dropped bytes are
+/// replaced by a marker saying how many there were.
+pub async fn read_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    cap: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut capped = Capped::new(cap);
+    capped.read_from(&mut reader).await?;
+    Ok(capped.finish())
+}
+
+/// Output of one stream, kept up to `cap` bytes. What was read survives
+/// the reading future being dropped.
+struct Capped {
+    buf: Vec<u8>,
+    dropped: u64,
+    cap: usize,
+}
+
+impl Capped {
+    fn new(cap: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            dropped: 0,
+            cap,
+        }
+    }
+
+    async fn read_from<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> std::io::Result<()> {
+        let mut chunk = [0u8; 8192];
+        loop {
+            let n = reader.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            let keep = self.cap.saturating_sub(self.buf.len()).min(n);
+            self.buf.extend_from_slice(&chunk[..keep]);
+            self.dropped += (n - keep) as u64;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.dropped > 0 {
+            debug!("Dropped {} bytes of command output", self.dropped);
+            let marker = truncation_marker(self.dropped, self.cap);
+            self.buf.extend_from_slice(marker.as_bytes());
+        }
+        self.buf
+    }
+}
+
+pub fn truncation_marker(dropped: u64, cap: usize) -> String {
+    format!(
+        "\n[output truncated: {} bytes omitted, limit is {} bytes]\n",
+        dropped, cap
+    )
+}
+
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use std::fs;
+    use std::io::Write;
+    use std::os::fd::{AsRawFd, RawFd};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tracing::debug;
+
+    use crate::config::ResourceLimits;
+
+    const ROOT: &str = "/sys/fs/cgroup";
+
+    pub struct Cgroup {
+        path: PathBuf,
+        procs: fs::File,
+    }
+
+    impl Cgroup {
+        /// Child of the daemon's cgroup with the memory and pids limits, or
+        /// `None` when cgroup v2 isn't delegated to us (rlimits still apply)
+        pub fn create(limits: &ResourceLimits) -> Option<Self> {
+            if limits.memory_mb.is_none() && limits.max_processes.is_none() {
+                return None;
+            }
+            let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+            let relative = own.lines().find_map(|l| l.strip_prefix("0::"))?;
+            let parent = Path::new(ROOT).join(relative.trim().trim_start_matches('/'));
+
+            // Only a cgroup2 hierarchy (not a v1/hybrid tmpfs) that hands
+            // the controllers down to children
+            let delegated = fs::read_to_string(parent.join("cgroup.subtree_control")).ok()?;
+            let needed = [
+                (limits.memory_mb.is_some(), "memory"),
+                (limits.max_processes.is_some(), "pids"),
+            ];
+            if needed
+                .iter()
+                .any(|(used, c)| *used && !delegated.split_whitespace().any(|d| d == *c))
+            {
+                return None;
+            }
+
+            static NEXT: AtomicU64 = AtomicU64::new(0);
+            let name = format!(
+                "zier-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = parent.join(name);
+            fs::create_dir(&path).ok()?;
+
+            // Interface files exist already; never create them
+            let open = |file: &str| fs::OpenOptions::new().write(true).open(path.join(file));
+            let configure = || -> std::io::Result<fs::File> {
+                if let Some(mb) = limits.memory_mb {
+                    open("memory.max")?.write_all((mb * 1024 * 1024).to_string().as_bytes())?;
+                }
+                if let Some(max) = limits.max_processes {
+                    open("pids.max")?.write_all(max.to_string().as_bytes())?;
+                }
+                open("cgroup.procs")
+            };
+            match configure() {
+                Ok(procs) => Some(Self { path, procs }),
+                Err(e) => {
+                    debug!("cgroup v2 limits unavailable, using rlimits: {}", e);
+                    let _ = fs::remove_dir(&path);
+                    None
+                }
+            }
+        }
+
+        pub fn procs_fd(&self) -> RawFd {
+            self.procs.as_raw_fd()
+        }
+    }
+
+    impl Drop for Cgroup {
+        fn drop(&mut self) {
+            if let Ok(mut kill) = fs::OpenOptions::new()
+                .write(true)
+                .open(self.path.join("cgroup.kill"))
+            {
+                let _ = kill.write_all(b"1");
+            }
+            for _ in 0..20 {
+                if fs::remove_dir(&self.path).is_ok() {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            debug!("Could not remove cgroup {}", self.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_capped() {
+        let data = vec![b'x'; 100];
+        let out = read_capped(&data[..], 10).await.unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("xxxxxxxxxx\n[output truncated: 90 bytes omitted"));
+
+        let out = read_capped(&data[..], 100).await.unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_tool_limits_override() {
+        let mut policy = crate::config::SandboxPolicy::default();
+        policy.limits.memory_mb = Some(512);
+        policy.limits.wall_clock_secs = Some(60);
+        policy.tool_limits.insert(
+            "bash".to_string(),
+            ResourceLimits {
+                memory_mb: Some(2048),
+                ..Default::default()
+            },
+        );
+        let bash = policy.limits_for("bash");
+        assert_eq!(bash.memory_mb, Some(2048));
+        assert_eq!(bash.wall_clock_secs, Some(60));
+        assert_eq!(policy.limits_for("other").memory_mb, Some(512));
+        assert_eq!(bash.output_cap(), crate::config::DEFAULT_MAX_OUTPUT_BYTES);
+    }
+}
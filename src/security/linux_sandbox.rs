@@ -0,0 +1,312 @@
+//! Native Linux sandbox backend
+//!
+//! Servers and containers often have neither `bwrap` nor unprivileged user
+//! namespaces. This backend needs neither: the child confines itself between
+//! `fork` and `exec` with Landlock filesystem rules derived from
+//! [`SandboxPolicy`] and, unless network access is allowed, a seccomp filter
+//! that refuses internet sockets. With an egress proxy the filter lets TCP
+//! sockets through and Landlock (ABI 4+) limits connects to the proxy port.
+//! Both are prepared in the parent so the `pre_exec` hook only makes syscalls.
+
+use anyhow::{bail, Context, Result};
+use landlock::{
+    path_beneath_rules, Access, AccessFs, AccessNet, NetPort, Ruleset, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr, RulesetStatus, ABI,
+};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::{LinuxSandboxBackend, SandboxPolicy};
+
+/// Landlock ABI the rules are written against; older kernels get a subset
+const LANDLOCK_ABI: ABI = ABI::V5;
+
+/// System paths commands may read and execute, like the bubblewrap binds
+const SYSTEM_READ: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/etc/alternatives",
+    "/etc/ssl",
+    "/etc/resolv.conf",
+    "/etc/ld.so.cache",
+    "/proc",
+];
+
+/// Socket families refused when `allow_network` is false
+const DENIED_FAMILIES: &[i32] = &[libc::AF_INET, libc::AF_INET6, libc::AF_PACKET];
+
+/// First Landlock ABI with TCP connect rules
+const LANDLOCK_NET_ABI: i32 = 4;
+
+/// What the running system offers for sandboxing
+#[derive(Debug, Clone)]
+pub struct SandboxProbe {
+    /// `bwrap` could be started
+    pub bubblewrap: bool,
+    /// Landlock ABI version of the kernel, if enabled
+    pub landlock_abi: Option<i32>,
+    /// Seccomp filters are supported for this architecture
+    pub seccomp: bool,
+    /// Unprivileged user namespaces aren't disabled by sysctl
+    pub user_namespaces: bool,
+}
+
+impl SandboxProbe {
+    /// Probe once per process
+    pub fn get() -> &'static SandboxProbe {
+        static PROBE: OnceLock<SandboxProbe> = OnceLock::new();
+        PROBE.get_or_init(Self::run)
+    }
+
+    fn run() -> Self {
+        let bubblewrap = std::process::Command::new("bwrap")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+
+        // landlock_create_ruleset(NULL, 0, LANDLOCK_CREATE_RULESET_VERSION)
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<libc::c_void>(),
+                0usize,
+                1u32,
+            )
+        };
+        let landlock_abi = (abi > 0).then_some(abi as i32);
+
+        let seccomp = unsafe { libc::prctl(libc::PR_GET_SECCOMP) } >= 0
+            && seccompiler::TargetArch::try_from(std::env::consts::ARCH).is_ok();
+
+        let disabled = |path: &str| {
+            std::fs::read_to_string(path)
+                .map(|v| v.trim() == "0")
+                .unwrap_or(false)
+        };
+        let user_namespaces = !disabled("/proc/sys/kernel/unprivileged_userns_clone")
+            && !disabled("/proc/sys/user/max_user_namespaces");
+
+        Self {
+            bubblewrap,
+            landlock_abi,
+            seccomp,
+            user_namespaces,
+        }
+    }
+
+    /// Backend `run_sandboxed_command` uses for `policy`
+    pub fn backend(&self, policy: &SandboxPolicy) -> Result<LinuxSandboxBackend> {
+        match policy.linux_backend {
+            LinuxSandboxBackend::Auto if self.bubblewrap => Ok(LinuxSandboxBackend::Bubblewrap),
+            LinuxSandboxBackend::Auto if self.landlock_abi.is_some() => {
+                Ok(LinuxSandboxBackend::Landlock)
+            }
+            LinuxSandboxBackend::Auto => Ok(LinuxSandboxBackend::Unshare),
+            LinuxSandboxBackend::Bubblewrap if !self.bubblewrap => {
+                bail!("sandbox.linux_backend is \"bubblewrap\" but bwrap is not installed")
+            }
+            LinuxSandboxBackend::Landlock if self.landlock_abi.is_none() => {
+                bail!(
+                    "sandbox.linux_backend is \"landlock\" but the kernel has no Landlock support"
+                )
+            }
+            backend => Ok(backend),
+        }
+    }
+}
+
+impl fmt::Display for SandboxProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let landlock = match self.landlock_abi {
+            Some(abi) => format!("ABI v{}", abi),
+            None => "no".to_string(),
+        };
+        write!(
+            f,
+            "bwrap: {}, landlock: {}, seccomp: {}, user namespaces: {}",
+            yes_no(self.bubblewrap),
+            landlock,
+            yes_no(self.seccomp),
+            yes_no(self.user_namespaces)
+        )
+    }
+}
+
+/// Restrictions for one command, applied by the child before `exec`
+pub struct NativeSandbox {
+    ruleset: Option<RulesetCreated>,
+    network_filter: Option<BpfProgram>,
+}
+
+impl NativeSandbox {
+    /// Build the Landlock ruleset (and network filter) for `policy`. `cwd` is
+    /// writable like the bubblewrap backend's bind of it. With `proxy_port`,
+    /// TCP connects are allowed to that loopback port only.
+    pub fn new(policy: &SandboxPolicy, cwd: &Path, proxy_port: Option<u16>) -> Result<Self> {
+        let abi = LANDLOCK_ABI;
+        let read = AccessFs::from_read(abi);
+        let write = AccessFs::from_all(abi);
+
+        let expand = |paths: &[String]| -> Vec<PathBuf> {
+            paths
+                .iter()
+                .map(|p| PathBuf::from(shellexpand::tilde(p).to_string()))
+                .collect()
+        };
+        let mut writable = expand(&policy.allow_write);
+        writable.push(cwd.to_path_buf());
+        writable.push(std::env::temp_dir());
+
+        let mut ruleset = Ruleset::default().handle_access(write)?;
+        if proxy_port.is_some() {
+            if SandboxProbe::get().landlock_abi.unwrap_or(0) < LANDLOCK_NET_ABI {
+                bail!(
+                    "sandbox.allow_domains needs Landlock ABI {} for TCP rules; \
+                     use the bubblewrap backend on this kernel",
+                    LANDLOCK_NET_ABI
+                );
+            }
+            ruleset = ruleset.handle_access(AccessNet::ConnectTcp)?;
+        }
+        let mut ruleset = ruleset
+            .create()?
+            .add_rules(path_beneath_rules(SYSTEM_READ, read))?
+            .add_rules(path_beneath_rules(["/dev"], AccessFs::from_file(abi)))?
+            .add_rules(path_beneath_rules(expand(&policy.allow_read), read))?
+            .add_rules(path_beneath_rules(writable, write))?;
+        if let Some(port) = proxy_port {
+            ruleset = ruleset.add_rule(NetPort::new(port, AccessNet::ConnectTcp))?;
+        }
+
+        let network_filter = if policy.allow_network {
+            None
+        } else {
+            Some(network_filter(proxy_port.is_some())?)
+        };
+
+        Ok(Self {
+            ruleset: Some(ruleset),
+            network_filter,
+        })
+    }
+
+    /// Confine the calling process. Meant for `pre_exec`: no allocation, and
+    /// errors are plain errno values.
+    pub fn apply(&mut self) -> io::Result<()> {
+        if let Some(ruleset) = self.ruleset.take() {
+            match ruleset.restrict_self() {
+                Ok(status) if status.ruleset != RulesetStatus::NotEnforced => {}
+                _ => return Err(io::Error::from_raw_os_error(libc::EPERM)),
+            }
+        }
+        if let Some(filter) = &self.network_filter {
+            seccompiler::apply_filter(filter)
+                .map_err(|_| io::Error::from_raw_os_error(libc::EPERM))?;
+        }
+        Ok(())
+    }
+}
+
+/// Seccomp program failing `socket(2)` for internet and packet sockets with
+/// `EACCES`, and `io_uring_setup(2)`, which could open them without the syscall.
+/// Unix sockets keep working. With `allow_tcp`, IPv4 stream sockets are let
+/// through for Landlock to restrict to the proxy port.
+pub fn network_filter(allow_tcp: bool) -> Result<BpfProgram> {
+    let family = |family: i32| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)
+    };
+    let mut families = Vec::new();
+    for denied in DENIED_FAMILIES {
+        if allow_tcp && *denied == libc::AF_INET {
+            // The type argument also carries SOCK_NONBLOCK/SOCK_CLOEXEC
+            let not_stream = SeccompCondition::new(
+                1,
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::MaskedEq(0xf),
+                libc::SOCK_DGRAM as u64,
+            )?;
+            let raw = SeccompCondition::new(
+                1,
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::MaskedEq(0xf),
+                libc::SOCK_RAW as u64,
+            )?;
+            families.push(SeccompRule::new(vec![family(*denied)?, not_stream])?);
+            families.push(SeccompRule::new(vec![family(*denied)?, raw])?);
+        } else {
+            families.push(SeccompRule::new(vec![family(*denied)?])?);
+        }
+    }
+
+    let mut rules = BTreeMap::new();
+    rules.insert(libc::SYS_socket, families);
+    rules.insert(libc::SYS_io_uring_setup, Vec::new());
+
+    let arch = std::env::consts::ARCH
+        .try_into()
+        .context("seccomp is not supported on this architecture")?;
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EACCES as u32),
+        arch,
+    )?;
+    Ok(filter.try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_selection() {
+        let mut probe = SandboxProbe {
+            bubblewrap: true,
+            landlock_abi: Some(4),
+            seccomp: true,
+            user_namespaces: false,
+        };
+        let mut policy = SandboxPolicy::default();
+        assert_eq!(
+            probe.backend(&policy).unwrap(),
+            LinuxSandboxBackend::Bubblewrap
+        );
+
+        probe.bubblewrap = false;
+        assert_eq!(
+            probe.backend(&policy).unwrap(),
+            LinuxSandboxBackend::Landlock
+        );
+        policy.linux_backend = LinuxSandboxBackend::Bubblewrap;
+        assert!(probe.backend(&policy).is_err());
+
+        probe.landlock_abi = None;
+        policy.linux_backend = LinuxSandboxBackend::Auto;
+        assert_eq!(
+            probe.backend(&policy).unwrap(),
+            LinuxSandboxBackend::Unshare
+        );
+        policy.linux_backend = LinuxSandboxBackend::Landlock;
+        assert!(probe.backend(&policy).is_err());
+    }
+
+    #[test]
+    fn test_network_filter_compiles() {
+        if std::env::consts::ARCH == "x86_64" || std::env::consts::ARCH == "aarch64" {
+            assert!(!network_filter(false).unwrap().is_empty());
+            assert!(!network_filter(true).unwrap().is_empty());
+        }
+    }
+}
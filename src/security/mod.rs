@@ -1,2 +1,11 @@
 pub mod apple_sandbox;
-pub use apple_sandbox::compile_profile;
+pub mod approval;
+pub mod audit;
+pub mod egress;
+pub mod limits;
+#[cfg(target_os = "linux")]
+pub mod linux_sandbox;
+pub mod secrets;
+pub mod taint;
+pub use apple_sandbox::{compile_profile, compile_profile_with_proxy};
+pub use audit::AuditLog;
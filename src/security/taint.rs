@@ -0,0 +1,113 @@
+//! Per-turn taint tracking
+//!
+//! Web pages, MCP servers and downloaded files can carry prompt injections,
+//! and an owner turn that reads them would otherwise act on them with the
+//! owner's authority. Tools report where untrusted output comes from
+//! ([`Tool::untrusted_source`]); once a turn has any, calls that tools mark
+//! as privileged ([`Tool::privileged`]) need the user's approval or are
+//! refused, per `[tools.taint]`. The taint is cleared when the next user
+//! message starts a new turn.
+//!
+//! [`Tool::untrusted_source`]: crate::agent::Tool::untrusted_source
+//! [`Tool::privileged`]: crate::agent::Tool::privileged
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::agent::tools::path::is_within;
+use crate::config::TaintConfig;
+
+/// Untrusted sources seen in the current turn
+#[derive(Debug, Default)]
+pub struct TurnTaint {
+    sources: Mutex<Vec<String>>,
+}
+
+impl TurnTaint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new turn with no untrusted content
+    pub fn clear(&self) {
+        self.sources.lock().unwrap().clear();
+    }
+
+    /// Note that untrusted content from `source` entered the turn
+    pub fn record(&self, source: String) {
+        let mut sources = self.sources.lock().unwrap();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    pub fn sources(&self) -> Vec<String> {
+        self.sources.lock().unwrap().clone()
+    }
+
+    pub fn is_tainted(&self) -> bool {
+        !self.sources.lock().unwrap().is_empty()
+    }
+
+    /// Why a privileged call to `tool` is gated, if the turn is tainted
+    pub fn reason(&self, tool: &str) -> Option<String> {
+        let sources = self.sources.lock().unwrap();
+        let first = sources.first()?;
+        let more = match sources.len() {
+            1 => String::new(),
+            n => format!(" (and {} more)", n - 1),
+        };
+        Some(format!(
+            "'{}' after untrusted content from {}{} entered this turn",
+            tool, first, more
+        ))
+    }
+}
+
+/// Directories whose files are untrusted, with `~` expanded
+pub fn untrusted_roots(config: &TaintConfig) -> Vec<PathBuf> {
+    config
+        .untrusted_paths
+        .iter()
+        .map(|p| PathBuf::from(shellexpand::tilde(p).to_string()))
+        .collect()
+}
+
+/// Whether `path` lies in one of the untrusted `roots`
+pub fn is_untrusted_path(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| is_within(path, root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_taint_reason() {
+        let taint = TurnTaint::new();
+        assert!(taint.reason("bash").is_none());
+
+        taint.record("web_fetch https://example.com/".to_string());
+        taint.record("web_fetch https://example.com/".to_string());
+        assert_eq!(
+            taint.reason("bash").unwrap(),
+            "'bash' after untrusted content from web_fetch https://example.com/ entered this turn"
+        );
+        taint.record("MCP server search".to_string());
+        assert!(taint.reason("bash").unwrap().contains("(and 1 more)"));
+
+        taint.clear();
+        assert!(!taint.is_tainted());
+    }
+
+    #[test]
+    fn test_untrusted_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloads = dir.path().join("Downloads");
+        std::fs::create_dir(&downloads).unwrap();
+        let roots = vec![downloads.clone()];
+
+        assert!(is_untrusted_path(&downloads.join("report.pdf"), &roots));
+        assert!(!is_untrusted_path(&dir.path().join("notes.md"), &roots));
+    }
+}
@@ -30,8 +30,10 @@ use crate::agent::{extract_tool_detail, Agent, AgentConfig, LlmError, StreamEven
 use crate::concurrency::{TurnGate, WorkspaceLock};
 use crate::config::Config;
 use crate::heartbeat::{get_last_heartbeat_event, HeartbeatStatus};
-use crate::ingress::IngressBus;
-use crate::memory::MemoryManager;
+use crate::ingress::approval::{ApprovalAnswer, ApprovalCoordinator, PendingApproval};
+use crate::ingress::{ApprovalDecision, IngressBus};
+use crate::memory::{parse_date_bound, MemoryManager, SearchFilter};
+use crate::security::approval::GrantScope;
 
 /// Embedded UI assets
 #[derive(RustEmbed)]
@@ -51,6 +53,7 @@ pub struct Server {
     config: Config,
     turn_gate: TurnGate,
     bus: Option<Arc<IngressBus>>,
+    approvals: Arc<ApprovalCoordinator>,
 }
 
 pub struct SessionEntry {
@@ -70,6 +73,8 @@ pub struct AppState {
     /// Cross-process workspace lock
     pub workspace_lock: WorkspaceLock,
     pub bus: Option<Arc<IngressBus>>,
+    /// Approval requests of daemon-driven turns (Telegram, scheduled jobs)
+    pub approvals: Arc<ApprovalCoordinator>,
 }
 
 impl Server {
@@ -78,6 +83,7 @@ impl Server {
             config: config.clone(),
             turn_gate: TurnGate::new(),
             bus: None,
+            approvals: Arc::new(ApprovalCoordinator::default()),
         })
     }
 
@@ -92,9 +98,16 @@ impl Server {
             config: config.clone(),
             turn_gate,
             bus,
+            approvals: Arc::new(ApprovalCoordinator::default()),
         })
     }
 
+    /// Serve the daemon's pending approvals at `/api/approvals`
+    pub fn with_approvals(mut self, approvals: Arc<ApprovalCoordinator>) -> Self {
+        self.approvals = approvals;
+        self
+    }
+
     pub async fn run(&self) -> Result<()> {
         // Create shared MemoryManager once to avoid reinitializing embedding provider
         let memory =
@@ -109,6 +122,7 @@ impl Server {
             turn_gate: self.turn_gate.clone(),
             workspace_lock,
             bus: self.bus.clone(),
+            approvals: self.approvals.clone(),
         });
 
         // Load persisted sessions on startup
@@ -158,9 +172,12 @@ impl Server {
             .route("/api/sessions/{session_id}/compact", post(compact_session))
             .route("/api/sessions/{session_id}/clear", post(clear_session))
             .route("/api/sessions/{session_id}/model", post(set_session_model))
+            .route("/api/sessions/{session_id}/rewind", post(rewind_session))
             .route("/api/chat", post(chat))
             .route("/api/chat/approve", post(approve_tool))
             .route("/api/chat/stream", post(chat_stream))
+            .route("/api/approvals", get(list_approvals))
+            .route("/api/approvals/{call_id}", post(answer_approval))
             .route("/api/memory/search", get(memory_search))
             .route("/api/memory/stats", get(memory_stats))
             .route("/api/memory/reindex", post(memory_reindex))
@@ -694,6 +711,77 @@ async fn set_session_model(
     }
 }
 
+#[derive(Deserialize)]
+struct RewindRequest {
+    /// Checkpoint to undo along with every later one; the last one when
+    /// omitted. Checkpoints count only turns that changed files.
+    #[serde(default)]
+    turn: Option<usize>,
+    /// Only preview the changes
+    #[serde(default)]
+    dry_run: bool,
+}
+
+// Restore files the session's agent changed to their state before a turn
+async fn rewind_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<RewindRequest>,
+) -> Response {
+    // A rewind must not race a turn writing the same files
+    let _gate_permit = state.turn_gate.acquire().await;
+
+    let ws_lock_path = state.workspace_lock.clone();
+    let ws_guard = match tokio::task::spawn_blocking(move || ws_lock_path.acquire()).await {
+        Ok(Ok(guard)) => guard,
+        Ok(Err(e)) => {
+            return AppError(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Lock error: {}", e),
+            )
+            .into_response()
+        }
+        Err(e) => {
+            return AppError(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Task error: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let agent = {
+        let mut sessions = state.sessions.lock().await;
+        let entry = match sessions.get_mut(&session_id) {
+            Some(e) => e,
+            None => {
+                return AppError(StatusCode::NOT_FOUND, "Session not found".to_string())
+                    .into_response()
+            }
+        };
+        entry.last_accessed = Instant::now();
+        entry.agent.clone()
+    };
+
+    let result = agent
+        .lock()
+        .await
+        .rewind(request.turn, request.dry_run)
+        .await;
+    drop(ws_guard);
+
+    match result {
+        Ok(plan) => Json(json!({
+            "session_id": session_id,
+            "turn": plan.turn,
+            "dry_run": request.dry_run,
+            "changes": plan.changes,
+        }))
+        .into_response(),
+        Err(e) => AppError(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
 // Chat endpoint
 #[derive(Deserialize)]
 struct ChatRequest {
@@ -717,6 +805,7 @@ struct ApprovalDetails {
     tool_name: String,
     tool_call_id: String,
     arguments: String,
+    reason: Option<String>,
 }
 
 async fn chat(State(state): State<Arc<AppState>>, Json(request): Json<ChatRequest>) -> Response {
@@ -765,6 +854,7 @@ async fn chat(State(state): State<Arc<AppState>>, Json(request): Json<ChatReques
     };
 
     let mut agent_lock = agent.lock().await;
+    state.approvals.note_activity("web");
 
     // Switch model if requested
     if let Some(ref model) = request.model {
@@ -775,12 +865,11 @@ async fn chat(State(state): State<Arc<AppState>>, Json(request): Json<ChatReques
     }
 
     let result = agent_lock.chat(&request.message).await;
-    let model = agent_lock.model().to_string();
 
     // Release workspace lock explicitly before returning
     drop(ws_guard);
 
-    handle_chat_result(result, session_id, model, &state).await
+    handle_chat_result(result, session_id, &agent_lock, &state).await
 }
 
 #[derive(Deserialize)]
@@ -788,23 +877,18 @@ struct ApproveRequest {
     session_id: String,
     tool_call_id: String,
     approved: bool,
+    /// `once` (default), `session` or `always`
+    #[serde(default)]
+    scope: GrantScope,
+    /// Told to the model when the call is rejected
+    #[serde(default)]
+    reason: Option<String>,
 }
 
 async fn approve_tool(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ApproveRequest>,
 ) -> Response {
-    if !request.approved {
-        // If rejected, maybe we should tell the agent?
-        // For now, we just return error or do nothing?
-        // TODO: Implement rejection flow (tell agent user denied)
-        return AppError(
-            StatusCode::BAD_REQUEST,
-            "Rejection not implemented".to_string(),
-        )
-        .into_response();
-    }
-
     // Acquire locks similar to chat
     let _gate_permit = state.turn_gate.acquire().await;
 
@@ -842,24 +926,70 @@ async fn approve_tool(
 
     let mut agent_lock = agent.lock().await;
 
-    // Approve the call
-    agent_lock.approve_tool_call(&request.tool_call_id);
+    // Approve the call, and calls like it for a wider scope, or give the
+    // model the rejection as the call's result
+    let decision = if request.approved {
+        ApprovalDecision::approve(request.scope)
+    } else {
+        ApprovalDecision::Deny
+    };
+    if let Err(e) = agent_lock
+        .resolve_tool_call(&request.tool_call_id, decision, request.reason.as_deref())
+        .await
+    {
+        return AppError(StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
 
     // Continue chat
     let result = agent_lock.continue_chat().await;
-    let model = agent_lock.model().to_string();
 
     drop(ws_guard);
 
-    handle_chat_result(result, request.session_id, model, &state).await
+    handle_chat_result(result, request.session_id, &agent_lock, &state).await
+}
+
+async fn list_approvals(State(state): State<Arc<AppState>>) -> Json<Vec<PendingApproval>> {
+    Json(state.approvals.list().await)
+}
+
+#[derive(Deserialize)]
+struct AnswerApprovalRequest {
+    /// `approve`, `session`, `always` or `deny`
+    decision: ApprovalDecision,
+    /// Told to the model when the call is denied
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Answer an approval request of a daemon-driven turn; the turn waiting
+/// on it continues in the background
+async fn answer_approval(
+    State(state): State<Arc<AppState>>,
+    Path(call_id): Path<String>,
+    Json(request): Json<AnswerApprovalRequest>,
+) -> Response {
+    state.approvals.note_activity("web");
+    let answer = ApprovalAnswer {
+        decision: request.decision,
+        reason: request.reason,
+    };
+    match state.approvals.answer(&call_id, answer).await {
+        Some(approval) => Json(approval).into_response(),
+        None => AppError(
+            StatusCode::NOT_FOUND,
+            format!("No pending approval: {}", call_id),
+        )
+        .into_response(),
+    }
 }
 
 async fn handle_chat_result(
     result: anyhow::Result<String>,
     session_id: String,
-    model: String,
+    agent: &Agent,
     state: &Arc<AppState>,
 ) -> Response {
+    let model = agent.model().to_string();
     match result {
         Ok(response) => {
             // Update dirty flag
@@ -901,6 +1031,7 @@ async fn handle_chat_result(
                                 tool_name: name.clone(),
                                 tool_call_id: call.id.clone(),
                                 arguments: call.arguments.clone(),
+                                reason: agent.approval_reason(call),
                             }),
                         }),
                     )
@@ -968,6 +1099,7 @@ async fn chat_stream(
         };
 
         let mut agent_lock = agent.lock().await;
+        state_clone.approvals.note_activity("web");
 
         // Use streaming with tools
         match agent_lock.chat_stream_with_tools(&message, Vec::new()).await {
@@ -988,9 +1120,9 @@ async fn chat_stream(
                             let data = json!({"type": "tool_start", "name": name, "id": id, "detail": detail});
                             yield Ok(Event::default().data(data.to_string()));
                         }
-                        Ok(StreamEvent::ApprovalRequired { name, id, arguments }) => {
+                        Ok(StreamEvent::ApprovalRequired { name, id, arguments, reason }) => {
                             let detail = extract_tool_detail(&name, &arguments);
-                            let data = json!({"type": "approval_required", "name": name, "id": id, "detail": detail, "arguments": arguments});
+                            let data = json!({"type": "approval_required", "name": name, "id": id, "detail": detail, "arguments": arguments, "reason": reason});
                             yield Ok(Event::default().data(data.to_string()));
                             break; // Stop stream until approval received
                         }
@@ -1030,6 +1162,38 @@ async fn chat_stream(
 struct SearchQuery {
     q: String,
     limit: Option<usize>,
+    /// Comma-separated path globs
+    path: Option<String>,
+    /// Comma-separated path globs to skip
+    exclude: Option<String>,
+    /// Comma-separated sources (memory, text, html, pdf, code)
+    source: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    min_score: Option<f64>,
+}
+
+impl SearchQuery {
+    fn filter(&self) -> Result<SearchFilter> {
+        let list = |value: &Option<String>| -> Vec<String> {
+            value
+                .iter()
+                .flat_map(|v| v.split(','))
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        Ok(SearchFilter {
+            paths: list(&self.path),
+            exclude: list(&self.exclude),
+            sources: list(&self.source),
+            since: self.since.as_deref().map(parse_date_bound).transpose()?,
+            until: self.until.as_deref().map(parse_date_bound).transpose()?,
+            min_score: self.min_score,
+            ..Default::default()
+        })
+    }
 }
 
 #[derive(Serialize)]
@@ -1039,6 +1203,10 @@ struct SearchResult {
     line_end: i32,
     content: String,
     score: f64,
+    source: String,
+    /// Federated workspace of the result (absent for this workspace)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -1051,7 +1219,11 @@ async fn memory_search(
     State(state): State<Arc<AppState>>,
     Query(query): Query<SearchQuery>,
 ) -> Response {
-    match memory_search_inner(&state.memory, &query.q, query.limit).await {
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(e) => return AppError(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    match memory_search_inner(&state.memory, &query.q, query.limit, &filter).await {
         Ok(response) => Json(response).into_response(),
         Err(e) => AppError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
@@ -1061,9 +1233,10 @@ async fn memory_search_inner(
     memory: &MemoryManager,
     query: &str,
     limit: Option<usize>,
+    filter: &SearchFilter,
 ) -> Result<SearchResponse, anyhow::Error> {
     let limit = limit.unwrap_or(10);
-    let results = memory.search(query, limit).await?;
+    let results = memory.search_filtered(query, limit, filter).await?;
 
     let results: Vec<SearchResult> = results
         .into_iter()
@@ -1073,6 +1246,8 @@ async fn memory_search_inner(
             line_end: r.line_end,
             content: r.content,
             score: r.score,
+            source: r.source,
+            origin: r.origin,
         })
         .collect();
 
@@ -1,8 +1,9 @@
 use crate::agent::ImageAttachment;
 use crate::config::Config;
+use crate::ingress::approval::ApprovalEvent;
 use crate::ingress::{
-    ApprovalCoordinator, ApprovalUIRequest, IngressBus, IngressMessage, RealTelegramClient,
-    TelegramApi, TrustLevel,
+    ApprovalCoordinator, ApprovalDecision, ApprovalUIRequest, IngressBus, IngressMessage,
+    RealTelegramClient, TelegramApi, TrustLevel,
 };
 use crate::server::audio::AudioTranscriber;
 use anyhow;
@@ -13,7 +14,7 @@ use std::sync::Arc;
 use tempfile::NamedTempFile;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
@@ -69,19 +70,43 @@ impl TelegramPollingService {
 
         // Spawn cleanup task for timed-out approvals
         let cleanup_coord = self.approval_coord.clone();
-        let cleanup_client = self.client.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(30));
             loop {
                 interval.tick().await;
-                let now = Instant::now();
-                let expired = cleanup_coord.cleanup(now).await;
-                for (call_id, chat_id, message_id) in expired {
-                    debug!("Cleaning up timed-out approval: {}", call_id);
-                    // Edit message to show timeout
-                    let _ = cleanup_client
-                        .edit_message_text(chat_id, message_id, "⌛️ Timed out")
-                        .await;
+                for (call_id, _, _) in cleanup_coord.cleanup(Instant::now()).await {
+                    debug!("Cleaned up timed-out approval: {}", call_id);
+                }
+            }
+        });
+
+        // Show how approvals ended on their Telegram messages, wherever
+        // they were answered
+        let mut events = self.approval_coord.subscribe();
+        let edit_client = self.client.clone();
+        tokio::spawn(async move {
+            loop {
+                let (answer, chat_id, message_id) = match events.recv().await {
+                    Ok(ApprovalEvent::Resolved {
+                        answer,
+                        telegram_message: Some((chat_id, message_id)),
+                        ..
+                    }) if message_id >= 0 => (answer, chat_id, message_id),
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let result_text = match answer.map(|a| a.decision) {
+                    Some(ApprovalDecision::Approve) => "✅ Approved",
+                    Some(ApprovalDecision::ApproveSession) => "✅ Approved for this session",
+                    Some(ApprovalDecision::ApproveAlways) => "✅ Always approved",
+                    Some(ApprovalDecision::Deny) => "❌ Denied",
+                    None => "⌛️ Timed out",
+                };
+                if let Err(e) = edit_client
+                    .edit_message_text(chat_id, message_id, result_text)
+                    .await
+                {
+                    error!("Failed to edit message for approval result: {}", e);
                 }
             }
         });
@@ -481,10 +506,13 @@ impl TelegramPollingService {
 
     async fn handle_approval_ui_request(&self, req: ApprovalUIRequest) -> anyhow::Result<()> {
         // Build text
-        let text = format!(
+        let mut text = format!(
             "Tool `{}` requires approval:\nArguments: `{}`",
             req.tool_name, req.arguments
         );
+        if let Some(reason) = &req.reason {
+            text.push_str(&format!("\nReason: {}", reason));
+        }
         // Send message with buttons
         let message_id = self
             .client
@@ -501,7 +529,6 @@ impl TelegramPollingService {
         &self,
         query: &crate::ingress::TelegramCallbackQuery,
     ) -> anyhow::Result<()> {
-        use crate::ingress::ApprovalDecision;
         let data = query
             .data
             .as_ref()
@@ -513,23 +540,20 @@ impl TelegramPollingService {
         let (decision_str, call_id) = (parts[0], parts[1]);
         let decision = match decision_str {
             "approve" => ApprovalDecision::Approve,
+            "session" => ApprovalDecision::ApproveSession,
+            "always" => ApprovalDecision::ApproveAlways,
             "deny" => ApprovalDecision::Deny,
             _ => anyhow::bail!("Unknown decision: {}", decision_str),
         };
-        // Resolve via coordinator
-        if let Some((chat_id, message_id)) = self.approval_coord.resolve(call_id, decision).await {
-            // Edit original message to show result
-            let result_text = match decision {
-                ApprovalDecision::Approve => "✅ Approved",
-                ApprovalDecision::Deny => "❌ Denied",
-            };
-            if let Err(e) = self
-                .client
-                .edit_message_text(chat_id, message_id, result_text)
-                .await
-            {
-                error!("Failed to edit message for approval result: {}", e);
-            }
+        // Resolve via coordinator; the message is edited when the event
+        // comes back
+        if self
+            .approval_coord
+            .resolve(call_id, decision)
+            .await
+            .is_none()
+        {
+            debug!("Approval {} is no longer pending", call_id);
         }
         // Answer callback query to remove loading indicator
         if let Err(e) = self
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use zier_alpha::config::Config;
+use zier_alpha::ingress::approval::{
+    ApprovalAnswer, ApprovalCoordinator, ApprovalEvent, ApprovalRequest,
+};
+use zier_alpha::ingress::ApprovalDecision;
+
+fn request(call_id: &str) -> ApprovalRequest {
+    ApprovalRequest {
+        call_id: call_id.to_string(),
+        tool_name: "bash".to_string(),
+        arguments: r#"{"command":"make deploy"}"#.to_string(),
+        reason: Some("runs the deploy".to_string()),
+        source: "scheduler:deploy".to_string(),
+        channel: None,
+        timeout: None,
+    }
+}
+
+#[tokio::test]
+async fn test_scheduled_job_asks_on_web() {
+    let (ui_tx, mut ui_rx) = mpsc::channel(4);
+    let coord = Arc::new(ApprovalCoordinator::new(ui_tx));
+    coord.note_activity("web");
+
+    let asker = coord.clone();
+    let waiting = tokio::spawn(async move { asker.ask(request("call_1")).await });
+    while coord.list().await.is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // Nothing goes to Telegram; the request is listed for the web UI
+    let pending = coord.list().await;
+    assert_eq!(pending[0].channel.as_deref(), Some("web"));
+    assert_eq!(pending[0].source, "scheduler:deploy");
+    assert!(ui_rx.try_recv().is_err());
+
+    let answered = coord
+        .answer("call_1", ApprovalAnswer::deny("not during office hours"))
+        .await
+        .expect("pending");
+    assert_eq!(answered.call_id, "call_1");
+    let answer = waiting.await.unwrap();
+    assert_eq!(answer.decision, ApprovalDecision::Deny);
+    assert_eq!(answer.reason.as_deref(), Some("not during office hours"));
+    assert!(coord
+        .answer("call_1", ApprovalAnswer::deny("x"))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_config_timeout_and_telegram_switch() {
+    let mut config = Config::default();
+    config.approvals.timeout_seconds = Some(0);
+    config.server.telegram_approval.enabled = false;
+    let (ui_tx, mut ui_rx) = mpsc::channel(4);
+    let coord = ApprovalCoordinator::new(ui_tx).with_config(&config);
+    coord.note_activity("telegram:42");
+    let mut events = coord.subscribe();
+
+    let answer = coord.ask(request("call_1")).await;
+    assert_eq!(answer.decision, ApprovalDecision::Deny);
+    assert!(answer.reason.unwrap().contains("timed out"));
+    // Buttons are off, so Telegram is never asked
+    assert!(ui_rx.try_recv().is_err());
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        ApprovalEvent::Requested(_)
+    ));
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        ApprovalEvent::Resolved { answer: None, .. }
+    ));
+}
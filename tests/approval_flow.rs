@@ -123,3 +123,139 @@ async fn test_approval_flow_non_streaming() {
         }
     }
 }
+
+async fn agent_with_mock_tool(temp_dir: &TempDir) -> Agent {
+    let mut config = Config::default();
+    config.memory.workspace = temp_dir.path().to_string_lossy().to_string();
+    config.agent.default_model = "mock/test".to_string();
+    config.tools.require_approval = vec!["test_write".to_string()];
+
+    let memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+    let agent_config = AgentConfig {
+        model: "mock/test".to_string(),
+        context_window: 100000,
+        reserve_tokens: 1000,
+    };
+    let mut agent = Agent::new(agent_config, &config, memory, ContextStrategy::Full, "test")
+        .await
+        .unwrap();
+    let mut tools = agent.tools().to_vec();
+    tools.push(Arc::new(MockTool));
+    agent.set_tools(tools);
+    agent.new_session().await.unwrap();
+    agent
+}
+
+fn tool_results(messages: &[zier_alpha::agent::Message]) -> Vec<String> {
+    messages
+        .iter()
+        .filter(|m| m.role == zier_alpha::agent::Role::Tool)
+        .map(|m| m.content.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_rejection_flow_non_streaming() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut agent = agent_with_mock_tool(&temp_dir).await;
+
+    let err = agent
+        .chat("test_tool:test_write|test.txt|content")
+        .await
+        .unwrap_err();
+    let call = match err.downcast_ref::<zier_alpha::agent::LlmError>() {
+        Some(zier_alpha::agent::LlmError::ApprovalRequired(_, call)) => call.clone(),
+        _ => panic!("Wrong error type: {:?}", err),
+    };
+
+    // The model gets the refusal as the call's result and answers
+    agent
+        .reject_tool_call(&call.id, Some("not now"))
+        .await
+        .unwrap();
+    assert_eq!(agent.continue_chat().await.unwrap(), "Mock response");
+    assert_eq!(
+        tool_results(&agent.session_messages().await),
+        vec!["User denied this tool call: not now"]
+    );
+
+    // Nothing is pending any more
+    assert!(agent.reject_tool_call(&call.id, None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_rejection_flow_streaming() {
+    use futures::StreamExt;
+    use zier_alpha::agent::StreamEvent;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut agent = agent_with_mock_tool(&temp_dir).await;
+
+    let call_id = {
+        let stream = agent
+            .chat_stream_with_tools("test_tool:test_write|test.txt|content", Vec::new())
+            .await
+            .unwrap();
+        let events: Vec<_> = stream.collect().await;
+        match events.last() {
+            Some(Ok(StreamEvent::ApprovalRequired { id, .. })) => id.clone(),
+            other => panic!("Expected ApprovalRequired, got {:?}", other),
+        }
+    };
+
+    agent.reject_tool_call(&call_id, None).await.unwrap();
+    let events: Vec<_> = agent
+        .resume_chat_stream_with_tools()
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert!(matches!(events.last(), Some(Ok(StreamEvent::Done))));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, Ok(StreamEvent::ToolCallStart { .. }))));
+    assert_eq!(
+        tool_results(&agent.session_messages().await),
+        vec!["User denied this tool call"]
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_then_resume_streaming() {
+    use futures::StreamExt;
+    use zier_alpha::agent::StreamEvent;
+    use zier_alpha::ingress::ApprovalDecision;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut agent = agent_with_mock_tool(&temp_dir).await;
+
+    let stream = agent
+        .chat_stream_with_tools("test_tool:test_write|test.txt|content", Vec::new())
+        .await
+        .unwrap();
+    let _: Vec<_> = stream.collect().await;
+    let call = agent.pending_tool_calls().await.remove(0);
+
+    // Front-ends approve through the agent; the call runs on resume
+    // instead of being put to the user again
+    agent
+        .resolve_tool_call(&call.id, ApprovalDecision::Approve, None)
+        .await
+        .unwrap();
+    let events: Vec<_> = agent
+        .resume_chat_stream_with_tools()
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, Ok(StreamEvent::ApprovalRequired { .. }))));
+    assert!(matches!(events.last(), Some(Ok(StreamEvent::Done))));
+    assert_eq!(
+        tool_results(&agent.session_messages().await),
+        vec!["Tool execution verified"]
+    );
+    assert!(agent.pending_tool_calls().await.is_empty());
+}
@@ -38,6 +38,7 @@ async fn test_approval_integration() {
         allow_write: vec![workspace_path.to_str().unwrap().to_string()],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
     let script_service = ScriptService::new(
         policy,
@@ -0,0 +1,139 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::agent::tools::create_default_tools_with_project;
+use zier_alpha::agent::{DiskMonitor, ToolCall, ToolExecutor};
+use zier_alpha::config::{ApprovalRule, Config, RuleAction};
+use zier_alpha::security::approval::GrantScope;
+
+fn config(temp: &TempDir) -> Config {
+    let mut config = Config::default();
+    config.memory.workspace = temp.path().join("workspace").to_string_lossy().to_string();
+    config.tools.require_approval = vec!["bash".to_string()];
+    config.tools.approval_rules = vec![
+        ApprovalRule {
+            tool: "bash".to_string(),
+            command: Some(r"\brm\s+-[a-z]*r".to_string()),
+            path: None,
+            host: None,
+            action: RuleAction::Deny,
+            reason: Some("recursive delete".to_string()),
+        },
+        ApprovalRule {
+            tool: "bash".to_string(),
+            command: Some(r"^(echo|ls)\b".to_string()),
+            path: None,
+            host: None,
+            action: RuleAction::Allow,
+            reason: None,
+        },
+        ApprovalRule {
+            tool: "write_file".to_string(),
+            command: None,
+            path: Some("secrets/*".to_string()),
+            host: None,
+            action: RuleAction::Ask,
+            reason: Some("writes under secrets/".to_string()),
+        },
+    ];
+    config
+}
+
+fn executor(temp: &TempDir, config: Config) -> ToolExecutor {
+    let project = temp.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(config.workspace_path()).unwrap();
+    let disk_monitor = DiskMonitor::new(config.disk.clone());
+    let tools = create_default_tools_with_project(&config, None, disk_monitor, project).unwrap();
+    ToolExecutor::new(tools, config).with_grants("main")
+}
+
+fn bash(id: &str, command: &str) -> ToolCall {
+    ToolCall {
+        id: id.to_string(),
+        name: "bash".to_string(),
+        arguments: serde_json::json!({ "command": command }).to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_rules_decide_by_arguments() {
+    let temp = TempDir::new().unwrap();
+    let executor = executor(&temp, config(&temp));
+
+    // Allowed commands skip require_approval
+    let echo = bash("call_1", "echo hi");
+    assert!(!executor.call_requires_approval(&echo));
+    assert!(executor.execute_tool(&echo).await.unwrap().contains("hi"));
+    assert!(executor.call_requires_approval(&bash("call_2", "make")));
+
+    // Deny rules refuse even approved calls
+    let rm = bash("call_3", "echo x && rm -rf build");
+    assert!(!executor.call_requires_approval(&rm));
+    executor.approve_tool_call(&rm.id);
+    let err = executor.execute_tool(&rm).await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Denied by approval rule: recursive delete"));
+
+    let write = ToolCall {
+        id: "call_4".to_string(),
+        name: "write_file".to_string(),
+        arguments: serde_json::json!({ "path": "secrets/token", "content": "x" }).to_string(),
+    };
+    assert_eq!(
+        executor.approval_reason(&write).as_deref(),
+        Some("writes under secrets/")
+    );
+}
+
+#[tokio::test]
+async fn test_session_and_always_grants() {
+    let temp = TempDir::new().unwrap();
+    let executor = executor(&temp, config(&temp));
+
+    let date = bash("call_1", "date +%s");
+    executor.grant(&date, GrantScope::Session).unwrap();
+    assert!(!executor.call_requires_approval(&bash("call_2", "date -u")));
+    assert!(executor.call_requires_approval(&bash("call_3", "uname")));
+
+    let uname = bash("call_4", "uname -a");
+    let rule = executor.grant(&uname, GrantScope::Always).unwrap().unwrap();
+    assert_eq!(rule.command.as_deref(), Some(r"^uname(\s|$)"));
+    // Grants stay below deny rules
+    assert!(executor
+        .execute_tool(&bash("call_5", "uname; rm -r /tmp/x"))
+        .await
+        .is_err());
+
+    // A new executor for the agent keeps "always" grants only
+    let executor = self::executor(&temp, config(&temp));
+    assert!(!executor.call_requires_approval(&bash("call_6", "uname -r")));
+    assert!(executor.call_requires_approval(&bash("call_7", "date")));
+    assert!(temp.path().join("agents/main/approvals.json").exists());
+}
+
+#[tokio::test]
+async fn test_path_grants_match_resolved_paths() {
+    let temp = TempDir::new().unwrap();
+    let mut config = config(&temp);
+    config.tools.require_approval.push("write_file".to_string());
+    let executor = executor(&temp, config);
+    let write = |id: &str, path: &str| ToolCall {
+        id: id.to_string(),
+        name: "write_file".to_string(),
+        arguments: serde_json::json!({ "path": path, "content": "x" }).to_string(),
+    };
+
+    let rule = executor
+        .grant(&write("call_1", "notes/a.md"), GrantScope::Session)
+        .unwrap()
+        .unwrap();
+    // The directory the tool writes to, not the argument as typed
+    assert!(rule.path.unwrap().ends_with("/project/notes"));
+
+    assert!(!executor.call_requires_approval(&write("call_2", "notes/b.md")));
+    let absolute = temp.path().join("project/notes/c.md");
+    assert!(!executor.call_requires_approval(&write("call_3", absolute.to_str().unwrap())));
+    assert!(executor.call_requires_approval(&write("call_4", "notes/../../outside.md")));
+    assert!(executor.call_requires_approval(&write("call_5", "notes/../secrets/token")));
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tempfile::TempDir;
+use zier_alpha::agent::tool_executor::{with_trust, ApprovalRequiredError};
+use zier_alpha::agent::tools::BashTool;
+use zier_alpha::agent::{Tool, ToolCall, ToolExecutor};
+use zier_alpha::config::{BashProfile, Config, SandboxPolicy, WorkdirStrategy};
+use zier_alpha::ingress::TrustLevel;
+
+fn bash(dir: &TempDir, sandbox: SandboxPolicy) -> BashTool {
+    BashTool::new(
+        dir.path().to_path_buf(),
+        dir.path().to_path_buf(),
+        WorkdirStrategy::Mount,
+        5000,
+    )
+    .with_sandbox(sandbox)
+}
+
+fn args(command: &str) -> String {
+    serde_json::json!({ "command": command }).to_string()
+}
+
+#[tokio::test]
+async fn test_bash_hard_block() {
+    let dir = TempDir::new().unwrap();
+    let tool = bash(&dir, SandboxPolicy::default());
+
+    let err = tool.execute(&args("rm -rf / --no-preserve-root")).await;
+    assert!(err
+        .unwrap_err()
+        .to_string()
+        .contains("blocked by safety policy"));
+
+    let out = tool.execute(&args("echo hello")).await.unwrap();
+    assert!(out.contains("hello"));
+}
+
+#[tokio::test]
+async fn test_bash_requires_approval() {
+    let dir = TempDir::new().unwrap();
+    let tool: Arc<dyn Tool> = Arc::new(bash(&dir, SandboxPolicy::default()));
+    assert!(tool.approval_reason(&args("nmap 10.0.0.1")).is_some());
+    assert!(tool.approval_reason(&args("ls")).is_none());
+
+    let executor = ToolExecutor::new(vec![tool], Config::default());
+    let call = ToolCall {
+        id: "call_1".to_string(),
+        name: "bash".to_string(),
+        arguments: args("nmap 10.0.0.1"),
+    };
+    let err = executor.execute_tool(&call).await.unwrap_err();
+    assert!(err.downcast_ref::<ApprovalRequiredError>().is_some());
+}
+
+#[tokio::test]
+async fn test_bash_profile_follows_trust() {
+    let dir = TempDir::new().unwrap();
+    let tool = bash(&dir, SandboxPolicy::default());
+    let chained = args("echo a && echo b");
+
+    // Owner turns use the standard profile, which allows chaining
+    let out = tool.execute(&chained).await.unwrap();
+    assert!(out.contains('a') && out.contains('b'));
+
+    // Scheduled jobs use the strict profile
+    let err = with_trust(TrustLevel::TrustedEvent, tool.execute(&chained))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Shell chaining"));
+
+    // Unrestricted skips the safety checks altogether
+    let mut sandbox = SandboxPolicy::default();
+    sandbox.bash.trusted = BashProfile::Unrestricted;
+    let tool = bash(&dir, sandbox);
+    let out = with_trust(TrustLevel::TrustedEvent, tool.execute(&chained))
+        .await
+        .unwrap();
+    assert!(out.contains('b'));
+}
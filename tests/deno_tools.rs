@@ -12,6 +12,7 @@ async fn test_deno_tool_registration_and_execution() {
         allow_write: vec!["/tmp".to_string()],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     // 2. Initialize Service
@@ -83,6 +84,7 @@ async fn test_deno_sandbox_fs_allowed() {
         allow_write: vec![],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     let temp_dir = tempfile::tempdir().unwrap();
@@ -146,6 +148,7 @@ async fn test_deno_sandbox_fs_denied() {
         allow_write: vec![],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     let temp_dir = tempfile::tempdir().unwrap();
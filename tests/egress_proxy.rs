@@ -0,0 +1,179 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use zier_alpha::agent::tools::runner::run_sandboxed_command;
+use zier_alpha::agent::tools::{Tool, WebFetchTool};
+use zier_alpha::config::{LinuxSandboxBackend, SandboxPolicy};
+use zier_alpha::security::egress::{proxy_for, EgressAllowlist};
+use zier_alpha::security::AuditLog;
+
+/// HTTP server answering every request with `hello`
+async fn target() -> Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+                    )
+                    .await;
+            });
+        }
+    });
+    Ok(port)
+}
+
+async fn through_proxy(proxy: std::net::SocketAddr, request: &str) -> Result<String> {
+    let mut stream = TcpStream::connect(proxy).await?;
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    Ok(String::from_utf8_lossy(&response).to_string())
+}
+
+fn policy(port: u16) -> SandboxPolicy {
+    SandboxPolicy {
+        allow_domains: vec![format!("127.0.0.1:{}", port)],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_proxy_enforces_allowlist() -> Result<()> {
+    let port = target().await?;
+    let proxy = proxy_for(&EgressAllowlist::new(&policy(port).allow_domains))?;
+
+    let request = format!(
+        "CONNECT 127.0.0.1:{0} HTTP/1.1\r\nHost: 127.0.0.1:{0}\r\n\r\nGET / HTTP/1.1\r\nHost: x\r\n\r\n",
+        port
+    );
+    let response = through_proxy(proxy.addr, &request).await?;
+    assert!(response.starts_with("HTTP/1.1 200 Connection Established"));
+    assert!(response.ends_with("hello"));
+
+    let request = format!(
+        "GET http://127.0.0.1:{}/x HTTP/1.1\r\nHost: x\r\n\r\n",
+        port
+    );
+    assert!(through_proxy(proxy.addr, &request)
+        .await?
+        .ends_with("hello"));
+
+    let request = "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n";
+    assert!(through_proxy(proxy.addr, request)
+        .await?
+        .starts_with("HTTP/1.1 403"));
+    let request = format!("GET http://127.0.0.1:{}/ HTTP/1.1\r\n\r\n", port + 1);
+    assert!(through_proxy(proxy.addr, &request)
+        .await?
+        .starts_with("HTTP/1.1 403"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_per_audit_log() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let port = target().await?;
+    let temp = TempDir::new()?;
+    let allowlist = EgressAllowlist::new(&policy(port).allow_domains);
+    let audit = AuditLog::new(temp.path().join("audit.jsonl"));
+    let audited = proxy_for(&allowlist.clone().with_audit(audit.clone()))?;
+    let other = AuditLog::new(temp.path().join("other.jsonl"));
+    let other = proxy_for(&allowlist.with_audit(other))?;
+    assert_ne!(audited.addr, other.addr);
+    assert_ne!(audited.socket.parent(), other.socket.parent());
+
+    // Each socket sits in its own private directory
+    let dir = audited.socket.parent().unwrap();
+    assert_eq!(std::fs::metadata(dir)?.permissions().mode() & 0o777, 0o700);
+
+    let request = "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n";
+    assert!(through_proxy(audited.addr, request)
+        .await?
+        .starts_with("HTTP/1.1 403"));
+    let records = audit.entries();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["host"], "example.com");
+    assert_eq!(records[0]["allowed"], false);
+    assert!(!temp.path().join("other.jsonl").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_web_fetch_allowlist() -> Result<()> {
+    let port = target().await?;
+    let tool = WebFetchTool::new(1024).with_sandbox(&policy(port), None);
+
+    let url = format!(r#"{{"url": "http://127.0.0.1:{}/"}}"#, port);
+    assert!(tool.execute(&url).await?.contains("hello"));
+
+    let err = tool
+        .execute(r#"{"url": "https://example.com/"}"#)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("egress policy"));
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_sandboxed_command_goes_through_proxy() -> Result<()> {
+    use zier_alpha::security::linux_sandbox::SandboxProbe;
+
+    if std::process::Command::new("curl")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return Ok(());
+    }
+    std::env::set_var("ZIER_ALPHA_BIN", env!("CARGO_BIN_EXE_zier-alpha"));
+
+    let port = target().await?;
+    let probe = SandboxProbe::get();
+    let mut backends = Vec::new();
+    if probe.landlock_abi.unwrap_or(0) >= 4 && probe.seccomp {
+        backends.push(LinuxSandboxBackend::Landlock);
+    }
+    if probe.user_namespaces {
+        backends.push(LinuxSandboxBackend::Unshare);
+    }
+
+    for backend in backends {
+        let cwd = TempDir::new()?;
+        let policy = SandboxPolicy {
+            enable_os_sandbox: true,
+            linux_backend: backend,
+            allow_read: vec!["/etc".to_string()],
+            ..policy(port)
+        };
+        let script = format!(
+            "curl -sS http://127.0.0.1:{0}/; \
+             curl -s -o /dev/null -w ' %{{http_code}}' http://127.0.0.1:{1}/; \
+             curl -s --noproxy '*' http://127.0.0.1:{0}/ || echo ' direct-failed'",
+            port,
+            port + 1
+        );
+        let args = vec!["-c".to_string(), script];
+        let output =
+            run_sandboxed_command("sh", &args, &cwd.path().to_path_buf(), None, &policy, None)
+                .await?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout.trim(),
+            "hello 403 direct-failed",
+            "{:?}: {:?}",
+            backend,
+            output
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use zier_alpha::config::{Config, ExtraProviderConfig, OllamaConfig};
+use zier_alpha::memory::{
+    EmbeddingProvider, MemoryManager, OllamaEmbeddingProvider, OpenAIEmbeddingProvider,
+};
+
+/// Requests seen by the mock server: (input count, had Authorization header)
+type Seen = Arc<Mutex<Vec<(usize, bool)>>>;
+
+fn inputs(body: &Value) -> usize {
+    body["input"].as_array().map(|a| a.len()).unwrap_or(0)
+}
+
+async fn ollama_embed(
+    State(seen): State<Seen>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let n = inputs(&body);
+    seen.lock()
+        .unwrap()
+        .push((n, headers.contains_key("authorization")));
+    Json(json!({ "model": body["model"], "embeddings": vec![vec![3.0, 4.0, 0.0]; n] }))
+}
+
+async fn openai_embeddings(
+    State(seen): State<Seen>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let n = inputs(&body);
+    seen.lock()
+        .unwrap()
+        .push((n, headers.contains_key("authorization")));
+    let data: Vec<Value> = (0..n)
+        .map(|i| json!({ "index": i, "embedding": [1.0, 0.0, 0.0, 0.0] }))
+        .collect();
+    Json(json!({ "data": data }))
+}
+
+async fn mock_server() -> (String, Seen) {
+    let seen: Seen = Arc::default();
+    let app = Router::new()
+        .route("/api/embed", post(ollama_embed))
+        .route("/v1/embeddings", post(openai_embeddings))
+        .with_state(seen.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), seen)
+}
+
+#[tokio::test]
+async fn test_ollama_batches_and_detects_dimensions() {
+    let (url, seen) = mock_server().await;
+    let provider = OllamaEmbeddingProvider::new(&url, "nomic-embed-text").unwrap();
+    assert_eq!(provider.id(), "ollama");
+    assert_eq!(provider.dimensions(), 0);
+
+    let texts: Vec<String> = (0..40).map(|i| format!("text {}", i)).collect();
+    let embeddings = provider.embed_batch(&texts).await.unwrap();
+
+    assert_eq!(embeddings.len(), 40);
+    assert!((embeddings[0][0] - 0.6).abs() < 1e-6);
+    assert_eq!(provider.dimensions(), 3);
+    assert_eq!(*seen.lock().unwrap(), vec![(32, false), (8, false)]);
+}
+
+#[tokio::test]
+async fn test_openai_compatible_endpoint() {
+    let (url, seen) = mock_server().await;
+    let provider =
+        OpenAIEmbeddingProvider::new_compatible("llamacpp", "", &format!("{}/v1/", url), "bge-m3")
+            .unwrap();
+    assert_eq!(provider.id(), "llamacpp");
+    assert_eq!(provider.dimensions(), 0);
+
+    let embedding = provider.embed("hello").await.unwrap();
+    assert_eq!(embedding.len(), 4);
+    assert_eq!(provider.dimensions(), 4);
+    // No API key configured: no Authorization header
+    assert_eq!(*seen.lock().unwrap(), vec![(1, false)]);
+}
+
+#[test]
+fn test_embedding_provider_selection() {
+    let temp = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.memory.workspace = temp.path().join("workspace").to_string_lossy().to_string();
+    config.providers.ollama = Some(OllamaConfig {
+        endpoint: "http://127.0.0.1:1".to_string(),
+        model: "llama3".to_string(),
+    });
+    config.providers.extra.insert(
+        "lmstudio".to_string(),
+        ExtraProviderConfig {
+            api_key: None,
+            base_url: "http://127.0.0.1:1/v1".to_string(),
+            r#type: Some("openai".to_string()),
+            _other: HashMap::new(),
+        },
+    );
+
+    let has_embeddings = |provider: &str| {
+        let mut config = config.clone();
+        config.memory.embedding_provider = provider.to_string();
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test")
+            .unwrap()
+            .has_embeddings()
+    };
+
+    assert!(has_embeddings("ollama"));
+    assert!(has_embeddings("lmstudio"));
+    assert!(!has_embeddings("unknown"));
+    assert!(!has_embeddings("none"));
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::agent::{Agent, AgentConfig, ContextStrategy, RewindAction};
+use zier_alpha::config::Config;
+use zier_alpha::memory::MemoryManager;
+
+async fn agent_in(temp_dir: &TempDir) -> Agent {
+    agent_with(temp_dir, |_| {}).await
+}
+
+async fn agent_with(temp_dir: &TempDir, configure: impl FnOnce(&mut Config)) -> Agent {
+    let mut config = Config::default();
+    config.memory.workspace = temp_dir
+        .path()
+        .join("workspace")
+        .to_string_lossy()
+        .to_string();
+    config.agent.default_model = "mock/test".to_string();
+    config.tools.allowed_builtin = vec!["*".to_string()];
+    config.disk.min_free_percent = 0.0;
+    configure(&mut config);
+
+    let memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+    let agent_config = AgentConfig {
+        model: "mock/test".to_string(),
+        context_window: 100000,
+        reserve_tokens: 1000,
+    };
+    let mut agent = Agent::new_with_project(
+        agent_config,
+        &config,
+        memory,
+        ContextStrategy::Full,
+        temp_dir.path().join("project"),
+        "test",
+    )
+    .await
+    .unwrap();
+    agent.new_session().await.unwrap();
+    agent
+}
+
+#[tokio::test]
+async fn test_rewind_agent_edits() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("notes.txt"), "original\n").unwrap();
+    let mut agent = agent_in(&temp_dir).await;
+
+    agent
+        .chat("test_tool:write_file|notes.txt|first edit\n")
+        .await
+        .unwrap();
+    agent
+        .chat(r#"test_tool_json:edit_file|{"path":"notes.txt","old_string":"first","new_string":"second"}"#)
+        .await
+        .unwrap();
+    agent
+        .chat("test_tool:write_file|created.txt|new file\n")
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(project.join("notes.txt")).unwrap(),
+        "second edit\n"
+    );
+
+    let turns = agent.checkpoints().await.unwrap();
+    assert_eq!(turns.len(), 3);
+
+    // Preview leaves files alone
+    let preview = agent.rewind(Some(2), true).await.unwrap();
+    assert_eq!(preview.changes.len(), 2);
+    assert!(preview
+        .changes
+        .iter()
+        .any(|c| c.action == RewindAction::Delete && c.path.ends_with("created.txt")));
+    assert!(project.join("created.txt").exists());
+
+    agent.rewind(Some(2), false).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(project.join("notes.txt")).unwrap(),
+        "first edit\n"
+    );
+    assert!(!project.join("created.txt").exists());
+
+    // Without a turn, the last one left is undone
+    let plan = agent.rewind(None, false).await.unwrap();
+    assert_eq!(plan.turn, 1);
+    assert_eq!(
+        fs::read_to_string(project.join("notes.txt")).unwrap(),
+        "original\n"
+    );
+    assert!(agent.rewind(None, true).await.is_err());
+}
+
+#[tokio::test]
+async fn test_rewind_section_and_memory_writes() {
+    let temp_dir = TempDir::new().unwrap();
+    let memory_file = temp_dir.path().join("workspace").join("MEMORY.md");
+    fs::create_dir_all(memory_file.parent().unwrap()).unwrap();
+    fs::write(
+        &memory_file,
+        "# Memory\n\n## Preferences\n\n- Shell: fish\n",
+    )
+    .unwrap();
+    let mut agent = agent_in(&temp_dir).await;
+    let original = fs::read_to_string(&memory_file).unwrap();
+
+    agent
+        .chat(r#"test_tool_json:memory_write|{"section":"People","fact":"Alice owns billing"}"#)
+        .await
+        .unwrap();
+    agent
+        .chat(r#"test_tool_json:edit_section|{"path":"MEMORY.md#Preferences","content":"- Shell: zsh\n"}"#)
+        .await
+        .unwrap();
+    let edited = fs::read_to_string(&memory_file).unwrap();
+    assert!(edited.contains("Alice owns billing"));
+    assert!(edited.contains("zsh"));
+    assert_eq!(agent.checkpoints().await.unwrap().len(), 2);
+
+    agent.rewind(None, false).await.unwrap();
+    let content = fs::read_to_string(&memory_file).unwrap();
+    assert!(content.contains("fish") && content.contains("Alice owns billing"));
+
+    agent.rewind(None, false).await.unwrap();
+    assert_eq!(fs::read_to_string(&memory_file).unwrap(), original);
+}
+
+#[tokio::test]
+async fn test_rewind_refused_when_checkpoints_disabled() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("project")).unwrap();
+    let mut agent = agent_with(&temp_dir, |config| config.tools.checkpoints = false).await;
+
+    agent
+        .chat("test_tool:write_file|notes.txt|first edit\n")
+        .await
+        .unwrap();
+    assert!(agent.checkpoints().await.unwrap().is_empty());
+    let err = agent.rewind(None, true).await.unwrap_err();
+    assert!(err.to_string().contains("disabled"), "{}", err);
+}
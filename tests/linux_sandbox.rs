@@ -0,0 +1,91 @@
+#![cfg(target_os = "linux")]
+
+use anyhow::Result;
+use tempfile::TempDir;
+use zier_alpha::agent::tools::runner::run_sandboxed_command;
+use zier_alpha::config::{LinuxSandboxBackend, SandboxPolicy};
+use zier_alpha::security::linux_sandbox::SandboxProbe;
+
+fn landlock_policy(write: &TempDir) -> SandboxPolicy {
+    SandboxPolicy {
+        enable_os_sandbox: true,
+        allow_write: vec![write.path().to_string_lossy().to_string()],
+        linux_backend: LinuxSandboxBackend::Landlock,
+        ..Default::default()
+    }
+}
+
+async fn sh(script: &str, cwd: &TempDir, policy: &SandboxPolicy) -> Result<std::process::Output> {
+    let args = vec!["-c".to_string(), script.to_string()];
+    run_sandboxed_command("sh", &args, &cwd.path().to_path_buf(), None, policy, None).await
+}
+
+#[tokio::test]
+async fn test_landlock_filesystem_rules() -> Result<()> {
+    if SandboxProbe::get().landlock_abi.is_none() {
+        eprintln!("Landlock not supported by this kernel, skipping");
+        return Ok(());
+    }
+
+    let cwd = TempDir::new()?;
+    let writable = TempDir::new()?;
+    let policy = landlock_policy(&writable);
+
+    // cwd and allow_write are writable
+    let target = writable.path().join("out.txt");
+    let script = format!("echo ok > out.txt && echo ok > {}", target.display());
+    let output = sh(&script, &cwd, &policy).await?;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(target.exists());
+
+    // Everything else in $HOME is not even readable
+    let home = std::env::var("HOME")?;
+    let output = sh(&format!("ls {}", home), &cwd, &policy).await?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seccomp_denies_network() -> Result<()> {
+    if SandboxProbe::get().landlock_abi.is_none() || !SandboxProbe::get().seccomp {
+        return Ok(());
+    }
+    if std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let cwd = TempDir::new()?;
+    let mut policy = landlock_policy(&cwd);
+    policy.allow_read.push("/etc".to_string());
+    let script = "import socket\ntry:\n    socket.socket(socket.AF_INET, socket.SOCK_STREAM)\n    print('opened')\nexcept OSError as e:\n    print('denied', e.errno)\n";
+    let args = vec!["-c".to_string(), script.to_string()];
+
+    let output = run_sandboxed_command(
+        "python3",
+        &args,
+        &cwd.path().to_path_buf(),
+        None,
+        &policy,
+        None,
+    )
+    .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("denied 13"), "{:?}", output);
+
+    policy.allow_network = true;
+    let output = run_sandboxed_command(
+        "python3",
+        &args,
+        &cwd.path().to_path_buf(),
+        None,
+        &policy,
+        None,
+    )
+    .await?;
+    assert!(String::from_utf8_lossy(&output.stdout).contains("opened"));
+    Ok(())
+}
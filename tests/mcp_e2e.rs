@@ -28,6 +28,7 @@ async fn test_mcp_e2e() {
         allow_write: vec![],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     // 3. Initialize Service
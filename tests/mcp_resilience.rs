@@ -18,6 +18,7 @@ async fn test_mcp_resilience_dead_process() {
         env: None,
         strategy: None,
         native_tools: vec![],
+        limits: Default::default(),
     };
 
     manager.initialize(vec![config]).await;
@@ -64,6 +65,7 @@ sys.exit(1) # Crash
         env: None,
         strategy: None,
         native_tools: vec![],
+        limits: Default::default(),
     };
 
     manager.initialize(vec![config]).await;
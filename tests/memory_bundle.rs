@@ -0,0 +1,147 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::config::Config;
+use zier_alpha::memory::{ConflictPolicy, ImportOptions, MemoryManager, OpenClawImport};
+
+fn manager(workspace: &std::path::Path, agent: &str) -> MemoryManager {
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    MemoryManager::new_with_full_config(&config.memory, None, agent).unwrap()
+}
+
+#[tokio::test]
+async fn test_export_and_import_bundle() {
+    let source = TempDir::new().unwrap();
+    let source_ws = source.path().join("workspace");
+    let memory = manager(&source_ws, "main");
+    fs::write(
+        source_ws.join("MEMORY.md"),
+        "# Memory\n\nAlice runs Alpha.\n",
+    )
+    .unwrap();
+    fs::create_dir_all(source_ws.join("skills").join("deploy")).unwrap();
+    fs::write(
+        source_ws.join("skills").join("deploy").join("run.sh"),
+        "#!/bin/sh\necho deploy\n",
+    )
+    .unwrap();
+    memory.reindex(false).await.unwrap();
+    memory
+        .graph_relate(
+            "Alice",
+            "works on",
+            "Alpha",
+            Some("person"),
+            Some("project"),
+        )
+        .await
+        .unwrap();
+
+    let bundle = source.path().join("export").join("memory.jsonl.gz");
+    let stats = memory.export_bundle(&bundle, true).await.unwrap();
+    assert!(stats.files >= 2);
+    assert_eq!(stats.relations, 1);
+
+    let target = TempDir::new().unwrap();
+    let target_ws = target.path().join("workspace");
+    let restored = manager(&target_ws, "main");
+    fs::write(target_ws.join("MEMORY.md"), "# Memory\n\nMine.\n").unwrap();
+
+    // Dry run writes nothing
+    let dry_run = ImportOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    let report = restored.import_bundle(&bundle, &dry_run).await.unwrap();
+    assert!(report.written.iter().any(|p| p == "skills/deploy/run.sh"));
+    assert!(!target_ws.join("skills").join("deploy").exists());
+
+    let rename = ImportOptions {
+        on_conflict: ConflictPolicy::Rename,
+        dry_run: false,
+    };
+    let report = restored.import_bundle(&bundle, &rename).await.unwrap();
+    assert_eq!(
+        report.renamed,
+        vec![("MEMORY.md".to_string(), "MEMORY.imported.md".to_string())]
+    );
+    assert_eq!(report.relations, 1);
+    assert_eq!(
+        fs::read_to_string(target_ws.join("MEMORY.md")).unwrap(),
+        "# Memory\n\nMine.\n"
+    );
+    assert!(fs::read_to_string(target_ws.join("MEMORY.imported.md"))
+        .unwrap()
+        .contains("Alice runs Alpha"));
+    assert_eq!(
+        fs::read_to_string(target_ws.join("skills").join("deploy").join("run.sh")).unwrap(),
+        "#!/bin/sh\necho deploy\n"
+    );
+
+    let alice = restored.graph_node("Alice").await.unwrap().unwrap();
+    assert_eq!(alice.kind, "person");
+    assert!(!restored.search("Alice", 5).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_import_openclaw_state_dir() {
+    let openclaw = TempDir::new().unwrap();
+    let state = openclaw.path();
+    fs::create_dir_all(state.join("workspace").join("memory")).unwrap();
+    fs::write(
+        state.join("workspace").join("MEMORY.md"),
+        "# Memory\n\nImported from OpenClaw.\n",
+    )
+    .unwrap();
+    let sessions = state.join("agents").join("main").join("sessions");
+    fs::create_dir_all(&sessions).unwrap();
+    fs::write(sessions.join("abc.jsonl"), "{\"type\":\"session\"}\n").unwrap();
+    fs::write(
+        sessions.join("sessions.json"),
+        r#"{"agent:main:main":{"sessionId":"abc","updatedAt":1}}"#,
+    )
+    .unwrap();
+    fs::create_dir_all(state.join("memory")).unwrap();
+    let db = rusqlite::Connection::open(state.join("memory").join("main.sqlite")).unwrap();
+    db.execute_batch(
+        "CREATE TABLE embedding_cache (provider TEXT, model TEXT, provider_key TEXT, hash TEXT, embedding TEXT, dims INTEGER, updated_at INTEGER);
+         INSERT INTO embedding_cache VALUES ('openai', 'text-embedding-3-small', '', 'h1', '[0.1,0.2]', 2, 0);",
+    )
+    .unwrap();
+    drop(db);
+
+    let target = TempDir::new().unwrap();
+    let workspace = target.path().join("workspace");
+    let memory = manager(&workspace, "ops");
+    let import = OpenClawImport {
+        options: ImportOptions::default(),
+        agents: [("main".to_string(), "ops".to_string())].into(),
+        state_dir: target.path().to_path_buf(),
+    };
+    let report = memory.import_openclaw(state, &import).await.unwrap();
+    assert_eq!(report.sessions, 1);
+    assert_eq!(report.embeddings, 1);
+    // The new workspace's template MEMORY.md is kept
+    assert_eq!(report.conflicts, vec!["MEMORY.md"]);
+
+    let imported = target.path().join("agents").join("ops").join("sessions");
+    assert!(imported.join("abc.jsonl").exists());
+    let store: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(imported.join("sessions.json")).unwrap()).unwrap();
+    assert_eq!(store["agent:main:main"]["sessionId"], "abc");
+
+    let index =
+        rusqlite::Connection::open(target.path().join("memory").join("ops.sqlite")).unwrap();
+    let cached: i64 = index
+        .query_row(
+            "SELECT COUNT(*) FROM embedding_cache WHERE hash = 'h1'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(cached, 1);
+
+    // A second import changes nothing
+    let report = memory.import_openclaw(state, &import).await.unwrap();
+    assert_eq!((report.sessions, report.embeddings), (0, 0));
+}
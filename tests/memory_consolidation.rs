@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use std::fs;
+use std::sync::Mutex;
+use tempfile::TempDir;
+use zier_alpha::agent::{LLMProvider, LLMResponse, Message, ToolSchema};
+use zier_alpha::config::Config;
+use zier_alpha::memory::{ConsolidationOptions, MemoryManager};
+
+/// Answers `[]` and keeps every prompt it was sent
+#[derive(Default)]
+struct RecordingProvider {
+    prompts: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl LLMProvider for RecordingProvider {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        _tools: Option<&[ToolSchema]>,
+    ) -> anyhow::Result<LLMResponse> {
+        let prompt = messages
+            .last()
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        self.prompts.lock().unwrap().push(prompt);
+        Ok(LLMResponse::text("[]".to_string()))
+    }
+
+    async fn summarize(&self, _text: &str) -> anyhow::Result<String> {
+        Ok(String::new())
+    }
+}
+
+fn options() -> ConsolidationOptions {
+    ConsolidationOptions {
+        lookback_days: 7,
+        auto_apply: false,
+        dry_run: false,
+        model: "mock/test".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_consolidation_prompt_withholds_private_content() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    config.memory.private_paths = vec![format!("memory/{}-health.md", today)];
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    fs::create_dir_all(workspace.join("memory")).unwrap();
+    fs::write(
+        workspace.join("MEMORY.md"),
+        "# Memory\n\n- Favourite tea is oolong\n\n<!-- private -->\n- Safe code 4321\n<!-- /private -->\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("memory").join(format!("{}.md", today)),
+        "Met Alice about the launch.\n<!-- private -->\nSalary talk: 90k\n<!-- /private -->\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace
+            .join("memory")
+            .join(format!("{}-health.md", today)),
+        "Blood pressure 130/85\n",
+    )
+    .unwrap();
+
+    let provider = RecordingProvider::default();
+    let run = memory
+        .consolidate(&provider, &options())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.logs, vec![format!("memory/{}.md", today)]);
+
+    let prompts = provider.prompts.lock().unwrap();
+    assert_eq!(prompts.len(), 1);
+    assert!(prompts[0].contains("oolong"));
+    assert!(prompts[0].contains("Met Alice"));
+    for secret in ["4321", "90k", "130/85"] {
+        assert!(!prompts[0].contains(secret), "leaked {}", secret);
+    }
+}
+
+#[tokio::test]
+async fn test_consolidation_sends_long_logs_whole() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    fs::create_dir_all(workspace.join("memory")).unwrap();
+    let long: String = (0..4000)
+        .map(|i| format!("Entry {} of a very busy day\n", i))
+        .collect();
+    fs::write(
+        workspace.join("memory").join(format!("{}.md", today)),
+        &long,
+    )
+    .unwrap();
+
+    let provider = RecordingProvider::default();
+    let run = memory
+        .consolidate(&provider, &options())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.logs.len(), 1);
+
+    // Spread over several prompts, but nothing is cut off
+    let prompts = provider.prompts.lock().unwrap();
+    assert!(prompts.len() > 1);
+    assert!(prompts.iter().any(|p| p.contains("Entry 0 of")));
+    assert!(prompts.iter().any(|p| p.contains("Entry 3999 of")));
+    drop(prompts);
+
+    // The log is done with until it changes
+    assert!(memory
+        .consolidate(&provider, &options())
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -1,48 +1,61 @@
-use rusqlite::Connection;
+use std::fs;
 use tempfile::TempDir;
-use zier_alpha::memory::MemoryIndex;
+use zier_alpha::memory::{MemoryIndex, ModelState};
 
 #[tokio::test]
-async fn test_vec_table_dimension() {
+async fn test_models_with_different_dimensions_coexist() {
     let temp = TempDir::new().unwrap();
     let db_path = temp.path().join("test.sqlite");
+    let note = temp.path().join("note.md");
+    fs::write(&note, "# Note\n\nVectors of any size").unwrap();
+
+    let index = MemoryIndex::new_with_db_path(temp.path(), &db_path).unwrap();
+    index.index_file(&note, false).await.unwrap();
+    let chunks = index.chunks_without_embeddings("small", 10).await.unwrap();
+    assert_eq!(chunks.len(), 1);
+    let chunk_id = &chunks[0].0;
+
+    index
+        .register_embedding_model("small", "local")
+        .await
+        .unwrap();
+    index
+        .store_embedding(chunk_id, &vec![0.5; 512], "small")
+        .await
+        .unwrap();
 
-    // Create with specific dimension
-    let _index = MemoryIndex::new_with_db_path(temp.path(), &db_path, Some(1024)).unwrap();
-
-    // Verify table dimension
-    let conn = Connection::open(&db_path).unwrap();
-
-    // sqlite-vec loads as extension, need to load it again if we want to query vec0 table details?
-    // Or just check sql schema.
-
-    let sql: String = conn
-        .query_row(
-            "SELECT sql FROM sqlite_master WHERE name='chunks_vec'",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or("".to_string());
-
-    if sql.is_empty() {
-        println!("Skipping test as sqlite-vec not loaded or table not created");
-        return;
-    }
-
-    assert!(sql.contains("float[1024]"), "SQL was: {}", sql);
-
-    // Try creating with DIFFERENT dimension - should fail or warn (and disable ext)
-    // We can't check logs easily, but we can check if table was altered (it shouldn't be)
-    let _index2 = MemoryIndex::new_with_db_path(temp.path(), &db_path, Some(512));
-
-    // Table should still be 1024
-    let sql2: String = conn
-        .query_row(
-            "SELECT sql FROM sqlite_master WHERE name='chunks_vec'",
-            [],
-            |row| row.get(0),
-        )
+    // Reopening with a model of another dimension no longer conflicts
+    let index = MemoryIndex::new_with_db_path(temp.path(), &db_path).unwrap();
+    assert_eq!(
+        index
+            .register_embedding_model("large", "openai")
+            .await
+            .unwrap(),
+        ModelState::Building
+    );
+    index
+        .store_embedding(chunk_id, &vec![0.5; 1024], "large")
+        .await
         .unwrap();
 
-    assert!(sql2.contains("float[1024]"), "SQL changed to: {}", sql2);
+    assert_eq!(
+        index
+            .search_vector(&vec![0.5; 512], "small", 5)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        index
+            .search_vector(&vec![0.5; 1024], "large", 5)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let models = index.embedding_models().await.unwrap();
+    let dims: Vec<_> = models.iter().map(|m| (m.model.as_str(), m.dims)).collect();
+    assert_eq!(dims, vec![("small", Some(512)), ("large", Some(1024))]);
 }
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+use zier_alpha::agent::{create_default_tools, DiskMonitor, SmartClient, ToolCall, ToolExecutor};
+use zier_alpha::config::{Config, ExtraProviderConfig, FederatedWorkspaceConfig};
+use zier_alpha::memory::{MemoryManager, SearchFilter};
+
+fn call(name: &str, arguments: &str) -> ToolCall {
+    ToolCall {
+        id: format!("call-{}", name),
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_federated_search_and_get() {
+    let temp = TempDir::new().unwrap();
+    let main_workspace = temp.path().join("main").join("workspace");
+    let work_workspace = temp.path().join("work").join("workspace");
+
+    // The "main" profile records something the "work" profile doesn't know
+    let mut main_config = Config::default();
+    main_config.memory.workspace = main_workspace.to_string_lossy().to_string();
+    let main = MemoryManager::new_with_full_config(&main_config.memory, None, "main").unwrap();
+    fs::create_dir_all(main_workspace.join("memory")).unwrap();
+    fs::write(
+        main_workspace.join("memory").join("notes.md"),
+        "# Notes\n\nThe zebra migration starts in March.\n",
+    )
+    .unwrap();
+    main.reindex(false).await.unwrap();
+
+    let mut config = Config::default();
+    config.memory.workspace = work_workspace.to_string_lossy().to_string();
+    config.disk.min_free_percent = 0.0;
+    config.memory.federation = vec![FederatedWorkspaceConfig {
+        name: "main".to_string(),
+        workspace: Some(main_workspace.to_string_lossy().to_string()),
+        profile: None,
+        agent: "main".to_string(),
+        allow_cloud: false,
+    }];
+    let work = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+
+    let results = work.search("zebra", 5).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].origin.as_deref(), Some("main"));
+    assert_eq!(results[0].file, "memory/notes.md");
+
+    // Not allowed to reach a cloud provider
+    let cloud = SearchFilter {
+        cloud_bound: true,
+        ..Default::default()
+    };
+    assert!(work
+        .search_filtered("zebra", 5, &cloud)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let tools = create_default_tools(
+        &config,
+        Some(Arc::new(work)),
+        DiskMonitor::new(config.disk.clone()),
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(tools, config.clone());
+    let search = call("memory_search", r#"{"query":"zebra"}"#);
+    let get = call(
+        "memory_get",
+        r#"{"path":"memory/notes.md","origin":"main"}"#,
+    );
+
+    let output = executor
+        .execute_tool_for_session(&search, "s1", true)
+        .await
+        .unwrap();
+    assert!(output.contains("[origin: main] memory/notes.md"));
+    let output = executor
+        .execute_tool_for_session(&get, "s1", true)
+        .await
+        .unwrap();
+    assert!(output.contains("zebra migration"));
+
+    let output = executor
+        .execute_tool_for_session(&search, "s1", false)
+        .await
+        .unwrap();
+    assert!(!output.contains("zebra"));
+    assert!(executor
+        .execute_tool_for_session(&get, "s1", false)
+        .await
+        .is_err());
+
+    // Reads stay inside the federated workspace
+    let escape = call(
+        "memory_get",
+        r#"{"path":"../../work/workspace/MEMORY.md","origin":"main"}"#,
+    );
+    assert!(executor
+        .execute_tool_for_session(&escape, "s1", true)
+        .await
+        .is_err());
+}
+
+#[test]
+fn test_local_provider_detection() {
+    let mut config = Config::default();
+    config.providers.extra.insert(
+        "lmstudio".to_string(),
+        ExtraProviderConfig {
+            api_key: None,
+            base_url: "http://127.0.0.1:1234/v1".to_string(),
+            r#type: Some("openai".to_string()),
+            _other: HashMap::new(),
+        },
+    );
+    config.providers.extra.insert(
+        "openrouter".to_string(),
+        ExtraProviderConfig {
+            api_key: None,
+            base_url: "https://openrouter.ai/api/v1".to_string(),
+            r#type: Some("openai".to_string()),
+            _other: HashMap::new(),
+        },
+    );
+
+    let is_local = |model: &str| SmartClient::new(config.clone(), model.to_string()).is_local();
+    assert!(is_local("mock/test"));
+    assert!(is_local("ollama/llama3"));
+    assert!(is_local("lmstudio/qwen"));
+    assert!(!is_local("openrouter/qwen"));
+    assert!(!is_local("anthropic/claude-sonnet-4-5"));
+    assert!(!is_local("claude-cli/opus"));
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::agent::CheckpointStore;
+use zier_alpha::config::Config;
+use zier_alpha::memory::{ForgetMode, ForgetOptions, MemoryManager, FORGOTTEN};
+
+#[tokio::test]
+async fn test_forget_purges_files_transcripts_and_index() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let sessions = temp.path().join("sessions");
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    config.memory.auto_commit = false;
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+
+    fs::write(
+        workspace.join("MEMORY.md"),
+        "# Memory\n\n- Bank PIN is 9Z7Q-SECRET\n- Likes tea\n",
+    )
+    .unwrap();
+    fs::create_dir_all(workspace.join("memory")).unwrap();
+    fs::write(
+        workspace.join("memory").join("2026-10-01.md"),
+        "# 2026-10-01\n\nPasted 9z7q-secret by mistake.\nWalked the dog.\n",
+    )
+    .unwrap();
+    fs::create_dir_all(workspace.join("artifacts")).unwrap();
+    fs::write(
+        workspace.join("artifacts").join("report.md"),
+        "Reminder: 9Z7Q-SECRET\n",
+    )
+    .unwrap();
+    fs::create_dir_all(&sessions).unwrap();
+    fs::write(
+        sessions.join("s1.jsonl"),
+        "{\"type\":\"session\",\"id\":\"s1\"}\n{\"message\":{\"role\":\"user\",\"content\":\"my pin is 9Z7Q-SECRET\"}}\n",
+    )
+    .unwrap();
+    memory.reindex(false).await.unwrap();
+    memory
+        .graph_relate("9Z7Q-SECRET", "unlocks", "Bank", None, None)
+        .await
+        .unwrap();
+    assert!(!memory.search("9Z7Q", 10).await.unwrap().is_empty());
+
+    let mut options = ForgetOptions {
+        mode: ForgetMode::Redact,
+        apply: false,
+        sessions_dir: Some(sessions.clone()),
+    };
+    let report = memory.forget("9z7q-secret", &options).await.unwrap();
+    assert_eq!(report.memory.len(), 2);
+    assert_eq!(report.artifacts.len(), 1);
+    assert_eq!(report.sessions[0].lines, vec![2]);
+    assert_eq!(report.relations, 1);
+    assert!(report.chunks >= 3);
+    // The dry run changes nothing
+    assert!(fs::read_to_string(workspace.join("MEMORY.md"))
+        .unwrap()
+        .contains("9Z7Q-SECRET"));
+
+    options.apply = true;
+    let report = memory.forget("9z7q-secret", &options).await.unwrap();
+    assert!(report.applied);
+    assert_eq!(
+        fs::read_to_string(workspace.join("MEMORY.md")).unwrap(),
+        format!("# Memory\n\n- Bank PIN is {}\n- Likes tea\n", FORGOTTEN)
+    );
+    let transcript = fs::read_to_string(sessions.join("s1.jsonl")).unwrap();
+    assert!(!transcript.to_lowercase().contains("9z7q"));
+    assert_eq!(transcript.lines().count(), 2);
+    assert!(memory.search("9Z7Q", 10).await.unwrap().is_empty());
+    assert!(memory.graph_node("9Z7Q-SECRET").await.unwrap().is_none());
+
+    // The tombstone records the purge without the forgotten text
+    let tombstones: Vec<_> = memory
+        .audit_log()
+        .entries()
+        .into_iter()
+        .filter(|e| e["event"] == "forget")
+        .collect();
+    assert_eq!(tombstones.len(), 1);
+    assert_eq!(tombstones[0]["memory_files"], 2);
+    assert!(!tombstones[0].to_string().to_lowercase().contains("9z7q"));
+
+    options.apply = false;
+    assert!(memory
+        .forget("9Z7Q-SECRET", &options)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Delete mode drops whole lines
+    options.mode = ForgetMode::Delete;
+    options.apply = true;
+    memory.forget("walked the dog", &options).await.unwrap();
+    assert!(
+        !fs::read_to_string(workspace.join("memory").join("2026-10-01.md"))
+            .unwrap()
+            .contains("Walked")
+    );
+    assert!(memory.forget("ab", &options).await.is_err());
+}
+
+#[tokio::test]
+async fn test_forget_redacts_checkpoints_and_reports_history() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let sessions = temp.path().join("sessions");
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    config.memory.auto_commit = true;
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+
+    let memory_md = workspace.join("MEMORY.md");
+    fs::write(&memory_md, "# Memory\n\n- Bank PIN is 9Z7Q-SECRET\n").unwrap();
+    let added = memory.commit_workspace("Add PIN").await.unwrap().unwrap();
+    let checkpoints = CheckpointStore::for_workspace(&workspace);
+    checkpoints.snapshot("s1", 100, &memory_md).unwrap();
+    memory.reindex(false).await.unwrap();
+
+    let options = ForgetOptions {
+        mode: ForgetMode::Redact,
+        apply: true,
+        sessions_dir: Some(sessions),
+    };
+    let report = memory.forget("9z7q-secret", &options).await.unwrap();
+    assert!(!fs::read_to_string(&memory_md).unwrap().contains("9Z7Q"));
+
+    // The forget commit is clean, the one before it still has the text
+    assert_eq!(report.history.len(), 1);
+    assert_eq!(report.history[0].id, added);
+    assert_eq!(report.checkpoints.len(), 1);
+    assert_eq!(report.checkpoints[0].session_id, "s1");
+    let output = report.to_string();
+    assert!(output.contains("git history still contains it in 1 commit(s)"));
+    assert!(output.contains("File checkpoints redacted (1)"));
+    assert!(checkpoints
+        .find(&regex::Regex::new("(?i)9z7q").unwrap())
+        .unwrap()
+        .is_empty());
+}
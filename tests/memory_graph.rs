@@ -0,0 +1,104 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::config::Config;
+use zier_alpha::memory::MemoryManager;
+
+fn config_in(temp: &TempDir) -> Config {
+    let mut config = Config::default();
+    config.memory.workspace = temp.path().join("workspace").to_string_lossy().to_string();
+    config
+}
+
+fn write(workspace: &std::path::Path, path: &str, content: &str) {
+    let path = workspace.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[tokio::test]
+async fn test_graph_from_indexed_files() {
+    let temp = TempDir::new().unwrap();
+    let config = config_in(&temp);
+    let workspace = temp.path().join("workspace");
+    write(
+        &workspace,
+        "a/notes.md",
+        "# Notes\n\nKickoff with [[Alice]].\n",
+    );
+    write(
+        &workspace,
+        "b/notes.md",
+        "# Notes\n\nNothing linked here.\n",
+    );
+    write(
+        &workspace,
+        "projects/launch.md",
+        "# Launch\n\n- owner:: [[Alice]]\n\nSee [[a/notes]].\n",
+    );
+    write(
+        &workspace,
+        "people/Alice.md",
+        "# Alice\n\nRuns the launch.\n",
+    );
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    memory.reindex(false).await.unwrap();
+
+    // Same file name in different folders gives different nodes
+    let a = memory.graph_node("a/notes.md").await.unwrap().expect("a");
+    let b = memory.graph_node("b/notes").await.unwrap().expect("b");
+    assert_eq!(a.path.as_deref(), Some("a/notes.md"));
+    assert_eq!(b.path.as_deref(), Some("b/notes.md"));
+    assert!(memory.graph_node("notes").await.unwrap().is_none());
+
+    // A bare name resolves to the only note with that name
+    let alice = memory.graph_node("Alice").await.unwrap().expect("alice");
+    assert_eq!(alice.name, "people/alice");
+    assert_eq!(alice.kind, "note");
+
+    let backlinks = memory.graph_backlinks("alice").await.unwrap();
+    assert!(backlinks.iter().any(|e| e.from == "a/notes"));
+    assert!(backlinks
+        .iter()
+        .any(|e| e.from == "projects/launch" && e.relation == "owner"));
+    assert!(memory.graph_backlinks("b/notes").await.unwrap().is_empty());
+
+    let path = memory.graph_path("b/notes", "people/alice").await.unwrap();
+    assert!(path.is_none());
+    let path = memory
+        .graph_path("projects/launch", "a/notes")
+        .await
+        .unwrap()
+        .expect("path");
+    assert_eq!(path.nodes, vec!["projects/launch", "a/notes"]);
+}
+
+#[tokio::test]
+async fn test_graph_backfills_unchanged_files() {
+    let temp = TempDir::new().unwrap();
+    let config = config_in(&temp);
+    let workspace = temp.path().join("workspace");
+    write(&workspace, "launch.md", "# Launch\n\nSee [[Alice]].\n");
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    memory.reindex(false).await.unwrap();
+    drop(memory);
+
+    // An index written before graph extraction: chunks, but no graph data
+    let db = temp.path().join("memory").join("main.sqlite");
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    conn.execute_batch(
+        "DELETE FROM links; DELETE FROM entities; DELETE FROM meta WHERE key = 'graph_version';",
+    )
+    .unwrap();
+    drop(conn);
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    let stats = memory.reindex(false).await.unwrap();
+    assert_eq!(stats.files_updated, 1);
+    assert_eq!(memory.graph_backlinks("alice").await.unwrap().len(), 1);
+
+    // Done once: the next sync skips the unchanged file again
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    assert_eq!(memory.reindex(false).await.unwrap().files_updated, 0);
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+use zier_alpha::agent::{create_default_tools, DiskMonitor, ToolCall, ToolExecutor};
+use zier_alpha::config::Config;
+use zier_alpha::memory::{MemoryManager, SearchFilter, WITHHELD};
+use zier_alpha::security::AuditLog;
+
+fn call(name: &str, arguments: &str) -> ToolCall {
+    ToolCall {
+        id: format!("call-{}", name),
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_private_memory_stays_local() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    config.memory.private_paths = vec!["memory/journal".to_string()];
+    config.disk.min_free_percent = 0.0;
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    fs::create_dir_all(workspace.join("memory").join("journal")).unwrap();
+    fs::write(
+        workspace.join("MEMORY.md"),
+        "# Memory\n\nFavourite tea is oolong.\n\n<!-- private -->\nThe oolong supplier PIN is 4321.\n<!-- /private -->\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("memory").join("journal").join("2026-10-01.md"),
+        "# Journal\n\nDrank oolong while worrying about the audit.\n",
+    )
+    .unwrap();
+    memory.reindex(false).await.unwrap();
+
+    // Local providers see everything
+    let all = memory.search("oolong", 10).await.unwrap();
+    assert!(all.iter().any(|c| c.file.starts_with("memory/journal/")));
+    assert!(all.iter().any(|c| c.content.contains("4321")));
+
+    // Cloud-bound searches lose private files and blocks
+    let cloud = SearchFilter {
+        cloud_bound: true,
+        ..Default::default()
+    };
+    let shared = memory.search_filtered("oolong", 10, &cloud).await.unwrap();
+    assert!(!shared.is_empty());
+    assert!(shared.iter().all(|c| !c.file.starts_with("memory/journal/")));
+    assert!(shared.iter().all(|c| !c.content.contains("4321")));
+
+    let audit = AuditLog::for_workspace(&workspace);
+    assert!(audit
+        .entries()
+        .iter()
+        .any(|e| e["event"] == "private_withheld" && e["context"] == "memory_search"));
+
+    let tools = create_default_tools(
+        &config,
+        Some(Arc::new(memory)),
+        DiskMonitor::new(config.disk.clone()),
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(tools, config.clone());
+    let get = call("memory_get", r#"{"path":"MEMORY.md"}"#);
+    let journal = call("memory_get", r#"{"path":"memory/journal/2026-10-01.md"}"#);
+
+    let output = executor
+        .execute_tool_for_session(&get, "s1", true)
+        .await
+        .unwrap();
+    assert!(output.contains("4321"));
+
+    let output = executor
+        .execute_tool_for_session(&get, "s1", false)
+        .await
+        .unwrap();
+    assert!(output.contains("Favourite tea"));
+    assert!(output.contains(WITHHELD));
+    assert!(!output.contains("4321"));
+
+    let output = executor
+        .execute_tool_for_session(&journal, "s1", false)
+        .await
+        .unwrap();
+    assert!(!output.contains("worrying"));
+    assert!(audit
+        .entries()
+        .iter()
+        .any(|e| e["context"] == "memory_get" && e["dropped"] == true && e["session"] == "s1"));
+}
+
+#[tokio::test]
+async fn test_private_links_stay_local() {
+    let temp = TempDir::new().unwrap();
+    let workspace = temp.path().join("workspace");
+    let mut config = Config::default();
+    config.memory.workspace = workspace.to_string_lossy().to_string();
+    config.disk.min_free_percent = 0.0;
+
+    let memory = MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap();
+    fs::write(
+        workspace.join("MEMORY.md"),
+        "# Memory\n\nWorks with [[Alice]].\n\n<!-- private -->\nSees [[Dr Voss]] on Tuesdays.\n<!-- /private -->\n",
+    )
+    .unwrap();
+    memory.reindex(false).await.unwrap();
+
+    let tools = create_default_tools(
+        &config,
+        Some(Arc::new(memory)),
+        DiskMonitor::new(config.disk.clone()),
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(tools, config.clone());
+    let neighbors = call("memory_graph", r#"{"action":"neighbors","node":"MEMORY"}"#);
+    let backlinks = call("memory_graph", r#"{"action":"backlinks","node":"Dr Voss"}"#);
+
+    let output = executor
+        .execute_tool_for_session(&neighbors, "s1", true)
+        .await
+        .unwrap();
+    assert!(output.contains("alice"));
+    assert!(output.contains("voss"));
+
+    let output = executor
+        .execute_tool_for_session(&neighbors, "s1", false)
+        .await
+        .unwrap();
+    assert!(output.contains("alice"));
+    assert!(!output.contains("voss"));
+
+    let output = executor
+        .execute_tool_for_session(&backlinks, "s1", false)
+        .await
+        .unwrap();
+    assert!(!output.contains("MEMORY.md"));
+
+    let audit = AuditLog::for_workspace(&workspace);
+    assert!(audit
+        .entries()
+        .iter()
+        .any(|e| e["event"] == "private_withheld"
+            && e["context"] == "memory_graph"
+            && e["file"] == "MEMORY.md"));
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::agent::tools::create_default_tools_with_project;
+use zier_alpha::agent::{DiskMonitor, ToolCall, ToolExecutor};
+use zier_alpha::config::{Config, WorkdirStrategy};
+
+const MEMORY: &str = "# Memory\n\n## Preferences\n\n### Editor\n\n- helix\n\n### Shell\n\n- fish\n\n## Projects\n\n- zier\n";
+
+fn call(name: &str, arguments: serde_json::Value) -> ToolCall {
+    ToolCall {
+        id: format!("call-{}", name),
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_section_read_and_replace_with_overlay() {
+    let workspace = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::write(workspace.path().join("MEMORY.md"), MEMORY).unwrap();
+    fs::write(
+        project.path().join("NOTES.md"),
+        "# Notes\n\n## Todo\n\n- ship\n",
+    )
+    .unwrap();
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace.path().to_string_lossy().to_string();
+    config.workdir.strategy = WorkdirStrategy::Overlay;
+    config.disk.min_free_percent = 0.0;
+    let tools = create_default_tools_with_project(
+        &config,
+        None,
+        DiskMonitor::new(config.disk.clone()),
+        project.path().to_path_buf(),
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(tools, config.clone());
+
+    let output = executor
+        .execute_tool_for_session(
+            &call(
+                "memory_get",
+                serde_json::json!({"path": "MEMORY.md#Preferences/Editor"}),
+            ),
+            "s1",
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(output.contains("# MEMORY.md#Memory/Preferences/Editor (lines 5-8 of"));
+    assert!(output.contains("helix"));
+    assert!(!output.contains("fish"));
+
+    // Cognitive files are edited in the workspace
+    let output = executor
+        .execute_tool_for_session(
+            &call(
+                "edit_section",
+                serde_json::json!({"path": "MEMORY.md", "section": "Editor", "content": "- neovim\n"}),
+            ),
+            "s1",
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(output.contains("Replaced section Memory/Preferences/Editor"));
+    assert_eq!(
+        fs::read_to_string(workspace.path().join("MEMORY.md")).unwrap(),
+        MEMORY.replace("- helix", "- neovim")
+    );
+
+    // Other files in the project directory
+    executor
+        .execute_tool_for_session(
+            &call(
+                "edit_section",
+                serde_json::json!({"path": "NOTES.md#Todo", "content": "- ship\n- celebrate"}),
+            ),
+            "s1",
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(project.path().join("NOTES.md")).unwrap(),
+        "# Notes\n\n## Todo\n\n- ship\n- celebrate\n"
+    );
+
+    let missing = executor
+        .execute_tool_for_session(
+            &call(
+                "edit_section",
+                serde_json::json!({"path": "MEMORY.md#Hobbies", "content": "- chess"}),
+            ),
+            "s1",
+            true,
+        )
+        .await;
+    assert!(missing.unwrap_err().to_string().contains("No section"));
+}
+
+#[tokio::test]
+async fn test_private_section_is_not_replaced_from_cloud_turn() {
+    let workspace = TempDir::new().unwrap();
+    fs::write(
+        workspace.path().join("MEMORY.md"),
+        "# Memory\n\n## Health\n\n<!-- private -->\n- allergy\n<!-- /private -->\n\n## Food\n\n- tea\n",
+    )
+    .unwrap();
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace.path().to_string_lossy().to_string();
+    config.disk.min_free_percent = 0.0;
+    let tools = create_default_tools_with_project(
+        &config,
+        None,
+        DiskMonitor::new(config.disk.clone()),
+        workspace.path().to_path_buf(),
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(tools, config.clone());
+
+    let replace = |section: &str| {
+        call(
+            "edit_section",
+            serde_json::json!({"path": format!("MEMORY.md#{}", section), "content": "- none"}),
+        )
+    };
+    assert!(executor
+        .execute_tool_for_session(&replace("Health"), "s1", false)
+        .await
+        .is_err());
+    executor
+        .execute_tool_for_session(&replace("Food"), "s1", false)
+        .await
+        .unwrap();
+    executor
+        .execute_tool_for_session(&replace("Health"), "s1", true)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(workspace.path().join("MEMORY.md")).unwrap(),
+        "# Memory\n\n## Health\n\n- none\n\n## Food\n\n- none\n"
+    );
+}
@@ -1,8 +1,9 @@
 use std::fs;
 use tempfile::TempDir;
-use zier_alpha::agent::{Agent, AgentConfig, ContextStrategy};
-use zier_alpha::config::Config;
-use zier_alpha::memory::MemoryManager;
+use zier_alpha::agent::tools::{EditSectionTool, Tool};
+use zier_alpha::agent::{Agent, AgentConfig, ContextStrategy, DiskMonitor};
+use zier_alpha::config::{Config, SandboxPolicy, WorkdirStrategy};
+use zier_alpha::memory::{ConflictMode, FactWrite, MemoryManager};
 
 #[tokio::test]
 async fn test_memory_write_integration() {
@@ -45,3 +46,210 @@ async fn test_memory_write_integration() {
     let content = fs::read_to_string(memory_file).unwrap();
     assert!(content.contains("Name: Kira"));
 }
+
+#[tokio::test]
+async fn test_memory_write_tool_dedup_and_conflicts() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspace_path = temp_dir.path().to_path_buf();
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace_path.to_string_lossy().to_string();
+    config.agent.default_model = "mock/test".to_string();
+    config.disk.min_free_percent = 0.0;
+
+    let memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+
+    let agent_config = AgentConfig {
+        model: "mock/test".to_string(),
+        context_window: 100000,
+        reserve_tokens: 1000,
+    };
+
+    let mut agent = Agent::new(agent_config, &config, memory, ContextStrategy::Full, "test")
+        .await
+        .unwrap();
+    agent.new_session().await.unwrap();
+    let session_id = agent.session_status().await.id;
+
+    let write = |fact: &str, extra: &str| {
+        format!(
+            r#"test_tool_json:memory_write|{{"section":"Preferences","fact":"{}"{}}}"#,
+            fact, extra
+        )
+    };
+
+    agent.chat(&write("Editor: helix", "")).await.unwrap();
+    let memory_file = workspace_path.join("MEMORY.md");
+    let content = fs::read_to_string(&memory_file).unwrap();
+    assert!(content.contains("## Preferences"));
+    assert!(content.contains("- Editor: helix <!-- zier:write"));
+    assert!(content.contains(&format!("session={}", session_id)));
+    assert!(content.contains("op=add"));
+
+    // Same fact again is rejected as a duplicate
+    agent.chat(&write("editor: Helix", "")).await.unwrap();
+    let content = fs::read_to_string(&memory_file).unwrap();
+    assert_eq!(content.matches("elix").count(), 1);
+
+    // A contradiction is reported and leaves the file alone
+    agent.chat(&write("Editor: vim", "")).await.unwrap();
+    let messages = agent.session_messages().await;
+    let tool_output = messages
+        .iter()
+        .rev()
+        .find(|m| m.role == zier_alpha::agent::Role::Tool)
+        .unwrap();
+    assert!(tool_output.content.contains("contradicts"));
+    assert!(!fs::read_to_string(&memory_file).unwrap().contains("vim"));
+
+    // ...unless the model asks to replace it
+    agent
+        .chat(&write("Editor: vim", r#","on_conflict":"replace""#))
+        .await
+        .unwrap();
+    let content = fs::read_to_string(&memory_file).unwrap();
+    assert!(content.contains("- Editor: vim"));
+    assert!(!content.contains("helix"));
+    assert!(content.contains("op=replace"));
+}
+
+#[tokio::test]
+async fn test_concurrent_memory_writes_keep_every_fact() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspace_path = temp_dir.path().join("workspace");
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace_path.to_string_lossy().to_string();
+    config.disk.min_free_percent = 0.0;
+
+    // Two managers of the same workspace, as the daemon and a tool may hold
+    let first = std::sync::Arc::new(
+        MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap(),
+    );
+    let second = std::sync::Arc::new(
+        MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap(),
+    );
+
+    let facts = [
+        "apple",
+        "banana",
+        "cherry",
+        "damson",
+        "elderberry",
+        "fig",
+        "grape",
+        "huckleberry",
+        "kiwi",
+        "lemon",
+        "mango",
+        "nectarine",
+        "olive",
+        "papaya",
+        "quince",
+        "raspberry",
+    ];
+    let writes = facts.iter().enumerate().map(|(i, fruit)| {
+        let memory = if i % 2 == 0 {
+            std::sync::Arc::clone(&first)
+        } else {
+            std::sync::Arc::clone(&second)
+        };
+        let fact = format!("Grows {}", fruit);
+        tokio::spawn(async move {
+            memory
+                .write_fact(FactWrite {
+                    section: "Garden".to_string(),
+                    fact,
+                    on_conflict: ConflictMode::Keep,
+                    session_id: None,
+                })
+                .await
+        })
+    });
+    for outcome in futures::future::join_all(writes).await {
+        outcome.unwrap().unwrap();
+    }
+
+    let content = fs::read_to_string(workspace_path.join("MEMORY.md")).unwrap();
+    for fruit in facts {
+        assert!(
+            content.contains(&format!("- Grows {}", fruit)),
+            "lost {}:\n{}",
+            fruit,
+            content
+        );
+    }
+    assert_eq!(content.matches("## Garden").count(), 1);
+}
+
+#[tokio::test]
+async fn test_edit_section_and_memory_write_share_the_lock() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspace_path = temp_dir.path().join("workspace");
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace_path.to_string_lossy().to_string();
+    config.disk.min_free_percent = 0.0;
+
+    let memory = std::sync::Arc::new(
+        MemoryManager::new_with_full_config(&config.memory, None, "main").unwrap(),
+    );
+    let memory_file = workspace_path.join("MEMORY.md");
+    fs::write(
+        &memory_file,
+        "# Memory\n\n## Notes\n\nNothing yet.\n\n## Garden\n\n",
+    )
+    .unwrap();
+    let edit = std::sync::Arc::new(EditSectionTool::new(
+        workspace_path.clone(),
+        workspace_path.clone(),
+        WorkdirStrategy::Overlay,
+        DiskMonitor::new(config.disk.clone()),
+        SandboxPolicy::default(),
+    ));
+
+    let facts = ["apple", "banana", "cherry", "damson", "fig", "grape"];
+    let mut tasks = Vec::new();
+    for (i, fruit) in facts.iter().enumerate() {
+        let memory = std::sync::Arc::clone(&memory);
+        let fact = format!("Grows {}", fruit);
+        tasks.push(tokio::spawn(async move {
+            memory
+                .write_fact(FactWrite {
+                    section: "Garden".to_string(),
+                    fact,
+                    on_conflict: ConflictMode::Keep,
+                    session_id: None,
+                })
+                .await
+                .map(|_| ())
+        }));
+
+        let edit = std::sync::Arc::clone(&edit);
+        let arguments = serde_json::json!({
+            "path": format!("{}#Notes", memory_file.display()),
+            "content": format!("Edit {}\n", i),
+        })
+        .to_string();
+        tasks.push(tokio::spawn(async move {
+            edit.execute(&arguments).await.map(|_| ())
+        }));
+    }
+    for outcome in futures::future::join_all(tasks).await {
+        outcome.unwrap().unwrap();
+    }
+
+    // No edit wrote back a copy read before a fact was added, or vice versa
+    let content = fs::read_to_string(&memory_file).unwrap();
+    for fruit in facts {
+        assert!(
+            content.contains(&format!("- Grows {}", fruit)),
+            "lost {}:\n{}",
+            fruit,
+            content
+        );
+    }
+    assert_eq!(content.matches("Edit ").count(), 1);
+    assert!(!content.contains("Nothing yet"));
+}
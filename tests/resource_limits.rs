@@ -0,0 +1,96 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use zier_alpha::agent::tools::runner::run_sandboxed_command;
+use zier_alpha::config::{ResourceLimits, SandboxPolicy};
+
+async fn sh(script: &str, limits: ResourceLimits) -> Result<std::process::Output> {
+    let cwd = TempDir::new()?;
+    let policy = SandboxPolicy {
+        limits,
+        ..Default::default()
+    };
+    let args = vec!["-c".to_string(), script.to_string()];
+    run_sandboxed_command("sh", &args, &cwd.path().to_path_buf(), None, &policy, None).await
+}
+
+#[tokio::test]
+async fn test_output_is_capped() -> Result<()> {
+    let limits = ResourceLimits {
+        max_output_bytes: Some(1000),
+        ..Default::default()
+    };
+    let output = sh("yes | head -c 5000000", limits).await?;
+    assert!(output.status.success());
+    assert!(output.stdout.len() < 1200);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[output truncated: 4999000 bytes omitted"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_wall_clock_kills_process_group() -> Result<()> {
+    let limits = ResourceLimits {
+        wall_clock_secs: Some(1),
+        ..Default::default()
+    };
+    let started = Instant::now();
+    // The background sleep holds the pipes open too
+    let output = sh("echo started; sleep 30 & sleep 30", limits).await?;
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("started"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wall clock limit of 1s"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detached_grandchild_does_not_hold_output() -> Result<()> {
+    if std::process::Command::new("setsid")
+        .arg("true")
+        .output()
+        .is_err()
+    {
+        return Ok(());
+    }
+    // A new session escapes the group kill, and keeps the pipes open
+    for wall_clock_secs in [None, Some(1)] {
+        let limits = ResourceLimits {
+            wall_clock_secs,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let output = sh("echo started; setsid sleep 30 & sleep 2", limits).await?;
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("started"));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memory_limit() -> Result<()> {
+    if std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return Ok(());
+    }
+    let limits = ResourceLimits {
+        memory_mb: Some(200),
+        ..Default::default()
+    };
+    let output = sh(
+        "python3 -c 'x = bytearray(512 * 1024 * 1024); print(\"allocated\")'",
+        limits,
+    )
+    .await?;
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("allocated"));
+
+    let output = sh("echo fine", ResourceLimits::default()).await?;
+    assert!(output.status.success());
+    Ok(())
+}
@@ -13,6 +13,7 @@ fn test_sandbox_network_denied() {
         allow_write: vec![],
         allow_env: false,
         enable_os_sandbox: true,
+        ..Default::default()
     };
 
     // Simple python script that tries to open a socket to google.com
@@ -75,6 +76,7 @@ fn test_sandbox_write_denied() {
         allow_write: vec![], // No write allowed
         allow_env: false,
         enable_os_sandbox: true,
+        ..Default::default()
     };
 
     let script = r#"
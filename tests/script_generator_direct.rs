@@ -14,6 +14,7 @@ async fn test_evaluate_generator_direct() {
         allow_write: vec![workspace.to_str().unwrap().to_string()],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     let service = ScriptService::new(
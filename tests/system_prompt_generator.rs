@@ -29,6 +29,7 @@ async fn test_system_prompt_generator_integration() {
         allow_write: vec![workspace.to_str().unwrap().to_string()],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
     let script_service = ScriptService::new(
         policy,
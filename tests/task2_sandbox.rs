@@ -17,6 +17,7 @@ async fn test_sandbox_execution() -> Result<()> {
         allow_write: vec![],
         allow_network: false,
         allow_env: true,
+        ..Default::default()
     };
 
     let cwd = std::env::current_dir()?;
@@ -27,7 +28,7 @@ async fn test_sandbox_execution() -> Result<()> {
     let cmd = "echo";
 
     // This checks that the sandbox wrapper (unshare or sandbox-exec) launches and runs the command
-    let output = run_sandboxed_command(cmd, &args, &cwd, Some(env), &policy).await?;
+    let output = run_sandboxed_command(cmd, &args, &cwd, Some(env), &policy, None).await?;
 
     // On GitHub Actions or some containers, unshare might fail or require privileges.
     // If it fails with "Operation not permitted", we can consider skipping or handling it.
@@ -24,6 +24,7 @@ async fn test_tmux_bridge_lifecycle() {
         allow_write: vec![workspace.to_string_lossy().to_string()],
         allow_env: false,
         enable_os_sandbox: false, // Disable OS sandboxing for tests in unprivileged env
+        ..Default::default()
     };
 
     let service = ScriptService::new(
@@ -147,6 +147,7 @@ async fn test_deno_tool_routing() {
         ],
         allow_env: false,
         enable_os_sandbox: false,
+        ..Default::default()
     };
 
     let service = ScriptService::new(
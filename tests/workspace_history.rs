@@ -0,0 +1,117 @@
+use std::fs;
+use tempfile::TempDir;
+use zier_alpha::agent::{Agent, AgentConfig, ContextStrategy};
+use zier_alpha::config::Config;
+use zier_alpha::memory::MemoryManager;
+
+#[tokio::test]
+async fn test_turn_auto_commit_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspace_path = temp_dir.path().join("workspace");
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace_path.to_string_lossy().to_string();
+    config.agent.default_model = "mock/test".to_string();
+    config.disk.min_free_percent = 0.0;
+    config.memory.auto_commit = true;
+
+    let memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+    let history_memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+
+    let agent_config = AgentConfig {
+        model: "mock/test".to_string(),
+        context_window: 100000,
+        reserve_tokens: 1000,
+    };
+
+    let mut agent = Agent::new(agent_config, &config, memory, ContextStrategy::Full, "test")
+        .await
+        .unwrap();
+    agent.new_session().await.unwrap();
+    let session_id = agent.session_status().await.id;
+
+    // A turn whose tool writes MEMORY.md is committed with session id and tool name
+    agent
+        .chat("test: write memory - my name is Kira")
+        .await
+        .unwrap();
+
+    let revisions = history_memory.file_history("MEMORY.md", 10).await.unwrap();
+    assert_eq!(revisions.len(), 1);
+
+    let repo = git2::Repository::open(&workspace_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let message = head.message().unwrap();
+    assert!(message.contains(&session_id), "{}", message);
+    assert!(message.contains("Tools: "), "{}", message);
+    assert!(
+        message.starts_with("Update memory via write_file"),
+        "{}",
+        message
+    );
+
+    // A turn without tool calls does not commit
+    agent.chat("hello").await.unwrap();
+    assert_eq!(
+        history_memory
+            .file_history("MEMORY.md", 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Clobber MEMORY.md outside a turn, inspect the diff, then restore it
+    let memory_file = workspace_path.join("MEMORY.md");
+    let committed = fs::read_to_string(&memory_file).unwrap();
+    fs::write(&memory_file, "oops\n").unwrap();
+
+    let diff = history_memory
+        .workspace_diff(Some("MEMORY.md"), None)
+        .await
+        .unwrap();
+    assert!(diff.contains("+oops"));
+
+    history_memory
+        .restore_file("MEMORY.md", &revisions[0].id)
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(&memory_file).unwrap(), committed);
+    assert!(history_memory
+        .workspace_diff(None, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_no_repository_without_opt_in() {
+    let temp_dir = TempDir::new().unwrap();
+    let workspace_path = temp_dir.path().join("workspace");
+
+    let mut config = Config::default();
+    config.memory.workspace = workspace_path.to_string_lossy().to_string();
+    config.agent.default_model = "mock/test".to_string();
+    config.disk.min_free_percent = 0.0;
+
+    let memory =
+        MemoryManager::new_with_full_config(&config.memory, Some(&config), "test-agent").unwrap();
+    let agent_config = AgentConfig {
+        model: "mock/test".to_string(),
+        context_window: 100000,
+        reserve_tokens: 1000,
+    };
+    let mut agent = Agent::new(agent_config, &config, memory, ContextStrategy::Full, "test")
+        .await
+        .unwrap();
+    agent.new_session().await.unwrap();
+
+    agent
+        .chat("test: write memory - my name is Kira")
+        .await
+        .unwrap();
+    assert!(workspace_path.join("MEMORY.md").exists());
+    assert!(!workspace_path.join(".git").exists());
+}